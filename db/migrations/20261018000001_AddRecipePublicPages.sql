@@ -0,0 +1,7 @@
+-- Recipes are private to the cooking agent by default. Flipping `is_public`
+-- exposes a recipe on /recipes, addressed by a stable `slug` that is assigned
+-- the first time the recipe is published and kept even if it is renamed later.
+ALTER TABLE recipes ADD COLUMN is_public BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE recipes ADD COLUMN slug TEXT UNIQUE;
+
+CREATE INDEX idx_recipes_public ON recipes (name) WHERE is_public;
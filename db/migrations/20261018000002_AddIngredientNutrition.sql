@@ -0,0 +1,22 @@
+-- Nutrition facts per 100g of an ingredient, imported from the bundled USDA-style CSV.
+-- Recipes measure ingredients in all sorts of units, so each row also carries the
+-- densities needed to turn volume (`grams_per_cup`) and count (`grams_per_each`)
+-- measurements into grams.
+CREATE TABLE
+  ingredient_nutrition (
+    ingredient_id UUID PRIMARY KEY REFERENCES ingredients (ingredient_id) ON DELETE CASCADE,
+    calories DOUBLE PRECISION NOT NULL,
+    protein_g DOUBLE PRECISION NOT NULL,
+    fat_g DOUBLE PRECISION NOT NULL,
+    carbohydrates_g DOUBLE PRECISION NOT NULL,
+    fiber_g DOUBLE PRECISION,
+    sugar_g DOUBLE PRECISION,
+    sodium_mg DOUBLE PRECISION,
+    grams_per_cup DOUBLE PRECISION,
+    grams_per_each DOUBLE PRECISION,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_ingredient_nutrition_updated_at BEFORE UPDATE ON ingredient_nutrition
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
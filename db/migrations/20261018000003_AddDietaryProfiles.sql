@@ -0,0 +1,22 @@
+-- Allergen tags on ingredients, e.g. '{milk,wheat}'. Values are the snake_case names of
+-- `db::cooking::Allergen`.
+ALTER TABLE ingredients
+ADD COLUMN allergens TEXT[] NOT NULL DEFAULT '{}';
+
+-- Per-person dietary restrictions, keyed by Discord user ID so they line up with the
+-- person memories the agents already keep.
+CREATE TABLE
+  dietary_profiles (
+    dietary_profile_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    discord_user_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    allergens TEXT[] NOT NULL DEFAULT '{}',
+    diets TEXT[] NOT NULL DEFAULT '{}',
+    avoid_ingredients TEXT[] NOT NULL DEFAULT '{}',
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_dietary_profiles_updated_at BEFORE UPDATE ON dietary_profiles
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
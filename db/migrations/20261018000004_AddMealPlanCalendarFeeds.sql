@@ -0,0 +1,19 @@
+-- Subscribable iCalendar feeds of the meal plan. Calendar apps can't log in, so each
+-- feed is authenticated by the secret token in its URL. Meal times are local wall-clock
+-- times and are emitted as floating times, so they show at the same hour wherever the
+-- calendar is viewed.
+CREATE TABLE
+  meal_plan_calendar_feeds (
+    calendar_feed_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    name TEXT NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    breakfast_time TIME NOT NULL DEFAULT '08:00',
+    lunch_time TIME NOT NULL DEFAULT '12:00',
+    dinner_time TIME NOT NULL DEFAULT '18:00',
+    snack_time TIME NOT NULL DEFAULT '15:00',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_meal_plan_calendar_feeds_updated_at BEFORE UPDATE ON meal_plan_calendar_feeds
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,38 @@
+-- Webmentions (https://www.w3.org/TR/webmention/) of our posts and notes. A row is only
+-- stored once the source page has been fetched and found to link to the target, and is
+-- deleted again if a later re-send finds the link gone.
+CREATE TABLE
+  webmentions (
+    webmention_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    -- Path of the post or note the mention is for, e.g. `/posts/some-post/`
+    target_path TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('reply', 'like', 'repost', 'mention')),
+    author_name TEXT,
+    author_url TEXT,
+    author_photo TEXT,
+    content TEXT,
+    published_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (source, target)
+  );
+
+CREATE INDEX webmentions_target_path_idx ON webmentions (target_path);
+
+CREATE TRIGGER update_webmentions_updated_at BEFORE UPDATE ON webmentions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Outbound links we've already tried to send a webmention for, so each is sent once.
+-- `endpoint` is NULL when the linked page doesn't advertise one.
+CREATE TABLE
+  sent_webmentions (
+    sent_webmention_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    endpoint TEXT,
+    status_code INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (source, target)
+  );
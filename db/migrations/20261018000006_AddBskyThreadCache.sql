@@ -0,0 +1,17 @@
+-- Bluesky comment threads for posts and notes, so pages render from local data instead of
+-- calling the Bluesky API on every view. `thread` is the raw `getPostThread` response and is
+-- NULL until the first fetch succeeds. `refresh_requested_at` records when a page view last
+-- queued a refetch, so a popular page with a stale thread only queues one at a time.
+CREATE TABLE
+  bsky_threads (
+    bsky_thread_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    post_url TEXT NOT NULL UNIQUE,
+    thread JSONB,
+    fetched_at TIMESTAMPTZ,
+    refresh_requested_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_bsky_threads_updated_at BEFORE UPDATE ON bsky_threads
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,10 @@
+-- Posts and notes with a `publish_at` that have gone live, so syndication (Bluesky and
+-- standard.site) is kicked off exactly once when each one's time comes.
+CREATE TABLE
+  scheduled_publications (
+    scheduled_publication_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    -- Path of the post or note, e.g. `/posts/some-post/`
+    content_path TEXT NOT NULL UNIQUE,
+    publish_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
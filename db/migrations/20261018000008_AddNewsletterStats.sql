@@ -0,0 +1,31 @@
+-- Open and click counts for each newsletter sent through Buttondown, refreshed by the
+-- RefreshNewsletterStats job.
+CREATE TABLE
+  newsletter_stats (
+    newsletter_stat_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    buttondown_id TEXT NOT NULL UNIQUE,
+    -- Path of the newsletter's post, e.g. `/posts/weekly/20260123/`
+    post_path TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    recipients INTEGER NOT NULL,
+    opens INTEGER NOT NULL,
+    clicks INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_newsletter_stats_updated_at BEFORE UPDATE ON newsletter_stats
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Subscriber count, one row per day so growth can be tracked over time
+CREATE TABLE
+  newsletter_subscriber_counts (
+    newsletter_subscriber_count_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    recorded_on DATE NOT NULL UNIQUE,
+    subscribers INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_newsletter_subscriber_counts_updated_at BEFORE UPDATE ON newsletter_subscriber_counts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,38 @@
+-- People signed up for the newsletter when it's delivered by our own SMTP sender instead of
+-- Buttondown. They only get newsletters once they've confirmed their address.
+CREATE TABLE
+  newsletter_subscribers (
+    newsletter_subscriber_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    -- Stored lowercased, so the same address can't sign up twice
+    email TEXT NOT NULL UNIQUE,
+    confirmed_at TIMESTAMPTZ,
+    unsubscribed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_newsletter_subscribers_updated_at BEFORE UPDATE ON newsletter_subscribers
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- One newsletter going to one subscriber. Rows are queued when the newsletter is due and
+-- sent in batches, with failed sends retried until they run out of attempts.
+CREATE TABLE
+  newsletter_deliveries (
+    newsletter_delivery_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    newsletter_subscriber_id UUID NOT NULL REFERENCES newsletter_subscribers (newsletter_subscriber_id) ON DELETE CASCADE,
+    -- Path of the newsletter's post, e.g. `/posts/weekly/20260123/`
+    post_path TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (newsletter_subscriber_id, post_path)
+  );
+
+CREATE INDEX newsletter_deliveries_unsent_idx ON newsletter_deliveries (post_path)
+WHERE
+  sent_at IS NULL;
+
+CREATE TRIGGER update_newsletter_deliveries_updated_at BEFORE UPDATE ON newsletter_deliveries
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,16 @@
+-- Mastodon replies for cross-posted posts and notes, cached like `bsky_threads`. `thread`
+-- holds the status and its `/context` descendants as fetched from the status's instance, and
+-- is NULL until the first fetch succeeds.
+CREATE TABLE
+  mastodon_threads (
+    mastodon_thread_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    status_url TEXT NOT NULL UNIQUE,
+    thread JSONB,
+    fetched_at TIMESTAMPTZ,
+    refresh_requested_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_mastodon_threads_updated_at BEFORE UPDATE ON mastodon_threads
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
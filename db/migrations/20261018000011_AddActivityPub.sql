@@ -0,0 +1,24 @@
+-- Fediverse accounts following the site's ActivityPub actor. New posts are delivered to
+-- `shared_inbox_url` when the follower's server has one, so each server gets one copy.
+CREATE TABLE
+  activitypub_followers (
+    activitypub_follower_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    actor_id TEXT NOT NULL UNIQUE,
+    inbox_url TEXT NOT NULL,
+    shared_inbox_url TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
+
+CREATE TRIGGER update_activitypub_followers_updated_at BEFORE UPDATE ON activitypub_followers
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Posts and notes whose `Create` activity has been fanned out to followers, so each is
+-- delivered once
+CREATE TABLE
+  activitypub_deliveries (
+    activitypub_delivery_id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    object_id TEXT NOT NULL UNIQUE,
+    follower_count INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+  );
@@ -0,0 +1,3 @@
+-- When a confirmation email was last queued for a signup, so signing up again with a pending
+-- address doesn't send another one every time the form is submitted.
+ALTER TABLE newsletter_subscribers ADD COLUMN confirmation_sent_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- When a send run took a delivery to send, so overlapping runs don't both send it. A claim
+-- that's never finished, because the run died, can be taken again once it's old enough.
+ALTER TABLE newsletter_deliveries ADD COLUMN claimed_at TIMESTAMPTZ;
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A Fediverse account following the site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityPubFollower {
+    pub activitypub_follower_id: Uuid,
+    pub actor_id: String,
+    pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ActivityPubFollower {
+    /// Where to deliver activities for this follower, sharing one inbox per server when
+    /// the server has one
+    #[must_use]
+    pub fn delivery_inbox(&self) -> &str {
+        self.shared_inbox_url.as_deref().unwrap_or(&self.inbox_url)
+    }
+
+    /// Record a follow, refreshing the inboxes of an account that follows again
+    pub async fn upsert(
+        pool: &PgPool,
+        actor_id: &str,
+        inbox_url: &str,
+        shared_inbox_url: Option<&str>,
+    ) -> Result<Self> {
+        let follower = sqlx::query_as!(
+            ActivityPubFollower,
+            r#"
+            INSERT INTO activitypub_followers (actor_id, inbox_url, shared_inbox_url)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (actor_id) DO UPDATE
+            SET inbox_url = EXCLUDED.inbox_url,
+                shared_inbox_url = EXCLUDED.shared_inbox_url
+            RETURNING *
+            "#,
+            actor_id,
+            inbox_url,
+            shared_inbox_url
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(follower)
+    }
+
+    /// Forget an account that unfollowed or was deleted. Returns whether it was following.
+    pub async fn delete(pool: &PgPool, actor_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM activitypub_followers WHERE actor_id = $1",
+            actor_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn all(pool: &PgPool) -> Result<Vec<Self>> {
+        let followers = sqlx::query_as!(
+            ActivityPubFollower,
+            "SELECT * FROM activitypub_followers ORDER BY created_at"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(followers)
+    }
+}
+
+/// A post or note that has been delivered to followers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityPubDelivery {
+    pub activitypub_delivery_id: Uuid,
+    pub object_id: String,
+    /// How many followers it went out to
+    pub follower_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ActivityPubDelivery {
+    pub async fn exists(pool: &PgPool, object_id: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM activitypub_deliveries WHERE object_id = $1
+            ) as "exists!"
+            "#,
+            object_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn record(pool: &PgPool, object_id: &str, follower_count: i32) -> Result<Self> {
+        let delivery = sqlx::query_as!(
+            ActivityPubDelivery,
+            r#"
+            INSERT INTO activitypub_deliveries (object_id, follower_count)
+            VALUES ($1, $2)
+            ON CONFLICT (object_id) DO UPDATE
+            SET follower_count = EXCLUDED.follower_count
+            RETURNING *
+            "#,
+            object_id,
+            follower_count
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(delivery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn following_again_updates_the_inboxes(pool: PgPool) {
+        let actor = "https://example.social/users/friend";
+        ActivityPubFollower::upsert(
+            &pool,
+            actor,
+            "https://example.social/users/friend/inbox",
+            None,
+        )
+        .await
+        .unwrap();
+        let follower = ActivityPubFollower::upsert(
+            &pool,
+            actor,
+            "https://example.social/users/friend/inbox",
+            Some("https://example.social/inbox"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(follower.delivery_inbox(), "https://example.social/inbox");
+        assert_eq!(ActivityPubFollower::all(&pool).await.unwrap().len(), 1);
+
+        assert!(ActivityPubFollower::delete(&pool, actor).await.unwrap());
+        assert!(!ActivityPubFollower::delete(&pool, actor).await.unwrap());
+        assert!(ActivityPubFollower::all(&pool).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn deliveries_are_recorded_once_per_object(pool: PgPool) {
+        let object = "https://coreyja.com/ap/objects/notes/hello";
+        assert!(!ActivityPubDelivery::exists(&pool, object).await.unwrap());
+
+        ActivityPubDelivery::record(&pool, object, 3).await.unwrap();
+        assert!(ActivityPubDelivery::exists(&pool, object).await.unwrap());
+    }
+}
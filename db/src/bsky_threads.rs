@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A cached Bluesky comment thread for one of our posts or notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BskyThread {
+    pub bsky_thread_id: Uuid,
+    pub post_url: String,
+    /// The raw `app.bsky.feed.getPostThread` response, if it has been fetched yet
+    pub thread: Option<JsonValue>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub refresh_requested_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BskyThread {
+    pub async fn find(pool: &PgPool, post_url: &str) -> Result<Option<Self>> {
+        let thread = sqlx::query_as!(
+            BskyThread,
+            r#"
+            SELECT
+                bsky_thread_id,
+                post_url,
+                thread,
+                fetched_at,
+                refresh_requested_at,
+                created_at,
+                updated_at
+            FROM bsky_threads
+            WHERE post_url = $1
+            "#,
+            post_url
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Whether the thread is missing or was fetched longer than `max_age` ago
+    #[must_use]
+    pub fn is_stale(&self, max_age: chrono::Duration) -> bool {
+        self.fetched_at
+            .is_none_or(|fetched_at| fetched_at < Utc::now() - max_age)
+    }
+
+    /// Store a freshly fetched thread
+    pub async fn upsert(pool: &PgPool, post_url: &str, thread: JsonValue) -> Result<Self> {
+        let thread = sqlx::query_as!(
+            BskyThread,
+            r#"
+            INSERT INTO bsky_threads (post_url, thread, fetched_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (post_url) DO UPDATE
+            SET thread = EXCLUDED.thread,
+                fetched_at = EXCLUDED.fetched_at
+            RETURNING *
+            "#,
+            post_url,
+            thread
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Mark a refresh of `post_url` as requested, unless one already was within
+    /// `min_interval`. Returns whether the caller should go ahead and queue the refresh.
+    pub async fn claim_refresh(
+        pool: &PgPool,
+        post_url: &str,
+        min_interval: chrono::Duration,
+    ) -> Result<bool> {
+        #[allow(clippy::cast_precision_loss)]
+        let min_interval_secs = min_interval.num_seconds() as f64;
+
+        let claimed = sqlx::query_scalar!(
+            r#"
+            INSERT INTO bsky_threads (post_url, refresh_requested_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (post_url) DO UPDATE
+            SET refresh_requested_at = NOW()
+            WHERE bsky_threads.refresh_requested_at IS NULL
+               OR bsky_threads.refresh_requested_at < NOW() - make_interval(secs => $2)
+            RETURNING bsky_thread_id
+            "#,
+            post_url,
+            min_interval_secs
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread_fetched_at(fetched_at: Option<DateTime<Utc>>) -> BskyThread {
+        BskyThread {
+            bsky_thread_id: Uuid::new_v4(),
+            post_url: "https://bsky.app/profile/coreyja.com/post/abc".to_string(),
+            thread: None,
+            fetched_at,
+            refresh_requested_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn threads_are_stale_until_fetched_and_again_after_max_age() {
+        let max_age = chrono::Duration::minutes(10);
+
+        assert!(thread_fetched_at(None).is_stale(max_age));
+        assert!(!thread_fetched_at(Some(Utc::now())).is_stale(max_age));
+        assert!(
+            thread_fetched_at(Some(Utc::now() - chrono::Duration::minutes(11))).is_stale(max_age)
+        );
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn only_one_refresh_is_claimed_per_interval(pool: PgPool) {
+        let url = "https://bsky.app/profile/coreyja.com/post/abc";
+        let interval = chrono::Duration::minutes(10);
+
+        assert!(BskyThread::claim_refresh(&pool, url, interval)
+            .await
+            .unwrap());
+        assert!(!BskyThread::claim_refresh(&pool, url, interval)
+            .await
+            .unwrap());
+
+        let thread = BskyThread::find(&pool, url).await.unwrap().unwrap();
+        assert!(thread.thread.is_none());
+        assert!(thread.is_stale(interval));
+
+        BskyThread::upsert(&pool, url, serde_json::json!({"thread": {}}))
+            .await
+            .unwrap();
+        let thread = BskyThread::find(&pool, url).await.unwrap().unwrap();
+        assert!(!thread.is_stale(interval));
+        assert_eq!(thread.thread, Some(serde_json::json!({"thread": {}})));
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::meal_planning::MealType;
+
+/// A subscribable iCalendar feed of the meal plan, authenticated by the token in its URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MealPlanCalendarFeed {
+    pub calendar_feed_id: Uuid,
+    pub name: String,
+    pub token: String,
+    pub breakfast_time: NaiveTime,
+    pub lunch_time: NaiveTime,
+    pub dinner_time: NaiveTime,
+    pub snack_time: NaiveTime,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MealPlanCalendarFeed {
+    pub async fn create(pool: &PgPool, name: String) -> Result<Self> {
+        // Two v4 UUIDs give 244 random bits, more than enough for a URL secret
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+        let feed = sqlx::query_as!(
+            MealPlanCalendarFeed,
+            r#"
+            INSERT INTO meal_plan_calendar_feeds (name, token)
+            VALUES ($1, $2)
+            RETURNING
+                calendar_feed_id,
+                name,
+                token,
+                breakfast_time,
+                lunch_time,
+                dinner_time,
+                snack_time,
+                created_at,
+                updated_at
+            "#,
+            name,
+            token
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(feed)
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<Self>> {
+        let feeds = sqlx::query_as!(
+            MealPlanCalendarFeed,
+            r#"
+            SELECT
+                calendar_feed_id,
+                name,
+                token,
+                breakfast_time,
+                lunch_time,
+                dinner_time,
+                snack_time,
+                created_at,
+                updated_at
+            FROM meal_plan_calendar_feeds
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(feeds)
+    }
+
+    pub async fn get_by_token(pool: &PgPool, token: &str) -> Result<Option<Self>> {
+        let feed = sqlx::query_as!(
+            MealPlanCalendarFeed,
+            r#"
+            SELECT
+                calendar_feed_id,
+                name,
+                token,
+                breakfast_time,
+                lunch_time,
+                dinner_time,
+                snack_time,
+                created_at,
+                updated_at
+            FROM meal_plan_calendar_feeds
+            WHERE token = $1
+            "#,
+            token
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(feed)
+    }
+
+    pub async fn update_times(
+        pool: &PgPool,
+        calendar_feed_id: Uuid,
+        breakfast_time: NaiveTime,
+        lunch_time: NaiveTime,
+        dinner_time: NaiveTime,
+        snack_time: NaiveTime,
+    ) -> Result<Option<Self>> {
+        let feed = sqlx::query_as!(
+            MealPlanCalendarFeed,
+            r#"
+            UPDATE meal_plan_calendar_feeds
+            SET breakfast_time = $2,
+                lunch_time = $3,
+                dinner_time = $4,
+                snack_time = $5
+            WHERE calendar_feed_id = $1
+            RETURNING
+                calendar_feed_id,
+                name,
+                token,
+                breakfast_time,
+                lunch_time,
+                dinner_time,
+                snack_time,
+                created_at,
+                updated_at
+            "#,
+            calendar_feed_id,
+            breakfast_time,
+            lunch_time,
+            dinner_time,
+            snack_time
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(feed)
+    }
+
+    pub async fn delete(pool: &PgPool, calendar_feed_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM meal_plan_calendar_feeds WHERE calendar_feed_id = $1",
+            calendar_feed_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub fn time_for(&self, meal_type: &MealType) -> NaiveTime {
+        match meal_type {
+            MealType::Breakfast => self.breakfast_time,
+            MealType::Lunch => self.lunch_time,
+            MealType::Dinner => self.dinner_time,
+            MealType::Snack => self.snack_time,
+        }
+    }
+}
+
+/// A planned meal with the recipe details a calendar event needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarMealEntry {
+    pub meal_plan_entry_id: Uuid,
+    pub meal_plan_name: String,
+    pub date: NaiveDate,
+    pub meal_type: Option<MealType>,
+    pub servings_override: Option<i32>,
+    pub recipe_name: String,
+    pub recipe_slug: Option<String>,
+    pub recipe_is_public: bool,
+    pub prep_time: Option<i32>,
+    pub cook_time: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CalendarMealEntry {
+    /// Every planned meal on or after `since`, across all meal plans
+    pub async fn list_since(pool: &PgPool, since: NaiveDate) -> Result<Vec<Self>> {
+        let entries = sqlx::query_as!(
+            CalendarMealEntry,
+            r#"
+            SELECT
+                mpe.meal_plan_entry_id as "meal_plan_entry_id!",
+                mp.name as meal_plan_name,
+                mpe.date,
+                mpe.meal_type as "meal_type: MealType",
+                mpe.servings_override,
+                r.name as recipe_name,
+                r.slug as recipe_slug,
+                r.is_public as recipe_is_public,
+                r.prep_time,
+                r.cook_time,
+                mpe.updated_at
+            FROM meal_plan_entries mpe
+            JOIN meal_plans mp ON mp.meal_plan_id = mpe.meal_plan_id
+            JOIN recipes r ON r.recipe_id = mpe.recipe_id
+            WHERE mpe.date >= $1
+            ORDER BY mpe.date, mpe.meal_type
+            "#,
+            since
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
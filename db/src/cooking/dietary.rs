@@ -0,0 +1,494 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Allergen {
+    Milk,
+    Egg,
+    Fish,
+    Shellfish,
+    TreeNuts,
+    Peanuts,
+    Wheat,
+    Gluten,
+    Soy,
+    Sesame,
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Allergen::Milk => write!(f, "milk"),
+            Allergen::Egg => write!(f, "egg"),
+            Allergen::Fish => write!(f, "fish"),
+            Allergen::Shellfish => write!(f, "shellfish"),
+            Allergen::TreeNuts => write!(f, "tree_nuts"),
+            Allergen::Peanuts => write!(f, "peanuts"),
+            Allergen::Wheat => write!(f, "wheat"),
+            Allergen::Gluten => write!(f, "gluten"),
+            Allergen::Soy => write!(f, "soy"),
+            Allergen::Sesame => write!(f, "sesame"),
+        }
+    }
+}
+
+impl std::str::FromStr for Allergen {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "milk" | "dairy" => Ok(Allergen::Milk),
+            "egg" | "eggs" => Ok(Allergen::Egg),
+            "fish" => Ok(Allergen::Fish),
+            "shellfish" | "crustacean" | "crustaceans" => Ok(Allergen::Shellfish),
+            "tree_nuts" | "tree_nut" | "nuts" => Ok(Allergen::TreeNuts),
+            "peanuts" | "peanut" => Ok(Allergen::Peanuts),
+            "wheat" => Ok(Allergen::Wheat),
+            "gluten" => Ok(Allergen::Gluten),
+            "soy" | "soya" => Ok(Allergen::Soy),
+            "sesame" => Ok(Allergen::Sesame),
+            _ => Err(format!("Unknown allergen: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Diet {
+    Vegetarian,
+    Vegan,
+    Pescatarian,
+    GlutenFree,
+    DairyFree,
+}
+
+impl fmt::Display for Diet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diet::Vegetarian => write!(f, "vegetarian"),
+            Diet::Vegan => write!(f, "vegan"),
+            Diet::Pescatarian => write!(f, "pescatarian"),
+            Diet::GlutenFree => write!(f, "gluten_free"),
+            Diet::DairyFree => write!(f, "dairy_free"),
+        }
+    }
+}
+
+impl std::str::FromStr for Diet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "vegetarian" => Ok(Diet::Vegetarian),
+            "vegan" => Ok(Diet::Vegan),
+            "pescatarian" => Ok(Diet::Pescatarian),
+            "gluten_free" => Ok(Diet::GlutenFree),
+            "dairy_free" => Ok(Diet::DairyFree),
+            _ => Err(format!("Unknown diet: {s}")),
+        }
+    }
+}
+
+impl Diet {
+    /// Allergen tags this diet rules out
+    pub fn excluded_allergens(self) -> &'static [Allergen] {
+        match self {
+            Diet::Vegan => &[
+                Allergen::Milk,
+                Allergen::Egg,
+                Allergen::Fish,
+                Allergen::Shellfish,
+            ],
+            Diet::Vegetarian => &[Allergen::Fish, Allergen::Shellfish],
+            Diet::Pescatarian => &[],
+            Diet::GlutenFree => &[Allergen::Wheat, Allergen::Gluten],
+            Diet::DairyFree => &[Allergen::Milk],
+        }
+    }
+
+    /// Ingredient category keywords this diet rules out. Categories are free text, so
+    /// these are matched as case-insensitive substrings.
+    pub fn excluded_categories(self) -> &'static [&'static str] {
+        match self {
+            Diet::Vegan | Diet::Vegetarian => &["meat", "poultry", "seafood", "fish", "shellfish"],
+            Diet::Pescatarian => &["meat", "poultry"],
+            Diet::GlutenFree | Diet::DairyFree => &[],
+        }
+    }
+}
+
+/// A person's dietary restrictions, keyed by their Discord user ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DietaryProfile {
+    pub dietary_profile_id: Uuid,
+    pub discord_user_id: String,
+    pub name: String,
+    pub allergens: Vec<String>,
+    pub diets: Vec<String>,
+    /// Ingredients this person dislikes or avoids, matched against ingredient names
+    pub avoid_ingredients: Vec<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DietaryProfile {
+    pub async fn upsert(
+        pool: &PgPool,
+        discord_user_id: String,
+        name: String,
+        allergens: &[Allergen],
+        diets: &[Diet],
+        avoid_ingredients: Vec<String>,
+        notes: Option<String>,
+    ) -> Result<Self> {
+        let allergens: Vec<String> = allergens.iter().map(ToString::to_string).collect();
+        let diets: Vec<String> = diets.iter().map(ToString::to_string).collect();
+        let avoid_ingredients: Vec<String> = avoid_ingredients
+            .into_iter()
+            .map(|i| i.trim().to_lowercase())
+            .filter(|i| !i.is_empty())
+            .collect();
+
+        let profile = sqlx::query_as!(
+            DietaryProfile,
+            r#"
+            INSERT INTO dietary_profiles (
+                discord_user_id, name, allergens, diets, avoid_ingredients, notes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (discord_user_id) DO UPDATE
+            SET name = EXCLUDED.name,
+                allergens = EXCLUDED.allergens,
+                diets = EXCLUDED.diets,
+                avoid_ingredients = EXCLUDED.avoid_ingredients,
+                notes = EXCLUDED.notes
+            RETURNING
+                dietary_profile_id,
+                discord_user_id,
+                name,
+                allergens,
+                diets,
+                avoid_ingredients,
+                notes,
+                created_at,
+                updated_at
+            "#,
+            discord_user_id,
+            name,
+            &allergens,
+            &diets,
+            &avoid_ingredients,
+            notes
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(profile)
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<Self>> {
+        let profiles = sqlx::query_as!(
+            DietaryProfile,
+            r#"
+            SELECT
+                dietary_profile_id,
+                discord_user_id,
+                name,
+                allergens,
+                diets,
+                avoid_ingredients,
+                notes,
+                created_at,
+                updated_at
+            FROM dietary_profiles
+            ORDER BY name
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(profiles)
+    }
+
+    pub async fn get_by_discord_user_ids(
+        pool: &PgPool,
+        discord_user_ids: &[String],
+    ) -> Result<Vec<Self>> {
+        let profiles = sqlx::query_as!(
+            DietaryProfile,
+            r#"
+            SELECT
+                dietary_profile_id,
+                discord_user_id,
+                name,
+                allergens,
+                diets,
+                avoid_ingredients,
+                notes,
+                created_at,
+                updated_at
+            FROM dietary_profiles
+            WHERE discord_user_id = ANY($1)
+            ORDER BY name
+            "#,
+            discord_user_ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(profiles)
+    }
+
+    /// Check a recipe's ingredients against this profile.
+    ///
+    /// Allergens and diets are blocking; avoided ingredients are only warnings. Anything
+    /// that comes from an optional ingredient is downgraded to a warning too, since it
+    /// can be left out.
+    pub fn conflicts_with(&self, ingredients: &[DietaryIngredient]) -> Vec<DietaryConflict> {
+        let allergens: Vec<Allergen> = self
+            .allergens
+            .iter()
+            .filter_map(|a| a.parse().ok())
+            .collect();
+        let diets: Vec<Diet> = self.diets.iter().filter_map(|d| d.parse().ok()).collect();
+
+        let mut conflicts = vec![];
+        for ingredient in ingredients {
+            let category = ingredient.category.as_deref().unwrap_or_default();
+            let ingredient_allergens: Vec<Allergen> = ingredient
+                .allergens
+                .iter()
+                .filter_map(|a| a.parse().ok())
+                .collect();
+
+            let mut push = |reason: String, severity: ConflictSeverity| {
+                let severity = if ingredient.is_optional {
+                    ConflictSeverity::Warning
+                } else {
+                    severity
+                };
+                conflicts.push(DietaryConflict {
+                    person: self.name.clone(),
+                    ingredient: ingredient.name.clone(),
+                    reason,
+                    severity,
+                });
+            };
+
+            for allergen in &allergens {
+                if ingredient_allergens.contains(allergen) {
+                    push(
+                        format!("allergic to {allergen}"),
+                        ConflictSeverity::Blocking,
+                    );
+                }
+            }
+
+            for diet in &diets {
+                let excluded_allergen = diet
+                    .excluded_allergens()
+                    .iter()
+                    .any(|a| ingredient_allergens.contains(a));
+                let excluded_category = diet
+                    .excluded_categories()
+                    .iter()
+                    .any(|c| mentions(category, c));
+                if excluded_allergen || excluded_category {
+                    push(format!("not {diet}"), ConflictSeverity::Blocking);
+                }
+            }
+
+            for avoid in &self.avoid_ingredients {
+                if mentions(&ingredient.name, avoid) {
+                    push(format!("avoids {avoid}"), ConflictSeverity::Warning);
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// Whether `phrase` appears in `text` as whole words, allowing for plurals, so "egg" matches
+/// "2 eggs" but not "eggplant" and "nut" doesn't match "nutmeg"
+fn mentions(text: &str, phrase: &str) -> bool {
+    fn words(s: &str) -> Vec<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let text = words(text);
+    let phrase = words(phrase);
+    if phrase.is_empty() {
+        return false;
+    }
+
+    text.windows(phrase.len()).any(|window| {
+        window.iter().zip(&phrase).all(|(word, wanted)| {
+            let singular = word.strip_suffix('s');
+            word == wanted
+                || singular == Some(wanted.as_str())
+                || singular.and_then(|w| w.strip_suffix('e')) == Some(wanted.as_str())
+        })
+    })
+}
+
+/// The parts of a recipe ingredient that matter for dietary checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DietaryIngredient {
+    pub name: String,
+    pub category: Option<String>,
+    pub allergens: Vec<String>,
+    pub is_optional: bool,
+}
+
+impl DietaryIngredient {
+    pub async fn get_by_recipe(pool: &PgPool, recipe_id: Uuid) -> Result<Vec<Self>> {
+        let ingredients = sqlx::query_as!(
+            DietaryIngredient,
+            r#"
+            SELECT
+                i.name,
+                i.category,
+                i.allergens,
+                COALESCE(ri.is_optional, false) as "is_optional!"
+            FROM recipe_ingredients ri
+            JOIN ingredients i ON i.ingredient_id = ri.ingredient_id
+            WHERE ri.recipe_id = $1
+            ORDER BY ri.display_order
+            "#,
+            recipe_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ingredients)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSeverity {
+    /// Worth mentioning, but fine to plan anyway
+    Warning,
+    /// Someone can't eat this; planning it needs an explicit override
+    Blocking,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DietaryConflict {
+    pub person: String,
+    pub ingredient: String,
+    pub reason: String,
+    pub severity: ConflictSeverity,
+}
+
+impl fmt::Display for DietaryConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.person, self.ingredient, self.reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(allergens: &[&str], diets: &[&str], avoid: &[&str]) -> DietaryProfile {
+        let strings = |v: &[&str]| v.iter().map(ToString::to_string).collect();
+        DietaryProfile {
+            dietary_profile_id: Uuid::new_v4(),
+            discord_user_id: "1234567890".to_string(),
+            name: "Sam".to_string(),
+            allergens: strings(allergens),
+            diets: strings(diets),
+            avoid_ingredients: strings(avoid),
+            notes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn ingredient(name: &str, category: Option<&str>, allergens: &[&str]) -> DietaryIngredient {
+        DietaryIngredient {
+            name: name.to_string(),
+            category: category.map(ToString::to_string),
+            allergens: allergens.iter().map(ToString::to_string).collect(),
+            is_optional: false,
+        }
+    }
+
+    #[test]
+    fn allergen_parsing_accepts_common_aliases() {
+        assert_eq!("Dairy".parse::<Allergen>(), Ok(Allergen::Milk));
+        assert_eq!("tree nuts".parse::<Allergen>(), Ok(Allergen::TreeNuts));
+        assert_eq!("gluten-free".parse::<Diet>(), Ok(Diet::GlutenFree));
+        assert!("celery".parse::<Allergen>().is_err());
+    }
+
+    #[test]
+    fn allergens_block() {
+        let conflicts = profile(&["peanuts"], &[], &[]).conflicts_with(&[ingredient(
+            "peanut butter",
+            None,
+            &["peanuts"],
+        )]);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].severity, ConflictSeverity::Blocking);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Sam: peanut butter (allergic to peanuts)"
+        );
+    }
+
+    #[test]
+    fn diets_check_allergens_and_categories() {
+        let vegetarian = profile(&[], &["vegetarian"], &[]);
+        let conflicts = vegetarian.conflicts_with(&[
+            ingredient("chicken thighs", Some("Poultry"), &[]),
+            ingredient("anchovies", None, &["fish"]),
+            ingredient("cheddar", Some("dairy"), &["milk"]),
+        ]);
+
+        let flagged: Vec<_> = conflicts.iter().map(|c| c.ingredient.as_str()).collect();
+        assert_eq!(flagged, vec!["chicken thighs", "anchovies"]);
+    }
+
+    #[test]
+    fn avoided_and_optional_ingredients_only_warn() {
+        let mut garnish = ingredient("chopped peanuts", None, &["peanuts"]);
+        garnish.is_optional = true;
+
+        let conflicts = profile(&["peanuts"], &[], &["cilantro"])
+            .conflicts_with(&[ingredient("fresh cilantro", Some("herbs"), &[]), garnish]);
+
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .all(|c| c.severity == ConflictSeverity::Warning));
+    }
+
+    #[test]
+    fn ingredients_are_matched_by_whole_words() {
+        let conflicts =
+            profile(&[], &["pescatarian"], &["egg", "nut", "tomato"]).conflicts_with(&[
+                ingredient("eggplant", None, &[]),
+                ingredient("ground nutmeg", None, &[]),
+                ingredient("2 large Eggs", None, &[]),
+                ingredient("roma tomatoes", None, &[]),
+                ingredient("smoked salmon", Some("Seafood"), &[]),
+                ingredient("sweetbreads", Some("meatless substitutes"), &[]),
+            ]);
+
+        let flagged: Vec<_> = conflicts.iter().map(|c| c.ingredient.as_str()).collect();
+        assert_eq!(flagged, vec!["2 large Eggs", "roma tomatoes"]);
+    }
+}
@@ -208,6 +208,31 @@ impl RecipeEquipment {
         Ok(equipment)
     }
 
+    /// Get a recipe's equipment with the equipment names resolved
+    pub async fn get_named_by_recipe(
+        pool: &PgPool,
+        recipe_id: Uuid,
+    ) -> Result<Vec<NamedRecipeEquipment>> {
+        let equipment = sqlx::query_as!(
+            NamedRecipeEquipment,
+            r#"
+            SELECT
+                e.name as equipment_name,
+                re.is_optional,
+                re.notes
+            FROM recipe_equipment re
+            JOIN equipment e ON e.equipment_id = re.equipment_id
+            WHERE re.recipe_id = $1
+            ORDER BY e.name
+            "#,
+            recipe_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(equipment)
+    }
+
     pub async fn delete(pool: &PgPool, recipe_id: Uuid, equipment_id: Uuid) -> Result<()> {
         sqlx::query!(
             "DELETE FROM recipe_equipment WHERE recipe_id = $1 AND equipment_id = $2",
@@ -220,3 +245,10 @@ impl RecipeEquipment {
         Ok(())
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedRecipeEquipment {
+    pub equipment_name: String,
+    pub is_optional: Option<bool>,
+    pub notes: Option<String>,
+}
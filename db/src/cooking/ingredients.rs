@@ -6,6 +6,8 @@ use sqlx::{PgPool, Type};
 use std::fmt;
 use uuid::Uuid;
 
+use super::dietary::Allergen;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq, Eq)]
 #[sqlx(type_name = "text")]
 #[sqlx(rename_all = "snake_case")]
@@ -213,6 +215,8 @@ pub struct Ingredient {
     pub name: String,
     pub category: Option<String>,
     pub default_unit_id: Option<Uuid>,
+    /// Allergen tags, as the `snake_case` names of `Allergen`
+    pub allergens: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -234,6 +238,7 @@ impl Ingredient {
                 name,
                 category,
                 default_unit_id,
+                allergens,
                 created_at,
                 updated_at
             "#,
@@ -256,6 +261,7 @@ impl Ingredient {
                 name,
                 category,
                 default_unit_id,
+                allergens,
                 created_at,
                 updated_at
             FROM ingredients
@@ -278,6 +284,7 @@ impl Ingredient {
                 name,
                 category,
                 default_unit_id,
+                allergens,
                 created_at,
                 updated_at
             FROM ingredients
@@ -290,6 +297,44 @@ impl Ingredient {
 
         Ok(ingredient)
     }
+
+    pub async fn set_allergens(&self, pool: &PgPool, allergens: &[Allergen]) -> Result<Self> {
+        let mut allergens: Vec<String> = allergens.iter().map(ToString::to_string).collect();
+        allergens.sort_unstable();
+        allergens.dedup();
+
+        let ingredient = sqlx::query_as!(
+            Ingredient,
+            r#"
+            UPDATE ingredients
+            SET allergens = $2
+            WHERE ingredient_id = $1
+            RETURNING
+                ingredient_id,
+                name,
+                category,
+                default_unit_id,
+                allergens,
+                created_at,
+                updated_at
+            "#,
+            self.ingredient_id,
+            &allergens
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(ingredient)
+    }
+}
+
+/// Render a decimal quantity without trailing zeros, so `2.250` shows as `2.25`
+#[must_use]
+pub fn format_quantity(quantity: &BigDecimal) -> String {
+    quantity
+        .to_string()
+        .parse::<f64>()
+        .map_or_else(|_| quantity.to_string(), |q| q.to_string())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -455,6 +500,37 @@ impl RecipeIngredient {
         Ok(updated)
     }
 
+    /// Get a recipe's ingredients with the ingredient and unit names resolved
+    pub async fn get_named_by_recipe(
+        pool: &PgPool,
+        recipe_id: Uuid,
+    ) -> Result<Vec<NamedRecipeIngredient>> {
+        let ingredients = sqlx::query_as!(
+            NamedRecipeIngredient,
+            r#"
+            SELECT
+                i.name as ingredient_name,
+                ri.quantity,
+                u.name as unit_name,
+                ri.ingredient_group,
+                ri.preparation as "preparation: IngredientPreparation",
+                ri.temperature as "temperature: IngredientTemperature",
+                ri.is_optional,
+                ri.notes
+            FROM recipe_ingredients ri
+            JOIN ingredients i ON i.ingredient_id = ri.ingredient_id
+            JOIN units u ON u.unit_id = ri.unit_id
+            WHERE ri.recipe_id = $1
+            ORDER BY ri.display_order, ri.created_at
+            "#,
+            recipe_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ingredients)
+    }
+
     pub async fn delete(&self, pool: &PgPool) -> Result<()> {
         sqlx::query!(
             "DELETE FROM recipe_ingredients WHERE recipe_ingredient_id = $1",
@@ -466,3 +542,28 @@ impl RecipeIngredient {
         Ok(())
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedRecipeIngredient {
+    pub ingredient_name: String,
+    pub quantity: BigDecimal,
+    pub unit_name: String,
+    pub ingredient_group: Option<String>,
+    pub preparation: Option<IngredientPreparation>,
+    pub temperature: Option<IngredientTemperature>,
+    pub is_optional: Option<bool>,
+    pub notes: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_quantity_drops_trailing_zeros() {
+        let q: BigDecimal = "2.250".parse().unwrap();
+        assert_eq!(format_quantity(&q), "2.25");
+        let q: BigDecimal = "3.00".parse().unwrap();
+        assert_eq!(format_quantity(&q), "3");
+    }
+}
@@ -1,17 +1,28 @@
+pub mod calendar;
+pub mod dietary;
 pub mod equipment;
 pub mod ingredients;
 pub mod inventory;
 pub mod meal_planning;
+pub mod nutrition;
 pub mod recipe;
 pub mod steps;
 pub mod tags;
 
-pub use equipment::{Equipment, EquipmentCategory, RecipeEquipment};
+pub use calendar::{CalendarMealEntry, MealPlanCalendarFeed};
+pub use dietary::{
+    Allergen, ConflictSeverity, Diet, DietaryConflict, DietaryIngredient, DietaryProfile,
+};
+pub use equipment::{Equipment, EquipmentCategory, NamedRecipeEquipment, RecipeEquipment};
 pub use ingredients::{
-    Ingredient, IngredientPreparation, IngredientTemperature, RecipeIngredient, Unit, UnitType,
+    format_quantity, Ingredient, IngredientPreparation, IngredientTemperature,
+    NamedRecipeIngredient, RecipeIngredient, Unit, UnitType,
 };
 pub use inventory::{ConfidenceLevel, Inventory, Location, LocationType};
 pub use meal_planning::{MealPlan, MealPlanEntry, MealType};
-pub use recipe::{Recipe, RecipeVariation, RecipeWithDetails};
+pub use nutrition::{
+    IngredientNutrition, NutritionCsvRow, NutritionFacts, RecipeNutrition, BUNDLED_NUTRITION_CSV,
+};
+pub use recipe::{Recipe, RecipeIngredientMatch, RecipeVariation, RecipeWithDetails};
 pub use steps::{RecipeStep, StepEquipment, StepIngredient, TemperatureUnit};
 pub use tags::{RecipeTag, Tag};
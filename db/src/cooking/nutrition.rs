@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::ops::{Add, AddAssign};
+
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::ingredients::Ingredient;
+use super::recipe::RecipeWithDetails;
+
+/// Nutrition data for common ingredients, in the same shape as `IngredientNutrition::import_csv` expects
+pub const BUNDLED_NUTRITION_CSV: &str = include_str!("../../data/nutrition.csv");
+
+const ML_PER_CUP: f64 = 236.588;
+
+/// Nutrition facts for an ingredient, per 100g
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngredientNutrition {
+    pub ingredient_id: Uuid,
+    pub calories: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbohydrates_g: f64,
+    pub fiber_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub grams_per_cup: Option<f64>,
+    pub grams_per_each: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A row of the nutrition CSV. Values are per 100g; blank cells are `None`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NutritionCsvRow {
+    pub name: String,
+    pub calories: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbohydrates_g: f64,
+    pub fiber_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub grams_per_cup: Option<f64>,
+    pub grams_per_each: Option<f64>,
+}
+
+impl IngredientNutrition {
+    pub async fn upsert(pool: &PgPool, ingredient_id: Uuid, row: &NutritionCsvRow) -> Result<Self> {
+        let nutrition = sqlx::query_as!(
+            IngredientNutrition,
+            r#"
+            INSERT INTO ingredient_nutrition (
+                ingredient_id, calories, protein_g, fat_g, carbohydrates_g,
+                fiber_g, sugar_g, sodium_mg, grams_per_cup, grams_per_each
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (ingredient_id) DO UPDATE
+            SET calories = EXCLUDED.calories,
+                protein_g = EXCLUDED.protein_g,
+                fat_g = EXCLUDED.fat_g,
+                carbohydrates_g = EXCLUDED.carbohydrates_g,
+                fiber_g = EXCLUDED.fiber_g,
+                sugar_g = EXCLUDED.sugar_g,
+                sodium_mg = EXCLUDED.sodium_mg,
+                grams_per_cup = EXCLUDED.grams_per_cup,
+                grams_per_each = EXCLUDED.grams_per_each
+            RETURNING
+                ingredient_id,
+                calories,
+                protein_g,
+                fat_g,
+                carbohydrates_g,
+                fiber_g,
+                sugar_g,
+                sodium_mg,
+                grams_per_cup,
+                grams_per_each,
+                created_at,
+                updated_at
+            "#,
+            ingredient_id,
+            row.calories,
+            row.protein_g,
+            row.fat_g,
+            row.carbohydrates_g,
+            row.fiber_g,
+            row.sugar_g,
+            row.sodium_mg,
+            row.grams_per_cup,
+            row.grams_per_each
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(nutrition)
+    }
+
+    pub async fn get_for_ingredients(pool: &PgPool, ingredient_ids: &[Uuid]) -> Result<Vec<Self>> {
+        let nutrition = sqlx::query_as!(
+            IngredientNutrition,
+            r#"
+            SELECT
+                ingredient_id,
+                calories,
+                protein_g,
+                fat_g,
+                carbohydrates_g,
+                fiber_g,
+                sugar_g,
+                sodium_mg,
+                grams_per_cup,
+                grams_per_each,
+                created_at,
+                updated_at
+            FROM ingredient_nutrition
+            WHERE ingredient_id = ANY($1)
+            "#,
+            ingredient_ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(nutrition)
+    }
+
+    /// Import nutrition rows from CSV, creating any ingredients that don't exist yet.
+    /// Returns the number of rows imported.
+    pub async fn import_csv(pool: &PgPool, reader: impl std::io::Read) -> Result<usize> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let mut imported = 0;
+
+        for row in csv.deserialize::<NutritionCsvRow>() {
+            let row = row?;
+            let name = row.name.trim().to_lowercase();
+
+            let ingredient = match Ingredient::get_by_name(pool, &name).await? {
+                Some(i) => i,
+                None => Ingredient::create(pool, name, None, None).await?,
+            };
+            Self::upsert(pool, ingredient.ingredient_id, &row).await?;
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+
+    /// Convert a recipe quantity to grams of this ingredient, if the unit is understood
+    /// and (for volume and count units) this ingredient has the density to do so
+    pub fn grams_for(&self, quantity: f64, unit_name: &str) -> Option<f64> {
+        match Measure::for_unit(unit_name)? {
+            Measure::Grams(factor) => Some(quantity * factor),
+            Measure::Cups(factor) => self.grams_per_cup.map(|g| quantity * factor * g),
+            Measure::Each => self.grams_per_each.map(|g| quantity * g),
+        }
+    }
+
+    pub fn facts_for_grams(&self, grams: f64) -> NutritionFacts {
+        NutritionFacts {
+            calories: self.calories,
+            protein_g: self.protein_g,
+            fat_g: self.fat_g,
+            carbohydrates_g: self.carbohydrates_g,
+            fiber_g: self.fiber_g.unwrap_or_default(),
+            sugar_g: self.sugar_g.unwrap_or_default(),
+            sodium_mg: self.sodium_mg.unwrap_or_default(),
+        }
+        .scale(grams / 100.0)
+    }
+}
+
+/// How a unit converts to grams: directly for weights, via cups for volumes, and via a
+/// per-item weight for counts ("each", "clove", "large", ...)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Measure {
+    Grams(f64),
+    Cups(f64),
+    Each,
+}
+
+impl Measure {
+    /// `None` for units we don't know, which could be anything from a can to a bunch
+    fn for_unit(unit_name: &str) -> Option<Self> {
+        let unit = unit_name.trim().trim_end_matches('.').to_lowercase();
+        let measure = match unit.as_str() {
+            "g" | "gram" | "grams" => Measure::Grams(1.0),
+            "kg" | "kilogram" | "kilograms" => Measure::Grams(1000.0),
+            "mg" | "milligram" | "milligrams" => Measure::Grams(0.001),
+            "oz" | "ounce" | "ounces" => Measure::Grams(28.3495),
+            "lb" | "lbs" | "pound" | "pounds" => Measure::Grams(453.592),
+            "cup" | "cups" | "c" => Measure::Cups(1.0),
+            "tbsp" | "tbs" | "tablespoon" | "tablespoons" => Measure::Cups(1.0 / 16.0),
+            "tsp" | "teaspoon" | "teaspoons" => Measure::Cups(1.0 / 48.0),
+            "pinch" | "pinches" => Measure::Cups(1.0 / 768.0),
+            "dash" | "dashes" => Measure::Cups(1.0 / 384.0),
+            "fl oz" | "fluid ounce" | "fluid ounces" => Measure::Cups(1.0 / 8.0),
+            "pint" | "pints" | "pt" => Measure::Cups(2.0),
+            "quart" | "quarts" | "qt" => Measure::Cups(4.0),
+            "gallon" | "gallons" | "gal" => Measure::Cups(16.0),
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => {
+                Measure::Cups(1.0 / ML_PER_CUP)
+            }
+            "l" | "liter" | "liters" | "litre" | "litres" => Measure::Cups(1000.0 / ML_PER_CUP),
+            "" | "each" | "ea" | "whole" | "piece" | "pieces" | "pc" | "pcs" | "item" | "items"
+            | "clove" | "cloves" | "slice" | "slices" | "small" | "medium" | "large"
+            | "extra large" => Measure::Each,
+            _ => return None,
+        };
+        Some(measure)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct NutritionFacts {
+    pub calories: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbohydrates_g: f64,
+    pub fiber_g: f64,
+    pub sugar_g: f64,
+    pub sodium_mg: f64,
+}
+
+impl NutritionFacts {
+    #[must_use]
+    pub fn scale(self, factor: f64) -> Self {
+        Self {
+            calories: self.calories * factor,
+            protein_g: self.protein_g * factor,
+            fat_g: self.fat_g * factor,
+            carbohydrates_g: self.carbohydrates_g * factor,
+            fiber_g: self.fiber_g * factor,
+            sugar_g: self.sugar_g * factor,
+            sodium_mg: self.sodium_mg * factor,
+        }
+    }
+
+    /// Round every value to one decimal place, for display
+    #[must_use]
+    pub fn rounded(self) -> Self {
+        let round = |v: f64| (v * 10.0).round() / 10.0;
+        Self {
+            calories: round(self.calories),
+            protein_g: round(self.protein_g),
+            fat_g: round(self.fat_g),
+            carbohydrates_g: round(self.carbohydrates_g),
+            fiber_g: round(self.fiber_g),
+            sugar_g: round(self.sugar_g),
+            sodium_mg: round(self.sodium_mg),
+        }
+    }
+}
+
+impl Add for NutritionFacts {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            calories: self.calories + rhs.calories,
+            protein_g: self.protein_g + rhs.protein_g,
+            fat_g: self.fat_g + rhs.fat_g,
+            carbohydrates_g: self.carbohydrates_g + rhs.carbohydrates_g,
+            fiber_g: self.fiber_g + rhs.fiber_g,
+            sugar_g: self.sugar_g + rhs.sugar_g,
+            sodium_mg: self.sodium_mg + rhs.sodium_mg,
+        }
+    }
+}
+
+impl AddAssign for NutritionFacts {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeNutrition {
+    pub per_serving: NutritionFacts,
+    pub total: NutritionFacts,
+    /// Required ingredients left out of the totals, either because they have no nutrition
+    /// data or because their unit couldn't be converted to grams
+    pub missing_ingredient_ids: Vec<Uuid>,
+}
+
+impl RecipeWithDetails {
+    /// Calculate nutrition from already-loaded nutrition rows and unit names. Optional
+    /// ingredients are not counted.
+    pub fn nutrition(
+        &self,
+        nutrition: &HashMap<Uuid, IngredientNutrition>,
+        unit_names: &HashMap<Uuid, String>,
+    ) -> RecipeNutrition {
+        let mut total = NutritionFacts::default();
+        let mut missing_ingredient_ids = vec![];
+
+        for ingredient in &self.ingredients {
+            if ingredient.is_optional == Some(true) {
+                continue;
+            }
+
+            let quantity = ingredient.quantity.to_string().parse::<f64>().ok();
+            let grams = match (
+                quantity,
+                nutrition.get(&ingredient.ingredient_id),
+                unit_names.get(&ingredient.unit_id),
+            ) {
+                (Some(quantity), Some(facts), Some(unit)) => facts
+                    .grams_for(quantity, unit)
+                    .map(|grams| facts.facts_for_grams(grams)),
+                _ => None,
+            };
+
+            match grams {
+                Some(facts) => total += facts,
+                None => missing_ingredient_ids.push(ingredient.ingredient_id),
+            }
+        }
+
+        let servings = self.recipe.servings.max(1);
+
+        RecipeNutrition {
+            per_serving: total.scale(1.0 / f64::from(servings)).rounded(),
+            total: total.rounded(),
+            missing_ingredient_ids,
+        }
+    }
+
+    pub async fn calculate_nutrition(&self, pool: &PgPool) -> Result<RecipeNutrition> {
+        let ingredient_ids: Vec<Uuid> = self.ingredients.iter().map(|i| i.ingredient_id).collect();
+        let unit_ids: Vec<Uuid> = self.ingredients.iter().map(|i| i.unit_id).collect();
+
+        let nutrition = IngredientNutrition::get_for_ingredients(pool, &ingredient_ids)
+            .await?
+            .into_iter()
+            .map(|n| (n.ingredient_id, n))
+            .collect();
+
+        let unit_names = sqlx::query!(
+            "SELECT unit_id, name FROM units WHERE unit_id = ANY($1)",
+            &unit_ids
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.unit_id, row.name))
+        .collect();
+
+        Ok(self.nutrition(&nutrition, &unit_names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flour() -> IngredientNutrition {
+        IngredientNutrition {
+            ingredient_id: Uuid::new_v4(),
+            calories: 364.0,
+            protein_g: 10.3,
+            fat_g: 1.0,
+            carbohydrates_g: 76.3,
+            fiber_g: Some(2.7),
+            sugar_g: None,
+            sodium_mg: Some(2.0),
+            grams_per_cup: Some(125.0),
+            grams_per_each: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn converts_weights_directly() {
+        let flour = flour();
+        assert_eq!(flour.grams_for(250.0, "g"), Some(250.0));
+        assert_eq!(flour.grams_for(1.0, "kg"), Some(1000.0));
+        assert_eq!(flour.grams_for(2.0, "lbs"), Some(907.184));
+    }
+
+    #[test]
+    fn converts_volumes_through_grams_per_cup() {
+        let flour = flour();
+        assert_eq!(flour.grams_for(2.0, "cups"), Some(250.0));
+        assert_eq!(flour.grams_for(4.0, "Tbsp."), Some(31.25));
+        assert_eq!(flour.grams_for(3.0, "tsp"), Some(7.8125));
+    }
+
+    #[test]
+    fn count_units_need_grams_per_each() {
+        let flour = flour();
+        assert_eq!(flour.grams_for(1.0, "each"), None);
+
+        let egg = IngredientNutrition {
+            grams_per_each: Some(50.0),
+            ..flour
+        };
+        assert_eq!(egg.grams_for(3.0, "large"), Some(150.0));
+        assert_eq!(egg.grams_for(2.0, "cloves"), Some(100.0));
+    }
+
+    #[test]
+    fn unknown_units_are_not_converted() {
+        let egg = IngredientNutrition {
+            grams_per_each: Some(50.0),
+            ..flour()
+        };
+        assert_eq!(egg.grams_for(1.0, "can"), None);
+        assert_eq!(egg.grams_for(1.0, "bunch"), None);
+    }
+
+    #[test]
+    fn facts_scale_from_per_100g() {
+        let facts = flour().facts_for_grams(250.0);
+        assert!((facts.calories - 910.0).abs() < 1e-9);
+        assert!((facts.sodium_mg - 5.0).abs() < 1e-9);
+        assert!(facts.sugar_g.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn bundled_csv_parses() {
+        let mut csv = csv::Reader::from_reader(BUNDLED_NUTRITION_CSV.as_bytes());
+        let rows: Vec<NutritionCsvRow> = csv.deserialize().collect::<Result<_, _>>().unwrap();
+        assert!(rows.iter().any(|r| r.name == "all-purpose flour"));
+        assert!(rows
+            .iter()
+            .any(|r| r.name == "bacon" && r.grams_per_cup.is_none()));
+    }
+}
@@ -22,6 +22,9 @@ pub struct Recipe {
     pub generated_by_stitch: Option<Uuid>,
     pub inspired_by_recipe_id: Option<Uuid>,
     pub forked_from_recipe_id: Option<Uuid>,
+    /// Stable URL slug, assigned the first time the recipe is made public
+    pub slug: Option<String>,
+    pub is_public: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -62,6 +65,8 @@ impl Recipe {
                 generated_by_stitch,
                 inspired_by_recipe_id,
                 forked_from_recipe_id,
+                slug,
+                is_public,
                 created_at,
                 updated_at
             "#,
@@ -98,6 +103,8 @@ impl Recipe {
                 generated_by_stitch,
                 inspired_by_recipe_id,
                 forked_from_recipe_id,
+                slug,
+                is_public,
                 created_at,
                 updated_at
             FROM recipes
@@ -127,6 +134,8 @@ impl Recipe {
                 generated_by_stitch,
                 inspired_by_recipe_id,
                 forked_from_recipe_id,
+                slug,
+                is_public,
                 created_at,
                 updated_at
             FROM recipes
@@ -139,6 +148,170 @@ impl Recipe {
         Ok(recipes)
     }
 
+    pub async fn list_public(pool: &PgPool) -> Result<Vec<Self>> {
+        let recipes = sqlx::query_as!(
+            Recipe,
+            r#"
+            SELECT
+                recipe_id,
+                name,
+                description,
+                prep_time,
+                cook_time,
+                servings,
+                yield_amount,
+                yield_unit,
+                generated_by_stitch,
+                inspired_by_recipe_id,
+                forked_from_recipe_id,
+                slug,
+                is_public,
+                created_at,
+                updated_at
+            FROM recipes
+            WHERE is_public AND slug IS NOT NULL
+            ORDER BY name
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(recipes)
+    }
+
+    pub async fn get_public_by_slug(pool: &PgPool, slug: &str) -> Result<Option<Self>> {
+        let recipe = sqlx::query_as!(
+            Recipe,
+            r#"
+            SELECT
+                recipe_id,
+                name,
+                description,
+                prep_time,
+                cook_time,
+                servings,
+                yield_amount,
+                yield_unit,
+                generated_by_stitch,
+                inspired_by_recipe_id,
+                forked_from_recipe_id,
+                slug,
+                is_public,
+                created_at,
+                updated_at
+            FROM recipes
+            WHERE is_public AND slug = $1
+            "#,
+            slug
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(recipe)
+    }
+
+    /// Recipes that use any of the given ingredients, ordered by how many of them they use
+    pub async fn find_using_ingredients(
+        pool: &PgPool,
+        ingredient_ids: &[Uuid],
+        limit: i64,
+    ) -> Result<Vec<RecipeIngredientMatch>> {
+        let matches = sqlx::query_as!(
+            RecipeIngredientMatch,
+            r#"
+            SELECT
+                r.recipe_id,
+                r.name,
+                array_agg(DISTINCT i.name ORDER BY i.name) as "matched_ingredients!"
+            FROM recipes r
+            JOIN recipe_ingredients ri ON ri.recipe_id = r.recipe_id
+            JOIN ingredients i ON i.ingredient_id = ri.ingredient_id
+            WHERE ri.ingredient_id = ANY($1)
+            GROUP BY r.recipe_id, r.name
+            ORDER BY COUNT(DISTINCT ri.ingredient_id) DESC, r.name
+            LIMIT $2
+            "#,
+            ingredient_ids,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(matches)
+    }
+
+    /// Publish or unpublish this recipe.
+    ///
+    /// The first time a recipe is published it is given a slug derived from its name,
+    /// suffixed with a counter if another recipe already claimed it. The slug is kept
+    /// when the recipe is unpublished so links stay stable if it is published again.
+    pub async fn set_public(&self, pool: &PgPool, is_public: bool) -> Result<Self> {
+        let slug = match &self.slug {
+            Some(slug) => slug.clone(),
+            None => self.unique_slug(pool).await?,
+        };
+
+        let updated = sqlx::query_as!(
+            Recipe,
+            r#"
+            UPDATE recipes
+            SET is_public = $2,
+                slug = $3,
+                updated_at = NOW()
+            WHERE recipe_id = $1
+            RETURNING
+                recipe_id,
+                name,
+                description,
+                prep_time,
+                cook_time,
+                servings,
+                yield_amount,
+                yield_unit,
+                generated_by_stitch,
+                inspired_by_recipe_id,
+                forked_from_recipe_id,
+                slug,
+                is_public,
+                created_at,
+                updated_at
+            "#,
+            self.recipe_id,
+            is_public,
+            slug
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(updated)
+    }
+
+    async fn unique_slug(&self, pool: &PgPool) -> Result<String> {
+        let base = match slugify(&self.name) {
+            s if s.is_empty() => self.recipe_id.to_string(),
+            s => s,
+        };
+
+        let mut candidate = base.clone();
+        let mut counter = 2;
+        loop {
+            let taken = sqlx::query_scalar!(
+                r#"SELECT EXISTS(SELECT 1 FROM recipes WHERE slug = $1 AND recipe_id != $2) as "taken!""#,
+                candidate,
+                self.recipe_id
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if !taken {
+                return Ok(candidate);
+            }
+
+            candidate = format!("{base}-{counter}");
+            counter += 1;
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn update(
         &self,
@@ -176,6 +349,8 @@ impl Recipe {
                 generated_by_stitch,
                 inspired_by_recipe_id,
                 forked_from_recipe_id,
+                slug,
+                is_public,
                 created_at,
                 updated_at
             "#,
@@ -214,6 +389,30 @@ impl Recipe {
     }
 }
 
+/// Lowercase ASCII slug for a recipe name, e.g. "Mom's Mac & Cheese" -> "moms-mac-cheese"
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_' || c == '&' || c == '/')
+            && !slug.is_empty()
+            && !slug.ends_with('-')
+        {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A recipe along with the names of the requested ingredients it uses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeIngredientMatch {
+    pub recipe_id: Uuid,
+    pub name: String,
+    pub matched_ingredients: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecipeWithDetails {
     pub recipe: Recipe,
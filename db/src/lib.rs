@@ -3,14 +3,21 @@ use color_eyre::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPoolOptions;
 
+pub mod activitypub;
 pub mod agentic_threads;
+pub mod bsky_threads;
 pub mod cooking;
 pub mod discord_threads;
 pub mod linear_threads;
+pub mod mastodon_threads;
 pub mod models;
+pub mod newsletter_stats;
+pub mod newsletter_subscribers;
+pub mod scheduled_publications;
 pub mod tool_suggestions;
 pub mod twitch_chatters;
 pub mod users;
+pub mod webmentions;
 
 pub use sqlx;
 pub use sqlx::PgPool;
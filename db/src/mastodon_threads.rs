@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Cached Mastodon replies to the status one of our posts or notes was cross-posted as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MastodonThread {
+    pub mastodon_thread_id: Uuid,
+    pub status_url: String,
+    /// The status and its replies, if they have been fetched yet
+    pub thread: Option<JsonValue>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub refresh_requested_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MastodonThread {
+    pub async fn find(pool: &PgPool, status_url: &str) -> Result<Option<Self>> {
+        let thread = sqlx::query_as!(
+            MastodonThread,
+            r#"
+            SELECT
+                mastodon_thread_id,
+                status_url,
+                thread,
+                fetched_at,
+                refresh_requested_at,
+                created_at,
+                updated_at
+            FROM mastodon_threads
+            WHERE status_url = $1
+            "#,
+            status_url
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Whether the thread is missing or was fetched longer than `max_age` ago
+    #[must_use]
+    pub fn is_stale(&self, max_age: chrono::Duration) -> bool {
+        self.fetched_at
+            .is_none_or(|fetched_at| fetched_at < Utc::now() - max_age)
+    }
+
+    /// Store a freshly fetched thread
+    pub async fn upsert(pool: &PgPool, status_url: &str, thread: JsonValue) -> Result<Self> {
+        let thread = sqlx::query_as!(
+            MastodonThread,
+            r#"
+            INSERT INTO mastodon_threads (status_url, thread, fetched_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (status_url) DO UPDATE
+            SET thread = EXCLUDED.thread,
+                fetched_at = EXCLUDED.fetched_at
+            RETURNING *
+            "#,
+            status_url,
+            thread
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Mark a refresh of `status_url` as requested, unless one already was within
+    /// `min_interval`. Returns whether the caller should go ahead and queue the refresh.
+    pub async fn claim_refresh(
+        pool: &PgPool,
+        status_url: &str,
+        min_interval: chrono::Duration,
+    ) -> Result<bool> {
+        #[allow(clippy::cast_precision_loss)]
+        let min_interval_secs = min_interval.num_seconds() as f64;
+
+        let claimed = sqlx::query_scalar!(
+            r#"
+            INSERT INTO mastodon_threads (status_url, refresh_requested_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (status_url) DO UPDATE
+            SET refresh_requested_at = NOW()
+            WHERE mastodon_threads.refresh_requested_at IS NULL
+               OR mastodon_threads.refresh_requested_at < NOW() - make_interval(secs => $2)
+            RETURNING mastodon_thread_id
+            "#,
+            status_url,
+            min_interval_secs
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+}
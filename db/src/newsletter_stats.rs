@@ -0,0 +1,205 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Open and click counts for a newsletter sent through Buttondown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterStat {
+    pub newsletter_stat_id: Uuid,
+    pub buttondown_id: String,
+    pub post_path: String,
+    pub subject: String,
+    pub recipients: i32,
+    pub opens: i32,
+    pub clicks: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The counts Buttondown reports for an email
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailCounts {
+    pub recipients: i32,
+    pub opens: i32,
+    pub clicks: i32,
+}
+
+impl NewsletterStat {
+    pub async fn upsert(
+        pool: &PgPool,
+        buttondown_id: &str,
+        post_path: &str,
+        subject: &str,
+        counts: EmailCounts,
+    ) -> Result<Self> {
+        let stat = sqlx::query_as!(
+            NewsletterStat,
+            r#"
+            INSERT INTO newsletter_stats (buttondown_id, post_path, subject, recipients, opens, clicks)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (buttondown_id) DO UPDATE
+            SET post_path = EXCLUDED.post_path,
+                subject = EXCLUDED.subject,
+                recipients = EXCLUDED.recipients,
+                opens = EXCLUDED.opens,
+                clicks = EXCLUDED.clicks
+            RETURNING *
+            "#,
+            buttondown_id,
+            post_path,
+            subject,
+            counts.recipients,
+            counts.opens,
+            counts.clicks,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stat)
+    }
+
+    /// Every newsletter with stats, most recently sent first
+    pub async fn all(pool: &PgPool) -> Result<Vec<Self>> {
+        let stats = sqlx::query_as!(
+            NewsletterStat,
+            r#"
+            SELECT * FROM newsletter_stats
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Opens as a share of recipients, or `None` before the email has gone out
+    pub fn open_rate(&self) -> Option<f64> {
+        rate(self.opens, self.recipients)
+    }
+
+    pub fn click_rate(&self) -> Option<f64> {
+        rate(self.clicks, self.recipients)
+    }
+
+    /// Opens as a share of recipients across every newsletter
+    pub fn overall_open_rate(stats: &[Self]) -> Option<f64> {
+        rate(
+            stats.iter().map(|s| s.opens).sum(),
+            stats.iter().map(|s| s.recipients).sum(),
+        )
+    }
+}
+
+fn rate(count: i32, recipients: i32) -> Option<f64> {
+    (recipients > 0).then(|| f64::from(count) / f64::from(recipients))
+}
+
+/// How many people were subscribed to the newsletter on a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterSubscriberCount {
+    pub newsletter_subscriber_count_id: Uuid,
+    pub recorded_on: NaiveDate,
+    pub subscribers: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NewsletterSubscriberCount {
+    /// Record today's count, replacing any earlier count from the same day
+    pub async fn record(pool: &PgPool, recorded_on: NaiveDate, subscribers: i32) -> Result<Self> {
+        let count = sqlx::query_as!(
+            NewsletterSubscriberCount,
+            r#"
+            INSERT INTO newsletter_subscriber_counts (recorded_on, subscribers)
+            VALUES ($1, $2)
+            ON CONFLICT (recorded_on) DO UPDATE
+            SET subscribers = EXCLUDED.subscribers
+            RETURNING *
+            "#,
+            recorded_on,
+            subscribers,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn latest(pool: &PgPool) -> Result<Option<Self>> {
+        let count = sqlx::query_as!(
+            NewsletterSubscriberCount,
+            r#"
+            SELECT * FROM newsletter_subscriber_counts
+            ORDER BY recorded_on DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn refreshing_stats_replaces_earlier_counts(pool: PgPool) {
+        let sent = EmailCounts {
+            recipients: 100,
+            opens: 10,
+            clicks: 1,
+        };
+        NewsletterStat::upsert(&pool, "email-1", "/posts/weekly/1/", "One", sent)
+            .await
+            .unwrap();
+        NewsletterStat::upsert(
+            &pool,
+            "email-1",
+            "/posts/weekly/1/",
+            "One",
+            EmailCounts { opens: 50, ..sent },
+        )
+        .await
+        .unwrap();
+        NewsletterStat::upsert(&pool, "email-2", "/posts/weekly/2/", "Two", sent)
+            .await
+            .unwrap();
+
+        let stats = NewsletterStat::all(&pool).await.unwrap();
+        assert_eq!(stats.len(), 2);
+        let one = stats.iter().find(|s| s.buttondown_id == "email-1").unwrap();
+        assert_eq!(one.opens, 50);
+        assert_eq!(one.open_rate(), Some(0.5));
+        assert_eq!(NewsletterStat::overall_open_rate(&stats), Some(0.3));
+
+        let today = Utc::now().date_naive();
+        NewsletterSubscriberCount::record(&pool, today, 90)
+            .await
+            .unwrap();
+        NewsletterSubscriberCount::record(&pool, today, 95)
+            .await
+            .unwrap();
+        NewsletterSubscriberCount::record(&pool, today - chrono::Duration::days(1), 80)
+            .await
+            .unwrap();
+
+        let latest = NewsletterSubscriberCount::latest(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.recorded_on, today);
+        assert_eq!(latest.subscribers, 95);
+    }
+
+    #[test]
+    fn rates_are_unknown_before_anyone_receives_the_email() {
+        assert_eq!(NewsletterStat::overall_open_rate(&[]), None);
+        assert_eq!(rate(0, 0), None);
+    }
+}
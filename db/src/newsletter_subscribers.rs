@@ -0,0 +1,484 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Someone signed up for newsletters sent by our own SMTP sender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterSubscriber {
+    pub newsletter_subscriber_id: Uuid,
+    pub email: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub unsubscribed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// When a confirmation email was last queued for the address
+    pub confirmation_sent_at: Option<DateTime<Utc>>,
+}
+
+impl NewsletterSubscriber {
+    /// Sign an address up, unconfirmed. Signing up again after unsubscribing starts over, so
+    /// the address has to be confirmed again.
+    pub async fn subscribe(pool: &PgPool, email: &str) -> Result<Self> {
+        let email = email.trim().to_lowercase();
+
+        let subscriber = sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            INSERT INTO newsletter_subscribers (email)
+            VALUES ($1)
+            ON CONFLICT (email) DO UPDATE
+            SET confirmed_at = CASE
+                    WHEN newsletter_subscribers.unsubscribed_at IS NULL
+                    THEN newsletter_subscribers.confirmed_at
+                END,
+                unsubscribed_at = NULL
+            RETURNING *
+            "#,
+            email,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(subscriber)
+    }
+
+    pub async fn find(pool: &PgPool, newsletter_subscriber_id: Uuid) -> Result<Option<Self>> {
+        let subscriber = sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            SELECT * FROM newsletter_subscribers
+            WHERE newsletter_subscriber_id = $1
+            "#,
+            newsletter_subscriber_id,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(subscriber)
+    }
+
+    /// Confirm the address, keeping the first confirmation time. Unsubscribed addresses
+    /// can't be confirmed, they have to sign up again.
+    pub async fn confirm(pool: &PgPool, newsletter_subscriber_id: Uuid) -> Result<Option<Self>> {
+        let subscriber = sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            UPDATE newsletter_subscribers
+            SET confirmed_at = COALESCE(confirmed_at, NOW())
+            WHERE newsletter_subscriber_id = $1 AND unsubscribed_at IS NULL
+            RETURNING *
+            "#,
+            newsletter_subscriber_id,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(subscriber)
+    }
+
+    /// Mark a confirmation email as sent, unless one already was within `min_interval`.
+    /// Returns whether the caller should go ahead and send it.
+    pub async fn claim_confirmation(
+        pool: &PgPool,
+        newsletter_subscriber_id: Uuid,
+        min_interval: chrono::Duration,
+    ) -> Result<bool> {
+        #[allow(clippy::cast_precision_loss)]
+        let min_interval_secs = min_interval.num_seconds() as f64;
+
+        let claimed = sqlx::query_scalar!(
+            r#"
+            UPDATE newsletter_subscribers
+            SET confirmation_sent_at = NOW()
+            WHERE newsletter_subscriber_id = $1
+                AND (confirmation_sent_at IS NULL
+                    OR confirmation_sent_at < NOW() - make_interval(secs => $2))
+            RETURNING newsletter_subscriber_id
+            "#,
+            newsletter_subscriber_id,
+            min_interval_secs
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    pub async fn unsubscribe(
+        pool: &PgPool,
+        newsletter_subscriber_id: Uuid,
+    ) -> Result<Option<Self>> {
+        let subscriber = sqlx::query_as!(
+            NewsletterSubscriber,
+            r#"
+            UPDATE newsletter_subscribers
+            SET unsubscribed_at = COALESCE(unsubscribed_at, NOW())
+            WHERE newsletter_subscriber_id = $1
+            RETURNING *
+            "#,
+            newsletter_subscriber_id,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(subscriber)
+    }
+
+    /// Confirmed subscribers who haven't unsubscribed
+    pub async fn count_active(pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM newsletter_subscribers
+            WHERE confirmed_at IS NOT NULL AND unsubscribed_at IS NULL
+            "#
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Whether newsletters should be sent to this address
+    pub fn is_active(&self) -> bool {
+        self.confirmed_at.is_some() && self.unsubscribed_at.is_none()
+    }
+}
+
+/// One newsletter going to one subscriber
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterDelivery {
+    pub newsletter_delivery_id: Uuid,
+    pub newsletter_subscriber_id: Uuid,
+    pub post_path: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A delivery still to be sent, with the address to send it to
+#[derive(Debug, Clone)]
+pub struct PendingDelivery {
+    pub newsletter_delivery_id: Uuid,
+    pub newsletter_subscriber_id: Uuid,
+    pub email: String,
+    pub attempts: i32,
+}
+
+impl NewsletterDelivery {
+    /// Queue a newsletter for everyone who had confirmed by `confirmed_by`, skipping anyone
+    /// it's already queued for. Returns how many deliveries were added.
+    pub async fn queue(pool: &PgPool, post_path: &str, confirmed_by: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO newsletter_deliveries (newsletter_subscriber_id, post_path)
+            SELECT newsletter_subscriber_id, $1
+            FROM newsletter_subscribers
+            WHERE confirmed_at <= $2 AND unsubscribed_at IS NULL
+            ON CONFLICT (newsletter_subscriber_id, post_path) DO NOTHING
+            "#,
+            post_path,
+            confirmed_by,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Claim the next batch of unsent deliveries of a newsletter to send, leaving out anyone
+    /// who has since unsubscribed and deliveries that have failed `max_attempts` times. Those
+    /// that have failed the least go first, so a few bad addresses can't hold up the rest.
+    ///
+    /// Claimed deliveries aren't handed out again until they're marked sent or failed, or
+    /// the claim is older than `claim_for`, so overlapping runs can't send the same email.
+    pub async fn claim_pending(
+        pool: &PgPool,
+        post_path: &str,
+        max_attempts: i32,
+        limit: i64,
+        claim_for: chrono::Duration,
+    ) -> Result<Vec<PendingDelivery>> {
+        #[allow(clippy::cast_precision_loss)]
+        let claim_for_secs = claim_for.num_seconds() as f64;
+
+        let pending = sqlx::query_as!(
+            PendingDelivery,
+            r#"
+            WITH claimable AS (
+                SELECT d.newsletter_delivery_id
+                FROM newsletter_deliveries d
+                JOIN newsletter_subscribers s USING (newsletter_subscriber_id)
+                WHERE d.post_path = $1
+                    AND d.sent_at IS NULL
+                    AND d.attempts < $2
+                    AND s.unsubscribed_at IS NULL
+                    AND (d.claimed_at IS NULL
+                        OR d.claimed_at < NOW() - make_interval(secs => $4))
+                ORDER BY d.attempts, d.created_at
+                LIMIT $3
+                FOR UPDATE OF d SKIP LOCKED
+            )
+            UPDATE newsletter_deliveries d
+            SET claimed_at = NOW()
+            FROM claimable, newsletter_subscribers s
+            WHERE d.newsletter_delivery_id = claimable.newsletter_delivery_id
+                AND s.newsletter_subscriber_id = d.newsletter_subscriber_id
+            RETURNING
+                d.newsletter_delivery_id,
+                d.newsletter_subscriber_id,
+                s.email,
+                d.attempts
+            "#,
+            post_path,
+            max_attempts,
+            limit,
+            claim_for_secs,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(pending)
+    }
+
+    pub async fn mark_sent(pool: &PgPool, newsletter_delivery_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE newsletter_deliveries
+            SET sent_at = NOW(), attempts = attempts + 1, last_error = NULL, claimed_at = NULL
+            WHERE newsletter_delivery_id = $1
+            "#,
+            newsletter_delivery_id,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_failure(
+        pool: &PgPool,
+        newsletter_delivery_id: Uuid,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE newsletter_deliveries
+            SET attempts = attempts + 1, last_error = $2, claimed_at = NULL
+            WHERE newsletter_delivery_id = $1
+            "#,
+            newsletter_delivery_id,
+            error,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Every delivery of a newsletter, for checking on how sending went
+    pub async fn for_post(pool: &PgPool, post_path: &str) -> Result<Vec<Self>> {
+        let deliveries = sqlx::query_as!(
+            NewsletterDelivery,
+            r#"
+            SELECT
+                newsletter_delivery_id,
+                newsletter_subscriber_id,
+                post_path,
+                attempts,
+                last_error,
+                sent_at,
+                claimed_at,
+                created_at,
+                updated_at
+            FROM newsletter_deliveries
+            WHERE post_path = $1
+            ORDER BY created_at
+            "#,
+            post_path,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POST: &str = "/posts/weekly/20261018/";
+    const CLAIM_FOR: chrono::Duration = chrono::Duration::minutes(30);
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn signing_up_needs_confirming_and_again_after_unsubscribing(pool: PgPool) {
+        let subscriber = NewsletterSubscriber::subscribe(&pool, " Reader@Example.com ")
+            .await
+            .unwrap();
+        assert_eq!(subscriber.email, "reader@example.com");
+        assert!(!subscriber.is_active());
+
+        let id = subscriber.newsletter_subscriber_id;
+        let confirmed = NewsletterSubscriber::confirm(&pool, id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(confirmed.is_active());
+
+        // Signing up twice doesn't lose the confirmation
+        let again = NewsletterSubscriber::subscribe(&pool, "reader@example.com")
+            .await
+            .unwrap();
+        assert_eq!(again.newsletter_subscriber_id, id);
+        assert_eq!(again.confirmed_at, confirmed.confirmed_at);
+        assert_eq!(NewsletterSubscriber::count_active(&pool).await.unwrap(), 1);
+
+        NewsletterSubscriber::unsubscribe(&pool, id).await.unwrap();
+        assert!(NewsletterSubscriber::confirm(&pool, id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(NewsletterSubscriber::count_active(&pool).await.unwrap(), 0);
+
+        let resubscribed = NewsletterSubscriber::subscribe(&pool, "reader@example.com")
+            .await
+            .unwrap();
+        assert!(resubscribed.unsubscribed_at.is_none());
+        assert!(resubscribed.confirmed_at.is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn confirmations_are_only_claimed_once_per_interval(pool: PgPool) {
+        let subscriber = NewsletterSubscriber::subscribe(&pool, "reader@example.com")
+            .await
+            .unwrap();
+        let id = subscriber.newsletter_subscriber_id;
+        let interval = chrono::Duration::minutes(15);
+
+        assert!(
+            NewsletterSubscriber::claim_confirmation(&pool, id, interval)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !NewsletterSubscriber::claim_confirmation(&pool, id, interval)
+                .await
+                .unwrap()
+        );
+        assert!(
+            NewsletterSubscriber::claim_confirmation(&pool, id, chrono::Duration::zero())
+                .await
+                .unwrap()
+        );
+
+        let subscriber = NewsletterSubscriber::find(&pool, id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(subscriber.confirmation_sent_at.is_some());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn deliveries_are_queued_once_and_retried_until_out_of_attempts(pool: PgPool) {
+        let mut ids = vec![];
+        for email in ["a@example.com", "b@example.com", "c@example.com"] {
+            let subscriber = NewsletterSubscriber::subscribe(&pool, email).await.unwrap();
+            ids.push(subscriber.newsletter_subscriber_id);
+        }
+        NewsletterSubscriber::confirm(&pool, ids[0]).await.unwrap();
+        NewsletterSubscriber::confirm(&pool, ids[1]).await.unwrap();
+
+        let now = Utc::now();
+        assert_eq!(
+            NewsletterDelivery::queue(&pool, POST, now).await.unwrap(),
+            2
+        );
+        assert_eq!(
+            NewsletterDelivery::queue(&pool, POST, now).await.unwrap(),
+            0
+        );
+
+        // Confirming after the newsletter went out doesn't get you it
+        NewsletterSubscriber::confirm(&pool, ids[2]).await.unwrap();
+        assert_eq!(
+            NewsletterDelivery::queue(&pool, POST, now).await.unwrap(),
+            0
+        );
+
+        let batch = NewsletterDelivery::claim_pending(&pool, POST, 2, 1, CLAIM_FOR)
+            .await
+            .unwrap();
+        assert_eq!(batch.len(), 1);
+        NewsletterDelivery::record_failure(&pool, batch[0].newsletter_delivery_id, "550")
+            .await
+            .unwrap();
+
+        let pending = NewsletterDelivery::claim_pending(&pool, POST, 2, 10, CLAIM_FOR)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].attempts, 0, "untried deliveries go first");
+        NewsletterDelivery::mark_sent(&pool, pending[0].newsletter_delivery_id)
+            .await
+            .unwrap();
+        NewsletterDelivery::record_failure(&pool, pending[1].newsletter_delivery_id, "550")
+            .await
+            .unwrap();
+
+        assert!(
+            NewsletterDelivery::claim_pending(&pool, POST, 2, 10, CLAIM_FOR)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let deliveries = NewsletterDelivery::for_post(&pool, POST).await.unwrap();
+        assert_eq!(deliveries.iter().filter(|d| d.sent_at.is_some()).count(), 1);
+        let failed = deliveries.iter().find(|d| d.sent_at.is_none()).unwrap();
+        assert_eq!(failed.attempts, 2);
+        assert_eq!(failed.last_error.as_deref(), Some("550"));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn claimed_deliveries_are_not_handed_out_again_until_the_claim_is_stale(pool: PgPool) {
+        for email in ["a@example.com", "b@example.com"] {
+            let subscriber = NewsletterSubscriber::subscribe(&pool, email).await.unwrap();
+            NewsletterSubscriber::confirm(&pool, subscriber.newsletter_subscriber_id)
+                .await
+                .unwrap();
+        }
+        NewsletterDelivery::queue(&pool, POST, Utc::now())
+            .await
+            .unwrap();
+
+        let first = NewsletterDelivery::claim_pending(&pool, POST, 2, 1, CLAIM_FOR)
+            .await
+            .unwrap();
+        let second = NewsletterDelivery::claim_pending(&pool, POST, 2, 10, CLAIM_FOR)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        assert_ne!(
+            first[0].newsletter_delivery_id,
+            second[0].newsletter_delivery_id
+        );
+        assert!(
+            NewsletterDelivery::claim_pending(&pool, POST, 2, 10, CLAIM_FOR)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // A run that died without finishing its batch leaves it to be picked up later
+        let stale = NewsletterDelivery::claim_pending(&pool, POST, 2, 10, chrono::Duration::zero())
+            .await
+            .unwrap();
+        assert_eq!(stale.len(), 2);
+    }
+}
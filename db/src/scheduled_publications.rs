@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A scheduled post or note whose publication has been handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPublication {
+    pub scheduled_publication_id: Uuid,
+    pub content_path: String,
+    pub publish_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScheduledPublication {
+    pub async fn exists(pool: &PgPool, content_path: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM scheduled_publications WHERE content_path = $1
+            ) as "exists!"
+            "#,
+            content_path
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn record(
+        pool: &PgPool,
+        content_path: &str,
+        publish_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO scheduled_publications (content_path, publish_at)
+            VALUES ($1, $2)
+            ON CONFLICT (content_path) DO NOTHING
+            "#,
+            content_path,
+            publish_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
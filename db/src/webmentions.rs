@@ -0,0 +1,234 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Type};
+use uuid::Uuid;
+
+/// What a webmention's source page does with the target, from its microformats
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[sqlx(type_name = "text")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebmentionKind {
+    Reply,
+    Like,
+    Repost,
+    Mention,
+}
+
+impl fmt::Display for WebmentionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebmentionKind::Reply => write!(f, "reply"),
+            WebmentionKind::Like => write!(f, "like"),
+            WebmentionKind::Repost => write!(f, "repost"),
+            WebmentionKind::Mention => write!(f, "mention"),
+        }
+    }
+}
+
+/// A verified webmention of one of our posts or notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webmention {
+    pub webmention_id: Uuid,
+    pub source: String,
+    pub target: String,
+    pub target_path: String,
+    pub kind: WebmentionKind,
+    pub author_name: Option<String>,
+    pub author_url: Option<String>,
+    pub author_photo: Option<String>,
+    pub content: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The parts of a webmention parsed from its source page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebmentionDetails {
+    pub author_name: Option<String>,
+    pub author_url: Option<String>,
+    pub author_photo: Option<String>,
+    pub content: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl Webmention {
+    /// Store a verified mention, replacing what we had for the same source and target so
+    /// re-sent webmentions pick up edits
+    pub async fn upsert(
+        pool: &PgPool,
+        source: &str,
+        target: &str,
+        target_path: &str,
+        kind: WebmentionKind,
+        details: WebmentionDetails,
+    ) -> Result<Self> {
+        let mention = sqlx::query_as!(
+            Webmention,
+            r#"
+            INSERT INTO webmentions (
+                source, target, target_path, kind,
+                author_name, author_url, author_photo, content, published_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (source, target) DO UPDATE
+            SET target_path = EXCLUDED.target_path,
+                kind = EXCLUDED.kind,
+                author_name = EXCLUDED.author_name,
+                author_url = EXCLUDED.author_url,
+                author_photo = EXCLUDED.author_photo,
+                content = EXCLUDED.content,
+                published_at = EXCLUDED.published_at
+            RETURNING
+                webmention_id,
+                source,
+                target,
+                target_path,
+                kind as "kind: WebmentionKind",
+                author_name,
+                author_url,
+                author_photo,
+                content,
+                published_at,
+                created_at,
+                updated_at
+            "#,
+            source,
+            target,
+            target_path,
+            kind.to_string(),
+            details.author_name,
+            details.author_url,
+            details.author_photo,
+            details.content,
+            details.published_at,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(mention)
+    }
+
+    /// Forget a mention whose source no longer links to us. Returns whether one existed.
+    pub async fn delete(pool: &PgPool, source: &str, target: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM webmentions WHERE source = $1 AND target = $2",
+            source,
+            target
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Forget every mention from `source` by `author_url`, for sources that are retracted
+    /// without saying which of our pages they were about. Only the author can take a mention
+    /// back, so anyone else retracting it is a no-op. Returns how many there were.
+    pub async fn delete_by_source_and_author(
+        pool: &PgPool,
+        source: &str,
+        author_url: &str,
+    ) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM webmentions WHERE source = $1 AND author_url = $2",
+            source,
+            author_url
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Mentions of a post or note, oldest first so replies read as a conversation
+    pub async fn list_for_target_path(pool: &PgPool, target_path: &str) -> Result<Vec<Self>> {
+        let mentions = sqlx::query_as!(
+            Webmention,
+            r#"
+            SELECT
+                webmention_id,
+                source,
+                target,
+                target_path,
+                kind as "kind: WebmentionKind",
+                author_name,
+                author_url,
+                author_photo,
+                content,
+                published_at,
+                created_at,
+                updated_at
+            FROM webmentions
+            WHERE target_path = $1
+            ORDER BY COALESCE(published_at, created_at)
+            "#,
+            target_path
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(mentions)
+    }
+}
+
+/// A webmention we've sent, or tried to, for a link in one of our posts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentWebmention {
+    pub sent_webmention_id: Uuid,
+    pub source: String,
+    pub target: String,
+    pub endpoint: Option<String>,
+    pub status_code: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SentWebmention {
+    pub async fn exists(pool: &PgPool, source: &str, target: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sent_webmentions WHERE source = $1 AND target = $2
+            ) as "exists!"
+            "#,
+            source,
+            target
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn record(
+        pool: &PgPool,
+        source: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status_code: Option<i32>,
+    ) -> Result<Self> {
+        let sent = sqlx::query_as!(
+            SentWebmention,
+            r#"
+            INSERT INTO sent_webmentions (source, target, endpoint, status_code)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (source, target) DO UPDATE
+            SET endpoint = EXCLUDED.endpoint,
+                status_code = EXCLUDED.status_code
+            RETURNING *
+            "#,
+            source,
+            target,
+            endpoint,
+            status_code,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(sent)
+    }
+}
@@ -2,41 +2,49 @@ use path_absolutize::Absolutize;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, NaiveDate, Utc};
-use include_dir::{include_dir, Dir, File};
+use include_dir::{include_dir, Dir};
 use markdown::mdast::Node;
 use serde::{Deserialize, Serialize};
 
-use crate::{MarkdownAst, Post};
+use crate::{
+    metadata::{rank_related, Fingerprint},
+    plain::IntoPlainText,
+    source::{ContentDir, ContentSource, SourceFile},
+    MarkdownAst, Post,
+};
 
 use super::{
     date::{ByRecency, PostedOn},
+    publish::{Publishable, Published},
     title::Title,
 };
 
 use color_eyre::{eyre::Context, Result};
 
-static BLOG_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../blog");
+pub(crate) static BLOG_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../blog");
+
+/// How many related posts are suggested under each post
+const RELATED_POSTS: usize = 3;
 
 #[derive(Debug, Clone)]
 pub struct BlogPosts {
     posts: Vec<BlogPost>,
+    /// Indices into `posts` of each post's related posts, best first. Computed once here
+    /// since comparing every pair of posts is too slow to do per request.
+    related: Vec<Vec<usize>>,
 }
 
 pub type BlogPost = Post<BlogFrontMatter>;
 
 impl BlogPost {
-    pub(crate) fn from_file(file: &File) -> Result<BlogPost> {
-        let ast = MarkdownAst::from_file(file)?;
+    pub(crate) fn from_file(file: &SourceFile) -> Result<BlogPost> {
+        let ast = MarkdownAst::from_bytes(file.contents())?;
 
         let metadata: BlogFrontMatter = ast.frontmatter()?;
 
         let path = file.path().to_owned();
 
-        Ok(BlogPost {
-            frontmatter: metadata,
-            path,
-            ast,
-        })
+        Ok(BlogPost::new(metadata, ast, path))
     }
 
     pub fn path(&self) -> &Path {
@@ -64,13 +72,12 @@ impl BlogPost {
     /// Slug used for the OG card route. Strips `weekly/` for newsletter posts and trims
     /// the trailing slash from the canonical path.
     pub fn og_slug(&self) -> String {
-        let canonical = self.path.canonical_path();
-        let trimmed = canonical.trim_end_matches('/');
-        if self.frontmatter.is_newsletter {
-            trimmed.trim_start_matches("weekly/").to_string()
-        } else {
-            trimmed.to_string()
-        }
+        og_slug(&self.path, self.frontmatter.is_newsletter)
+    }
+
+    /// Route the post's OG card SVG is served from
+    pub fn og_card_route(&self) -> String {
+        og_card_route(&self.path, self.frontmatter.is_newsletter)
     }
 
     fn validate_images(&self) -> Result<()> {
@@ -154,18 +161,31 @@ impl ValidateMarkdown for Node {
 
 impl BlogPosts {
     pub fn from_static_dir() -> Result<Self> {
-        Self::from_dir(&BLOG_DIR)
+        Self::from_files(&ContentSource::Embedded.files(ContentDir::Blog)?)
     }
 
     pub fn from_dir(dir: &Dir) -> Result<Self> {
-        let posts = dir
-            .find("**/*.md")?
-            .filter_map(|e| e.as_file())
-            .map(BlogPost::from_file)
+        Self::from_files(&SourceFile::all_in(dir)?)
+    }
+
+    pub fn from_files(files: &[SourceFile]) -> Result<Self> {
+        let posts = files
+            .iter()
+            .filter(|f| f.is_markdown())
+            .map(|f| {
+                BlogPost::from_file(f)
+                    .wrap_err_with(|| format!("Failed to parse {}", f.path().display()))
+            })
             .collect::<Result<Vec<_>>>()
             .wrap_err("One of the blog posts failed to parse")?;
 
-        Ok(Self { posts })
+        let fingerprints: Vec<_> = posts
+            .iter()
+            .map(|p| Fingerprint::new(&p.frontmatter.tags, &p.ast.0.plain_text()))
+            .collect();
+        let related = rank_related(&fingerprints, RELATED_POSTS);
+
+        Ok(Self { posts, related })
     }
 
     pub fn posts(&self) -> &Vec<BlogPost> {
@@ -175,6 +195,26 @@ impl BlogPosts {
     pub fn by_recency(&self) -> Vec<&BlogPost> {
         self.posts.by_recency()
     }
+
+    /// Posts readers can see right now, most recent first
+    pub fn published(&self) -> Vec<&BlogPost> {
+        self.posts.published()
+    }
+
+    /// The published posts sharing the most tags and vocabulary with `post`, best first
+    pub fn related_posts(&self, post: &BlogPost) -> Vec<&BlogPost> {
+        self.posts
+            .iter()
+            .position(|p| p.path == post.path)
+            .map(|i| {
+                self.related[i]
+                    .iter()
+                    .map(|&j| &self.posts[j])
+                    .filter(|p| p.is_published())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 pub struct BlogPostPath {
@@ -243,10 +283,19 @@ pub struct BlogFrontMatter {
     #[serde(default = "default_is_newsletter")]
     pub is_newsletter: bool,
     pub bsky_url: Option<String>,
-    /// When to send the newsletter. If `None` and `is_newsletter` is true, send immediately.
+    /// Mastodon status the post was cross-posted as, whose replies are shown as comments
+    pub mastodon_url: Option<String>,
+    /// Shown as the Mastodon status's content warning, hiding the post behind it
+    pub content_warning: Option<String>,
+    /// When to send the newsletter. Buttondown schedules it for this time, and without one
+    /// the email is left as a draft to be sent by hand. When we send it ourselves over SMTP
+    /// it goes out at this time, or on the post's date without one.
     pub newsletter_send_at: Option<DateTime<Utc>>,
     /// Buttondown email ID, populated after publishing to Buttondown.
     pub buttondown_id: Option<String>,
+    /// Hash of the subject, body and send time last synced to Buttondown, so edits to the
+    /// post are pushed to its email.
+    pub buttondown_hash: Option<String>,
     /// Absolute URL of an OG image to use instead of the auto-generated branded card.
     pub og_image: Option<String>,
     /// Optional short subtitle/tagline shown on the OG card in place of the
@@ -254,6 +303,17 @@ pub struct BlogFrontMatter {
     pub subtitle: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Name of the series this post is part of. Posts and notes sharing a series are
+    /// linked in date order with previous/next navigation.
+    pub series: Option<String>,
+    /// Show a table of contents built from the post's headings
+    #[serde(default)]
+    pub toc: bool,
+    /// Drafts are only visible to admins, through the preview pages
+    #[serde(default)]
+    pub draft: bool,
+    /// Hide the post until this time, then publish it without a redeploy
+    pub publish_at: Option<DateTime<Utc>>,
     pub author: Option<String>,
     /// AT URI of the `site.standard.document` record on the PDS, set after
     /// the first successful sync. Acts as the idempotency key — its presence
@@ -284,6 +344,16 @@ impl PostedOn for BlogFrontMatter {
     }
 }
 
+impl Publishable for BlogFrontMatter {
+    fn draft(&self) -> bool {
+        self.draft
+    }
+
+    fn publish_at(&self) -> Option<DateTime<Utc>> {
+        self.publish_at
+    }
+}
+
 impl Title for BlogFrontMatter {
     fn title(&self) -> &str {
         &self.title
@@ -294,6 +364,27 @@ fn default_is_newsletter() -> bool {
     false
 }
 
+/// [`BlogPost::og_slug`] for a post that hasn't been loaded, by its path under `blog/`
+pub fn og_slug(path: &Path, is_newsletter: bool) -> String {
+    let canonical = path.to_path_buf().canonical_path();
+    let trimmed = canonical.trim_end_matches('/');
+    if is_newsletter {
+        trimmed.trim_start_matches("weekly/").to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// [`BlogPost::og_card_route`] for a post that hasn't been loaded, by its path under `blog/`
+pub fn og_card_route(path: &Path, is_newsletter: bool) -> String {
+    let slug = og_slug(path, is_newsletter);
+    if is_newsletter {
+        format!("/og/weekly/{slug}.svg")
+    } else {
+        format!("/og/posts/{slug}.svg")
+    }
+}
+
 pub trait ToCanonicalPath {
     fn canonical_path(&self) -> String;
 }
@@ -356,24 +447,31 @@ mod test {
             track: Track::default(),
             is_newsletter: false,
             bsky_url: None,
+            mastodon_url: None,
+            content_warning: None,
             newsletter_send_at: None,
             buttondown_id: None,
+            buttondown_hash: None,
             og_image: None,
             subtitle: None,
             tags: vec![],
+            series: None,
+            toc: false,
+            draft: false,
+            publish_at: None,
             author: None,
             atproto_uri: None,
             atproto_pub_cid: None,
             publication: "blog".to_string(),
         };
-        let post = BlogPost {
-            path,
-            ast: MarkdownAst(Root {
+        let post = BlogPost::new(
+            meta,
+            MarkdownAst(Root {
                 children: vec![],
                 position: None,
             }),
-            frontmatter: meta,
-        };
+            path,
+        );
 
         assert_eq!(post.matches_path("2020-01-01-test/"), Some(CanonicalPath));
         assert_eq!(
@@ -385,29 +483,36 @@ mod test {
     }
 
     fn test_post(path: &str, is_newsletter: bool) -> BlogPost {
-        BlogPost {
-            path: PathBuf::from(path),
-            ast: MarkdownAst(Root {
-                children: vec![],
-                position: None,
-            }),
-            frontmatter: BlogFrontMatter {
+        BlogPost::new(
+            BlogFrontMatter {
                 title: "T".to_string(),
                 date: NaiveDate::default(),
                 track: Track::default(),
                 is_newsletter,
                 bsky_url: None,
+                mastodon_url: None,
+                content_warning: None,
                 newsletter_send_at: None,
                 buttondown_id: None,
+                buttondown_hash: None,
                 og_image: None,
                 subtitle: None,
                 tags: vec![],
+                series: None,
+                toc: false,
+                draft: false,
+                publish_at: None,
                 author: None,
                 atproto_uri: None,
                 atproto_pub_cid: None,
                 publication: "blog".to_string(),
             },
-        }
+            MarkdownAst(Root {
+                children: vec![],
+                position: None,
+            }),
+            PathBuf::from(path),
+        )
     }
 
     #[test]
@@ -428,6 +533,15 @@ mod test {
         assert_eq!(post.og_slug(), "weekly/20230713");
     }
 
+    #[test]
+    fn og_card_route_uses_the_weekly_route_for_newsletters() {
+        let post = test_post("battlesnake-in-2026/index.md", false);
+        assert_eq!(post.og_card_route(), "/og/posts/battlesnake-in-2026.svg");
+
+        let newsletter = test_post("weekly/20230713/index.md", true);
+        assert_eq!(newsletter.og_card_route(), "/og/weekly/20230713.svg");
+    }
+
     #[test]
     fn frontmatter_defaults_apply_when_fields_absent() {
         let yaml = "title: T\ndate: 2026-05-01";
@@ -0,0 +1,155 @@
+//! Anchor ids for headings, and the table of contents built from them. Rendering and the
+//! table of contents both walk the document with a [`Slugger`], so their ids always agree.
+
+use std::collections::HashSet;
+
+use markdown::mdast::{Node, Root};
+
+/// Lowercased, with spaces turned into `-` and punctuation dropped, like GitHub's heading
+/// anchors. Headings with no usable text get `section`.
+pub fn slugify(text: &str) -> String {
+    let slug: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_whitespace() {
+                Some('-')
+            } else if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Hands out unique heading ids within one document. Repeats of a slug get `-1`, `-2`, …
+/// in the order they appear, so ids stay stable as long as earlier headings don't change.
+#[derive(Debug, Default)]
+pub struct Slugger {
+    seen: HashSet<String>,
+}
+
+impl Slugger {
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+
+        let mut slug = base.clone();
+        let mut n = 1;
+        while !self.seen.insert(slug.clone()) {
+            slug = format!("{base}-{n}");
+            n += 1;
+        }
+        slug
+    }
+}
+
+/// The text of a heading without any markup, so emphasis, links and code spans contribute
+/// just their text
+pub fn heading_text(children: &[Node]) -> String {
+    children
+        .iter()
+        .map(|node| match node {
+            Node::Text(t) => t.value.clone(),
+            Node::InlineCode(c) => c.value.clone(),
+            Node::InlineMath(m) => m.value.clone(),
+            other => other
+                .children()
+                .map(|children| heading_text(children))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    pub depth: u8,
+    pub text: String,
+    pub id: String,
+}
+
+/// Every heading in the document in order, with the id it's rendered with. Headings inside
+/// footnotes are skipped, as they are rendered separately after the content.
+pub fn table_of_contents(root: &Root) -> Vec<TocEntry> {
+    fn collect(nodes: &[Node], slugger: &mut Slugger, entries: &mut Vec<TocEntry>) {
+        for node in nodes {
+            match node {
+                Node::Heading(h) => {
+                    let text = heading_text(&h.children);
+                    entries.push(TocEntry {
+                        depth: h.depth,
+                        id: slugger.slug(&text),
+                        text,
+                    });
+                }
+                Node::FootnoteDefinition(_) => {}
+                other => {
+                    if let Some(children) = other.children() {
+                        collect(children, slugger, entries);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut entries = vec![];
+    collect(&root.children, &mut Slugger::default(), &mut entries);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::MarkdownAst;
+
+    use super::*;
+
+    #[test]
+    fn slugs_drop_punctuation_and_dedupe_in_order() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.slug("What's New?"), "whats-new");
+        assert_eq!(slugger.slug("Setup"), "setup");
+        assert_eq!(slugger.slug("Setup"), "setup-1");
+        assert_eq!(slugger.slug("Setup 1"), "setup-1-1");
+        assert_eq!(slugger.slug("Setup"), "setup-2");
+        assert_eq!(slugger.slug("🎉"), "section");
+    }
+
+    #[test]
+    fn table_of_contents_uses_the_plain_text_of_rich_headings() {
+        let ast = MarkdownAst::from_str(
+            "# Using **fzf** with `vim`\n\nSome text\n\n## Install\n\n> ## Install\n\n[^1]: ## Not in the contents\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            table_of_contents(&ast.0),
+            vec![
+                TocEntry {
+                    depth: 1,
+                    text: "Using fzf with vim".to_string(),
+                    id: "using-fzf-with-vim".to_string(),
+                },
+                TocEntry {
+                    depth: 2,
+                    text: "Install".to_string(),
+                    id: "install".to_string(),
+                },
+                TocEntry {
+                    depth: 2,
+                    text: "Install".to_string(),
+                    id: "install-1".to_string(),
+                },
+            ]
+        );
+    }
+}
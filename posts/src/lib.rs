@@ -10,24 +10,33 @@ use serde::Deserialize;
 
 use color_eyre::Result;
 
-use self::{blog::PostMarkdown, date::PostedOn, title::Title};
+use self::{blog::PostMarkdown, date::PostedOn, metadata::ReadingStats, title::Title};
 
 pub mod blog;
 pub mod notes;
 pub mod podcast;
 
 pub mod date;
+pub mod headings;
+pub mod metadata;
+pub mod publish;
 pub mod title;
 
 pub mod plain;
 
 pub mod projects;
 
+pub mod search;
+pub mod source;
+pub mod tags;
+
 #[derive(Debug, Clone)]
 pub struct Post<FrontmatterType> {
     pub frontmatter: FrontmatterType,
     pub ast: MarkdownAst,
     pub path: PathBuf,
+    /// Worked out from `ast` when the post is loaded, so lists don't re-walk every post
+    pub reading_stats: ReadingStats,
 }
 
 #[derive(Clone, Debug)]
@@ -39,8 +48,14 @@ impl FromStr for MarkdownAst {
     fn from_str(contents: &str) -> Result<Self> {
         let mut options: ParseOptions = ParseOptions::default();
         options.constructs.gfm_footnote_definition = true;
+        options.constructs.gfm_label_start_footnote = true;
         options.constructs.gfm_table = true;
         options.constructs.frontmatter = true;
+        options.constructs.math_flow = true;
+        options.constructs.math_text = true;
+        // Require doubled markers so prices like "$5 and $10" and approximations like
+        // "~5 to ~10 minutes" stay plain text
+        options.math_text_single_dollar = false;
 
         match to_mdast(contents, &options) {
             Ok(Node::Root(ast)) => Ok(Self(ast)),
@@ -55,7 +70,10 @@ impl FromStr for MarkdownAst {
 
 impl MarkdownAst {
     pub fn from_file(file: &File) -> Result<Self> {
-        let contents = file.contents();
+        Self::from_bytes(file.contents())
+    }
+
+    pub fn from_bytes(contents: &[u8]) -> Result<Self> {
         let contents = std::str::from_utf8(contents).wrap_err("File is not UTF8")?;
 
         Self::from_str(contents)
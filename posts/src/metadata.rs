@@ -0,0 +1,167 @@
+//! Metadata derived from a post's content rather than its frontmatter: how long it is to
+//! read, and which other posts are most like it.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::{plain::IntoPlainText, search::stems, tags::tag_slug, MarkdownAst, Post};
+
+/// Average adult silent reading speed for non-fiction
+pub const WORDS_PER_MINUTE: usize = 230;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadingStats {
+    pub word_count: usize,
+    /// Rounded up, and never less than a minute
+    pub reading_minutes: usize,
+}
+
+impl ReadingStats {
+    pub fn from_text(text: &str) -> Self {
+        let word_count = text.split_whitespace().count();
+
+        Self {
+            word_count,
+            reading_minutes: word_count.div_ceil(WORDS_PER_MINUTE).max(1),
+        }
+    }
+
+    /// e.g. `5 min read`
+    pub fn label(&self) -> String {
+        format!("{} min read", self.reading_minutes)
+    }
+}
+
+impl<FrontMatter> Post<FrontMatter> {
+    /// Counting words walks the whole AST, so it happens once here rather than per render
+    pub(crate) fn new(frontmatter: FrontMatter, ast: MarkdownAst, path: PathBuf) -> Self {
+        let reading_stats = ReadingStats::from_text(&ast.0.plain_text());
+
+        Self {
+            frontmatter,
+            ast,
+            path,
+            reading_stats,
+        }
+    }
+}
+
+/// A post's tags and word stems, for comparing it to other posts
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Fingerprint {
+    tag_slugs: HashSet<String>,
+    /// Stem frequencies, scaled to unit length so similarity is a plain dot product
+    stems: HashMap<String, f64>,
+}
+
+impl Fingerprint {
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn new(tags: &[String], text: &str) -> Self {
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for stem in stems(text) {
+            *counts.entry(stem).or_default() += 1.0;
+        }
+
+        let length = counts.values().map(|c| c * c).sum::<f64>().sqrt();
+        if length > 0.0 {
+            for count in counts.values_mut() {
+                *count /= length;
+            }
+        }
+
+        Self {
+            tag_slugs: tags.iter().map(|t| tag_slug(t)).collect(),
+            stems: counts,
+        }
+    }
+
+    /// How related two posts are. Each shared tag counts for a whole point and the cosine
+    /// similarity of their text adds up to one more, so tags decide the order and the text
+    /// breaks ties and finds related posts nobody tagged.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn relatedness(&self, other: &Self) -> f64 {
+        let shared_tags = self.tag_slugs.intersection(&other.tag_slugs).count() as f64;
+
+        let (smaller, larger) = if self.stems.len() < other.stems.len() {
+            (&self.stems, &other.stems)
+        } else {
+            (&other.stems, &self.stems)
+        };
+        let text_similarity: f64 = smaller
+            .iter()
+            .filter_map(|(stem, weight)| larger.get(stem).map(|w| w * weight))
+            .sum();
+
+        shared_tags + text_similarity
+    }
+}
+
+/// Posts with less than this in common aren't suggested, even if there's nothing better
+const MIN_RELATEDNESS: f64 = 0.1;
+
+/// For each fingerprint, the indices of the most related others, best first
+pub(crate) fn rank_related(fingerprints: &[Fingerprint], limit: usize) -> Vec<Vec<usize>> {
+    fingerprints
+        .iter()
+        .enumerate()
+        .map(|(i, fingerprint)| {
+            let mut scored: Vec<(usize, f64)> = fingerprints
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(j, other)| (j, fingerprint.relatedness(other)))
+                .filter(|(_, score)| *score >= MIN_RELATEDNESS)
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+            scored.into_iter().take(limit).map(|(j, _)| j).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn reading_time_rounds_up_to_whole_minutes() {
+        assert_eq!(
+            ReadingStats::from_text(""),
+            ReadingStats {
+                word_count: 0,
+                reading_minutes: 1
+            }
+        );
+        assert_eq!(
+            ReadingStats::from_text(&"word ".repeat(231)).reading_minutes,
+            2
+        );
+        assert_eq!(
+            ReadingStats::from_text(&"word ".repeat(460)).label(),
+            "2 min read"
+        );
+    }
+
+    #[test]
+    fn shared_tags_outrank_similar_text() {
+        let fingerprints = vec![
+            Fingerprint::new(&tags(&["Rust"]), "Writing a battlesnake in rust"),
+            Fingerprint::new(&tags(&["rust"]), "Cooking dinner tonight"),
+            Fingerprint::new(&[], "My battlesnake got better at writing"),
+            Fingerprint::new(&[], "Gardening in the spring"),
+        ];
+
+        let related = rank_related(&fingerprints, 3);
+
+        assert_eq!(related[0], vec![1, 2]);
+        assert_eq!(related[1], vec![0]);
+        assert_eq!(related[2], vec![0]);
+        assert!(related[3].is_empty());
+    }
+}
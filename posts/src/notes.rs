@@ -1,15 +1,19 @@
 use std::path::PathBuf;
 
-use chrono::NaiveDate;
-use include_dir::{include_dir, Dir, File};
+use chrono::{DateTime, NaiveDate, Utc};
+use include_dir::{include_dir, Dir};
 use markdown::mdast::Node;
 use serde::{Deserialize, Serialize};
 
-use crate::{MarkdownAst, Post};
+use crate::{
+    source::{ContentDir, ContentSource, SourceFile},
+    MarkdownAst, Post,
+};
 
 use super::{
     blog::{Track, ValidateMarkdown},
     date::{ByRecency, PostedOn},
+    publish::{Publishable, Published},
     title::Title,
 };
 
@@ -30,10 +34,31 @@ pub struct FrontMatter {
     pub date: NaiveDate,
     pub slug: String,
     pub bsky_url: Option<String>,
+    /// Syndicate to Bluesky as a thread of replies when the note is too long for one post,
+    /// instead of truncating it
+    #[serde(default)]
+    pub bsky_thread: bool,
+    /// Mastodon status the note was cross-posted as, whose replies are shown as comments
+    pub mastodon_url: Option<String>,
+    /// Shown as the Mastodon status's content warning, hiding the note behind it
+    pub content_warning: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Series this note is part of, shared with blog posts (see `BlogFrontMatter::series`)
+    pub series: Option<String>,
     #[serde(default)]
     pub track: Track,
+    /// Drafts are only visible to admins, through the preview pages
+    #[serde(default)]
+    pub draft: bool,
+    /// Hide the note until this time, then publish it without a redeploy
+    pub publish_at: Option<DateTime<Utc>>,
+    /// AT URI of the note's `site.standard.document` record, like
+    /// `BlogFrontMatter::atproto_uri`
+    pub atproto_uri: Option<String>,
+    /// CID of the `notes` publication record the document was last synced against, like
+    /// `BlogFrontMatter::atproto_pub_cid`
+    pub atproto_pub_cid: Option<String>,
 }
 
 impl PostedOn for FrontMatter {
@@ -42,6 +67,16 @@ impl PostedOn for FrontMatter {
     }
 }
 
+impl Publishable for FrontMatter {
+    fn draft(&self) -> bool {
+        self.draft
+    }
+
+    fn publish_at(&self) -> Option<DateTime<Utc>> {
+        self.publish_at
+    }
+}
+
 impl Title for FrontMatter {
     fn title(&self) -> &str {
         &self.title
@@ -49,16 +84,12 @@ impl Title for FrontMatter {
 }
 
 impl NotePost {
-    fn from_file(file: &File) -> Result<Self> {
-        let ast = MarkdownAst::from_file(file)?;
+    fn from_file(file: &SourceFile) -> Result<Self> {
+        let ast = MarkdownAst::from_bytes(file.contents())?;
         let metadata: FrontMatter = ast.frontmatter()?;
         let path = file.path().to_owned();
 
-        Ok(Self {
-            ast,
-            path,
-            frontmatter: metadata,
-        })
+        Ok(Self::new(metadata, ast, path))
     }
 
     pub(crate) fn validate(&self) -> Result<()> {
@@ -80,14 +111,21 @@ impl NotePost {
 
 impl NotePosts {
     pub fn from_static_dir() -> Result<Self> {
-        Self::from_dir(&NOTES_DIR)
+        Self::from_files(&ContentSource::Embedded.files(ContentDir::Notes)?)
     }
 
     pub fn from_dir(dir: &Dir) -> Result<Self> {
-        let posts = dir
-            .find("**/*.md")?
-            .filter_map(|e| e.as_file())
-            .map(NotePost::from_file)
+        Self::from_files(&SourceFile::all_in(dir)?)
+    }
+
+    pub fn from_files(files: &[SourceFile]) -> Result<Self> {
+        let posts = files
+            .iter()
+            .filter(|f| f.is_markdown())
+            .map(|f| {
+                NotePost::from_file(f)
+                    .wrap_err_with(|| format!("Failed to parse {}", f.path().display()))
+            })
             .collect::<Result<Vec<_>>>()
             .wrap_err("One of the notes failed to parse")?;
 
@@ -134,6 +172,11 @@ impl NotePosts {
     pub fn by_recency(&self) -> Vec<&NotePost> {
         self.posts.by_recency()
     }
+
+    /// Notes readers can see right now, most recent first
+    pub fn published(&self) -> Vec<&NotePost> {
+        self.posts.published()
+    }
 }
 
 #[cfg(test)]
@@ -223,6 +266,23 @@ bsky_url: https://bsky.app/profile/coreyja.com/post/abc123
         );
     }
 
+    #[test]
+    fn frontmatter_deserializes_with_mastodon_url_and_content_warning() {
+        let yaml = r"
+title: Tooted Note
+date: 2026-10-18
+slug: tooted-note
+mastodon_url: https://hachyderm.io/@coreyja/113000000000000000
+content_warning: Food, spoilers
+";
+        let fm: FrontMatter = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            fm.mastodon_url,
+            Some("https://hachyderm.io/@coreyja/113000000000000000".to_string())
+        );
+        assert_eq!(fm.content_warning, Some("Food, spoilers".to_string()));
+    }
+
     #[test]
     fn frontmatter_roundtrips_through_serde() {
         let yaml = r"
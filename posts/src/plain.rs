@@ -24,6 +24,7 @@ impl IntoPlainText for Node {
             Node::Delete(x) => surround_with("~", &x.children.plain_text()),
             Node::Emphasis(x) => surround_with("*", &x.children.plain_text()),
             Node::Link(x) => x.children.plain_text(),
+            Node::LinkReference(x) => x.children.plain_text(),
             Node::Strong(x) => surround_with("*", &x.children.plain_text()),
             Node::Text(x) => x.value.clone(),
             Node::Code(x) => surround_with("\n```\n", &x.value),
@@ -46,7 +47,6 @@ impl IntoPlainText for Node {
             | Node::Image(_)
             | Node::ImageReference(_)
             | Node::MdxJsxTextElement(_)
-            | Node::LinkReference(_)
             | Node::MdxFlowExpression(_)
             | Node::ThematicBreak(_) => String::new(),
         }
@@ -1,8 +1,11 @@
 use chrono::NaiveDate;
-use include_dir::{include_dir, Dir, File};
+use include_dir::{include_dir, Dir};
 use serde::{Deserialize, Serialize};
 
-use crate::{MarkdownAst, Post};
+use crate::{
+    source::{ContentDir, ContentSource, SourceFile},
+    MarkdownAst, Post,
+};
 
 use super::{
     date::{ByRecency, PostedOn},
@@ -11,7 +14,7 @@ use super::{
 
 use color_eyre::{eyre::Context, Result};
 
-static PODCAST_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../podcast");
+pub(crate) static PODCAST_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../podcast");
 
 #[derive(Debug, Clone)]
 pub struct PodcastEpisodes {
@@ -36,6 +39,12 @@ pub struct PodcastFrontMatter {
     pub transcript_url: Option<String>,
     /// Absolute URL of an OG image to use instead of the auto-generated branded card.
     pub og_image: Option<String>,
+    /// AT URI of the episode's `site.standard.document` record, like
+    /// `BlogFrontMatter::atproto_uri`
+    pub atproto_uri: Option<String>,
+    /// CID of the `podcast` publication record the document was last synced against, like
+    /// `BlogFrontMatter::atproto_pub_cid`
+    pub atproto_pub_cid: Option<String>,
 }
 
 impl PostedOn for PodcastFrontMatter {
@@ -51,28 +60,31 @@ impl Title for PodcastFrontMatter {
 }
 
 impl PodcastEpisode {
-    fn from_file(file: &File) -> Result<Self> {
-        let ast = MarkdownAst::from_file(file)?;
+    fn from_file(file: &SourceFile) -> Result<Self> {
+        let ast = MarkdownAst::from_bytes(file.contents())?;
         let metadata: PodcastFrontMatter = ast.frontmatter()?;
         let path = file.path().to_owned();
-        Ok(Self {
-            ast,
-            path,
-            frontmatter: metadata,
-        })
+        Ok(Self::new(metadata, ast, path))
     }
 }
 
 impl PodcastEpisodes {
     pub fn from_static_dir() -> Result<Self> {
-        Self::from_dir(&PODCAST_DIR)
+        Self::from_files(&ContentSource::Embedded.files(ContentDir::Podcast)?)
     }
 
     pub fn from_dir(dir: &Dir) -> Result<Self> {
-        let episodes = dir
-            .find("**/*.md")?
-            .filter_map(|e| e.as_file())
-            .map(PodcastEpisode::from_file)
+        Self::from_files(&SourceFile::all_in(dir)?)
+    }
+
+    pub fn from_files(files: &[SourceFile]) -> Result<Self> {
+        let episodes = files
+            .iter()
+            .filter(|f| f.is_markdown())
+            .map(|f| {
+                PodcastEpisode::from_file(f)
+                    .wrap_err_with(|| format!("Failed to parse {}", f.path().display()))
+            })
             .collect::<Result<Vec<_>>>()
             .wrap_err("One of the podcast episodes failed to parse")?;
         Ok(Self { episodes })
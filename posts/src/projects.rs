@@ -5,7 +5,11 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::Url;
 
-use crate::{title::Title, MarkdownAst, Post};
+use crate::{
+    source::{ContentDir, ContentSource, SourceFile},
+    title::Title,
+    MarkdownAst, Post,
+};
 use color_eyre::{eyre::Context, Result};
 
 pub(crate) static PROJECTS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../projects");
@@ -85,14 +89,21 @@ pub type Project = Post<FrontMatterWithKey>;
 
 impl Projects {
     pub fn from_static_dir() -> Result<Self> {
-        Self::from_dir(&PROJECTS_DIR)
+        Self::from_files(&ContentSource::Embedded.files(ContentDir::Projects)?)
     }
 
     pub fn from_dir(dir: &Dir) -> Result<Self> {
-        let projects = dir
-            .find("**/*.md")?
-            .filter_map(|e| e.as_file())
-            .map(Project::from_file)
+        Self::from_files(&SourceFile::all_in(dir)?)
+    }
+
+    pub fn from_files(files: &[SourceFile]) -> Result<Self> {
+        let projects = files
+            .iter()
+            .filter(|f| f.is_markdown())
+            .map(|f| {
+                Project::from_file(f, files)
+                    .wrap_err_with(|| format!("Failed to parse {}", f.path().display()))
+            })
             .collect::<Result<Vec<_>>>()
             .wrap_err("One of the Projects failed to parse")?;
 
@@ -133,8 +144,10 @@ impl Projects {
 }
 
 impl Project {
-    fn from_file(file: &include_dir::File) -> Result<Self> {
-        let ast = MarkdownAst::from_file(file)?;
+    /// `files` is everything in the projects directory, where a project's public key sits
+    /// beside its markdown
+    fn from_file(file: &SourceFile, files: &[SourceFile]) -> Result<Self> {
+        let ast = MarkdownAst::from_bytes(file.contents())?;
         let metadata: FrontMatter = ast.frontmatter()?;
         let path = file.path().to_owned();
 
@@ -145,17 +158,19 @@ impl Project {
             std::env::var("AUTH_KEY_FILENAME").unwrap_or_else(|_| "key.pub.pem".to_string());
         pub_key_path.push(key_filename);
 
-        let pub_key_path = &pub_key_path;
-        // THIS IS A HACK!
-        // it will only work from static but thats the only way i use it
-        let pub_key_file = PROJECTS_DIR.get_file(pub_key_path);
-        let pub_key = pub_key_file.map(|f| f.contents_utf8().unwrap().to_string());
+        let pub_key_file = files.iter().find(|f| f.path() == pub_key_path);
+        let pub_key = pub_key_file
+            .map(|f| {
+                String::from_utf8(f.contents().to_vec())
+                    .wrap_err_with(|| format!("{} is not UTF8", f.path().display()))
+            })
+            .transpose()?;
 
-        Ok(Self {
+        Ok(Self::new(
+            FrontMatterWithKey::from_frontmatter(metadata, pub_key),
             ast,
             path,
-            frontmatter: FrontMatterWithKey::from_frontmatter(metadata, pub_key),
-        })
+        ))
     }
 
     pub fn validate(&self, projects: &Projects) -> Result<()> {
@@ -0,0 +1,113 @@
+//! The `draft` and `publish_at` frontmatter fields, for content that's committed and
+//! deployed but not live yet. Visibility is checked against the clock on every request, so
+//! scheduled content appears at its time without a redeploy.
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    date::{ByRecency, PostedOn},
+    Post,
+};
+
+pub trait Publishable {
+    fn draft(&self) -> bool;
+
+    fn publish_at(&self) -> Option<DateTime<Utc>>;
+
+    /// Whether readers can see this at `now`: it isn't a draft and any `publish_at` has passed
+    fn is_published_at(&self, now: DateTime<Utc>) -> bool {
+        !self.draft() && self.publish_at().is_none_or(|at| at <= now)
+    }
+
+    fn is_published(&self) -> bool {
+        self.is_published_at(Utc::now())
+    }
+}
+
+impl<FrontMatter> Publishable for Post<FrontMatter>
+where
+    FrontMatter: Publishable,
+{
+    fn draft(&self) -> bool {
+        self.frontmatter.draft()
+    }
+
+    fn publish_at(&self) -> Option<DateTime<Utc>> {
+        self.frontmatter.publish_at()
+    }
+}
+
+pub trait Published<Item> {
+    /// Only what readers can see right now, most recent first
+    fn published(&self) -> Vec<&Item>;
+
+    /// Drafts and scheduled content, most recent first, for admins to preview
+    fn unpublished(&self) -> Vec<&Item>;
+}
+
+impl<T> Published<T> for Vec<T>
+where
+    T: PostedOn + Publishable,
+{
+    fn published(&self) -> Vec<&T> {
+        let now = Utc::now();
+        self.by_recency()
+            .into_iter()
+            .filter(|item| item.is_published_at(now))
+            .collect()
+    }
+
+    fn unpublished(&self) -> Vec<&T> {
+        let now = Utc::now();
+        self.by_recency()
+            .into_iter()
+            .filter(|item| !item.is_published_at(now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct Content {
+        draft: bool,
+        publish_at: Option<DateTime<Utc>>,
+    }
+
+    impl Publishable for Content {
+        fn draft(&self) -> bool {
+            self.draft
+        }
+
+        fn publish_at(&self) -> Option<DateTime<Utc>> {
+            self.publish_at
+        }
+    }
+
+    #[test]
+    fn drafts_stay_hidden_and_scheduled_content_appears_at_its_time() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        let before = at - chrono::Duration::minutes(1);
+
+        let live = Content {
+            draft: false,
+            publish_at: None,
+        };
+        let scheduled = Content {
+            draft: false,
+            publish_at: Some(at),
+        };
+        let draft = Content {
+            draft: true,
+            publish_at: Some(before),
+        };
+
+        assert!(live.is_published_at(before));
+        assert!(!scheduled.is_published_at(before));
+        assert!(scheduled.is_published_at(at));
+        assert!(!draft.is_published_at(at));
+    }
+}
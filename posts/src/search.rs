@@ -0,0 +1,449 @@
+//! In-memory full-text search across the site's content.
+//!
+//! The index is built once at boot from already-flattened documents (see
+//! [`crate::plain::IntoPlainText`]). Text is split into lowercase words, stop words are
+//! dropped and the rest are reduced to their English stems, so "testing" finds "tests".
+//! Results are ranked with BM25, with matches in titles and tags weighted above matches
+//! in the body.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+/// BM25 document length normalization
+const B: f64 = 0.75;
+
+const TITLE_WEIGHT: u32 = 5;
+const TAG_WEIGHT: u32 = 3;
+
+/// Number of words shown in a result snippet
+const SNIPPET_WORDS: usize = 30;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "if", "in", "into",
+    "is", "it", "its", "of", "on", "or", "so", "that", "the", "their", "then", "there", "these",
+    "this", "to", "was", "were", "will", "with",
+];
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Post,
+    Note,
+    Podcast,
+    Project,
+}
+
+impl ContentKind {
+    pub const ALL: [ContentKind; 4] = [
+        ContentKind::Post,
+        ContentKind::Note,
+        ContentKind::Podcast,
+        ContentKind::Project,
+    ];
+}
+
+impl Display for ContentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentKind::Post => f.write_str("post"),
+            ContentKind::Note => f.write_str("note"),
+            ContentKind::Podcast => f.write_str("podcast"),
+            ContentKind::Project => f.write_str("project"),
+        }
+    }
+}
+
+impl FromStr for ContentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "post" => Ok(ContentKind::Post),
+            "note" => Ok(ContentKind::Note),
+            "podcast" => Ok(ContentKind::Podcast),
+            "project" => Ok(ContentKind::Project),
+            _ => Err(format!("Unknown content kind: {s}")),
+        }
+    }
+}
+
+/// A piece of content to index. `body` is the plain text of the content's markdown.
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    pub kind: ContentKind,
+    pub title: String,
+    pub url: String,
+    pub date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub body: String,
+    /// Scheduled content is indexed at boot but left out of results until this time
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    document: usize,
+    frequency: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    documents: Vec<SearchDocument>,
+    postings: HashMap<String, Vec<Posting>>,
+    lengths: Vec<u32>,
+    average_length: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    pub document: &'a SearchDocument,
+    pub score: f64,
+    pub snippet: Snippet,
+}
+
+/// An excerpt of a document's body, split into runs of plain and highlighted text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub parts: Vec<SnippetPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+impl Snippet {
+    fn push(&mut self, text: &str, highlighted: bool) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.parts.last_mut() {
+            Some(last) if last.highlighted == highlighted => last.text.push_str(text),
+            _ => self.parts.push(SnippetPart {
+                text: text.to_string(),
+                highlighted,
+            }),
+        }
+    }
+
+    pub fn plain_text(&self) -> String {
+        self.parts.iter().map(|p| p.text.as_str()).collect()
+    }
+}
+
+/// A word in some text, with its byte range and stemmed form
+struct Term {
+    start: usize,
+    end: usize,
+    stem: Option<String>,
+}
+
+fn terms(text: &str) -> Vec<Term> {
+    let stemmer = Stemmer::create(Algorithm::English);
+
+    let mut terms = vec![];
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word = text[s..i].to_lowercase();
+                let stem = (!STOP_WORDS.contains(&word.as_str()))
+                    .then(|| stemmer.stem(&word).into_owned());
+                terms.push(Term {
+                    start: s,
+                    end: i,
+                    stem,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    terms
+}
+
+pub(crate) fn stems(text: &str) -> impl Iterator<Item = String> {
+    terms(text).into_iter().filter_map(|t| t.stem)
+}
+
+impl SearchIndex {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(documents: Vec<SearchDocument>) -> Self {
+        let mut documents = documents;
+        for document in &mut documents {
+            document.body = document
+                .body
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+
+        let mut postings: HashMap<String, Vec<Posting>> = HashMap::new();
+        let mut lengths = Vec::with_capacity(documents.len());
+
+        for (i, document) in documents.iter().enumerate() {
+            let mut frequencies: HashMap<String, u32> = HashMap::new();
+            let mut add = |text: &str, weight: u32| {
+                let mut length = 0;
+                for stem in stems(text) {
+                    *frequencies.entry(stem).or_default() += weight;
+                    length += weight;
+                }
+                length
+            };
+
+            let length = add(&document.title, TITLE_WEIGHT)
+                + document
+                    .tags
+                    .iter()
+                    .map(|tag| add(tag, TAG_WEIGHT))
+                    .sum::<u32>()
+                + add(&document.body, 1);
+            lengths.push(length);
+
+            for (stem, frequency) in frequencies {
+                postings.entry(stem).or_default().push(Posting {
+                    document: i,
+                    frequency,
+                });
+            }
+        }
+
+        let average_length = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().map(|l| f64::from(*l)).sum::<f64>() / lengths.len() as f64
+        };
+
+        Self {
+            documents,
+            postings,
+            lengths,
+            average_length,
+        }
+    }
+
+    pub fn documents(&self) -> &[SearchDocument] {
+        &self.documents
+    }
+
+    /// Every document matching at least one word of `query`, best match first. Documents
+    /// matching more of the query's words always outrank those matching fewer.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let query_stems: HashSet<String> = stems(query).collect();
+        if query_stems.is_empty() {
+            return vec![];
+        }
+
+        let total = self.documents.len() as f64;
+        let mut scores: HashMap<usize, (usize, f64)> = HashMap::new();
+        for stem in &query_stems {
+            let Some(postings) = self.postings.get(stem) else {
+                continue;
+            };
+
+            let matching = postings.len() as f64;
+            let idf = (1.0 + (total - matching + 0.5) / (matching + 0.5)).ln();
+            for posting in postings {
+                let frequency = f64::from(posting.frequency);
+                let length = f64::from(self.lengths[posting.document]);
+                let normalized = frequency * (K1 + 1.0)
+                    / (frequency + K1 * (1.0 - B + B * length / self.average_length));
+
+                let (matched, score) = scores.entry(posting.document).or_default();
+                *matched += 1;
+                *score += idf * normalized;
+            }
+        }
+
+        let now = Utc::now();
+        let mut ranked: Vec<_> = scores
+            .into_iter()
+            .filter(|(i, _)| self.documents[*i].publish_at.is_none_or(|at| at <= now))
+            .collect();
+        ranked.sort_by(
+            |(a_doc, (a_matched, a_score)), (b_doc, (b_matched, b_score))| {
+                b_matched
+                    .cmp(a_matched)
+                    .then(b_score.total_cmp(a_score))
+                    .then(a_doc.cmp(b_doc))
+            },
+        );
+
+        ranked
+            .into_iter()
+            .map(|(i, (_, score))| {
+                let document = &self.documents[i];
+                SearchHit {
+                    document,
+                    score,
+                    snippet: snippet(&document.body, &query_stems),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The window of the body with the most query matches, with the matching words highlighted
+fn snippet(body: &str, query_stems: &HashSet<String>) -> Snippet {
+    let words = terms(body);
+    if words.is_empty() {
+        return Snippet::default();
+    }
+
+    let is_match = |t: &Term| t.stem.as_ref().is_some_and(|s| query_stems.contains(s));
+
+    let window = SNIPPET_WORDS.min(words.len());
+    let mut matches = words[..window].iter().filter(|t| is_match(t)).count();
+    let (mut best_start, mut best_matches) = (0, matches);
+    for start in 1..=(words.len() - window) {
+        matches -= usize::from(is_match(&words[start - 1]));
+        matches += usize::from(is_match(&words[start + window - 1]));
+        if matches > best_matches {
+            (best_start, best_matches) = (start, matches);
+        }
+    }
+    let window = &words[best_start..best_start + window];
+
+    let mut snippet = Snippet::default();
+    if best_start > 0 {
+        snippet.push("… ", false);
+    }
+    let mut cursor = window[0].start;
+    for word in window.iter().filter(|t| is_match(t)) {
+        snippet.push(&body[cursor..word.start], false);
+        snippet.push(&body[word.start..word.end], true);
+        cursor = word.end;
+    }
+    let end = window[window.len() - 1].end;
+    snippet.push(&body[cursor..end], false);
+    if end < body.trim_end().len() {
+        snippet.push(" …", false);
+    }
+
+    snippet
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn document(kind: ContentKind, title: &str, body: &str) -> SearchDocument {
+        SearchDocument {
+            kind,
+            title: title.to_string(),
+            url: format!("/{}", title.to_lowercase().replace(' ', "-")),
+            date: None,
+            tags: vec![],
+            body: body.to_string(),
+            publish_at: None,
+        }
+    }
+
+    fn titles(hits: &[SearchHit<'_>]) -> Vec<String> {
+        hits.iter().map(|h| h.document.title.clone()).collect()
+    }
+
+    #[test]
+    fn stems_so_word_forms_match() {
+        let index = SearchIndex::new(vec![
+            document(ContentKind::Post, "Testing Axum", "We tested the handlers."),
+            document(ContentKind::Note, "Cooking", "Nothing relevant here."),
+        ]);
+
+        assert_eq!(titles(&index.search("tests")), vec!["Testing Axum"]);
+        assert_eq!(titles(&index.search("HANDLER")), vec!["Testing Axum"]);
+        assert!(index.search("the").is_empty());
+    }
+
+    #[test]
+    fn title_matches_outrank_body_matches() {
+        let index = SearchIndex::new(vec![
+            document(
+                ContentKind::Post,
+                "Battlesnake Recap",
+                "A long post about many things.",
+            ),
+            document(
+                ContentKind::Note,
+                "Weekly Update",
+                "I played some battlesnake this week.",
+            ),
+        ]);
+
+        assert_eq!(
+            titles(&index.search("battlesnake")),
+            vec!["Battlesnake Recap", "Weekly Update"]
+        );
+    }
+
+    #[test]
+    fn documents_matching_more_terms_rank_first() {
+        let index = SearchIndex::new(vec![
+            document(ContentKind::Post, "Rust Rust Rust", "rust rust rust rust"),
+            document(ContentKind::Project, "Sqlx", "Using rust with postgres."),
+        ]);
+
+        assert_eq!(
+            titles(&index.search("rust postgres")),
+            vec!["Sqlx", "Rust Rust Rust"]
+        );
+    }
+
+    #[test]
+    fn snippet_highlights_matches_in_best_window() {
+        let filler = "word ".repeat(50);
+        let body = format!("{filler}the deploying of servers is fun\n\nand {filler}");
+        let index = SearchIndex::new(vec![document(ContentKind::Post, "Ops", &body)]);
+
+        let hits = index.search("deploy server");
+        let snippet = &hits[0].snippet;
+
+        assert!(snippet.plain_text().starts_with("… "));
+        assert!(snippet.plain_text().ends_with(" …"));
+        assert!(!snippet.plain_text().contains('\n'));
+        let highlighted: Vec<_> = snippet
+            .parts
+            .iter()
+            .filter(|p| p.highlighted)
+            .map(|p| p.text.as_str())
+            .collect();
+        assert_eq!(highlighted, vec!["deploying", "servers"]);
+    }
+
+    #[test]
+    fn scheduled_documents_are_hidden_until_their_time() {
+        let mut scheduled = document(ContentKind::Post, "Upcoming", "rust");
+        scheduled.publish_at = Some(Utc::now() + chrono::Duration::hours(1));
+        let mut past = document(ContentKind::Post, "Already out", "rust");
+        past.publish_at = Some(Utc::now() - chrono::Duration::hours(1));
+        let index = SearchIndex::new(vec![scheduled, past]);
+
+        assert_eq!(titles(&index.search("rust")), vec!["Already out"]);
+    }
+
+    #[test]
+    fn content_kind_roundtrips_through_strings() {
+        for kind in ContentKind::ALL {
+            assert_eq!(kind.to_string().parse::<ContentKind>(), Ok(kind));
+        }
+        assert!("video".parse::<ContentKind>().is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Where the blog, notes, podcast and project content is read from. Deployed servers use the
+//! copies embedded at compile time; in development the content directories can be read from
+//! disk and watched, so editing a post doesn't need a recompile.
+
+use std::{
+    borrow::Cow,
+    path::{Component, Path, PathBuf},
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::{eyre::Context, Result};
+use include_dir::Dir;
+
+use crate::{
+    blog::{BlogPosts, BLOG_DIR},
+    notes::{NotePosts, NOTES_DIR},
+    podcast::{PodcastEpisodes, PODCAST_DIR},
+    projects::{Projects, PROJECTS_DIR},
+};
+
+/// A file from one of the content directories, with its path relative to that directory
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    path: PathBuf,
+    contents: Cow<'static, [u8]>,
+}
+
+impl SourceFile {
+    pub fn new(path: impl Into<PathBuf>, contents: impl Into<Cow<'static, [u8]>>) -> Self {
+        Self {
+            path: path.into(),
+            contents: contents.into(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+
+    pub fn into_contents(self) -> Cow<'static, [u8]> {
+        self.contents
+    }
+
+    pub fn is_markdown(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+    }
+
+    /// Every file in an embedded directory, copied since `dir` may not outlive them
+    pub(crate) fn all_in(dir: &Dir) -> Result<Vec<Self>> {
+        Ok(dir
+            .find("**/*")?
+            .filter_map(|e| e.as_file())
+            .map(|f| Self::new(f.path(), f.contents().to_vec()))
+            .collect())
+    }
+
+    /// Like [`Self::all_in`], but borrows the files' contents instead of copying them
+    fn all_in_static(dir: &'static Dir<'static>) -> Result<Vec<Self>> {
+        Ok(dir
+            .find("**/*")?
+            .filter_map(|e| e.as_file())
+            .map(|f| Self::new(f.path(), f.contents()))
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentDir {
+    Blog,
+    Notes,
+    Podcast,
+    Projects,
+}
+
+impl ContentDir {
+    pub const ALL: [ContentDir; 4] = [Self::Blog, Self::Notes, Self::Podcast, Self::Projects];
+
+    /// The directory's name, under the repo root
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blog => "blog",
+            Self::Notes => "notes",
+            Self::Podcast => "podcast",
+            Self::Projects => "projects",
+        }
+    }
+
+    fn embedded(self) -> &'static Dir<'static> {
+        match self {
+            Self::Blog => &BLOG_DIR,
+            Self::Notes => &NOTES_DIR,
+            Self::Podcast => &PODCAST_DIR,
+            Self::Projects => &PROJECTS_DIR,
+        }
+    }
+}
+
+/// Everything parsed from the content directories
+#[derive(Debug, Clone)]
+pub struct Content {
+    pub blog_posts: BlogPosts,
+    pub note_posts: NotePosts,
+    pub podcast_episodes: PodcastEpisodes,
+    pub projects: Projects,
+}
+
+impl Content {
+    /// No content at all, for when the content on disk doesn't parse yet
+    pub fn empty() -> Self {
+        Self {
+            blog_posts: BlogPosts::from_files(&[]).expect("No posts always parse"),
+            note_posts: NotePosts::from_files(&[]).expect("No notes always parse"),
+            podcast_episodes: PodcastEpisodes::from_files(&[]).expect("No episodes always parse"),
+            projects: Projects::from_files(&[]).expect("No projects always parse"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentSource {
+    /// The content directories as they were when the binary was compiled
+    Embedded,
+    /// The content directories under this root, read from disk every time they're loaded
+    Disk(PathBuf),
+}
+
+impl ContentSource {
+    /// Reads from disk under `CONTENT_DIR` when it's set, and uses the embedded content
+    /// otherwise
+    pub fn from_env() -> Self {
+        match std::env::var("CONTENT_DIR") {
+            Ok(root) if !root.is_empty() => Self::Disk(PathBuf::from(root)),
+            _ => Self::Embedded,
+        }
+    }
+
+    pub fn files(&self, dir: ContentDir) -> Result<Vec<SourceFile>> {
+        match self {
+            Self::Embedded => SourceFile::all_in_static(dir.embedded()),
+            Self::Disk(root) => {
+                let base = root.join(dir.name());
+                let mut files = vec![];
+                read_dir_recursive(&base, &base, &mut files)?;
+                Ok(files)
+            }
+        }
+    }
+
+    /// A single file, like an image that sits next to a post. Paths that would escape the
+    /// content directory are never found.
+    pub fn file(&self, dir: ContentDir, path: &str) -> Option<SourceFile> {
+        let path = Path::new(path);
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return None;
+        }
+
+        match self {
+            Self::Embedded => dir
+                .embedded()
+                .get_file(path)
+                .map(|f| SourceFile::new(f.path(), f.contents())),
+            Self::Disk(root) => std::fs::read(root.join(dir.name()).join(path))
+                .ok()
+                .map(|contents| SourceFile::new(path, contents)),
+        }
+    }
+
+    pub fn load(&self) -> Result<Content> {
+        Ok(Content {
+            blog_posts: BlogPosts::from_files(&self.files(ContentDir::Blog)?)?,
+            note_posts: NotePosts::from_files(&self.files(ContentDir::Notes)?)?,
+            podcast_episodes: PodcastEpisodes::from_files(&self.files(ContentDir::Podcast)?)?,
+            projects: Projects::from_files(&self.files(ContentDir::Projects)?)?,
+        })
+    }
+
+    /// Reload whenever a file under one of the content directories is added, removed or
+    /// modified, checking every `interval`. Embedded content never changes, so there's
+    /// nothing to watch and this returns `None`.
+    pub fn watch<F>(&self, interval: Duration, on_change: F) -> Option<JoinHandle<()>>
+    where
+        F: Fn(Result<Content>) + Send + 'static,
+    {
+        let Self::Disk(root) = self else {
+            return None;
+        };
+
+        let source = self.clone();
+        let root = root.clone();
+        // Taken before returning so changes made right after this call are still noticed
+        let mut last_seen = snapshot(&root);
+        Some(std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+
+            let seen = snapshot(&root);
+            if seen != last_seen {
+                last_seen = seen;
+                on_change(source.load());
+            }
+        }))
+    }
+}
+
+fn read_dir_recursive(base: &Path, dir: &Path, files: &mut Vec<SourceFile>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).wrap_err_with(|| format!("Failed to read {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_dir_recursive(base, &path, files)?;
+        } else {
+            let contents = std::fs::read(&path)
+                .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+            let relative = path.strip_prefix(base)?.to_owned();
+            files.push(SourceFile::new(relative, contents));
+        }
+    }
+
+    Ok(())
+}
+
+/// Path, modified time and size of every file under the content directories, which changes
+/// whenever any of them is edited
+fn snapshot(root: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    fn walk(dir: &Path, out: &mut Vec<(PathBuf, Option<SystemTime>, u64)>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, out);
+            } else if let Ok(metadata) = entry.metadata() {
+                out.push((path, metadata.modified().ok(), metadata.len()));
+            }
+        }
+    }
+
+    let mut seen = vec![];
+    for dir in ContentDir::ALL {
+        walk(&root.join(dir.name()), &mut seen);
+    }
+    seen.sort();
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Hello\ndate: 2026-10-18\nslug: hello\n---\n\nHi there\n";
+
+    fn content_root() -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ContentDir::ALL {
+            std::fs::create_dir_all(root.path().join(dir.name())).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn embedded_content_matches_the_static_dirs() {
+        let content = ContentSource::Embedded.load().unwrap();
+
+        assert_eq!(
+            content.blog_posts.posts().len(),
+            BlogPosts::from_static_dir().unwrap().posts().len()
+        );
+        assert!(!content.note_posts.posts.is_empty());
+    }
+
+    #[test]
+    fn files_outside_the_content_dir_are_never_found() {
+        let root = content_root();
+        std::fs::write(root.path().join("secret.txt"), "nope").unwrap();
+        let source = ContentSource::Disk(root.path().to_path_buf());
+
+        assert!(source.file(ContentDir::Blog, "../secret.txt").is_none());
+        assert!(source.file(ContentDir::Blog, "/etc/hostname").is_none());
+        assert!(ContentSource::Embedded
+            .file(ContentDir::Blog, "../notes/x.md")
+            .is_none());
+    }
+
+    #[test]
+    fn disk_content_is_reloaded_when_it_changes() {
+        let root = content_root();
+        let source = ContentSource::Disk(root.path().to_path_buf());
+        assert!(source.load().unwrap().note_posts.posts.is_empty());
+
+        let (tx, rx) = mpsc::channel();
+        source.watch(Duration::from_millis(10), move |content| {
+            let _ = tx.send(content.map_err(|e| format!("{e:#}")));
+        });
+
+        std::fs::write(root.path().join("notes/hello.md"), NOTE).unwrap();
+        // The watcher can catch the file half written, so wait for the load that parses
+        let content = loop {
+            if let Ok(content) = rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+                break content;
+            }
+        };
+        assert_eq!(content.note_posts.posts[0].frontmatter.slug, "hello");
+
+        std::fs::write(root.path().join("notes/broken.md"), "---\ntitle: [\n---\n").unwrap();
+        let error = rx
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .unwrap_err();
+        assert!(error.contains("broken.md"), "{error}");
+    }
+
+    #[test]
+    fn unreadable_project_keys_are_load_errors() {
+        let root = content_root();
+        std::fs::write(
+            root.path().join("projects/demo.md"),
+            "---\ntitle: Demo\nrepo: https://github.com/coreyja/demo\nstatus: active\n---\n\nA demo\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.path().join("projects/demo")).unwrap();
+        std::fs::write(root.path().join("projects/demo/key.pub.pem"), [0xff, 0xfe]).unwrap();
+
+        let error = ContentSource::Disk(root.path().to_path_buf())
+            .load()
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("not UTF8"), "{error:#}");
+    }
+}
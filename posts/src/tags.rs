@@ -0,0 +1,126 @@
+//! Helpers for the free-form `tags` and `series` frontmatter fields, which are grouped by
+//! their slug so "GitHub Actions" and "github-actions" land on the same page.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Lowercased, with every run of non-alphanumeric characters collapsed to a single `-`
+pub fn tag_slug(tag: &str) -> String {
+    tag.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Pairs of tag spellings that probably mean the same thing: the same slug spelled
+/// differently ("Minimax" and "minimax"), singular and plural forms ("test" and
+/// "tests"), or longer slugs a single typo apart ("devicons" and "devicns").
+///
+/// Each pair is reported once, sorted, with the spellings as they appear in frontmatter.
+pub fn near_duplicate_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    let spellings: BTreeSet<&str> = tags.into_iter().map(str::trim).collect();
+
+    let mut by_slug: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for spelling in &spellings {
+        by_slug
+            .entry(tag_slug(spelling))
+            .or_default()
+            .push(spelling);
+    }
+
+    let mut pairs = vec![];
+    for spellings in by_slug.values() {
+        for (i, a) in spellings.iter().enumerate() {
+            for b in &spellings[i + 1..] {
+                pairs.push(((*a).to_string(), (*b).to_string()));
+            }
+        }
+    }
+
+    let slugs: Vec<&String> = by_slug.keys().collect();
+    for (i, a) in slugs.iter().enumerate() {
+        for b in &slugs[i + 1..] {
+            if slugs_are_similar(a, b) {
+                pairs.push((by_slug[*a][0].to_string(), by_slug[*b][0].to_string()));
+            }
+        }
+    }
+
+    pairs
+}
+
+fn slugs_are_similar(a: &str, b: &str) -> bool {
+    let plural = |singular: &str, plural: &str| {
+        plural
+            .strip_suffix("es")
+            .is_some_and(|stem| stem == singular)
+            || plural
+                .strip_suffix('s')
+                .is_some_and(|stem| stem == singular)
+    };
+
+    plural(a, b)
+        || plural(b, a)
+        || (a.chars().count().min(b.chars().count()) >= 5 && edit_distance(a, b) == 1)
+}
+
+/// Levenshtein distance, counting each inserted, removed or replaced character as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slugs_ignore_case_and_separators() {
+        assert_eq!(tag_slug("GitHub Actions"), "github-actions");
+        assert_eq!(tag_slug("github_actions"), "github-actions");
+        assert_eq!(tag_slug("  Rust! "), "rust");
+    }
+
+    #[test]
+    fn finds_near_duplicate_spellings() {
+        let pairs = near_duplicate_tags([
+            "battlesnake",
+            "Minimax",
+            "minimax",
+            "test",
+            "tests",
+            "devicons",
+            "devicns",
+            "vim",
+            "vin",
+            "rust",
+        ]);
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("Minimax".to_string(), "minimax".to_string()),
+                ("devicns".to_string(), "devicons".to_string()),
+                ("test".to_string(), "tests".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_single_character_edits() {
+        assert_eq!(edit_distance("kitten", "sitten"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
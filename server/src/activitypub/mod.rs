@@ -0,0 +1,652 @@
+//! The site as an ActivityPub actor, so Fediverse accounts can follow it directly and see
+//! new posts and notes in their timelines. Blog posts federate as `Article`s and notes as
+//! `Note`s, and likes, boosts and replies sent to our inbox show up with the webmentions.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use cja::color_eyre::eyre::{eyre, Context as _};
+use rsa::{
+    pkcs8::{DecodePrivateKey, EncodePublicKey, LineEnding},
+    RsaPrivateKey, RsaPublicKey,
+};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use url::Url;
+
+use crate::{
+    http_server::{feeds::FeedItem, feeds::ToFeedItem, LinkTo},
+    state::AppConfig,
+    webmention::{is_fetchable, is_web_url, public_client_builder},
+    AppState,
+};
+
+pub mod signatures;
+
+/// The `acct:` name the site answers to, as in `@coreyja@coreyja.com`
+pub const USERNAME: &str = "coreyja";
+
+pub const ACTOR_PATH: &str = "/ap/actor";
+pub const INBOX_PATH: &str = "/ap/inbox";
+pub const OUTBOX_PATH: &str = "/ap/outbox";
+pub const FOLLOWERS_PATH: &str = "/ap/followers";
+/// Objects live under this prefix followed by the page's own path, so
+/// `/posts/some-post/` is `/ap/objects/posts/some-post/`
+pub const OBJECTS_PATH: &str = "/ap/objects";
+
+/// The content type ActivityPub documents are served and sent with
+pub const ACTIVITY_CONTENT_TYPE: &str = "application/activity+json";
+
+pub const PUBLIC_COLLECTION: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// How long a fetched actor, and the key that came with it, is used before fetching it again
+const ACTOR_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_CACHED_ACTORS: usize = 10_000;
+
+/// Actor fetches allowed to one host per [`ACTOR_FETCH_WINDOW`]. Anyone can make the inbox
+/// fetch a key by naming it in a signature, so this keeps it from being used to hammer a
+/// server.
+const MAX_ACTOR_FETCHES_PER_HOST: u32 = 10;
+const ACTOR_FETCH_WINDOW: Duration = Duration::from_secs(60);
+
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    public_client_builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("coreyja.com ActivityPub (+https://coreyja.com)")
+        .build()
+        .expect("reqwest client builds with a static config")
+});
+
+/// The key the actor signs its requests with
+#[derive(Clone)]
+pub(crate) struct ActivityPubConfig {
+    pub private_key: RsaPrivateKey,
+    pub public_key_pem: String,
+}
+
+impl std::fmt::Debug for ActivityPubConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActivityPubConfig")
+            .field("public_key_pem", &self.public_key_pem)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ActivityPubConfig {
+    pub(crate) fn new(private_key: RsaPrivateKey) -> cja::Result<Self> {
+        let public_key_pem = RsaPublicKey::from(&private_key).to_public_key_pem(LineEnding::LF)?;
+
+        Ok(Self {
+            private_key,
+            public_key_pem,
+        })
+    }
+
+    /// Federation is only on when `ACTIVITYPUB_PRIVATE_KEY` is set, to a PKCS#8 PEM RSA key
+    pub(crate) fn from_env() -> cja::Result<Option<Self>> {
+        let pem = match std::env::var("ACTIVITYPUB_PRIVATE_KEY") {
+            Ok(pem) if !pem.is_empty() => pem,
+            _ => return Ok(None),
+        };
+        let private_key =
+            RsaPrivateKey::from_pkcs8_pem(&pem).wrap_err("Invalid ACTIVITYPUB_PRIVATE_KEY")?;
+
+        Self::new(private_key).map(Some)
+    }
+}
+
+pub fn actor_id(config: &AppConfig) -> String {
+    config.app_url(ACTOR_PATH)
+}
+
+pub fn key_id(config: &AppConfig) -> String {
+    format!("{}#main-key", actor_id(config))
+}
+
+/// The id of the object for the page at `relative_link`
+pub fn object_id(config: &AppConfig, relative_link: &str) -> String {
+    config.app_url(&format!("{OBJECTS_PATH}{relative_link}"))
+}
+
+/// The page path an object id, or the page's own URL, points at. Replies and likes can
+/// name either, depending on whether they came from the federated object or a pasted link.
+pub fn page_path(config: &AppConfig, id: &str) -> Option<String> {
+    let url = Url::parse(id).ok()?;
+    if url.host_str() != config.base_url.host_str() {
+        return None;
+    }
+
+    let path = url.path();
+    Some(
+        path.strip_prefix(OBJECTS_PATH)
+            .filter(|p| p.starts_with('/'))
+            .unwrap_or(path)
+            .to_string(),
+    )
+}
+
+/// The id of an activity's `object` (or any other link), whether it's given inline or just
+/// by id
+pub fn id_of(value: &JsonValue) -> Option<&str> {
+    value
+        .as_str()
+        .or_else(|| value.get("id").and_then(JsonValue::as_str))
+}
+
+/// Whether two URLs are on the same server, scheme and port included
+pub fn same_origin(a: &str, b: &str) -> bool {
+    match (Url::parse(a), Url::parse(b)) {
+        (Ok(a), Ok(b)) => a.origin().is_tuple() && a.origin() == b.origin(),
+        _ => false,
+    }
+}
+
+pub fn actor_document(config: &AppConfig, public_key_pem: &str) -> JsonValue {
+    let id = actor_id(config);
+
+    json!({
+        "@context": [
+            "https://www.w3.org/ns/activitystreams",
+            "https://w3id.org/security/v1",
+        ],
+        "id": id,
+        "type": "Person",
+        "preferredUsername": USERNAME,
+        "name": "coreyja",
+        "summary": "Posts and notes from coreyja.com",
+        "url": config.app_url("/"),
+        "icon": {
+            "type": "Image",
+            "mediaType": "image/jpeg",
+            "url": config.app_url("/static/headshot.jpg"),
+        },
+        "inbox": config.app_url(INBOX_PATH),
+        "outbox": config.app_url(OUTBOX_PATH),
+        "followers": config.app_url(FOLLOWERS_PATH),
+        "manuallyApprovesFollowers": false,
+        "discoverable": true,
+        "publicKey": {
+            "id": key_id(config),
+            "owner": id,
+            "publicKeyPem": public_key_pem,
+        },
+    })
+}
+
+/// A post or note as an ActivityPub object, addressed to everyone and copied to followers
+pub fn object_document(
+    config: &AppConfig,
+    kind: &str,
+    relative_link: &str,
+    item: &FeedItem,
+) -> JsonValue {
+    let mut object = json!({
+        "id": object_id(config, relative_link),
+        "type": kind,
+        "attributedTo": actor_id(config),
+        "name": item.title,
+        "content": item.content_html,
+        "url": item.url,
+        "published": item.published.to_rfc3339(),
+        "to": [PUBLIC_COLLECTION],
+        "cc": [config.app_url(FOLLOWERS_PATH)],
+    });
+    if let Some(summary) = &item.summary {
+        object["summary"] = json!(summary);
+    }
+
+    object
+}
+
+/// The `Create` activity announcing an object
+pub fn create_activity(config: &AppConfig, object: JsonValue) -> JsonValue {
+    let object_id = id_of(&object).unwrap_or_default().to_string();
+
+    json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": format!("{object_id}#create"),
+        "type": "Create",
+        "actor": actor_id(config),
+        "published": object["published"],
+        "to": object["to"],
+        "cc": object["cc"],
+        "object": object,
+    })
+}
+
+/// A published post or note, federated
+#[derive(Debug, Clone)]
+pub struct PublishedObject {
+    /// Path of the page it's for
+    pub path: String,
+    pub object: JsonValue,
+}
+
+/// Every published post and note as an object, newest first
+pub fn published_objects(state: &AppState) -> cja::Result<Vec<PublishedObject>> {
+    fn objects<T>(
+        state: &AppState,
+        kind: &str,
+        posts: Vec<&posts::Post<T>>,
+    ) -> cja::Result<Vec<(chrono::DateTime<Utc>, PublishedObject)>>
+    where
+        posts::Post<T>: ToFeedItem + LinkTo,
+    {
+        posts
+            .into_iter()
+            .map(|post| {
+                let item = post.to_feed_item(&state.app, &state.syntax_highlighting_context)?;
+                let path = post.relative_link();
+                let object = object_document(&state.app, kind, &path, &item);
+                Ok((item.published, PublishedObject { path, object }))
+            })
+            .collect()
+    }
+
+    let blog_posts = state.blog_posts();
+    let note_posts = state.note_posts();
+    let mut all = objects(state, "Article", blog_posts.published())?;
+    all.extend(objects(state, "Note", note_posts.published())?);
+    all.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(all.into_iter().map(|(_, object)| object).collect())
+}
+
+/// The path of the published post or note an object id or page URL refers to
+pub fn find_published_path(state: &AppState, id: &str) -> Option<String> {
+    let path = page_path(&state.app, id)?;
+    let path = path.trim_end_matches('/');
+
+    let blog_posts = state.blog_posts();
+    let note_posts = state.note_posts();
+    blog_posts
+        .published()
+        .into_iter()
+        .map(LinkTo::relative_link)
+        .chain(
+            note_posts
+                .published()
+                .into_iter()
+                .map(LinkTo::relative_link),
+        )
+        .find(|link| link.trim_end_matches('/') == path)
+}
+
+/// The parts of a remote actor we use
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteActor {
+    pub id: String,
+    pub inbox: String,
+    #[serde(default)]
+    pub endpoints: Option<ActorEndpoints>,
+    pub public_key: ActorPublicKey,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub preferred_username: Option<String>,
+    #[serde(default)]
+    pub url: Option<JsonValue>,
+    #[serde(default)]
+    pub icon: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorEndpoints {
+    #[serde(default)]
+    pub shared_inbox: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorPublicKey {
+    pub id: String,
+    pub owner: String,
+    pub public_key_pem: String,
+}
+
+impl RemoteActor {
+    /// The server-wide inbox, if the actor's own server has one
+    pub fn shared_inbox(&self) -> Option<&str> {
+        self.endpoints
+            .as_ref()?
+            .shared_inbox
+            .as_deref()
+            .filter(|inbox| same_origin(inbox, &self.id))
+    }
+
+    pub fn display_name(&self) -> Option<String> {
+        self.name
+            .clone()
+            .filter(|n| !n.is_empty())
+            .or_else(|| self.preferred_username.clone())
+    }
+
+    /// The actor's profile page, falling back to its id. Only a web page on the actor's own
+    /// server is trusted as their profile, since it's linked to wherever they show up.
+    pub fn profile_url(&self) -> String {
+        self.url
+            .as_ref()
+            .and_then(id_of)
+            .filter(|url| is_web_url(url) && same_origin(url, &self.id))
+            .unwrap_or(&self.id)
+            .to_string()
+    }
+
+    pub fn avatar_url(&self) -> Option<String> {
+        self.icon
+            .as_ref()
+            .and_then(|icon| icon.get("url"))
+            .and_then(id_of)
+            .filter(|url| is_web_url(url))
+            .map(str::to_string)
+    }
+}
+
+/// Actors fetched recently, and how often each host has been fetched from
+#[derive(Debug, Default)]
+struct ActorFetches {
+    cached: HashMap<String, (Instant, RemoteActor)>,
+    per_host: HashMap<String, (Instant, u32)>,
+}
+
+impl ActorFetches {
+    fn cached(&self, url: &str, now: Instant) -> Option<RemoteActor> {
+        self.cached
+            .get(url)
+            .filter(|(fetched_at, _)| now.duration_since(*fetched_at) < ACTOR_CACHE_TTL)
+            .map(|(_, actor)| actor.clone())
+    }
+
+    /// Count a fetch from `host`, returning false if it's already had its share this window
+    fn allow_fetch(&mut self, host: &str, now: Instant) -> bool {
+        self.per_host
+            .retain(|_, (started, _)| now.duration_since(*started) < ACTOR_FETCH_WINDOW);
+
+        let (_, count) = self.per_host.entry(host.to_string()).or_insert((now, 0));
+        if *count >= MAX_ACTOR_FETCHES_PER_HOST {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    fn store(&mut self, url: String, actor: RemoteActor, now: Instant) {
+        if self.cached.len() >= MAX_CACHED_ACTORS {
+            self.cached
+                .retain(|_, (fetched_at, _)| now.duration_since(*fetched_at) < ACTOR_CACHE_TTL);
+        }
+        if self.cached.len() >= MAX_CACHED_ACTORS {
+            self.cached.clear();
+        }
+        self.cached.insert(url, (now, actor));
+    }
+}
+
+static ACTOR_FETCHES: LazyLock<Mutex<ActorFetches>> = LazyLock::new(Mutex::default);
+
+fn actor_fetches() -> std::sync::MutexGuard<'static, ActorFetches> {
+    ACTOR_FETCHES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Fetch a remote actor, signing the request since many servers refuse unsigned fetches.
+/// Actors are cached for [`ACTOR_CACHE_TTL`] and fetches are rate limited per host, since
+/// the inbox fetches whatever key an incoming signature names before it can check anything.
+pub async fn fetch_actor(
+    ap: &ActivityPubConfig,
+    config: &AppConfig,
+    actor_url: &str,
+) -> cja::Result<RemoteActor> {
+    let mut url = Url::parse(actor_url)?;
+    // Key ids are usually the actor with a `#main-key` fragment
+    url.set_fragment(None);
+    if !is_fetchable(&url) {
+        return Err(eyre!("Refusing to fetch actor {url}"));
+    }
+
+    let cached = actor_fetches().cached(url.as_str(), Instant::now());
+    if let Some(actor) = cached {
+        return Ok(actor);
+    }
+    let host = url.host_str().unwrap_or_default().to_string();
+    let allowed = actor_fetches().allow_fetch(&host, Instant::now());
+    if !allowed {
+        return Err(eyre!(
+            "Too many actor fetches from {host}, not fetching {url}"
+        ));
+    }
+
+    let headers = signatures::sign_request(
+        &ap.private_key,
+        &key_id(config),
+        "GET",
+        &url,
+        None,
+        Utc::now(),
+    )?;
+    let mut request = HTTP_CLIENT
+        .get(url.clone())
+        .header(reqwest::header::ACCEPT, ACTIVITY_CONTENT_TYPE);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let actor = request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RemoteActor>()
+        .await
+        .wrap_err_with(|| format!("Invalid actor at {url}"))?;
+
+    // Only the actor's own server can speak for it, and we only deliver to inboxes there
+    if !same_origin(&actor.id, url.as_str()) {
+        return Err(eyre!("Actor at {url} claims to be {}", actor.id));
+    }
+    if !same_origin(&actor.inbox, &actor.id) {
+        return Err(eyre!(
+            "Actor {} has an inbox on another server: {}",
+            actor.id,
+            actor.inbox
+        ));
+    }
+
+    actor_fetches().store(url.to_string(), actor.clone(), Instant::now());
+
+    Ok(actor)
+}
+
+/// POST a signed activity to an inbox
+pub async fn deliver(
+    ap: &ActivityPubConfig,
+    config: &AppConfig,
+    inbox: &str,
+    activity: &JsonValue,
+) -> cja::Result<()> {
+    let url = Url::parse(inbox)?;
+    if !is_fetchable(&url) {
+        return Err(eyre!("Refusing to deliver to {url}"));
+    }
+
+    let body = serde_json::to_vec(activity)?;
+    let headers = signatures::sign_request(
+        &ap.private_key,
+        &key_id(config),
+        "POST",
+        &url,
+        Some(&body),
+        Utc::now(),
+    )?;
+    let mut request = HTTP_CLIENT
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, ACTIVITY_CONTENT_TYPE);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    request.body(body).send().await?.error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            base_url: Url::parse("https://coreyja.com").unwrap(),
+            imgproxy_url: None,
+        }
+    }
+
+    #[test]
+    fn object_ids_map_back_to_their_page() {
+        let config = config();
+        let id = object_id(&config, "/posts/some-post/");
+        assert_eq!(id, "https://coreyja.com/ap/objects/posts/some-post/");
+        assert_eq!(
+            page_path(&config, &id).as_deref(),
+            Some("/posts/some-post/")
+        );
+    }
+
+    #[test]
+    fn page_urls_are_their_own_path() {
+        let config = config();
+        assert_eq!(
+            page_path(&config, "https://coreyja.com/notes/hello").as_deref(),
+            Some("/notes/hello")
+        );
+        assert_eq!(
+            page_path(&config, "https://example.social/notes/hello"),
+            None
+        );
+        assert_eq!(page_path(&config, "not a url"), None);
+    }
+
+    #[test]
+    fn objects_are_public_and_copied_to_followers() {
+        let config = config();
+        let item = FeedItem {
+            title: "Hello".to_string(),
+            url: "https://coreyja.com/notes/hello".to_string(),
+            summary: None,
+            content_html: "<p>Hi!</p>".to_string(),
+            published: Utc::now(),
+        };
+
+        let activity = create_activity(
+            &config,
+            object_document(&config, "Note", "/notes/hello", &item),
+        );
+        assert_eq!(
+            activity["id"],
+            "https://coreyja.com/ap/objects/notes/hello#create"
+        );
+        assert_eq!(activity["actor"], "https://coreyja.com/ap/actor");
+        assert_eq!(activity["to"], json!([PUBLIC_COLLECTION]));
+        assert_eq!(activity["cc"], json!(["https://coreyja.com/ap/followers"]));
+        assert_eq!(activity["object"]["type"], "Note");
+        assert!(activity["object"].get("summary").is_none());
+    }
+
+    #[test]
+    fn remote_actors_fall_back_to_their_username_and_id() {
+        let actor: RemoteActor = serde_json::from_value(json!({
+            "id": "https://example.social/users/friend",
+            "inbox": "https://example.social/users/friend/inbox",
+            "preferredUsername": "friend",
+            "name": "",
+            "publicKey": {
+                "id": "https://example.social/users/friend#main-key",
+                "owner": "https://example.social/users/friend",
+                "publicKeyPem": "pem",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(actor.display_name().as_deref(), Some("friend"));
+        assert_eq!(actor.profile_url(), "https://example.social/users/friend");
+        assert_eq!(actor.shared_inbox(), None);
+        assert_eq!(actor.avatar_url(), None);
+    }
+
+    #[test]
+    fn remote_actors_only_link_to_web_pages_on_their_own_server() {
+        let actor = |url: &str, shared_inbox: &str| -> RemoteActor {
+            serde_json::from_value(json!({
+                "id": "https://example.social/users/friend",
+                "inbox": "https://example.social/users/friend/inbox",
+                "endpoints": { "sharedInbox": shared_inbox },
+                "url": url,
+                "icon": { "url": "javascript:alert(1)" },
+                "publicKey": {
+                    "id": "https://example.social/users/friend#main-key",
+                    "owner": "https://example.social/users/friend",
+                    "publicKeyPem": "pem",
+                },
+            }))
+            .unwrap()
+        };
+
+        let friend = actor(
+            "https://example.social/@friend",
+            "https://example.social/inbox",
+        );
+        assert_eq!(friend.profile_url(), "https://example.social/@friend");
+        assert_eq!(friend.shared_inbox(), Some("https://example.social/inbox"));
+        assert_eq!(friend.avatar_url(), None);
+
+        for url in ["javascript:alert(1)", "https://elsewhere.example/@friend"] {
+            let impostor = actor(url, "https://elsewhere.example/inbox");
+            assert_eq!(
+                impostor.profile_url(),
+                "https://example.social/users/friend"
+            );
+            assert_eq!(impostor.shared_inbox(), None);
+        }
+    }
+
+    #[test]
+    fn actor_fetches_are_limited_per_host() {
+        let mut fetches = ActorFetches::default();
+        let now = Instant::now();
+
+        for _ in 0..MAX_ACTOR_FETCHES_PER_HOST {
+            assert!(fetches.allow_fetch("example.social", now));
+        }
+        assert!(!fetches.allow_fetch("example.social", now));
+        assert!(fetches.allow_fetch("other.example", now));
+        assert!(fetches.allow_fetch("example.social", now + ACTOR_FETCH_WINDOW));
+    }
+
+    #[test]
+    fn cached_actors_expire() {
+        let actor: RemoteActor = serde_json::from_value(json!({
+            "id": "https://example.social/users/friend",
+            "inbox": "https://example.social/users/friend/inbox",
+            "publicKey": {
+                "id": "https://example.social/users/friend#main-key",
+                "owner": "https://example.social/users/friend",
+                "publicKeyPem": "pem",
+            },
+        }))
+        .unwrap();
+        let mut fetches = ActorFetches::default();
+        let now = Instant::now();
+        fetches.store(actor.id.clone(), actor, now);
+
+        assert!(fetches
+            .cached("https://example.social/users/friend", now)
+            .is_some());
+        assert!(fetches
+            .cached("https://example.social/users/friend", now + ACTOR_CACHE_TTL)
+            .is_none());
+    }
+}
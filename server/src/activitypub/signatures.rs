@@ -0,0 +1,249 @@
+//! [HTTP signatures](https://datatracker.ietf.org/doc/html/draft-cavage-http-signatures-12),
+//! as the Fediverse uses them: `rsa-sha256` over `(request-target)`, `host`, `date` and,
+//! for requests with a body, `digest`.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use cja::color_eyre::eyre::eyre;
+use rsa::{
+    pkcs1v15::{Signature, SigningKey, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::{SignatureEncoding, Signer, Verifier},
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// How far a request's `Date` can be from now, either way, before its signature is refused
+/// as a possible replay. Mastodon allows the same.
+const MAX_CLOCK_SKEW: chrono::Duration = chrono::Duration::hours(12);
+
+/// Headers every signed request to us has to cover
+const REQUIRED_HEADERS: [&str; 4] = ["(request-target)", "host", "date", "digest"];
+
+/// A parsed `Signature` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeader {
+    /// The key to verify with, usually `{actor}#main-key`
+    pub key_id: String,
+    /// Which headers were signed, in signing order
+    pub headers: Vec<String>,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    pub fn parse(value: &str) -> cja::Result<Self> {
+        let mut key_id = None;
+        let mut headers = None;
+        let mut signature = None;
+
+        for param in value.split(',') {
+            let Some((name, value)) = param.trim().split_once('=') else {
+                continue;
+            };
+            let value = value.trim_matches('"');
+            match name {
+                "keyId" => key_id = Some(value.to_string()),
+                "headers" => {
+                    headers = Some(value.split_whitespace().map(str::to_lowercase).collect());
+                }
+                "signature" => signature = Some(STANDARD.decode(value)?),
+                "algorithm" if !matches!(value, "rsa-sha256" | "hs2019") => {
+                    return Err(eyre!("Unsupported signature algorithm {value}"));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            key_id: key_id.ok_or_else(|| eyre!("Signature has no keyId"))?,
+            // Without a list, only the date is signed
+            headers: headers.unwrap_or_else(|| vec!["date".to_string()]),
+            signature: signature.ok_or_else(|| eyre!("Signature has no signature"))?,
+        })
+    }
+
+    /// Check the signature covers everything it has to
+    pub fn check_covers_required_headers(&self) -> cja::Result<()> {
+        match REQUIRED_HEADERS
+            .iter()
+            .find(|required| !self.headers.iter().any(|h| h == *required))
+        {
+            Some(missing) => Err(eyre!("Signature doesn't cover {missing}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The `Digest` header for a body
+pub fn digest_header(body: &[u8]) -> String {
+    format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// The `Date` header for a request sent now
+pub fn date_header(now: DateTime<Utc>) -> String {
+    now.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether a request's `Date` header is close enough to `now` to trust
+pub fn date_is_fresh(date: &str, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc2822(date)
+        .is_ok_and(|date| (now - date.with_timezone(&Utc)).abs() <= MAX_CLOCK_SKEW)
+}
+
+/// The string that gets signed: one `name: value` line per signed header, with the
+/// pseudo-header `(request-target)` as the lowercased method and path
+pub fn signing_string<'a>(
+    method: &str,
+    path_and_query: &str,
+    headers: &[String],
+    header_value: impl Fn(&str) -> Option<&'a str>,
+) -> cja::Result<String> {
+    let lines = headers
+        .iter()
+        .map(|name| {
+            if name == "(request-target)" {
+                return Ok(format!(
+                    "(request-target): {} {path_and_query}",
+                    method.to_lowercase()
+                ));
+            }
+            let value = header_value(name)
+                .ok_or_else(|| eyre!("Signed header {name} is missing from the request"))?;
+            Ok(format!("{name}: {}", value.trim()))
+        })
+        .collect::<cja::Result<Vec<_>>>()?;
+
+    Ok(lines.join("\n"))
+}
+
+/// Verify a signature made by the holder of the key in `public_key_pem`
+pub fn verify(public_key_pem: &str, signing_string: &str, signature: &[u8]) -> cja::Result<()> {
+    let public_key = RsaPublicKey::from_public_key_pem(public_key_pem)?;
+    let signature = Signature::try_from(signature)?;
+
+    VerifyingKey::<Sha256>::new(public_key)
+        .verify(signing_string.as_bytes(), &signature)
+        .map_err(|_| eyre!("HTTP signature doesn't match"))
+}
+
+/// The headers that sign a request to `url`: `Host`, `Date`, `Digest` when there's a body,
+/// and the `Signature` over them
+pub fn sign_request(
+    private_key: &RsaPrivateKey,
+    key_id: &str,
+    method: &str,
+    url: &Url,
+    body: Option<&[u8]>,
+    now: DateTime<Utc>,
+) -> cja::Result<Vec<(&'static str, String)>> {
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let mut signed: Vec<(&'static str, String)> = vec![("host", host), ("date", date_header(now))];
+    if let Some(body) = body {
+        signed.push(("digest", digest_header(body)));
+    }
+
+    let mut path_and_query = url.path().to_string();
+    if let Some(query) = url.query() {
+        path_and_query.push('?');
+        path_and_query.push_str(query);
+    }
+    let header_names: Vec<String> = std::iter::once("(request-target)".to_string())
+        .chain(signed.iter().map(|(name, _)| (*name).to_string()))
+        .collect();
+    let to_sign = signing_string(method, &path_and_query, &header_names, |name| {
+        signed
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    })?;
+
+    let signature = SigningKey::<Sha256>::new(private_key.clone()).sign(to_sign.as_bytes());
+    let signature_header = format!(
+        r#"keyId="{key_id}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+        header_names.join(" "),
+        STANDARD.encode(signature.to_bytes())
+    );
+    signed.push(("signature", signature_header));
+
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::pkcs8::{EncodePublicKey, LineEnding};
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn signed_requests_verify_with_the_public_key() {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        let public_pem = RsaPublicKey::from(&private_key)
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        let url = Url::parse("https://example.social/inbox").unwrap();
+        let body = br#"{"type":"Accept"}"#;
+
+        let headers = sign_request(
+            &private_key,
+            "https://coreyja.com/ap/actor#main-key",
+            "POST",
+            &url,
+            Some(body),
+            now(),
+        )
+        .unwrap();
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(header("host"), Some("example.social"));
+        assert_eq!(header("date"), Some("Sun, 18 Oct 2026 12:00:00 GMT"));
+        assert_eq!(header("digest"), Some(digest_header(body).as_str()));
+
+        let signature = SignatureHeader::parse(header("signature").unwrap()).unwrap();
+        assert_eq!(signature.key_id, "https://coreyja.com/ap/actor#main-key");
+        signature.check_covers_required_headers().unwrap();
+
+        let to_verify = signing_string("POST", "/inbox", &signature.headers, header).unwrap();
+        verify(&public_pem, &to_verify, &signature.signature).unwrap();
+
+        let tampered = to_verify.replace("/inbox", "/other");
+        assert!(verify(&public_pem, &tampered, &signature.signature).is_err());
+    }
+
+    #[test]
+    fn signatures_must_cover_the_digest() {
+        let signature = SignatureHeader::parse(
+            r#"keyId="https://example.social/users/a#main-key",algorithm="rsa-sha256",headers="(request-target) host date",signature="AAAA""#,
+        )
+        .unwrap();
+        assert!(signature.check_covers_required_headers().is_err());
+    }
+
+    #[test]
+    fn unknown_algorithms_are_refused() {
+        assert!(
+            SignatureHeader::parse(r#"keyId="k",algorithm="hmac-sha256",signature="AAAA""#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn stale_dates_are_refused() {
+        assert!(date_is_fresh("Sun, 18 Oct 2026 11:00:00 GMT", now()));
+        assert!(!date_is_fresh("Sat, 17 Oct 2026 11:00:00 GMT", now()));
+        assert!(!date_is_fresh("yesterday", now()));
+    }
+}
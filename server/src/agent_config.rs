@@ -228,8 +228,14 @@ impl AgentId {
                     Tool::CreateMealPlan,
                     Tool::PlanMeal,
                     Tool::GetAllPlannedMeals,
+                    Tool::UpsertDietaryProfile,
+                    Tool::ListDietaryProfiles,
+                    Tool::SetIngredientAllergens,
                     Tool::ReadUserMemory,
                     Tool::AppendUserMemory,
+                    // Autonomous-only, used by the daily expiring inventory digest
+                    Tool::SendDiscordMessage,
+                    Tool::CompleteThread,
                 ],
             },
         }
@@ -274,6 +280,9 @@ pub enum Tool {
     CreateMealPlan,
     PlanMeal,
     GetAllPlannedMeals,
+    UpsertDietaryProfile,
+    ListDietaryProfiles,
+    SetIngredientAllergens,
 
     // Memory tools
     SaveUserMemory,
@@ -281,6 +290,7 @@ pub enum Tool {
     AppendUserMemory,
 
     // Other tools
+    SearchMyWriting,
     SuggestionsSubmit,
 }
 
@@ -339,9 +349,13 @@ impl Tool {
             Tool::CreateMealPlan => "create_meal_plan",
             Tool::PlanMeal => "plan_meal",
             Tool::GetAllPlannedMeals => "get_all_planned_meals",
+            Tool::UpsertDietaryProfile => "upsert_dietary_profile",
+            Tool::ListDietaryProfiles => "list_dietary_profiles",
+            Tool::SetIngredientAllergens => "set_ingredient_allergens",
             Tool::SaveUserMemory => "save_user_memory",
             Tool::ReadUserMemory => "read_user_memory",
             Tool::AppendUserMemory => "append_user_memory",
+            Tool::SearchMyWriting => "search_my_writing",
             Tool::SuggestionsSubmit => "tool_suggestions_submit",
         }
     }
@@ -361,8 +375,9 @@ impl Tool {
     pub fn create_instance(self) -> Box<dyn crate::al::tools::GenericTool> {
         use crate::al::tools::{
             cooking_simple::{
-                AddRecipeToMealPlan, CheckInventory, CreateMealPlan, GetRecipe, ListMealPlans,
-                UpdateInventory, UpsertRecipe,
+                AddRecipeToMealPlan, CheckInventory, CreateMealPlan, GetRecipe,
+                ListDietaryProfiles, ListMealPlans, SetIngredientAllergens, UpdateInventory,
+                UpsertDietaryProfile, UpsertRecipe,
             },
             discord::{
                 ListServerEmojis, ListenToThread, ReactToMessage, RenameDiscordThread,
@@ -373,6 +388,7 @@ impl Tool {
                 SearchLinearQueries,
             },
             memory::{AppendUserMemory, ReadUserMemory, SaveUserMemory},
+            search::SearchMyWriting,
             threads::CompleteThread,
             Tool as ToolTrait,
         };
@@ -397,9 +413,13 @@ impl Tool {
             Tool::CreateMealPlan => CreateMealPlan.to_generic(),
             Tool::PlanMeal => AddRecipeToMealPlan.to_generic(),
             Tool::GetAllPlannedMeals => ListMealPlans.to_generic(),
+            Tool::UpsertDietaryProfile => UpsertDietaryProfile.to_generic(),
+            Tool::ListDietaryProfiles => ListDietaryProfiles.to_generic(),
+            Tool::SetIngredientAllergens => SetIngredientAllergens.to_generic(),
             Tool::SaveUserMemory => SaveUserMemory::new().to_generic(),
             Tool::ReadUserMemory => ReadUserMemory::new().to_generic(),
             Tool::AppendUserMemory => AppendUserMemory::new().to_generic(),
+            Tool::SearchMyWriting => SearchMyWriting.to_generic(),
             Tool::SuggestionsSubmit => {
                 crate::al::tools::tool_suggestions::ToolSuggestionsSubmit::new().to_generic()
             }
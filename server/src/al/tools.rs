@@ -11,6 +11,7 @@ pub mod cooking_simple;
 pub mod discord;
 pub mod linear_graphql;
 pub mod memory;
+pub mod search;
 pub mod threads;
 pub mod tool_suggestions;
 
@@ -1,7 +1,11 @@
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
 use chrono::NaiveDate;
-use db::cooking::{MealPlan, MealPlanEntry, MealType, Recipe};
+use db::cooking::{
+    Allergen, ConflictSeverity, Diet, DietaryIngredient, DietaryProfile, Ingredient, MealPlan,
+    MealPlanEntry, MealType, NutritionFacts, Recipe,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -27,6 +31,9 @@ pub struct UpsertRecipeInput {
     pub steps: Vec<RecipeStepInput>,
     pub equipment: Vec<RecipeEquipmentInput>,
     pub tags: Vec<String>,
+    /// Publish (true) or unpublish (false) the recipe on the public /recipes pages.
+    /// Leave unset to keep the current visibility.
+    pub is_public: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -60,6 +67,8 @@ pub struct RecipeEquipmentInput {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UpsertRecipeOutput {
     pub recipe_id: String,
+    /// Public URL of the recipe, present when it is published
+    pub public_url: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -71,6 +80,8 @@ impl Tool for UpsertRecipe {
     If recipe_id is provided, updates the existing recipe (replacing all ingredients, steps, equipment).
     If recipe_id is not provided, creates a new recipe.
     Automatically creates any missing ingredients, units, equipment, or tags.
+    Recipes are private by default. Set is_public to true to publish the recipe on the
+    public recipes page, or false to take it down. Leave it out to keep the current visibility.
 
     Example:
     ```json
@@ -168,6 +179,8 @@ impl Tool for UpsertRecipe {
                     generated_by_stitch,
                     inspired_by_recipe_id,
                     forked_from_recipe_id,
+                    slug,
+                    is_public,
                     created_at,
                     updated_at
                 "#,
@@ -378,12 +391,25 @@ impl Tool for UpsertRecipe {
             db::cooking::RecipeTag::set_tags_for_recipe(pool, recipe.recipe_id, tag_ids).await?;
         }
 
+        let recipe = match input.is_public {
+            Some(is_public) => recipe.set_public(pool, is_public).await?,
+            None => recipe,
+        };
+
         Ok(UpsertRecipeOutput {
             recipe_id: recipe.recipe_id.to_string(),
+            public_url: public_recipe_url(&app_state, &recipe),
         })
     }
 }
 
+fn public_recipe_url(app_state: &AppState, recipe: &Recipe) -> Option<String> {
+    match (&recipe.slug, recipe.is_public) {
+        (Some(slug), true) => Some(app_state.app.app_url(&format!("/recipes/{slug}"))),
+        _ => None,
+    }
+}
+
 // GetRecipe Tool
 #[derive(Clone, Debug)]
 pub struct GetRecipe;
@@ -406,6 +432,43 @@ pub struct RecipeDetails {
     pub steps: Vec<RecipeStepDetails>,
     pub equipment: Vec<RecipeEquipmentDetails>,
     pub tags: Vec<String>,
+    pub is_public: bool,
+    pub public_url: Option<String>,
+    pub nutrition: RecipeNutritionDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecipeNutritionDetails {
+    pub per_serving: NutritionDetails,
+    /// Ingredients left out of the calculation because they have no nutrition data or
+    /// their unit couldn't be converted to grams
+    pub missing_ingredients: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct NutritionDetails {
+    pub calories: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbohydrates_g: f64,
+    pub fiber_g: f64,
+    pub sugar_g: f64,
+    pub sodium_mg: f64,
+}
+
+impl From<NutritionFacts> for NutritionDetails {
+    fn from(facts: NutritionFacts) -> Self {
+        let facts = facts.rounded();
+        Self {
+            calories: facts.calories,
+            protein_g: facts.protein_g,
+            fat_g: facts.fat_g,
+            carbohydrates_g: facts.carbohydrates_g,
+            fiber_g: facts.fiber_g,
+            sugar_g: facts.sugar_g,
+            sodium_mg: facts.sodium_mg,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -446,6 +509,7 @@ impl Tool for GetRecipe {
     const NAME: &'static str = "get_recipe";
     const DESCRIPTION: &'static str = r#"
     Retrieve a recipe by ID or name with all details including ingredients, steps, equipment, and tags.
+    Also includes estimated nutrition per serving, along with any ingredients that couldn't be counted.
 
     Provide either recipe_id or name (one is required).
 
@@ -491,19 +555,21 @@ impl Tool for GetRecipe {
             ));
         };
 
-        let recipe = if let Some(id) = recipe_id {
-            Recipe::get_by_id(pool, id).await?
+        let full = if let Some(id) = recipe_id {
+            Recipe::get_full(pool, id).await?
         } else {
             None
         };
 
-        if let Some(recipe) = recipe {
+        if let Some(full) = full {
+            let recipe = &full.recipe;
+            let nutrition = full.calculate_nutrition(pool).await?;
+
             // Get ingredients
-            let ingredients =
-                db::cooking::RecipeIngredient::get_by_recipe(pool, recipe.recipe_id).await?;
             let mut ingredient_details = Vec::new();
+            let mut ingredient_names = HashMap::new();
 
-            for ri in ingredients {
+            for ri in &full.ingredients {
                 // Get ingredient and unit details
                 let ingredient = db::cooking::Ingredient::get_by_id(pool, ri.ingredient_id)
                     .await?
@@ -512,39 +578,39 @@ impl Tool for GetRecipe {
                     .await?
                     .ok_or_else(|| cja::color_eyre::eyre::eyre!("Unit not found"))?;
 
+                ingredient_names.insert(ri.ingredient_id, ingredient.name.clone());
                 ingredient_details.push(RecipeIngredientDetails {
                     ingredient_name: ingredient.name,
                     quantity: ri.quantity.to_string().parse::<f64>()?,
                     unit_name: unit.name,
                     is_optional: ri.is_optional,
-                    notes: ri.notes,
-                    ingredient_group: ri.ingredient_group,
-                    preparation: ri.preparation.map(|p| p.to_string()),
-                    temperature: ri.temperature.map(|t| t.to_string()),
+                    notes: ri.notes.clone(),
+                    ingredient_group: ri.ingredient_group.clone(),
+                    preparation: ri.preparation.as_ref().map(ToString::to_string),
+                    temperature: ri.temperature.as_ref().map(ToString::to_string),
                 });
             }
 
             // Get steps
-            let steps = db::cooking::RecipeStep::get_by_recipe(pool, recipe.recipe_id).await?;
-            let step_details = steps
-                .into_iter()
+            let step_details = full
+                .steps
+                .iter()
                 .map(|step| RecipeStepDetails {
                     step_number: step.step_number,
-                    instruction: step.instruction,
+                    instruction: step.instruction.clone(),
                     duration: step.duration,
                     temperature: step
                         .temperature
+                        .as_ref()
                         .and_then(|t| t.to_string().parse::<f64>().ok()),
-                    temperature_unit: step.temperature_unit.map(|t| t.to_string()),
+                    temperature_unit: step.temperature_unit.as_ref().map(ToString::to_string),
                 })
                 .collect();
 
             // Get equipment
-            let equipment =
-                db::cooking::RecipeEquipment::get_by_recipe(pool, recipe.recipe_id).await?;
             let mut equipment_details = Vec::new();
 
-            for re in equipment {
+            for re in &full.equipment {
                 let equip = db::cooking::Equipment::get_by_id(pool, re.equipment_id)
                     .await?
                     .ok_or_else(|| cja::color_eyre::eyre::eyre!("Equipment not found"))?;
@@ -552,7 +618,7 @@ impl Tool for GetRecipe {
                 equipment_details.push(RecipeEquipmentDetails {
                     equipment_name: equip.name,
                     is_optional: re.is_optional,
-                    notes: re.notes,
+                    notes: re.notes.clone(),
                 });
             }
 
@@ -567,11 +633,18 @@ impl Tool for GetRecipe {
                 tag_names.push(tag.name);
             }
 
+            let public_url = public_recipe_url(&app_state, recipe);
+            let missing_ingredients = nutrition
+                .missing_ingredient_ids
+                .iter()
+                .filter_map(|id| ingredient_names.get(id).cloned())
+                .collect();
+
             Ok(GetRecipeOutput {
                 recipe: Some(RecipeDetails {
                     recipe_id: recipe.recipe_id.to_string(),
-                    name: recipe.name,
-                    description: recipe.description,
+                    name: recipe.name.clone(),
+                    description: recipe.description.clone(),
                     prep_time: recipe.prep_time,
                     cook_time: recipe.cook_time,
                     servings: recipe.servings,
@@ -579,6 +652,12 @@ impl Tool for GetRecipe {
                     steps: step_details,
                     equipment: equipment_details,
                     tags: tag_names,
+                    is_public: recipe.is_public,
+                    public_url,
+                    nutrition: RecipeNutritionDetails {
+                        per_serving: nutrition.per_serving.into(),
+                        missing_ingredients,
+                    },
                 }),
             })
         } else {
@@ -909,6 +988,16 @@ pub struct MealPlanItem {
     pub start_date: String,
     pub end_date: String,
     pub entries: Vec<MealPlanEntryItem>,
+    /// Estimated nutrition for one person eating a serving of every meal planned that day
+    pub daily_totals: Vec<DailyNutritionItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DailyNutritionItem {
+    pub date: String,
+    pub per_person: NutritionDetails,
+    /// True when some of the day's recipes have ingredients without nutrition data
+    pub incomplete: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -927,7 +1016,8 @@ pub struct ListMealPlansOutput {
 impl Tool for ListMealPlans {
     const NAME: &'static str = "list_meal_plans";
     const DESCRIPTION: &'static str = r#"
-    List current and upcoming meal plans with their scheduled recipes.
+    List current and upcoming meal plans with their scheduled recipes, and estimated
+    nutrition totals per person for each planned day.
 
     - start_date: defaults to today
     - end_date: defaults to 7 days from start_date
@@ -973,6 +1063,7 @@ impl Tool for ListMealPlans {
         let meal_plans = MealPlan::get_by_date_range(pool, start_date, end_date).await?;
 
         let mut result = Vec::new();
+        let mut recipe_nutrition = HashMap::new();
 
         for meal_plan in meal_plans {
             // Get entries for this meal plan
@@ -981,6 +1072,7 @@ impl Tool for ListMealPlans {
                 SELECT
                     mpe.date,
                     mpe.meal_type,
+                    mpe.recipe_id,
                     r.name as recipe_name
                 FROM meal_plan_entries mpe
                 JOIN recipes r ON mpe.recipe_id = r.recipe_id
@@ -992,6 +1084,34 @@ impl Tool for ListMealPlans {
             .fetch_all(pool)
             .await?;
 
+            let mut daily: BTreeMap<NaiveDate, (NutritionFacts, bool)> = BTreeMap::new();
+            for entry in &entries {
+                if !recipe_nutrition.contains_key(&entry.recipe_id) {
+                    let nutrition = match Recipe::get_full(pool, entry.recipe_id).await? {
+                        Some(full) => Some(full.calculate_nutrition(pool).await?),
+                        None => None,
+                    };
+                    recipe_nutrition.insert(entry.recipe_id, nutrition);
+                }
+
+                let day = daily.entry(entry.date).or_default();
+                match &recipe_nutrition[&entry.recipe_id] {
+                    Some(nutrition) => {
+                        day.0 += nutrition.per_serving;
+                        day.1 |= !nutrition.missing_ingredient_ids.is_empty();
+                    }
+                    None => day.1 = true,
+                }
+            }
+            let daily_totals = daily
+                .into_iter()
+                .map(|(date, (facts, incomplete))| DailyNutritionItem {
+                    date: date.to_string(),
+                    per_person: facts.into(),
+                    incomplete,
+                })
+                .collect();
+
             let entry_details = entries
                 .into_iter()
                 .map(|entry| MealPlanEntryItem {
@@ -1008,6 +1128,7 @@ impl Tool for ListMealPlans {
                 start_date: meal_plan.start_date.to_string(),
                 end_date: meal_plan.end_date.to_string(),
                 entries: entry_details,
+                daily_totals,
             });
         }
 
@@ -1088,11 +1209,17 @@ pub struct AddRecipeToMealPlanInput {
     pub meal_type: String,
     pub servings: Option<i32>,
     pub notes: Option<String>,
+    /// Discord user IDs of the people eating this meal. Defaults to everyone with a dietary profile.
+    pub for_people: Option<Vec<String>>,
+    /// Plan the meal even though it conflicts with someone's allergies or diet
+    pub allow_conflicts: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AddRecipeToMealPlanOutput {
     pub entry_id: String,
+    /// Dietary conflicts worth mentioning, e.g. an ingredient someone dislikes
+    pub warnings: Vec<String>,
 }
 
 #[async_trait::async_trait]
@@ -1104,6 +1231,11 @@ impl Tool for AddRecipeToMealPlan {
     Provide either recipe_id or recipe_name.
     Meal types: breakfast, lunch, dinner, snack
 
+    The recipe is checked against the dietary profiles of the people in for_people (Discord user
+    IDs), or everyone with a profile if omitted. Everyone in for_people needs a profile. Allergy
+    and diet conflicts are refused unless allow_conflicts is true; disliked ingredients come back
+    as warnings.
+
     Example:
     ```json
     {
@@ -1159,6 +1291,46 @@ impl Tool for AddRecipeToMealPlan {
             _ => return Err(cja::color_eyre::eyre::eyre!("Invalid meal type")),
         };
 
+        // Check the recipe against the dietary profiles of whoever is eating
+        let profiles = match &input.for_people {
+            Some(ids) => {
+                let profiles = DietaryProfile::get_by_discord_user_ids(pool, ids).await?;
+                // Planning for someone without a profile would skip their allergies silently
+                let missing: Vec<&str> = ids
+                    .iter()
+                    .filter(|id| !profiles.iter().any(|p| &p.discord_user_id == *id))
+                    .map(String::as_str)
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(cja::color_eyre::eyre::eyre!(
+                        "No dietary profile for {}. Ask about their allergies and diet and save \
+                        them with upsert_dietary_profile first, or leave them out of for_people.",
+                        missing.join(", ")
+                    ));
+                }
+                profiles
+            }
+            None => DietaryProfile::list(pool).await?,
+        };
+        let ingredients = DietaryIngredient::get_by_recipe(pool, recipe_id).await?;
+        let conflicts: Vec<_> = profiles
+            .iter()
+            .flat_map(|p| p.conflicts_with(&ingredients))
+            .collect();
+
+        let blocking: Vec<String> = conflicts
+            .iter()
+            .filter(|c| c.severity == ConflictSeverity::Blocking)
+            .map(ToString::to_string)
+            .collect();
+        if !blocking.is_empty() && !input.allow_conflicts.unwrap_or(false) {
+            return Err(cja::color_eyre::eyre::eyre!(
+                "Recipe was not planned because it conflicts with dietary restrictions: {}. \
+                Pick a different recipe, or set allow_conflicts to true if this is intended.",
+                blocking.join("; ")
+            ));
+        }
+
         // Create meal plan entry
         let entry = MealPlanEntry::create(
             pool,
@@ -1172,6 +1344,212 @@ impl Tool for AddRecipeToMealPlan {
 
         Ok(AddRecipeToMealPlanOutput {
             entry_id: entry.meal_plan_entry_id.to_string(),
+            warnings: conflicts.iter().map(ToString::to_string).collect(),
         })
     }
 }
+
+// UpsertDietaryProfile Tool
+#[derive(Clone, Debug)]
+pub struct UpsertDietaryProfile;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpsertDietaryProfileInput {
+    /// The Discord user ID for the person (e.g., "1234567890")
+    pub discord_user_id: String,
+    /// The person's preferred name
+    pub name: String,
+    /// milk, egg, fish, shellfish, tree_nuts, peanuts, wheat, gluten, soy, sesame
+    pub allergens: Vec<String>,
+    /// vegetarian, vegan, pescatarian, gluten_free, dairy_free
+    pub diets: Vec<String>,
+    /// Ingredients the person dislikes or avoids, e.g. "cilantro"
+    pub avoid_ingredients: Vec<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpsertDietaryProfileOutput {
+    pub dietary_profile_id: String,
+    pub message: String,
+}
+
+#[async_trait::async_trait]
+impl Tool for UpsertDietaryProfile {
+    const NAME: &'static str = "upsert_dietary_profile";
+    const DESCRIPTION: &'static str = r#"
+    Create or replace a person's dietary profile: allergies, diets, and ingredients they avoid.
+    Profiles are keyed by Discord user ID, and are checked whenever a recipe is added to a meal plan.
+
+    Example:
+    ```json
+    {
+        "discord_user_id": "1234567890",
+        "name": "Sam",
+        "allergens": ["peanuts"],
+        "diets": ["vegetarian"],
+        "avoid_ingredients": ["cilantro"],
+        "notes": "Mild spice only"
+    }
+    ```
+    "#;
+
+    type ToolInput = UpsertDietaryProfileInput;
+    type ToolOutput = UpsertDietaryProfileOutput;
+
+    async fn run(
+        &self,
+        input: Self::ToolInput,
+        app_state: AppState,
+        _context: ThreadContext,
+    ) -> cja::Result<Self::ToolOutput> {
+        let allergens: Vec<Allergen> = parse_all(&input.allergens)?;
+        let diets: Vec<Diet> = parse_all(&input.diets)?;
+
+        let profile = DietaryProfile::upsert(
+            &app_state.db,
+            input.discord_user_id,
+            input.name,
+            &allergens,
+            &diets,
+            input.avoid_ingredients,
+            input.notes,
+        )
+        .await?;
+
+        Ok(UpsertDietaryProfileOutput {
+            dietary_profile_id: profile.dietary_profile_id.to_string(),
+            message: format!("Saved dietary profile for {}", profile.name),
+        })
+    }
+}
+
+// ListDietaryProfiles Tool
+#[derive(Clone, Debug)]
+pub struct ListDietaryProfiles;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDietaryProfilesInput {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DietaryProfileItem {
+    pub discord_user_id: String,
+    pub name: String,
+    pub allergens: Vec<String>,
+    pub diets: Vec<String>,
+    pub avoid_ingredients: Vec<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDietaryProfilesOutput {
+    pub profiles: Vec<DietaryProfileItem>,
+}
+
+#[async_trait::async_trait]
+impl Tool for ListDietaryProfiles {
+    const NAME: &'static str = "list_dietary_profiles";
+    const DESCRIPTION: &'static str = r#"
+    List everyone's dietary profile: allergies, diets, and ingredients they avoid.
+
+    Example:
+    ```json
+    {}
+    ```
+    "#;
+
+    type ToolInput = ListDietaryProfilesInput;
+    type ToolOutput = ListDietaryProfilesOutput;
+
+    async fn run(
+        &self,
+        _input: Self::ToolInput,
+        app_state: AppState,
+        _context: ThreadContext,
+    ) -> cja::Result<Self::ToolOutput> {
+        let profiles = DietaryProfile::list(&app_state.db)
+            .await?
+            .into_iter()
+            .map(|p| DietaryProfileItem {
+                discord_user_id: p.discord_user_id,
+                name: p.name,
+                allergens: p.allergens,
+                diets: p.diets,
+                avoid_ingredients: p.avoid_ingredients,
+                notes: p.notes,
+            })
+            .collect();
+
+        Ok(ListDietaryProfilesOutput { profiles })
+    }
+}
+
+// SetIngredientAllergens Tool
+#[derive(Clone, Debug)]
+pub struct SetIngredientAllergens;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetIngredientAllergensInput {
+    pub ingredient_name: String,
+    /// milk, egg, fish, shellfish, tree_nuts, peanuts, wheat, gluten, soy, sesame.
+    /// Replaces any existing tags; pass an empty list to clear them.
+    pub allergens: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetIngredientAllergensOutput {
+    pub ingredient_id: String,
+    pub allergens: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl Tool for SetIngredientAllergens {
+    const NAME: &'static str = "set_ingredient_allergens";
+    const DESCRIPTION: &'static str = r#"
+    Tag an ingredient with the allergens it contains, so meal planning can catch conflicts.
+    Tag new ingredients when saving recipes, e.g. butter is milk, soy sauce is soy and wheat.
+
+    Example:
+    ```json
+    {
+        "ingredient_name": "soy sauce",
+        "allergens": ["soy", "wheat"]
+    }
+    ```
+    "#;
+
+    type ToolInput = SetIngredientAllergensInput;
+    type ToolOutput = SetIngredientAllergensOutput;
+
+    async fn run(
+        &self,
+        input: Self::ToolInput,
+        app_state: AppState,
+        _context: ThreadContext,
+    ) -> cja::Result<Self::ToolOutput> {
+        let pool = &app_state.db;
+        let allergens: Vec<Allergen> = parse_all(&input.allergens)?;
+
+        let ingredient = Ingredient::get_by_name(pool, &input.ingredient_name)
+            .await?
+            .ok_or_else(|| {
+                cja::color_eyre::eyre::eyre!("Ingredient not found: {}", input.ingredient_name)
+            })?;
+        let ingredient = ingredient.set_allergens(pool, &allergens).await?;
+
+        Ok(SetIngredientAllergensOutput {
+            ingredient_id: ingredient.ingredient_id.to_string(),
+            allergens: ingredient.allergens,
+        })
+    }
+}
+
+fn parse_all<T: FromStr<Err = String>>(values: &[String]) -> cja::Result<Vec<T>> {
+    values
+        .iter()
+        .map(|v| {
+            v.parse()
+                .map_err(|e: String| cja::color_eyre::eyre::eyre!(e))
+        })
+        .collect()
+}
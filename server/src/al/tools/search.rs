@@ -0,0 +1,92 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    al::tools::{ThreadContext, Tool},
+    http_server::pages::search::search,
+    AppState,
+};
+
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 20;
+
+#[derive(Clone, Debug)]
+pub struct SearchMyWriting;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchMyWritingInput {
+    /// Words to search for. Matching ignores case and word endings, so "testing" also finds "tests".
+    pub query: String,
+    /// Only return one kind of content: "post", "note", "podcast" or "project"
+    pub kind: Option<String>,
+    /// Maximum number of results to return (default 5, max 20)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMyWritingOutput {
+    pub results: Vec<WritingSearchResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritingSearchResult {
+    pub kind: String,
+    pub title: String,
+    pub url: String,
+    pub date: Option<String>,
+    /// Excerpt around the best match, with matching words wrapped in `**`
+    pub snippet: String,
+}
+
+#[async_trait::async_trait]
+impl Tool for SearchMyWriting {
+    const NAME: &'static str = "search_my_writing";
+    const DESCRIPTION: &'static str =
+        "Full-text search across Corey's published writing on coreyja.com: \
+        blog posts, notes, podcast episodes and project pages. \
+        Returns the best matches first, each with its URL and a short excerpt around the match. \
+        Use this to find what Corey has already written about a topic, or to link to it.";
+
+    type ToolInput = SearchMyWritingInput;
+    type ToolOutput = SearchMyWritingOutput;
+
+    async fn run(
+        &self,
+        input: Self::ToolInput,
+        app_state: AppState,
+        _context: ThreadContext,
+    ) -> cja::Result<Self::ToolOutput> {
+        let kind = input
+            .kind
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(|e: String| cja::color_eyre::eyre::eyre!(e))?;
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let search_index = app_state.search_index();
+        let results = search(&search_index, &input.query, kind, limit)
+            .into_iter()
+            .map(|hit| WritingSearchResult {
+                kind: hit.document.kind.to_string(),
+                title: hit.document.title.clone(),
+                url: app_state.app.app_url(&hit.document.url),
+                date: hit.document.date.map(|d| d.to_string()),
+                snippet: hit
+                    .snippet
+                    .parts
+                    .iter()
+                    .map(|part| {
+                        if part.highlighted {
+                            format!("**{}**", part.text)
+                        } else {
+                            part.text.clone()
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(SearchMyWritingOutput { results })
+    }
+}
@@ -107,7 +107,16 @@ struct PostRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     facets: Option<Vec<Facet>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    embed: Option<EmbedExternal>,
+    embed: Option<PostEmbed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply: Option<ReplyRef>,
+}
+
+/// Where a reply sits in a thread: the post that started it, and the post it answers
+#[derive(Serialize, Debug)]
+struct ReplyRef {
+    root: StrongRef,
+    parent: StrongRef,
 }
 
 /// `site.standard.publication` record. One per publication (the blog, the
@@ -191,27 +200,62 @@ pub struct WriteRecordResponse {
     pub cid: String,
 }
 
+/// A thread posted by [`BlueskyClient::create_note_thread`], and how far it got
+#[derive(Debug)]
+pub struct NoteThread {
+    pub root: WriteRecordResponse,
+    /// Replies that were posted, in order
+    pub replies: Vec<WriteRecordResponse>,
+    /// How many posts the whole thread should have had, the root included
+    pub len: usize,
+    /// Why the thread stops short, when a reply failed after the root was posted
+    pub error: Option<cja::color_eyre::Report>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadBlobResponse {
     pub blob: Blob,
 }
 
+/// A record as `com.atproto.repo.listRecords` returns it. `value` is left
+/// as JSON since a collection can hold records written by other clients.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListedRecord {
+    pub uri: String,
+    pub cid: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct ListRecordsResponse {
+    cursor: Option<String>,
+    records: Vec<ListedRecord>,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
+struct DeleteRecordRequest {
+    repo: String,
+    collection: String,
+    rkey: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 struct Facet {
     index: ByteSlice,
     features: Vec<FacetFeature>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct ByteSlice {
     byte_start: usize,
     byte_end: usize,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct FacetFeature {
     #[serde(rename = "$type")]
@@ -219,6 +263,36 @@ struct FacetFeature {
     uri: String,
 }
 
+/// A post has at most one embed: a link card, or up to [`BSKY_MAX_IMAGES`] images
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum PostEmbed {
+    External(EmbedExternal),
+    Images(EmbedImages),
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EmbedImages {
+    #[serde(rename = "$type")]
+    embed_type: String,
+    images: Vec<EmbedImage>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EmbedImage {
+    alt: String,
+    image: Blob,
+    aspect_ratio: AspectRatio,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub width: u32,
+    pub height: u32,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct EmbedExternal {
@@ -239,6 +313,8 @@ struct ExternalEmbed {
     /// records instead of scraping OG tags from the URL.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     associated_refs: Vec<StrongRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumb: Option<Blob>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -289,7 +365,8 @@ impl BlueskyClient {
     }
 
     /// Publish a note-shaped post: title, markdown body, and an external
-    /// link card pointing back to the note's web page. The markdown body is
+    /// link card pointing back to the note's web page, or the note's images
+    /// when it has any (see [`BlueskyClient::note_embed`]). The markdown body is
     /// converted to Bluesky-flavored plain text + rich-text facets so
     /// `[text](url)` markdown links become real clickable links and other
     /// formatting markers (bold, italic, headings, etc.) don't leak into
@@ -298,14 +375,17 @@ impl BlueskyClient {
     /// The post text fits Bluesky's 300-grapheme limit by truncating the
     /// body if necessary; truncation drops body facets (the link metadata
     /// no longer aligns with the visible text), but the title and trailing
-    /// URL facets are always preserved.
+    /// URL facets are always preserved. Use `create_note_thread` to keep the
+    /// whole body instead.
     pub async fn create_note_post(
         &self,
         title: &str,
         body_markdown: &str,
         note_url: &str,
+        images: Vec<ImageUpload>,
     ) -> cja::Result<CreateRecordResponse> {
         let (text, facets) = compose_note_post(title, body_markdown, note_url);
+        let embed = self.note_embed(title, note_url, images).await?;
 
         let record = CreateRecordRequest {
             repo: self.session.did.clone(),
@@ -315,20 +395,13 @@ impl BlueskyClient {
                 record_type: "app.bsky.feed.post".to_string(),
                 text,
                 created_at: chrono::Utc::now().to_rfc3339(),
+                reply: None,
                 facets: if facets.is_empty() {
                     None
                 } else {
                     Some(facets)
                 },
-                embed: Some(EmbedExternal {
-                    embed_type: "app.bsky.embed.external".to_string(),
-                    external: ExternalEmbed {
-                        uri: note_url.to_string(),
-                        title: title.to_string(),
-                        description: String::new(),
-                        associated_refs: Vec::new(),
-                    },
-                }),
+                embed: Some(embed),
             },
         };
 
@@ -357,6 +430,102 @@ impl BlueskyClient {
         Ok(response)
     }
 
+    /// Publish a long note as a thread instead of truncating it: the note is split at
+    /// paragraph and sentence boundaries (see [`compose_note_thread`]) and each post
+    /// replies to the one before. The root post carries the embed (the link card back to
+    /// the note, or its images), so callers can link to the whole thread.
+    ///
+    /// Once the root is posted this doesn't fail: a reply that can't be posted ends the
+    /// thread early and is reported in [`NoteThread::error`], so callers can still record
+    /// the root instead of posting the whole thread again.
+    pub async fn create_note_thread(
+        &self,
+        title: &str,
+        body_markdown: &str,
+        note_url: &str,
+        images: Vec<ImageUpload>,
+    ) -> cja::Result<NoteThread> {
+        let posts = compose_note_thread(title, body_markdown, note_url);
+        let len = posts.len();
+        let mut posts = posts.into_iter();
+        let (text, facets) = posts
+            .next()
+            .ok_or_else(|| cja::color_eyre::eyre::eyre!("Note has nothing to post"))?;
+        let embed = self.note_embed(title, note_url, images).await?;
+
+        let root = self
+            .create_record(
+                "app.bsky.feed.post",
+                None,
+                &PostRecord {
+                    record_type: "app.bsky.feed.post".to_string(),
+                    text,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    facets: if facets.is_empty() {
+                        None
+                    } else {
+                        Some(facets)
+                    },
+                    embed: Some(embed),
+                    reply: None,
+                },
+            )
+            .await?;
+
+        let root_ref = StrongRef {
+            uri: root.uri.clone(),
+            cid: root.cid.clone(),
+        };
+        let mut parent_ref = root_ref.clone();
+        let mut replies = vec![];
+        for (text, facets) in posts {
+            let reply = match self
+                .create_record(
+                    "app.bsky.feed.post",
+                    None,
+                    &PostRecord {
+                        record_type: "app.bsky.feed.post".to_string(),
+                        text,
+                        created_at: chrono::Utc::now().to_rfc3339(),
+                        facets: if facets.is_empty() {
+                            None
+                        } else {
+                            Some(facets)
+                        },
+                        embed: None,
+                        reply: Some(ReplyRef {
+                            root: root_ref.clone(),
+                            parent: parent_ref,
+                        }),
+                    },
+                )
+                .await
+            {
+                Ok(reply) => reply,
+                Err(e) => {
+                    return Ok(NoteThread {
+                        root,
+                        replies,
+                        len,
+                        error: Some(e),
+                    })
+                }
+            };
+            parent_ref = StrongRef {
+                uri: reply.uri.clone(),
+                cid: reply.cid.clone(),
+            };
+            replies.push(reply);
+        }
+
+        Ok(NoteThread {
+            root,
+            replies,
+            len,
+            error: None,
+        })
+    }
+
     /// DID of the authenticated account. Used as the `repo` field when
     /// composing `createRecord` / `putRecord` calls outside this module.
     pub fn did(&self) -> &str {
@@ -474,6 +643,89 @@ impl BlueskyClient {
             .await
     }
 
+    /// Every record in one of the account's collections, following
+    /// `listRecords`' cursor until the PDS runs out of pages.
+    pub async fn list_records(&self, collection: &str) -> cja::Result<Vec<ListedRecord>> {
+        let mut records = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("repo", self.session.did.clone()),
+                ("collection", collection.to_string()),
+                ("limit", "100".to_string()),
+            ];
+            if let Some(cursor) = cursor.take() {
+                query.push(("cursor", cursor));
+            }
+
+            let resp = self
+                .client
+                .get(format!(
+                    "{}/xrpc/com.atproto.repo.listRecords",
+                    self.pds_url
+                ))
+                .bearer_auth(&self.session.access_jwt)
+                .query(&query)
+                .send()
+                .await?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                return Err(cja::color_eyre::eyre::eyre!(
+                    "Failed to listRecords in {} ({}): {}",
+                    collection,
+                    status,
+                    body
+                ));
+            }
+
+            let page: ListRecordsResponse = resp.json().await?;
+            let is_last_page = page.records.is_empty() || page.cursor.is_none();
+            records.extend(page.records);
+            if is_last_page {
+                return Ok(records);
+            }
+            cursor = page.cursor;
+        }
+    }
+
+    /// Generic `com.atproto.repo.deleteRecord` call. Deleting a record
+    /// that's already gone succeeds.
+    pub async fn delete_record(&self, collection: &str, rkey: &str) -> cja::Result<()> {
+        let req = DeleteRecordRequest {
+            repo: self.session.did.clone(),
+            collection: collection.to_string(),
+            rkey: rkey.to_string(),
+        };
+
+        let resp = self
+            .client
+            .post(format!(
+                "{}/xrpc/com.atproto.repo.deleteRecord",
+                self.pds_url
+            ))
+            .bearer_auth(&self.session.access_jwt)
+            .json(&req)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(cja::color_eyre::eyre::eyre!(
+                "Failed to deleteRecord in {} at rkey {} ({}): {}",
+                collection,
+                rkey,
+                status,
+                body
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Upload a raw blob (e.g. publication cover image). Returns the `Blob`
     /// payload to be embedded into a record under `cover` or similar fields.
     pub async fn upload_blob(&self, bytes: Vec<u8>, mime_type: &str) -> cja::Result<Blob> {
@@ -500,17 +752,65 @@ impl BlueskyClient {
         Ok(response.blob)
     }
 
+    /// Upload an image for an embed, shrinking it first if it's over Bluesky's
+    /// size limits (see [`fit_image_to_blob_limit`]).
+    pub async fn upload_image(&self, bytes: &[u8]) -> cja::Result<(Blob, AspectRatio)> {
+        let image = fit_image_to_blob_limit(bytes)?;
+        let blob = self.upload_blob(image.bytes, image.mime_type).await?;
+        Ok((blob, image.aspect_ratio))
+    }
+
+    /// Embed for a note's post: its images when it has any, otherwise a link
+    /// card back to the note. A post only gets one embed, and the note's URL
+    /// is always linked at the end of the text anyway.
+    async fn note_embed(
+        &self,
+        title: &str,
+        note_url: &str,
+        images: Vec<ImageUpload>,
+    ) -> cja::Result<PostEmbed> {
+        if images.is_empty() {
+            return Ok(PostEmbed::External(EmbedExternal {
+                embed_type: "app.bsky.embed.external".to_string(),
+                external: ExternalEmbed {
+                    uri: note_url.to_string(),
+                    title: title.to_string(),
+                    description: String::new(),
+                    associated_refs: Vec::new(),
+                    thumb: None,
+                },
+            }));
+        }
+
+        let mut embedded = Vec::with_capacity(images.len().min(BSKY_MAX_IMAGES));
+        for image in images.into_iter().take(BSKY_MAX_IMAGES) {
+            let (blob, aspect_ratio) = self.upload_image(&image.bytes).await?;
+            embedded.push(EmbedImage {
+                alt: image.alt,
+                image: blob,
+                aspect_ratio,
+            });
+        }
+
+        Ok(PostEmbed::Images(EmbedImages {
+            embed_type: "app.bsky.embed.images".to_string(),
+            images: embedded,
+        }))
+    }
+
     /// Publish a blog post as an `app.bsky.feed.post` with an external embed.
     ///
     /// `associatedRefs` points at the post's `site.standard.document` and the
     /// parent `site.standard.publication`. Body is just `"{title}\n\n{url}"`
-    /// since the enhanced card replaces the typical body text.
+    /// since the enhanced card replaces the typical body text. `thumb` is the
+    /// card's image, usually the post's OG card uploaded with `upload_image`.
     pub async fn create_blog_post(
         &self,
         title: &str,
         post_url: &str,
         description: &str,
         associated_refs: Vec<StrongRef>,
+        thumb: Option<Blob>,
     ) -> cja::Result<CreateRecordResponse> {
         let (text, facets) = compose_blog_post_text(title, post_url);
 
@@ -522,20 +822,22 @@ impl BlueskyClient {
                 record_type: "app.bsky.feed.post".to_string(),
                 text,
                 created_at: chrono::Utc::now().to_rfc3339(),
+                reply: None,
                 facets: if facets.is_empty() {
                     None
                 } else {
                     Some(facets)
                 },
-                embed: Some(EmbedExternal {
+                embed: Some(PostEmbed::External(EmbedExternal {
                     embed_type: "app.bsky.embed.external".to_string(),
                     external: ExternalEmbed {
                         uri: post_url.to_string(),
                         title: title.to_string(),
                         description: description.to_string(),
                         associated_refs,
+                        thumb,
                     },
-                }),
+                })),
             },
         };
 
@@ -565,6 +867,138 @@ impl BlueskyClient {
     }
 }
 
+/// Most images a post can embed
+pub const BSKY_MAX_IMAGES: usize = 4;
+
+/// Largest blob Bluesky accepts for an embedded image
+const BSKY_IMAGE_MAX_BYTES: usize = 1_000_000;
+
+/// Longest side Bluesky keeps for images; anything bigger is scaled down
+/// on their end anyway, so there's no point uploading the extra pixels.
+const BSKY_IMAGE_MAX_DIMENSION: u32 = 2000;
+
+/// An image from a note's markdown, by its URL as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownImage {
+    pub url: String,
+    pub alt: String,
+}
+
+/// A note image's contents, ready to upload
+#[derive(Debug, Clone)]
+pub struct ImageUpload {
+    pub bytes: Vec<u8>,
+    pub alt: String,
+}
+
+/// Every image in the markdown, in document order. `markdown_to_bsky_text`
+/// drops them from the text since they're attached as embeds instead.
+pub fn markdown_images(body_markdown: &str) -> Vec<MarkdownImage> {
+    fn collect(node: &markdown::mdast::Node, images: &mut Vec<MarkdownImage>) {
+        if let markdown::mdast::Node::Image(image) = node {
+            images.push(MarkdownImage {
+                url: image.url.clone(),
+                alt: image.alt.clone(),
+            });
+        }
+        for child in node.children().into_iter().flatten() {
+            collect(child, images);
+        }
+    }
+
+    let Ok(root) = markdown::to_mdast(body_markdown, &markdown::ParseOptions::default()) else {
+        return Vec::new();
+    };
+    let mut images = Vec::new();
+    collect(&root, &mut images);
+    images
+}
+
+#[derive(Debug)]
+struct FittedImage {
+    bytes: Vec<u8>,
+    mime_type: &'static str,
+    aspect_ratio: AspectRatio,
+}
+
+/// Make an image uploadable as a Bluesky embed. PNGs and JPEGs already within
+/// the limits go up untouched; anything else is scaled to
+/// [`BSKY_IMAGE_MAX_DIMENSION`] and re-encoded as JPEG, shrinking further
+/// until it fits in [`BSKY_IMAGE_MAX_BYTES`].
+fn fit_image_to_blob_limit(bytes: &[u8]) -> cja::Result<FittedImage> {
+    use image::ImageFormat;
+
+    let format = image::guess_format(bytes)
+        .map_err(|e| cja::color_eyre::eyre::eyre!("Unrecognized image format: {}", e))?;
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| cja::color_eyre::eyre::eyre!("Failed to decode image: {}", e))?;
+    let (width, height) = (img.width(), img.height());
+
+    let passthrough_mime = match format {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        _ => None,
+    };
+    if let Some(mime_type) = passthrough_mime {
+        if bytes.len() <= BSKY_IMAGE_MAX_BYTES && width.max(height) <= BSKY_IMAGE_MAX_DIMENSION {
+            return Ok(FittedImage {
+                bytes: bytes.to_vec(),
+                mime_type,
+                aspect_ratio: AspectRatio { width, height },
+            });
+        }
+    }
+
+    let img = if width.max(height) > BSKY_IMAGE_MAX_DIMENSION {
+        img.resize(
+            BSKY_IMAGE_MAX_DIMENSION,
+            BSKY_IMAGE_MAX_DIMENSION,
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        img
+    };
+    // JPEG has no alpha channel, so flatten before encoding
+    let img = image::DynamicImage::ImageRgb8(img.to_rgb8());
+
+    for scale in [1.0, 0.8, 0.6, 0.4, 0.3, 0.2] {
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_sign_loss)]
+        let new_width = (f64::from(img.width()) * scale) as u32;
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_sign_loss)]
+        let new_height = (f64::from(img.height()) * scale) as u32;
+
+        if new_width == 0 || new_height == 0 {
+            continue;
+        }
+
+        let resized = img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3);
+        let mut jpeg_bytes = Vec::new();
+        resized
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg_bytes),
+                ImageFormat::Jpeg,
+            )
+            .map_err(|e| cja::color_eyre::eyre::eyre!("Failed to encode image: {}", e))?;
+
+        if jpeg_bytes.len() <= BSKY_IMAGE_MAX_BYTES {
+            return Ok(FittedImage {
+                bytes: jpeg_bytes,
+                mime_type: "image/jpeg",
+                aspect_ratio: AspectRatio {
+                    width: resized.width(),
+                    height: resized.height(),
+                },
+            });
+        }
+    }
+
+    Err(cja::color_eyre::eyre::eyre!(
+        "Could not shrink {width}x{height} image under Bluesky's {BSKY_IMAGE_MAX_BYTES} byte limit"
+    ))
+}
+
 /// Compose blog post body text + facets. Output is `"{title}\n\n{post_url}"`
 /// — the `site.standard` card replaces what would normally be the body.
 fn compose_blog_post_text(title: &str, post_url: &str) -> (String, Vec<Facet>) {
@@ -601,6 +1035,31 @@ fn markdown_to_bsky_text(body_markdown: &str) -> (String, Vec<Facet>) {
     (text, builder.facets)
 }
 
+/// The same text as [`markdown_to_bsky_text`], for places without link facets like
+/// Mastodon: a markdown link's URL follows its text in parentheses, unless the text is
+/// already the URL.
+pub fn markdown_to_plain_text(body_markdown: &str) -> String {
+    let (text, mut facets) = markdown_to_bsky_text(body_markdown);
+    facets.sort_by_key(|f| f.index.byte_start);
+
+    let mut plain = String::with_capacity(text.len());
+    let mut written = 0;
+    for facet in &facets {
+        let (start, end) = (facet.index.byte_start, facet.index.byte_end);
+        let Some(uri) = facet.features.first().map(|f| f.uri.as_str()) else {
+            continue;
+        };
+        if start < written || &text[start..end] == uri {
+            continue;
+        }
+        plain.push_str(&text[written..end]);
+        plain.push_str(&format!(" ({uri})"));
+        written = end;
+    }
+    plain.push_str(&text[written..]);
+    plain
+}
+
 #[derive(Default)]
 struct PostBodyBuilder {
     text: String,
@@ -798,6 +1257,136 @@ fn compose_note_post(title: &str, body_markdown: &str, note_url: &str) -> (Strin
     (text, facets)
 }
 
+/// Bluesky's post length limit, in graphemes. Counted as chars, like the rest of this module.
+const BSKY_POST_LIMIT: usize = 300;
+
+/// Split a note into a thread of posts, each within [`BSKY_POST_LIMIT`]. The whole
+/// `{title}\n\n{body}\n\n{note_url}` layout of [`compose_note_post`] is spread across the
+/// thread, so the title opens it and the URL closes it, and notes that fit come out as the
+/// same single post.
+///
+/// Posts break at paragraphs where possible, then sentences, then words, and never in the
+/// middle of a link's text unless that text alone is longer than a post. Facets follow
+/// their text into whichever post it lands in, clipped in the rare case a link is split.
+fn compose_note_thread(
+    title: &str,
+    body_markdown: &str,
+    note_url: &str,
+) -> Vec<(String, Vec<Facet>)> {
+    let (body_text, body_facets) = markdown_to_bsky_text(body_markdown);
+
+    let title_bytes = title.len() + "\n\n".len();
+    let mut facets: Vec<Facet> = body_facets
+        .into_iter()
+        .map(|f| Facet {
+            index: ByteSlice {
+                byte_start: f.index.byte_start + title_bytes,
+                byte_end: f.index.byte_end + title_bytes,
+            },
+            features: f.features,
+        })
+        .collect();
+
+    let text = format!("{title}\n\n{body_text}\n\n{note_url}");
+    if let Some(f) = make_url_facet(&text, note_url) {
+        facets.push(f);
+    }
+
+    let mut posts = Vec::new();
+    let mut start = 0;
+    loop {
+        let rest = &text[start..];
+        start += rest.len() - rest.trim_start().len();
+        if start >= text.len() {
+            break;
+        }
+
+        let end = thread_post_end(&text, start, &facets);
+        let post = text[start..end].trim_end();
+        let post_end = start + post.len();
+        let post_facets = facets
+            .iter()
+            .filter_map(|f| clip_facet(f, start, post_end))
+            .collect();
+
+        posts.push((post.to_string(), post_facets));
+        start = end;
+    }
+
+    posts
+}
+
+/// Where the thread post starting at byte `start` of `text` should end. Prefers the last
+/// paragraph break that still fills at least half a post, then the last sentence break,
+/// then the last word break, and only cuts mid-word when there's no break at all.
+fn thread_post_end(text: &str, start: usize, facets: &[Facet]) -> usize {
+    const BREAKS: [&[&str]; 3] = [
+        &["\n\n"],
+        &[". ", "! ", "? ", ".\n", "!\n", "?\n"],
+        &[" ", "\n"],
+    ];
+
+    let rest = &text[start..];
+    let Some((limit, _)) = rest.char_indices().nth(BSKY_POST_LIMIT) else {
+        return text.len();
+    };
+    let limit_end = start + limit;
+    let half_full = start
+        + rest
+            .char_indices()
+            .nth(BSKY_POST_LIMIT / 2)
+            .map_or(rest.len(), |(i, _)| i);
+
+    // Look a couple of characters past the limit, so a break that starts right at it counts
+    let search_end = rest[limit..]
+        .char_indices()
+        .nth(2)
+        .map_or(text.len(), |(i, _)| limit_end + i);
+    let window = &text[start..search_end];
+
+    let inside_link = |at: usize| {
+        facets
+            .iter()
+            .any(|f| f.index.byte_start < at && at < f.index.byte_end)
+    };
+    let last_break = |separators: &[&str]| -> Option<usize> {
+        separators
+            .iter()
+            .filter_map(|sep| {
+                window
+                    .rmatch_indices(sep)
+                    .map(|(i, _)| start + i + sep.trim_end().len())
+                    .find(|&cut| cut > start && cut <= limit_end && !inside_link(cut))
+            })
+            .max()
+    };
+
+    BREAKS
+        .into_iter()
+        .find_map(|separators| last_break(separators).filter(|&cut| cut >= half_full))
+        .or_else(|| BREAKS.into_iter().filter_map(&last_break).max())
+        .unwrap_or(limit_end)
+}
+
+/// The part of `facet` within `start..end`, relative to `start`
+fn clip_facet(facet: &Facet, start: usize, end: usize) -> Option<Facet> {
+    let byte_start = facet.index.byte_start.max(start);
+    let byte_end = facet.index.byte_end.min(end);
+
+    (byte_start < byte_end).then(|| Facet {
+        index: ByteSlice {
+            byte_start: byte_start - start,
+            byte_end: byte_end - start,
+        },
+        features: facet.features.clone(),
+    })
+}
+
+/// How many posts a note posted as a thread takes
+pub fn bsky_thread_len(title: &str, body_markdown: &str, note_url: &str) -> usize {
+    compose_note_thread(title, body_markdown, note_url).len()
+}
+
 /// How many characters the un-truncated post would consume. Bluesky's limit
 /// is 300; values above 300 mean `compose_note_post` would truncate the
 /// body. CI tests use this to fail fast on notes that won't fit.
@@ -906,6 +1495,7 @@ mod tests {
             created_at: "2026-03-07T00:00:00Z".to_string(),
             facets: None,
             embed: None,
+            reply: None,
         };
 
         let json = serde_json::to_value(&record).unwrap();
@@ -928,6 +1518,7 @@ mod tests {
                 created_at: "2026-03-07T00:00:00Z".to_string(),
                 facets: None,
                 embed: None,
+                reply: None,
             },
         };
 
@@ -992,6 +1583,7 @@ mod tests {
                 title: "Test Note".to_string(),
                 description: String::new(),
                 associated_refs: Vec::new(),
+                thumb: None,
             },
         };
 
@@ -1000,6 +1592,119 @@ mod tests {
         assert_eq!(json["external"]["uri"], "https://coreyja.com/notes/test");
     }
 
+    fn test_blob() -> Blob {
+        Blob {
+            r#type: "blob".to_string(),
+            r#ref: BlobRef {
+                link: "bafkreitest".to_string(),
+            },
+            mime_type: "image/png".to_string(),
+            size: 1234,
+        }
+    }
+
+    #[test]
+    fn external_embed_includes_thumb_when_present() {
+        let embed = ExternalEmbed {
+            uri: "https://coreyja.com/posts/x/".to_string(),
+            title: "x".to_string(),
+            description: String::new(),
+            associated_refs: Vec::new(),
+            thumb: Some(test_blob()),
+        };
+
+        let json = serde_json::to_value(&embed).unwrap();
+        assert_eq!(json["thumb"]["ref"]["$link"], "bafkreitest");
+    }
+
+    #[test]
+    fn images_embed_serializes_with_alt_and_aspect_ratio() {
+        let embed = PostEmbed::Images(EmbedImages {
+            embed_type: "app.bsky.embed.images".to_string(),
+            images: vec![EmbedImage {
+                alt: "A crab".to_string(),
+                image: test_blob(),
+                aspect_ratio: AspectRatio {
+                    width: 1200,
+                    height: 630,
+                },
+            }],
+        });
+
+        let json = serde_json::to_value(&embed).unwrap();
+        assert_eq!(json["$type"], "app.bsky.embed.images");
+        assert_eq!(json["images"][0]["alt"], "A crab");
+        assert_eq!(json["images"][0]["aspectRatio"]["width"], 1200);
+        assert_eq!(json["images"][0]["image"]["mimeType"], "image/png");
+    }
+
+    // ==================== images ====================
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(width, height))
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn markdown_images_are_collected_in_order_with_alt_text() {
+        let images = markdown_images(
+            "![First](./one.png) text\n\n- a list with ![](https://example.com/two.jpg)",
+        );
+        assert_eq!(
+            images,
+            vec![
+                MarkdownImage {
+                    url: "./one.png".to_string(),
+                    alt: "First".to_string(),
+                },
+                MarkdownImage {
+                    url: "https://example.com/two.jpg".to_string(),
+                    alt: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn small_pngs_are_uploaded_untouched() {
+        let bytes = png(40, 20);
+        let fitted = fit_image_to_blob_limit(&bytes).unwrap();
+        assert_eq!(fitted.bytes, bytes);
+        assert_eq!(fitted.mime_type, "image/png");
+        assert_eq!(
+            fitted.aspect_ratio,
+            AspectRatio {
+                width: 40,
+                height: 20
+            }
+        );
+    }
+
+    #[test]
+    fn oversized_images_are_scaled_down_to_jpeg() {
+        let fitted = fit_image_to_blob_limit(&png(2400, 1200)).unwrap();
+        assert_eq!(fitted.mime_type, "image/jpeg");
+        assert!(fitted.bytes.len() <= BSKY_IMAGE_MAX_BYTES);
+        assert_eq!(
+            fitted.aspect_ratio,
+            AspectRatio {
+                width: 2000,
+                height: 1000
+            }
+        );
+    }
+
+    #[test]
+    fn unreadable_images_are_an_error() {
+        assert!(fit_image_to_blob_limit(b"not an image").is_err());
+    }
+
     // ==================== markdown -> bsky text + facets ====================
 
     fn link_uri(f: &Facet) -> &str {
@@ -1041,6 +1746,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn plain_text_spells_out_link_urls() {
+        let plain = markdown_to_plain_text(
+            "Read [the docs](https://docs.rs/) or https://coreyja.com, **now**.",
+        );
+        assert_eq!(
+            plain,
+            "Read the docs (https://docs.rs/) or https://coreyja.com, now."
+        );
+    }
+
     #[test]
     fn bare_url_in_plain_text_gets_a_facet() {
         let (text, facets) = markdown_to_bsky_text("Check https://coreyja.com out");
@@ -1175,6 +1891,125 @@ mod tests {
         assert!(!text.contains("Body that won't fit."));
     }
 
+    // ==================== compose_note_thread ====================
+
+    const NOTE_URL: &str = "https://coreyja.com/notes/x";
+
+    fn assert_posts_fit(thread: &[(String, Vec<Facet>)]) {
+        for (text, _) in thread {
+            assert!(
+                text.chars().count() <= BSKY_POST_LIMIT,
+                "post is over the limit: {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn thread_of_a_short_note_is_the_single_post() {
+        let body = "See [first](https://a.example).";
+        let thread = compose_note_thread("Title", body, NOTE_URL);
+        let (text, facets) = compose_note_post("Title", body, NOTE_URL);
+
+        assert_eq!(thread.len(), 1);
+        assert_eq!(thread[0].0, text);
+        assert_eq!(thread[0].1.len(), facets.len());
+    }
+
+    #[test]
+    fn thread_splits_between_paragraphs_with_title_first_and_url_last() {
+        let paragraph = "word ".repeat(40).trim().to_string();
+        let body = format!("{paragraph}\n\n{paragraph}\n\n{paragraph}");
+        let thread = compose_note_thread("Title", &body, NOTE_URL);
+
+        assert_posts_fit(&thread);
+        assert_eq!(thread.len(), 3);
+        assert_eq!(thread[0].0, format!("Title\n\n{paragraph}"));
+        assert_eq!(thread[1].0, paragraph);
+        assert_eq!(thread[2].0, format!("{paragraph}\n\n{NOTE_URL}"));
+        assert_eq!(link_uri(&thread[2].1[0]), NOTE_URL);
+    }
+
+    #[test]
+    fn thread_splits_long_paragraphs_between_sentences() {
+        let body = "A sentence that goes on for a while. ".repeat(20);
+        let thread = compose_note_thread("Title", &body, NOTE_URL);
+
+        assert_posts_fit(&thread);
+        assert_eq!(thread.len(), 3);
+        for (text, _) in &thread[..thread.len() - 1] {
+            assert!(text.ends_with("a while."), "split mid-sentence: {text:?}");
+        }
+    }
+
+    #[test]
+    fn thread_moves_links_whole_to_the_next_post() {
+        let body = format!(
+            "{}[a link with several words in it](https://a.example) and more words after",
+            "word ".repeat(56)
+        );
+        let thread = compose_note_thread("Title", &body, NOTE_URL);
+
+        assert_posts_fit(&thread);
+        assert_eq!(thread.len(), 2);
+        assert!(thread[0].1.is_empty());
+
+        let (text, facets) = &thread[1];
+        let link = facets
+            .iter()
+            .find(|f| link_uri(f) == "https://a.example")
+            .expect("link facet moved with its text");
+        assert_eq!(
+            &text[link.index.byte_start..link.index.byte_end],
+            "a link with several words in it"
+        );
+    }
+
+    #[test]
+    fn thread_clips_facets_of_links_longer_than_a_post() {
+        let body = format!("[{}](https://a.example)", "x".repeat(350));
+        let thread = compose_note_thread("T", &body, NOTE_URL);
+
+        assert_posts_fit(&thread);
+        let linked: usize = thread
+            .iter()
+            .flat_map(|(text, facets)| {
+                facets
+                    .iter()
+                    .filter(|f| link_uri(f) == "https://a.example")
+                    .map(move |f| &text[f.index.byte_start..f.index.byte_end])
+            })
+            .inspect(|linked| assert!(linked.chars().all(|c| c == 'x')))
+            .map(str::len)
+            .sum();
+        assert_eq!(linked, 350);
+    }
+
+    #[test]
+    fn replies_reference_the_thread_root_and_parent() {
+        let strong_ref = |uri: &str, cid: &str| StrongRef {
+            uri: uri.to_string(),
+            cid: cid.to_string(),
+        };
+        let record = PostRecord {
+            record_type: "app.bsky.feed.post".to_string(),
+            text: "Reply".to_string(),
+            created_at: "2026-03-07T00:00:00Z".to_string(),
+            facets: None,
+            embed: None,
+            reply: Some(ReplyRef {
+                root: strong_ref("at://did:plc:x/app.bsky.feed.post/1", "cid1"),
+                parent: strong_ref("at://did:plc:x/app.bsky.feed.post/2", "cid2"),
+            }),
+        };
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            json["reply"]["root"]["uri"],
+            "at://did:plc:x/app.bsky.feed.post/1"
+        );
+        assert_eq!(json["reply"]["parent"]["cid"], "cid2");
+    }
+
     // ==================== standard.site record serialization ====================
 
     #[test]
@@ -1275,6 +2110,7 @@ mod tests {
             title: "x".to_string(),
             description: String::new(),
             associated_refs: Vec::new(),
+            thumb: None,
         };
         let json = serde_json::to_value(&embed).unwrap();
         assert!(
@@ -1299,6 +2135,7 @@ mod tests {
                     cid: "bafy2".to_string(),
                 },
             ],
+            thumb: None,
         };
         let json = serde_json::to_value(&embed).unwrap();
         let refs = json
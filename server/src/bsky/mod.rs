@@ -1,10 +1,41 @@
+use std::{sync::LazyLock, time::Duration};
+
+use cja::{color_eyre::eyre::eyre, jobs::Job as _};
+use db::bsky_threads::BskyThread;
 use regex::Regex;
 use rsky_lexicon::app::bsky::feed::{GetPostThreadOutput, ThreadViewPost, ThreadViewPostEnum};
+use serde_json::Value as JsonValue;
 use url::Url;
 
-pub async fn fetch_thread(post_url: &str) -> cja::Result<ThreadViewPost> {
+use crate::{jobs::bsky_threads::RefreshBskyThread, AppState};
+
+const DEFAULT_THREAD_REFRESH_MINUTES: u64 = 10;
+
+static THREAD_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    let minutes = std::env::var("BSKY_THREAD_REFRESH_MINUTES")
+        .ok()
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(DEFAULT_THREAD_REFRESH_MINUTES);
+
+    Duration::from_secs(minutes * 60)
+});
+
+/// How long a cached Bluesky thread is served before it's refetched, so new replies show up
+/// within this long. Set with `BSKY_THREAD_REFRESH_MINUTES`, defaulting to 10 minutes.
+pub fn thread_refresh_interval() -> Duration {
+    *THREAD_REFRESH_INTERVAL
+}
+
+fn thread_refresh_max_age() -> chrono::Duration {
+    chrono::Duration::from_std(thread_refresh_interval()).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Fetch the raw `getPostThread` response for a `bsky.app` post URL
+pub async fn fetch_thread_json(post_url: &str) -> cja::Result<JsonValue> {
     let re = Regex::new(r"/profile/([\w.:]+)/post/([\w]+)").unwrap();
-    let caps = re.captures(post_url).unwrap();
+    let caps = re
+        .captures(post_url)
+        .ok_or_else(|| eyre!("Not a Bluesky post URL: {post_url}"))?;
 
     let did = caps.get(1).unwrap().as_str();
     let post_id = caps.get(2).unwrap().as_str();
@@ -13,12 +44,72 @@ pub async fn fetch_thread(post_url: &str) -> cja::Result<ThreadViewPost> {
     let mut url = Url::parse("https://public.api.bsky.app/xrpc/app.bsky.feed.getPostThread")?;
     url.set_query(Some(&format!("uri={at_proto_uri}")));
 
-    let res = reqwest::get(url).await?;
-    let data = res.json::<GetPostThreadOutput>().await?;
+    let res = reqwest::get(url).await?.error_for_status()?;
+    let data = res.json::<JsonValue>().await?;
+
+    Ok(data)
+}
+
+/// Parse a `getPostThread` response, as stored in the cache, into the root of the thread
+pub fn parse_thread(data: JsonValue) -> cja::Result<ThreadViewPost> {
+    let data: GetPostThreadOutput = serde_json::from_value(data)?;
 
     let ThreadViewPostEnum::ThreadViewPost(thread) = data.thread else {
-        return Err(cja::color_eyre::eyre::eyre!("Expected thread view post"));
+        return Err(eyre!("Expected thread view post"));
     };
 
     Ok(thread)
 }
+
+/// Refetch the thread for `post_url` and store it in the cache. The response is checked to
+/// parse before it replaces what we had, so a bad response never blanks out the comments.
+pub async fn refresh_thread(state: &AppState, post_url: &str) -> cja::Result<()> {
+    let data = fetch_thread_json(post_url).await?;
+    parse_thread(data.clone())?;
+
+    BskyThread::upsert(&state.db, post_url, data).await?;
+
+    Ok(())
+}
+
+/// The cached thread for `post_url`, for rendering comments. Pages never wait on Bluesky:
+/// a missing or stale thread queues a [`RefreshBskyThread`] job and whatever we have is
+/// served in the meantime.
+pub async fn cached_thread(state: &AppState, post_url: &str) -> Option<ThreadViewPost> {
+    let cached = match BskyThread::find(&state.db, post_url).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            tracing::warn!(?e, post_url, "Failed to load cached Bluesky thread");
+            return None;
+        }
+    };
+
+    if cached
+        .as_ref()
+        .is_none_or(|c| c.is_stale(thread_refresh_max_age()))
+    {
+        if let Err(e) = queue_refresh(state, post_url).await {
+            tracing::warn!(?e, post_url, "Failed to queue Bluesky thread refresh");
+        }
+    }
+
+    match parse_thread(cached?.thread?) {
+        Ok(thread) => Some(thread),
+        Err(e) => {
+            tracing::warn!(?e, post_url, "Failed to parse cached Bluesky thread");
+            None
+        }
+    }
+}
+
+async fn queue_refresh(state: &AppState, post_url: &str) -> cja::Result<()> {
+    if BskyThread::claim_refresh(&state.db, post_url, thread_refresh_max_age()).await? {
+        RefreshBskyThread {
+            post_url: post_url.to_string(),
+        }
+        .enqueue(state.clone(), "Stale Bluesky thread".to_string(), None)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -46,6 +46,57 @@ pub struct CreateEmailResponse {
     pub id: String,
 }
 
+/// Request body for editing an email. Fields left as `None` are unchanged.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateEmailRequest {
+    pub subject: String,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<EmailStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_date: Option<DateTime<Utc>>,
+}
+
+/// An email as Buttondown has it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Email {
+    pub id: String,
+    pub subject: String,
+    /// `draft`, `scheduled`, `about_to_send`, `in_flight`, `sent` and others
+    pub status: String,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl Email {
+    /// Drafts and scheduled emails haven't gone out, so they can still be edited and
+    /// rescheduled
+    pub fn is_unsent(&self) -> bool {
+        matches!(self.status.as_str(), "draft" | "scheduled")
+    }
+}
+
+/// Delivery stats for a sent email. Every count is required, so a change to Buttondown's
+/// response fails loudly instead of recording zeros.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct EmailAnalytics {
+    pub recipients: i32,
+    pub opens: i32,
+    pub clicks: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SubscriberPage {
+    count: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EmailPage {
+    results: Vec<Email>,
+    /// URL of the next page, if there is one
+    next: Option<String>,
+}
+
 /// Client for interacting with the Buttondown API
 pub struct ButtondownClient {
     client: reqwest::Client,
@@ -60,19 +111,11 @@ impl ButtondownClient {
         }
     }
 
-    /// Create a new email in Buttondown
-    pub async fn create_email(
-        &self,
-        request: &CreateEmailRequest,
-    ) -> cja::Result<CreateEmailResponse> {
-        let url = format!("{BUTTONDOWN_API_BASE}/emails");
-
-        let response = self
-            .client
-            .post(&url)
+    /// Send a request with the API key, failing on any non-success status
+    async fn send_raw(&self, request: reqwest::RequestBuilder) -> cja::Result<reqwest::Response> {
+        let response = request
             .header("Authorization", format!("Token {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(request)
             .send()
             .await
             .context("Failed to send request to Buttondown API")?;
@@ -90,11 +133,70 @@ impl ButtondownClient {
             ));
         }
 
-        response
-            .json::<CreateEmailResponse>()
+        Ok(response)
+    }
+
+    /// Send a request with the API key and parse the JSON it responds with
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> cja::Result<T> {
+        self.send_raw(request)
+            .await?
+            .json::<T>()
             .await
             .context("Failed to parse Buttondown API response")
     }
+
+    /// Create a new email in Buttondown
+    pub async fn create_email(
+        &self,
+        request: &CreateEmailRequest,
+    ) -> cja::Result<CreateEmailResponse> {
+        let url = format!("{BUTTONDOWN_API_BASE}/emails");
+        self.send(self.client.post(&url).json(request)).await
+    }
+
+    pub async fn get_email(&self, id: &str) -> cja::Result<Email> {
+        let url = format!("{BUTTONDOWN_API_BASE}/emails/{id}");
+        self.send(self.client.get(&url)).await
+    }
+
+    /// Edit an existing email's content, and its status when one is given
+    pub async fn update_email(&self, id: &str, request: &UpdateEmailRequest) -> cja::Result<Email> {
+        let url = format!("{BUTTONDOWN_API_BASE}/emails/{id}");
+        self.send(self.client.patch(&url).json(request)).await
+    }
+
+    /// Every email, following Buttondown's pages until there are no more
+    pub async fn list_emails(&self) -> cja::Result<Vec<Email>> {
+        let mut emails = Vec::new();
+        let mut url = Some(format!("{BUTTONDOWN_API_BASE}/emails"));
+        while let Some(page_url) = url {
+            let page: EmailPage = self.send(self.client.get(&page_url)).await?;
+            emails.extend(page.results);
+            url = page.next;
+        }
+        Ok(emails)
+    }
+
+    pub async fn delete_email(&self, id: &str) -> cja::Result<()> {
+        let url = format!("{BUTTONDOWN_API_BASE}/emails/{id}");
+        self.send_raw(self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    pub async fn email_analytics(&self, id: &str) -> cja::Result<EmailAnalytics> {
+        let url = format!("{BUTTONDOWN_API_BASE}/emails/{id}/analytics");
+        self.send(self.client.get(&url)).await
+    }
+
+    /// How many people are currently subscribed
+    pub async fn subscriber_count(&self) -> cja::Result<i32> {
+        let url = format!("{BUTTONDOWN_API_BASE}/subscribers?type=regular");
+        let page: SubscriberPage = self.send(self.client.get(&url)).await?;
+        Ok(page.count)
+    }
 }
 
 #[cfg(test)]
@@ -150,4 +252,50 @@ mod tests {
         assert_eq!(json["status"], "scheduled");
         assert!(json.get("publish_date").is_some());
     }
+
+    #[test]
+    fn test_update_email_request_omits_unchanged_status() {
+        let request = UpdateEmailRequest {
+            subject: "Edited".to_string(),
+            body: "New body".to_string(),
+            status: None,
+            publish_date: None,
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["subject"], "Edited");
+        assert!(json.get("status").is_none());
+        assert!(json.get("publish_date").is_none());
+    }
+
+    #[test]
+    fn test_only_drafts_and_scheduled_emails_are_unsent() {
+        let email = |status: &str| Email {
+            id: "id".to_string(),
+            subject: "s".to_string(),
+            status: status.to_string(),
+            body: String::new(),
+        };
+
+        assert!(email("draft").is_unsent());
+        assert!(email("scheduled").is_unsent());
+        assert!(!email("sent").is_unsent());
+        assert!(!email("about_to_send").is_unsent());
+    }
+
+    #[test]
+    fn test_email_analytics_requires_every_count() {
+        let analytics: EmailAnalytics = serde_json::from_str(
+            r#"{"recipients": 120, "opens": 64, "clicks": 12, "unsubscriptions": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(analytics.recipients, 120);
+        assert_eq!(analytics.opens, 64);
+        assert_eq!(analytics.clicks, 12);
+
+        assert!(serde_json::from_str::<EmailAnalytics>(
+            r#"{"recipients": 120, "opens": 64, "unsubscriptions": 1}"#
+        )
+        .is_err());
+    }
 }
@@ -1,425 +1,259 @@
-use std::path::{Path, PathBuf};
+//! Syndicates notes to Bluesky as posts linking back to the site. Notes with
+//! `bsky_thread: true` are split into a thread when they don't fit in one post, and
+//! images in a note are attached to its post.
+
+use std::path::Path;
 
-use crate::bluesky::{at_uri_to_web_url, BlueskyClient, BlueskyConfig};
-use crate::commands::frontmatter;
 use chrono::NaiveDate;
-use clap::Args;
-use posts::notes::FrontMatter;
+
+use crate::bluesky::{
+    at_uri_to_web_url, bsky_thread_len, markdown_images, BlueskyClient, BlueskyConfig, ImageUpload,
+    BSKY_MAX_IMAGES,
+};
+use crate::commands::publish::{ContentItem, Destination, DestinationKind, PlannedWrite, Step};
 
 /// Cutoff date - only publish notes dated on or after this date
 const CUTOFF_DATE: &str = "2026-03-01";
 
-#[derive(Args, Debug)]
-#[group(required = true, multiple = false)]
-pub struct PublishBlueskyArgs {
-    /// Path to a single note markdown file
-    #[arg(long, group = "input")]
-    pub path: Option<PathBuf>,
-
-    /// Directory to scan for unpublished notes (notes/*.md)
-    #[arg(long, group = "input")]
-    pub dir: Option<PathBuf>,
-}
-
 fn cutoff_date() -> NaiveDate {
     NaiveDate::parse_from_str(CUTOFF_DATE, "%Y-%m-%d").expect("CUTOFF_DATE should be valid")
 }
 
-/// Classify a note file: should it be published?
-///
-/// Returns `Ok(Some(frontmatter))` when the note is eligible for publishing,
-/// `Ok(None)` when it should be skipped (already published or before cutoff),
-/// and `Err` when the file can't be read or parsed.
-fn classify_note(path: &Path) -> cja::Result<Option<FrontMatter>> {
-    let content = std::fs::read_to_string(path).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to read file {}: {}", path.display(), e)
-    })?;
-
-    // Cheap idempotency check before YAML parse.
-    if content.contains("bsky_url:") {
-        return Ok(None);
-    }
-
-    let (frontmatter, _body) = parse_frontmatter(&content)?;
-
-    if frontmatter.date < cutoff_date() {
-        return Ok(None);
-    }
-
-    Ok(Some(frontmatter))
+pub(crate) fn note_url(slug: &str) -> String {
+    format!("https://coreyja.com/notes/{slug}")
 }
 
-/// Parse frontmatter from raw markdown content
-fn parse_frontmatter(content: &str) -> cja::Result<(FrontMatter, String)> {
-    let (yaml, body) = frontmatter::split_frontmatter(content)?;
-    let fm: FrontMatter = serde_yaml::from_str(yaml)
-        .map_err(|e| cja::color_eyre::eyre::eyre!("Invalid YAML: {}", e))?;
-    Ok((fm, body.to_string()))
-}
-
-/// Update frontmatter in a markdown file with the `bsky_url`
-fn update_frontmatter_with_bsky_url(content: &str, url: &str) -> String {
-    frontmatter::append_frontmatter_keys(content, &[("bsky_url", url)])
-}
-
-/// Publish a single note, given an authenticated client. Idempotent: skips
-/// notes that already have a `bsky_url` or are dated before the cutoff.
-async fn publish_one(path: &Path, client: &BlueskyClient) -> cja::Result<bool> {
-    let content = std::fs::read_to_string(path).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to read file {}: {}", path.display(), e)
-    })?;
-
-    if content.contains("bsky_url:") {
-        println!("Note already has bsky_url, skipping: {}", path.display());
-        return Ok(false);
-    }
-
-    let (frontmatter, body) = parse_frontmatter(&content)?;
-
-    if frontmatter.date < cutoff_date() {
+/// The note's images, up to as many as a post can embed. Relative paths are read from
+/// next to the note, anything else is fetched.
+pub(crate) async fn load_images(item: &ContentItem) -> cja::Result<Vec<ImageUpload>> {
+    let images = markdown_images(item.body());
+    if images.len() > BSKY_MAX_IMAGES {
         println!(
-            "Note date {} is before cutoff date {}, skipping",
-            frontmatter.date, CUTOFF_DATE
+            "Note has {} images, only the first {BSKY_MAX_IMAGES} will be attached",
+            images.len()
         );
-        return Ok(false);
     }
 
-    let note_url = format!("https://coreyja.com/notes/{}", frontmatter.slug);
-
-    println!("Publishing note to Bluesky: {}", frontmatter.title);
-    let response = client
-        .create_note_post(&frontmatter.title, &body, &note_url)
-        .await?;
-
-    let web_url = at_uri_to_web_url(&response.uri)?;
-    println!("Published to Bluesky: {web_url}");
+    let dir = item.path.parent().unwrap_or_else(|| Path::new(""));
+    let mut uploads = Vec::new();
+    for image in images.into_iter().take(BSKY_MAX_IMAGES) {
+        let bytes = if image.url.starts_with("https://") || image.url.starts_with("http://") {
+            reqwest::get(&image.url)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec()
+        } else {
+            std::fs::read(dir.join(&image.url)).map_err(|e| {
+                cja::color_eyre::eyre::eyre!("Failed to read image {}: {}", image.url, e)
+            })?
+        };
+        uploads.push(ImageUpload {
+            bytes,
+            alt: image.alt,
+        });
+    }
 
-    let updated_content = update_frontmatter_with_bsky_url(&content, &web_url);
-    std::fs::write(path, updated_content).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to write file {}: {}", path.display(), e)
-    })?;
+    Ok(uploads)
+}
 
-    println!("Updated {} with bsky_url", path.display());
-    Ok(true)
+/// Posts each note once, recording the post's `bsky_url` in the note's frontmatter
+#[derive(Default)]
+pub(crate) struct BlueskyNotes {
+    client: Option<BlueskyClient>,
 }
 
-/// Scan a directory for `.md` notes that are eligible for publishing.
-fn find_unpublished_notes(dir: &Path) -> cja::Result<Vec<PathBuf>> {
-    let mut paths = Vec::new();
-    let entries = std::fs::read_dir(dir).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to read directory {}: {}", dir.display(), e)
-    })?;
+#[async_trait::async_trait]
+impl Destination for BlueskyNotes {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::Bluesky
+    }
 
-    for entry in entries {
-        let entry = entry
-            .map_err(|e| cja::color_eyre::eyre::eyre!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+    fn cutoff(&self) -> Option<NaiveDate> {
+        Some(cutoff_date())
+    }
 
-        if !path.is_file() {
-            continue;
-        }
-        if path.extension().and_then(|s| s.to_str()) != Some("md") {
-            continue;
+    fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>> {
+        let Some(note) = item.note() else {
+            return Ok(None);
+        };
+        if note.bsky_url.is_some() {
+            return Ok(None);
         }
 
-        match classify_note(&path) {
-            Ok(Some(_)) => paths.push(path),
-            Ok(None) => {}
-            Err(e) => {
-                // Surface but don't abort — a single malformed note shouldn't
-                // block syndication of the rest.
-                eprintln!("Failed to classify {}: {}", path.display(), e);
-            }
+        let thread_len = if note.bsky_thread {
+            bsky_thread_len(&note.title, item.body(), &note_url(&note.slug))
+        } else {
+            1
+        };
+        let mut description = format!("post note \"{}\"", note.title);
+        if thread_len > 1 {
+            description.push_str(&format!(" as a thread of {thread_len} posts"));
         }
-    }
-
-    paths.sort();
-    Ok(paths)
-}
-
-pub async fn publish_bluesky(args: &PublishBlueskyArgs) -> cja::Result<()> {
-    let config = BlueskyConfig::from_env()?;
-    let client = BlueskyClient::login(&config).await?;
-
-    match (&args.path, &args.dir) {
-        (Some(path), None) => {
-            publish_one(path, &client).await?;
-            Ok(())
+        match markdown_images(item.body()).len().min(BSKY_MAX_IMAGES) {
+            0 => {}
+            1 => description.push_str(" with 1 image"),
+            n => description.push_str(&format!(" with {n} images")),
         }
-        (None, Some(dir)) => {
-            let paths = find_unpublished_notes(dir)?;
-            if paths.is_empty() {
-                println!("No unpublished notes found in {}", dir.display());
-                return Ok(());
-            }
 
-            println!("Found {} note(s) to publish", paths.len());
-            let mut failures: Vec<(PathBuf, cja::color_eyre::Report)> = Vec::new();
-            let mut published = 0;
-            for path in &paths {
-                match publish_one(path, &client).await {
-                    Ok(true) => published += 1,
-                    Ok(false) => {}
-                    Err(e) => {
-                        eprintln!("Failed to publish {}: {}", path.display(), e);
-                        failures.push((path.clone(), e));
-                    }
-                }
-            }
+        Ok(Some(Step {
+            description,
+            writes: vec![PlannedWrite::pending("bsky_url")],
+        }))
+    }
 
-            println!(
-                "Done. Published: {published}, skipped: {}, failed: {}",
-                paths.len() - published - failures.len(),
-                failures.len()
-            );
+    async fn connect(&mut self) -> cja::Result<()> {
+        let config = BlueskyConfig::from_env()?;
+        self.client = Some(BlueskyClient::login(&config).await?);
+        Ok(())
+    }
 
-            if !failures.is_empty() {
-                return Err(cja::color_eyre::eyre::eyre!(
-                    "{} note(s) failed to publish (see logs above); next run will retry",
-                    failures.len()
-                ));
+    async fn publish(
+        &self,
+        item: &ContentItem,
+        _step: &Step,
+    ) -> cja::Result<Vec<(&'static str, String)>> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| cja::color_eyre::eyre::eyre!("Not logged in to Bluesky"))?;
+        let note = item
+            .note()
+            .ok_or_else(|| cja::color_eyre::eyre::eyre!("Only notes are posted to Bluesky"))?;
+
+        let url = note_url(&note.slug);
+        let images = load_images(item).await?;
+        // Threads are linked by their first post
+        let uri = if note.bsky_thread {
+            let thread = client
+                .create_note_thread(&note.title, item.body(), &url, images)
+                .await?;
+            // The root is still recorded, so the next run doesn't post the thread again
+            if let Some(e) = &thread.error {
+                eprintln!(
+                    "Bluesky thread for {} stopped after {} of {} posts, the rest need posting by hand: {e}",
+                    item.path.display(),
+                    thread.replies.len() + 1,
+                    thread.len,
+                );
             }
-
-            Ok(())
-        }
-        // clap's ArgGroup with required=true, multiple=false guarantees we
-        // never land here, but keep an explicit branch for clarity.
-        _ => Err(cja::color_eyre::eyre::eyre!(
-            "exactly one of --path or --dir must be provided"
-        )),
+            thread.root.uri
+        } else {
+            client
+                .create_note_post(&note.title, item.body(), &url, images)
+                .await?
+                .uri
+        };
+        let web_url = at_uri_to_web_url(&uri)?;
+        println!("Published to Bluesky: {web_url}");
+
+        Ok(vec![("bsky_url", web_url)])
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    // ==================== parse_frontmatter tests ====================
-
-    #[test]
-    fn parse_frontmatter_basic_note() {
-        let content = r"---
-title: Test Note
-date: 2026-03-05
-slug: test-note
----
-
-This is the body of the note.
-";
-        let (fm, body) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "Test Note");
-        assert_eq!(fm.slug, "test-note");
-        assert!(body.contains("This is the body of the note."));
-    }
+    use std::path::PathBuf;
 
-    #[test]
-    fn parse_frontmatter_with_bsky_url() {
-        let content = r"---
-title: Already Published
-date: 2026-03-05
-slug: already-published
-bsky_url: https://bsky.app/profile/coreyja.com/post/abc123
----
-
-Body text.
-";
-        let (fm, _body) = parse_frontmatter(content).unwrap();
-        assert_eq!(
-            fm.bsky_url,
-            Some("https://bsky.app/profile/coreyja.com/post/abc123".to_string())
-        );
-    }
+    use posts::source::ContentDir;
 
-    #[test]
-    fn parse_frontmatter_missing_opening_delimiter() {
-        let content = r"title: Bad
-date: 2026-03-05
-slug: bad
----
-
-Body.
-";
-        assert!(parse_frontmatter(content).is_err());
-    }
+    use super::*;
 
-    #[test]
-    fn parse_frontmatter_missing_closing_delimiter() {
-        let content = r"---
-title: Bad
-date: 2026-03-05
-slug: bad
-
-Body without closing delimiter.
-";
-        assert!(parse_frontmatter(content).is_err());
+    fn note(raw: &str) -> ContentItem {
+        ContentItem::parse(
+            PathBuf::from("notes/n.md"),
+            raw.to_string(),
+            ContentDir::Notes,
+        )
+        .unwrap()
     }
 
-    // ==================== update_frontmatter_with_bsky_url tests ====================
-
     #[test]
-    fn update_frontmatter_adds_bsky_url() {
-        let content = r"---
-title: Test Note
-date: 2026-03-05
-slug: test-note
----
-
-Body content here.
-";
-        let updated = update_frontmatter_with_bsky_url(
-            content,
-            "https://bsky.app/profile/coreyja.com/post/abc123",
+    fn plan_skips_already_published_notes() {
+        let item = note(
+            "---\ntitle: A\ndate: 2026-03-05\nslug: a\nbsky_url: https://bsky.app/x\n---\n\nbody\n",
         );
-        assert!(updated.contains("bsky_url: https://bsky.app/profile/coreyja.com/post/abc123"));
-        assert!(updated.contains("Body content here."));
+        assert!(BlueskyNotes::default().plan(&item).unwrap().is_none());
     }
 
     #[test]
-    fn update_frontmatter_preserves_all_existing_fields() {
-        let content = r"---
-title: Full Note
-date: 2026-03-05
-slug: full-note
----
-
-Body.
-";
-        let updated = update_frontmatter_with_bsky_url(
-            content,
-            "https://bsky.app/profile/coreyja.com/post/xyz",
-        );
-
-        assert!(updated.contains("title: Full Note"));
-        assert!(updated.contains("date: 2026-03-05"));
-        assert!(updated.contains("slug: full-note"));
-        assert!(updated.contains("bsky_url: https://bsky.app/profile/coreyja.com/post/xyz"));
+    fn plan_posts_new_notes_and_records_the_url() {
+        let item = note("---\ntitle: New\ndate: 2026-04-15\nslug: new\n---\n\nbody\n");
+        let step = BlueskyNotes::default()
+            .plan(&item)
+            .unwrap()
+            .expect("should be planned");
+        assert_eq!(step.writes, vec![PlannedWrite::pending("bsky_url")]);
     }
 
     #[test]
-    fn update_frontmatter_preserves_body_formatting() {
-        let content = r"---
-title: Test
-date: 2026-03-05
-slug: test
----
-
-# Heading
-
-Paragraph with **bold** and _italic_.
-
-- List item 1
-- List item 2
-
-```rust
-fn main() {}
-```
-";
-        let updated =
-            update_frontmatter_with_bsky_url(content, "https://bsky.app/profile/x/post/y");
-
-        assert!(updated.contains("# Heading"));
-        assert!(updated.contains("**bold**"));
-        assert!(updated.contains("- List item 1"));
-        assert!(updated.contains("```rust"));
-    }
-
-    // ==================== classify_note / scan tests ====================
-
-    fn write_note(dir: &std::path::Path, name: &str, body: &str) -> PathBuf {
-        let path = dir.join(name);
-        std::fs::write(&path, body).expect("write test note");
-        path
-    }
-
-    #[test]
-    fn classify_note_skips_already_published() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = write_note(
-            tmp.path(),
-            "a.md",
-            "---\ntitle: A\ndate: 2026-03-05\nslug: a\nbsky_url: https://bsky.app/x\n---\n\nbody\n",
-        );
-        assert!(classify_note(&path).unwrap().is_none());
-    }
-
-    #[test]
-    fn classify_note_skips_before_cutoff() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = write_note(
-            tmp.path(),
-            "old.md",
-            "---\ntitle: Old\ndate: 2024-01-01\nslug: old\n---\n\nbody\n",
-        );
-        assert!(classify_note(&path).unwrap().is_none());
+    fn plan_describes_long_notes_posted_as_threads() {
+        let body = "A sentence that goes on for a while. ".repeat(20);
+        let threaded = note(&format!(
+            "---\ntitle: Long\ndate: 2026-04-15\nslug: long\nbsky_thread: true\n---\n\n{body}\n"
+        ));
+        let step = BlueskyNotes::default().plan(&threaded).unwrap().unwrap();
+        assert!(step.description.contains("as a thread of 3 posts"));
+        assert_eq!(step.writes, vec![PlannedWrite::pending("bsky_url")]);
+
+        let truncated = note(&format!(
+            "---\ntitle: Long\ndate: 2026-04-15\nslug: long\n---\n\n{body}\n"
+        ));
+        let step = BlueskyNotes::default().plan(&truncated).unwrap().unwrap();
+        assert_eq!(step.description, "post note \"Long\"");
     }
 
     #[test]
-    fn classify_note_accepts_eligible_note() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = write_note(
-            tmp.path(),
-            "new.md",
-            "---\ntitle: New\ndate: 2026-04-15\nslug: new\n---\n\nbody\n",
+    fn plan_mentions_attached_images() {
+        let item = note(
+            "---\ntitle: Pics\ndate: 2026-04-15\nslug: pics\n---\n\n![one](a.png) ![two](b.png)\n",
         );
-        let fm = classify_note(&path).unwrap().expect("should be eligible");
-        assert_eq!(fm.slug, "new");
+        let step = BlueskyNotes::default().plan(&item).unwrap().unwrap();
+        assert_eq!(step.description, "post note \"Pics\" with 2 images");
     }
 
-    #[test]
-    fn classify_note_accepts_cutoff_day_boundary() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = write_note(
-            tmp.path(),
-            "boundary.md",
-            "---\ntitle: Boundary\ndate: 2026-03-01\nslug: boundary\n---\n\nbody\n",
-        );
-        assert!(classify_note(&path).unwrap().is_some());
+    #[tokio::test]
+    async fn images_are_read_from_next_to_the_note() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("pics")).unwrap();
+        std::fs::write(dir.path().join("pics/crab.png"), b"crab bytes").unwrap();
+        let item = ContentItem::parse(
+            dir.path().join("n.md"),
+            "---\ntitle: A\ndate: 2026-04-15\nslug: a\n---\n\n![A crab](pics/crab.png)\n"
+                .to_string(),
+            ContentDir::Notes,
+        )
+        .unwrap();
+
+        let images = load_images(&item).await.unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].bytes, b"crab bytes");
+        assert_eq!(images[0].alt, "A crab");
     }
 
     #[test]
-    fn find_unpublished_notes_returns_only_eligible_md_files() {
-        let tmp = tempfile::tempdir().unwrap();
-        write_note(
-            tmp.path(),
-            "published.md",
-            "---\ntitle: P\ndate: 2026-04-01\nslug: p\nbsky_url: https://bsky.app/x\n---\n\nbody\n",
-        );
-        write_note(
-            tmp.path(),
-            "old.md",
-            "---\ntitle: O\ndate: 2024-01-01\nslug: o\n---\n\nbody\n",
-        );
-        let eligible = write_note(
-            tmp.path(),
-            "fresh.md",
-            "---\ntitle: F\ndate: 2026-04-15\nslug: f\n---\n\nbody\n",
-        );
-        // Non-markdown files should be ignored.
-        write_note(tmp.path(), "readme.txt", "not a note");
-
-        let found = find_unpublished_notes(tmp.path()).unwrap();
-        assert_eq!(found, vec![eligible]);
+    fn plan_ignores_blog_posts() {
+        let item = ContentItem::parse(
+            PathBuf::from("blog/p/index.md"),
+            "---\ntitle: P\ndate: 2026-04-15\n---\n\nbody\n".to_string(),
+            ContentDir::Blog,
+        )
+        .unwrap();
+        assert!(BlueskyNotes::default().plan(&item).unwrap().is_none());
     }
 
     #[test]
-    fn find_unpublished_notes_skips_malformed_files() {
-        let tmp = tempfile::tempdir().unwrap();
-        // Missing frontmatter — should be logged and skipped, not abort the scan.
-        write_note(tmp.path(), "bad.md", "no frontmatter here\n");
-        let eligible = write_note(
-            tmp.path(),
-            "good.md",
-            "---\ntitle: G\ndate: 2026-04-15\nslug: g\n---\n\nbody\n",
-        );
-
-        let found = find_unpublished_notes(tmp.path()).unwrap();
-        assert_eq!(found, vec![eligible]);
+    fn cutoff_day_is_included() {
+        let cutoff = BlueskyNotes::default().cutoff().unwrap();
+        assert_eq!(cutoff, NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
     }
 
     /// Every note that *could* publish to Bluesky (date >= cutoff) must fit
     /// in Bluesky's 300-character post limit so the publish step doesn't
-    /// have to silently truncate the body. Catches "wrote too much" at PR
-    /// review time rather than at publish time.
+    /// have to silently truncate the body, unless it opts into being posted
+    /// as a thread. Catches "wrote too much" at PR review time rather than
+    /// at publish time.
     #[test]
     fn all_publishable_notes_fit_within_bsky_post_limit() {
         let notes_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -436,14 +270,15 @@ fn main() {}
                 continue;
             }
             let content = std::fs::read_to_string(&path).expect("read note file");
-            let Ok((fm, body)) = parse_frontmatter(&content) else {
+            let Ok(item) = ContentItem::parse(path.clone(), content, ContentDir::Notes) else {
                 continue;
             };
-            if fm.date < cutoff {
+            let fm = item.note().unwrap();
+            if fm.date < cutoff || fm.bsky_thread {
                 continue;
             }
-            let url = format!("https://coreyja.com/notes/{}", fm.slug);
-            let count = crate::bluesky::bsky_post_char_count(&fm.title, &body, &url);
+            let count =
+                crate::bluesky::bsky_post_char_count(&fm.title, item.body(), &note_url(&fm.slug));
             if count > 300 {
                 failures.push(format!(
                     "{}: {count} chars (over by {})",
@@ -456,7 +291,8 @@ fn main() {}
         assert!(
             failures.is_empty(),
             "These notes would exceed Bluesky's 300-character post limit and \
-             get truncated when syndicated. Shorten the title, body, or slug:\n  {}",
+             get truncated when syndicated. Shorten the title, body, or slug, or \
+             add `bsky_thread: true` to post it as a thread:\n  {}",
             failures.join("\n  ")
         );
     }
@@ -1,39 +1,35 @@
-use std::path::{Path, PathBuf};
+//! Sends newsletters to Buttondown, as drafts to be reviewed and sent from there or
+//! scheduled for their `newsletter_send_at`, and keeps unsent emails in sync with edits.
 
-use chrono::NaiveDate;
-use clap::Args;
-use posts::blog::BlogFrontMatter;
+use std::{path::Path, str::FromStr as _};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use posts::{blog::BlogFrontMatter, MarkdownAst};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    buttondown::{ButtondownClient, ButtondownConfig, CreateEmailRequest, EmailStatus},
+    buttondown::{
+        ButtondownClient, ButtondownConfig, CreateEmailRequest, EmailStatus, UpdateEmailRequest,
+    },
+    commands::publish::{ContentItem, Destination, DestinationKind, PlannedWrite, Step},
+    http_server::pages::blog::md::{
+        html::MarkdownRenderContext, IntoEmailHtml as _, SyntaxHighlightingContext,
+    },
     AppConfig,
 };
 
 /// Cutoff date - only publish newsletters dated on or after this date
 const CUTOFF_DATE: &str = "2026-01-25";
 
-#[derive(Args, Debug)]
-pub struct PublishButtondownArgs {
-    /// Path to the newsletter markdown file
-    #[arg(long)]
-    pub path: PathBuf,
-}
-
-/// Rewrite relative URLs to absolute URLs using the app's base URL
-fn rewrite_relative_urls(content: &str, post_dir: &str, app_config: &AppConfig) -> String {
-    // Replace ./path with absolute URL for local assets (served at /posts/)
-    let assets_url = app_config.app_url(&format!("/posts/{post_dir}"));
-    let content = content.replace("](./", &format!("]({assets_url}/"));
-
-    // Replace root-relative links ](/path with absolute URLs
-    let site_url = app_config.home_page().trim_end_matches('/').to_string();
-    content.replace("](/", &format!("]({site_url}/"))
-}
+/// Starts every email body sent from here. Plaintext mode sends the body as it is, instead
+/// of through Buttondown's own markdown rendering, and it marks which emails came from the
+/// site rather than Buttondown's editor.
+pub(crate) const PLAINTEXT_MODE_COMMENT: &str = "<!-- buttondown-editor-mode: plaintext -->";
 
 /// Extract the directory path from a file path for URL construction
 ///
 /// e.g., `blog/weekly/20260123/index.md` -> `weekly/20260123`
-fn extract_post_dir(path: &Path) -> cja::Result<String> {
+pub(crate) fn extract_post_dir(path: &Path) -> cja::Result<String> {
     let path_str = path.to_string_lossy();
 
     // Find the blog/ prefix and extract the rest
@@ -52,143 +48,173 @@ fn extract_post_dir(path: &Path) -> cja::Result<String> {
     ))
 }
 
-/// Parse frontmatter from raw markdown content
-fn parse_frontmatter(content: &str) -> cja::Result<(BlogFrontMatter, String)> {
-    // Find the frontmatter delimiters
-    let content = content.trim_start();
-    if !content.starts_with("---") {
-        return Err(cja::color_eyre::eyre::eyre!(
-            "Missing frontmatter delimiter"
-        ));
-    }
-
-    // Find the closing delimiter
-    let rest = &content[3..];
-    let Some(end_idx) = rest.find("\n---") else {
-        return Err(cja::color_eyre::eyre::eyre!(
-            "Missing closing frontmatter delimiter"
-        ));
-    };
-
-    let yaml = &rest[..end_idx].trim();
-    let body = &rest[end_idx + 4..]; // Skip "\n---"
-
-    let frontmatter: BlogFrontMatter = serde_yaml::from_str(yaml)
-        .map_err(|e| cja::color_eyre::eyre::eyre!("Invalid YAML: {}", e))?;
-
-    Ok((frontmatter, body.to_string()))
+/// Hash of everything synced to the email, so edits to the post are noticed without
+/// asking Buttondown
+fn content_hash(post: &BlogFrontMatter, body: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(post.title.as_bytes());
+    hasher.update([0]);
+    hasher.update(body.as_bytes());
+    if let Some(send_at) = post.newsletter_send_at {
+        hasher.update([0]);
+        hasher.update(send_at.to_rfc3339().as_bytes());
+    }
+    hex::encode(&hasher.finalize()[..8])
 }
 
-/// Update frontmatter in a markdown file with the `buttondown_id`
-fn update_frontmatter_with_id(content: &str, buttondown_id: &str) -> cja::Result<String> {
-    // Find the frontmatter delimiters
-    let content = content.trim_start();
-    if !content.starts_with("---") {
-        return Err(cja::color_eyre::eyre::eyre!(
-            "Missing frontmatter delimiter"
-        ));
+/// Newsletters with a `newsletter_send_at` still to come are scheduled for it. The rest
+/// are drafts, to be reviewed in Buttondown before sending.
+fn delivery(
+    send_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> (EmailStatus, Option<DateTime<Utc>>) {
+    match send_at {
+        Some(at) if at > now => (EmailStatus::Scheduled, Some(at)),
+        _ => (EmailStatus::Draft, None),
     }
-
-    // Find the closing delimiter
-    let rest = &content[3..];
-    let Some(end_idx) = rest.find("\n---") else {
-        return Err(cja::color_eyre::eyre::eyre!(
-            "Missing closing frontmatter delimiter"
-        ));
-    };
-
-    let yaml = &rest[..end_idx];
-    let body = &rest[end_idx + 4..]; // Skip "\n---"
-
-    // Add buttondown_id to the frontmatter
-    let updated_yaml = format!("{}\nbuttondown_id: {buttondown_id}", yaml.trim_end());
-
-    Ok(format!("---\n{updated_yaml}\n---{body}"))
 }
 
-pub async fn publish_buttondown(args: &PublishButtondownArgs) -> cja::Result<()> {
-    let path = &args.path;
-
-    // Read the file
-    let content = std::fs::read_to_string(path).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to read file {}: {}", path.display(), e)
-    })?;
-
-    // Parse frontmatter
-    let (frontmatter, body) = parse_frontmatter(&content)?;
+/// The post rendered as email HTML, with links and images made absolute
+fn email_body(item: &ContentItem, app_config: &AppConfig) -> cja::Result<String> {
+    let post_dir = extract_post_dir(&item.path)?;
+    let html = MarkdownAst::from_str(item.body())?.0.into_email_html(
+        app_config,
+        &MarkdownRenderContext {
+            syntax_highlighting: SyntaxHighlightingContext,
+            current_article_path: format!("/posts/{post_dir}"),
+        },
+    )?;
+
+    Ok(format!("{PLAINTEXT_MODE_COMMENT}\n{}", html.into_string()))
+}
 
-    // Validate this is a newsletter
-    if !frontmatter.is_newsletter {
-        return Err(cja::color_eyre::eyre::eyre!(
-            "Post is not marked as a newsletter (is_newsletter: false)"
-        ));
-    }
+/// Creates an email for each newsletter, recording its `buttondown_id`, and pushes later
+/// edits to it until it's sent
+#[derive(Default)]
+pub(crate) struct Buttondown {
+    connection: Option<(AppConfig, ButtondownClient)>,
+}
 
-    // Check if already published
-    if let Some(id) = &frontmatter.buttondown_id {
-        println!("Newsletter already published to Buttondown (id: {id})");
-        return Ok(());
+#[async_trait::async_trait]
+impl Destination for Buttondown {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::Buttondown
     }
 
-    // Check cutoff date
-    let cutoff =
-        NaiveDate::parse_from_str(CUTOFF_DATE, "%Y-%m-%d").expect("CUTOFF_DATE should be valid");
-    if frontmatter.date < cutoff {
-        println!(
-            "Newsletter date {} is before cutoff date {}, skipping",
-            frontmatter.date, CUTOFF_DATE
-        );
-        return Ok(());
+    fn cutoff(&self) -> Option<NaiveDate> {
+        Some(
+            NaiveDate::parse_from_str(CUTOFF_DATE, "%Y-%m-%d")
+                .expect("CUTOFF_DATE should be valid"),
+        )
     }
 
-    // Extract post directory for URL rewriting
-    let post_dir = extract_post_dir(path)?;
-
-    // Rewrite relative URLs to absolute
-    let app_config = AppConfig::from_env()?;
-    let body_with_absolute_urls = rewrite_relative_urls(&body, &post_dir, &app_config);
-
-    // Prepend editor mode hint so Buttondown renders markdown
-    let body_with_absolute_urls =
-        format!("<!-- buttondown-editor-mode: fancy -->\n{body_with_absolute_urls}");
-
-    // Always create as draft so it can be reviewed in Buttondown before sending
-    let (status, publish_date) = (EmailStatus::Draft, None);
-
-    // Create the request
-    let request = CreateEmailRequest {
-        subject: frontmatter.title.clone(),
-        body: body_with_absolute_urls,
-        status,
-        publish_date,
-    };
-
-    // Load config and create client
-    let config = ButtondownConfig::from_env()?;
-    let client = ButtondownClient::new(&config);
-
-    // Send the request
-    println!("Publishing newsletter: {}", frontmatter.title);
-    let response = client.create_email(&request).await?;
-
-    println!(
-        "Successfully published to Buttondown with id: {}",
-        response.id
-    );
-
-    // Update the file with the buttondown_id
-    let updated_content = update_frontmatter_with_id(&content, &response.id)?;
-    std::fs::write(path, updated_content).map_err(|e| {
-        cja::color_eyre::eyre::eyre!("Failed to write file {}: {}", path.display(), e)
-    })?;
+    fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>> {
+        let Some(post) = item.post() else {
+            return Ok(None);
+        };
+        if !post.is_newsletter {
+            return Ok(None);
+        }
 
-    println!("Updated {} with buttondown_id", path.display());
+        let hash = content_hash(post, item.body());
+        let step = match &post.buttondown_id {
+            None => {
+                let description = match delivery(post.newsletter_send_at, Utc::now()) {
+                    (EmailStatus::Scheduled, Some(at)) => {
+                        format!("schedule email \"{}\" for {at}", post.title)
+                    }
+                    _ => format!("create draft email \"{}\"", post.title),
+                };
+                Step {
+                    description,
+                    writes: vec![
+                        PlannedWrite::pending("buttondown_id"),
+                        PlannedWrite::known("buttondown_hash", hash),
+                    ],
+                }
+            }
+            Some(_) if post.buttondown_hash.as_deref() == Some(hash.as_str()) => return Ok(None),
+            Some(id) => Step {
+                description: format!("sync edits to email {id} \"{}\"", post.title),
+                writes: vec![PlannedWrite::known("buttondown_hash", hash)],
+            },
+        };
+
+        Ok(Some(step))
+    }
+
+    async fn connect(&mut self) -> cja::Result<()> {
+        let app_config = AppConfig::from_env()?;
+        let config = ButtondownConfig::from_env()?;
+        self.connection = Some((app_config, ButtondownClient::new(&config)));
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        item: &ContentItem,
+        _step: &Step,
+    ) -> cja::Result<Vec<(&'static str, String)>> {
+        let (app_config, client) = self
+            .connection
+            .as_ref()
+            .ok_or_else(|| cja::color_eyre::eyre::eyre!("Not connected to Buttondown"))?;
+        let post = item
+            .post()
+            .ok_or_else(|| cja::color_eyre::eyre::eyre!("Only blog posts are newsletters"))?;
+
+        let body = email_body(item, app_config)?;
+        let (status, publish_date) = delivery(post.newsletter_send_at, Utc::now());
+        let hash = content_hash(post, item.body());
+
+        let Some(id) = &post.buttondown_id else {
+            let request = CreateEmailRequest {
+                subject: post.title.clone(),
+                body,
+                status,
+                publish_date,
+            };
+
+            let response = client.create_email(&request).await?;
+            println!(
+                "Successfully published to Buttondown with id: {}",
+                response.id
+            );
+
+            return Ok(vec![
+                ("buttondown_id", response.id),
+                ("buttondown_hash", hash),
+            ]);
+        };
+
+        // Sent emails can't be changed, so their edits only live on the site
+        let email = client.get_email(id).await?;
+        if email.is_unsent() {
+            let request = UpdateEmailRequest {
+                subject: post.title.clone(),
+                body,
+                status: Some(status),
+                publish_date,
+            };
+            client.update_email(id, &request).await?;
+            println!("Updated Buttondown email {id}");
+        } else {
+            println!(
+                "Buttondown email {id} is already {}, leaving it as it is",
+                email.status
+            );
+        }
 
-    Ok(())
+        Ok(vec![("buttondown_hash", hash)])
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use posts::source::ContentDir;
+
     use super::*;
 
     // ==================== extract_post_dir tests ====================
@@ -223,7 +249,7 @@ mod tests {
         assert!(extract_post_dir(&path).is_err());
     }
 
-    // ==================== rewrite_relative_urls tests ====================
+    // ==================== email_body tests ====================
 
     fn test_config() -> AppConfig {
         AppConfig {
@@ -232,301 +258,169 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_rewrite_relative_urls_multiple_images() {
-        let config = test_config();
-        let content = "![alt text](./image.png)\nSome text\n![another](./path/to/image.jpg)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
-
-        assert!(rewritten.contains("](https://coreyja.com/posts/weekly/20260123/image.png)"));
-        assert!(
-            rewritten.contains("](https://coreyja.com/posts/weekly/20260123/path/to/image.jpg)")
-        );
-        assert!(!rewritten.contains("](./"));
-    }
-
-    #[test]
-    fn test_rewrite_relative_urls_no_images() {
-        let config = test_config();
-        let content = "Just some text without any images.";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
-        assert_eq!(rewritten, content);
-    }
-
-    #[test]
-    fn test_rewrite_relative_urls_absolute_urls_unchanged() {
-        let config = test_config();
-        let content = "![external](https://example.com/image.png)\n![local](./local.png)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
-
-        assert!(rewritten.contains("](https://example.com/image.png)"));
-        assert!(rewritten.contains("](https://coreyja.com/posts/weekly/20260123/local.png)"));
+    fn body_of(markdown: &str) -> String {
+        let item = post(&format!(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\n---\n\n{markdown}"
+        ));
+        email_body(&item, &test_config()).unwrap()
     }
 
     #[test]
-    fn test_rewrite_relative_urls_link_syntax_unchanged() {
-        let config = test_config();
-        let content = "[link text](https://example.com)\n![image](./img.png)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
+    fn test_email_body_is_html_sent_as_is() {
+        let body = body_of("Hello **friends**.\n");
 
-        assert!(rewritten.contains("](https://example.com)"));
-        assert!(rewritten.contains("](https://coreyja.com/posts/weekly/20260123/img.png)"));
+        assert!(body.starts_with("<!-- buttondown-editor-mode: plaintext -->\n"));
+        assert!(body.contains("<strong>friends</strong>"));
     }
 
     #[test]
-    fn test_rewrite_relative_urls_preserves_alt_text() {
-        let config = test_config();
-        let content = "![My descriptive alt text](./screenshot.png)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
+    fn test_email_body_images_are_absolute_under_posts() {
+        let body = body_of("![My descriptive alt text](./screenshot.png)\n");
 
-        assert!(rewritten.contains("![My descriptive alt text]"));
-        assert!(rewritten.contains("https://coreyja.com/posts/weekly/20260123/screenshot.png"));
+        assert!(
+            body.contains(r#"src="https://coreyja.com/posts/weekly/20260123/screenshot.png""#),
+            "Images should use the /posts/ path where they are served, got: {body}"
+        );
+        assert!(body.contains(r#"alt="My descriptive alt text""#));
+        assert!(!body.contains("coreyja.com/blog/"));
     }
 
     #[test]
-    fn test_rewrite_relative_urls_uses_posts_path_not_blog() {
-        let config = test_config();
-        let content = "![img](./photo.png)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260123", &config);
-
-        assert!(
-            rewritten.contains("/posts/"),
-            "URL should use /posts/ path where images are served, got: {rewritten}"
-        );
-        assert!(
-            !rewritten.contains("coreyja.com/blog/"),
-            "URL must not use /blog/ path (legacy redirect only), got: {rewritten}"
+    fn test_email_body_images_go_through_imgproxy() {
+        let item = post(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\n---\n\n![img](./photo.png)\n",
         );
+        let config = AppConfig {
+            imgproxy_url: Some("https://img.coreyja.com".to_string()),
+            ..test_config()
+        };
+        let body = email_body(&item, &config).unwrap();
+
+        assert!(body.contains(
+            "src=\"https://img.coreyja.com/unsafe/rs:fit:1200:0/plain/https%3A%2F%2Fcoreyja.com%2Fposts%2Fweekly%2F20260123%2Fphoto.png\""
+        ));
     }
 
     #[test]
-    fn test_rewrite_relative_urls_root_relative_links() {
-        let config = test_config();
-        let content = "[Episode 1 here](/podcast/why-im-starting-a-podcast)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260306", &config);
+    fn test_email_body_links_are_absolute() {
+        let body = body_of("[podcast](/podcast/ep1) and [ext](https://example.com/page)\n");
 
-        assert!(rewritten.contains("](https://coreyja.com/podcast/why-im-starting-a-podcast)"));
-        assert!(!rewritten.contains("](/"));
+        assert!(body.contains(r#"href="https://coreyja.com/podcast/ep1""#));
+        assert!(body.contains(r#"href="https://example.com/page""#));
+        assert!(!body.contains(r#"href="/"#));
     }
 
     #[test]
-    fn test_rewrite_relative_urls_mixed_links() {
-        let config = test_config();
-        let content = "[podcast](/podcast/ep1)\n![img](./photo.png)\n[ext](https://example.com)";
-        let rewritten = rewrite_relative_urls(content, "weekly/20260306", &config);
-
-        assert!(rewritten.contains("](https://coreyja.com/podcast/ep1)"));
-        assert!(rewritten.contains("](https://coreyja.com/posts/weekly/20260306/photo.png)"));
-        assert!(rewritten.contains("](https://example.com)"));
-    }
-
-    // ==================== parse_frontmatter tests ====================
+    fn test_email_body_styles_are_inline() {
+        let body = body_of("| a | b |\n|---|--:|\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n");
 
-    #[test]
-    fn test_parse_frontmatter_basic() {
-        let content = r"---
-title: Test Newsletter
-date: 2026-01-25
-is_newsletter: true
----
-
-This is the body.
-";
-        let (fm, body) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "Test Newsletter");
-        assert!(fm.is_newsletter);
-        assert!(body.contains("This is the body."));
+        assert!(!body.contains("blog-table"));
+        assert!(!body.contains("bg-coding_background"));
+        assert!(body.contains(r#"<td align="right" style=""#));
+        assert!(body.contains(r#"<span style="color: #cba6f7;">fn</span>"#));
+        assert!(!body.contains("<a-k>"));
     }
 
-    #[test]
-    fn test_parse_frontmatter_with_optional_fields() {
-        let content = r"---
-title: Scheduled Newsletter
-date: 2026-01-25
-is_newsletter: true
-newsletter_send_at: 2026-01-26T12:00:00Z
-bsky_url: https://bsky.app/profile/coreyja.com/post/123
----
-
-Body here.
-";
-        let (fm, _body) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "Scheduled Newsletter");
-        assert!(fm.newsletter_send_at.is_some());
-        assert!(fm.bsky_url.is_some());
-    }
+    // ==================== plan tests ====================
 
-    /// Regression test for the shape of frontmatter written by the
-    /// `publish-standard-site sync` step. PRs #404-#408 added `atproto_uri`,
-    /// `atproto_pub_cid`, `subtitle`, and `publication` fields. A working
-    /// `publish-buttondown` must still parse a newsletter that has those
-    /// fields and no `buttondown_id` yet — otherwise the publish step
-    /// errors out instead of creating the Buttondown draft.
-    #[test]
-    fn test_parse_frontmatter_with_atproto_fields() {
-        let content = r#"---
-title: "coreyja.fm Episode 3: Lean Into the Latency"
-author: Corey Alexander
-date: 2026-04-07
-is_newsletter: true
-subtitle: Async is the natural mode for AI agent workflows
-atproto_uri: at://did:plc:bg2gnrjiv6htfynausierbm2/site.standard.document/weekly-20260407
-atproto_pub_cid: bafyreibn466glibcavfowito3sot5hosargmk4jmhg2ldx2lcw3ifcyxti
-publication: blog
----
-
-Hey Team!
-"#;
-        let (fm, body) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "coreyja.fm Episode 3: Lean Into the Latency");
-        assert!(fm.is_newsletter);
-        assert!(fm.buttondown_id.is_none());
-        assert!(body.contains("Hey Team!"));
+    fn post(raw: &str) -> ContentItem {
+        ContentItem::parse(
+            PathBuf::from("blog/weekly/20260123/index.md"),
+            raw.to_string(),
+            ContentDir::Blog,
+        )
+        .unwrap()
     }
 
-    /// Regression test for the exact byte shape that the syndication sync
-    /// produces on disk — `---` followed by blank lines before the first
-    /// key. Reproduces the layout of `blog/weekly/20260407/index.md` on
-    /// main as of 2026-06-05.
     #[test]
-    fn test_parse_frontmatter_blank_lines_between_delimiter_and_first_key() {
-        let content =
-            "---\n\n\n\n\ntitle: Test\ndate: 2026-04-07\nis_newsletter: true\n---\n\nBody.\n";
-        let (fm, body) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "Test");
-        assert!(body.contains("Body."));
+    fn test_plan_creates_a_draft_for_new_newsletters() {
+        let item = post("---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\n---\n\nBody.\n");
+        let step = Buttondown::default()
+            .plan(&item)
+            .unwrap()
+            .expect("should be planned");
+        assert!(step.description.starts_with("create draft email"));
+        assert_eq!(
+            step.writes,
+            vec![
+                PlannedWrite::pending("buttondown_id"),
+                PlannedWrite::known(
+                    "buttondown_hash",
+                    content_hash(item.post().unwrap(), item.body())
+                ),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_frontmatter_missing_opening_delimiter() {
-        let content = r"title: Test
-date: 2026-01-25
----
-
-Body.
-";
-        assert!(parse_frontmatter(content).is_err());
+    fn test_plan_schedules_newsletters_with_a_future_send_time() {
+        let item = post(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\nnewsletter_send_at: 2999-01-01T12:00:00Z\n---\n\nBody.\n",
+        );
+        let step = Buttondown::default().plan(&item).unwrap().unwrap();
+        assert!(step.description.starts_with("schedule email"));
     }
 
     #[test]
-    fn test_parse_frontmatter_missing_closing_delimiter() {
-        let content = r"---
-title: Test
-date: 2026-01-25
-
-Body without closing delimiter.
-";
-        assert!(parse_frontmatter(content).is_err());
+    fn test_plan_skips_newsletters_in_sync_with_buttondown() {
+        let unsynced = post(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\nbuttondown_id: abc123\n---\n\nBody.\n",
+        );
+        let hash = content_hash(unsynced.post().unwrap(), unsynced.body());
+        let synced = post(&format!(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\nbuttondown_id: abc123\nbuttondown_hash: {hash}\n---\n\nBody.\n"
+        ));
+        assert!(Buttondown::default().plan(&synced).unwrap().is_none());
     }
 
     #[test]
-    fn test_parse_frontmatter_is_newsletter_defaults_false() {
-        let content = r"---
-title: Regular Post
-date: 2026-01-25
----
-
-Not a newsletter.
-";
-        let (fm, _) = parse_frontmatter(content).unwrap();
-        assert!(!fm.is_newsletter);
+    fn test_plan_syncs_edits_to_existing_emails() {
+        let item = post(
+            "---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\nbuttondown_id: abc123\nbuttondown_hash: stale\n---\n\nEdited body.\n",
+        );
+        let step = Buttondown::default().plan(&item).unwrap().unwrap();
+        assert!(step.description.contains("abc123"));
+        assert_eq!(step.writes.len(), 1);
+        assert_eq!(step.writes[0].key, "buttondown_hash");
     }
 
     #[test]
-    fn test_parse_frontmatter_with_leading_whitespace() {
-        let content = r"
----
-title: Test Newsletter
-date: 2026-01-25
-is_newsletter: true
----
-
-Body.
-";
-        let (fm, _) = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.title, "Test Newsletter");
-    }
+    fn test_content_hash_changes_with_subject_body_and_send_time() {
+        let item = post("---\ntitle: T\ndate: 2026-01-25\nis_newsletter: true\n---\n\nBody.\n");
+        let fm = item.post().unwrap();
+        let hash = content_hash(fm, "Body.");
 
-    // ==================== update_frontmatter_with_id tests ====================
+        let mut retitled = fm.clone();
+        retitled.title = "New".to_string();
+        let mut rescheduled = fm.clone();
+        rescheduled.newsletter_send_at = Some(Utc::now());
 
-    #[test]
-    fn test_update_frontmatter_with_id_basic() {
-        let content = r"---
-title: Test Newsletter
-date: 2026-01-25
-is_newsletter: true
----
-
-Body content here.
-";
-        let updated = update_frontmatter_with_id(content, "abc123").unwrap();
-        assert!(updated.contains("buttondown_id: abc123"));
-        assert!(updated.contains("Body content here."));
+        assert_eq!(hash, content_hash(fm, "Body."));
+        assert_ne!(hash, content_hash(fm, "Edited."));
+        assert_ne!(hash, content_hash(&retitled, "Body."));
+        assert_ne!(hash, content_hash(&rescheduled, "Body."));
     }
 
     #[test]
-    fn test_update_frontmatter_preserves_all_fields() {
-        let content = r"---
-title: Full Newsletter
-date: 2026-01-25
-is_newsletter: true
-bsky_url: https://bsky.app/test
-newsletter_send_at: 2026-01-26T12:00:00Z
----
-
-Body.
-";
-        let updated = update_frontmatter_with_id(content, "xyz789").unwrap();
-
-        assert!(updated.contains("title: Full Newsletter"));
-        assert!(updated.contains("date: 2026-01-25"));
-        assert!(updated.contains("is_newsletter: true"));
-        assert!(updated.contains("bsky_url: https://bsky.app/test"));
-        assert!(updated.contains("newsletter_send_at:"));
-        assert!(updated.contains("buttondown_id: xyz789"));
-        assert!(updated.contains("Body."));
-    }
-
-    #[test]
-    fn test_update_frontmatter_preserves_body_formatting() {
-        let content = r"---
-title: Test
-date: 2026-01-25
-is_newsletter: true
----
-
-# Heading
-
-Paragraph with **bold** and _italic_.
-
-- List item 1
-- List item 2
-
-```rust
-fn main() {}
-```
-";
-        let updated = update_frontmatter_with_id(content, "id123").unwrap();
-
-        assert!(updated.contains("# Heading"));
-        assert!(updated.contains("**bold**"));
-        assert!(updated.contains("- List item 1"));
-        assert!(updated.contains("```rust"));
+    fn test_delivery_only_schedules_future_send_times() {
+        let now = Utc::now();
+        let later = now + chrono::Duration::hours(1);
+        let earlier = now - chrono::Duration::hours(1);
+
+        assert!(matches!(
+            delivery(Some(later), now),
+            (EmailStatus::Scheduled, Some(at)) if at == later
+        ));
+        assert!(matches!(
+            delivery(Some(earlier), now),
+            (EmailStatus::Draft, None)
+        ));
+        assert!(matches!(delivery(None, now), (EmailStatus::Draft, None)));
     }
 
     #[test]
-    fn test_update_frontmatter_special_characters_in_id() {
-        let content = r"---
-title: Test
-date: 2026-01-25
-is_newsletter: true
----
-
-Body.
-";
-        // Buttondown IDs are typically UUIDs
-        let updated =
-            update_frontmatter_with_id(content, "550e8400-e29b-41d4-a716-446655440000").unwrap();
-        assert!(updated.contains("buttondown_id: 550e8400-e29b-41d4-a716-446655440000"));
+    fn test_plan_skips_posts_that_are_not_newsletters() {
+        let item = post("---\ntitle: Regular Post\ndate: 2026-01-25\n---\n\nNot a newsletter.\n");
+        assert!(Buttondown::default().plan(&item).unwrap().is_none());
     }
 }
@@ -26,6 +26,16 @@ pub fn split_frontmatter(content: &str) -> cja::Result<(&str, &str)> {
     Ok((yaml, body))
 }
 
+/// The raw value of a top-level `key:` line in the YAML frontmatter, as
+/// written. Returns `None` if the key or the frontmatter is missing.
+pub fn frontmatter_value<'a>(content: &'a str, key: &str) -> Option<&'a str> {
+    let (yaml, _body) = split_frontmatter(content).ok()?;
+    let prefix = format!("{key}:");
+    yaml.lines()
+        .find(|line| line.starts_with(&prefix))
+        .map(|line| line[prefix.len()..].trim())
+}
+
 /// Upsert `key: value` lines in the YAML frontmatter.
 ///
 /// For each key in `kv`: if a top-level `<key>:` line already exists, replace
@@ -191,4 +201,16 @@ mod tests {
         assert!(updated.contains("- items"));
         assert!(updated.contains("k: v"));
     }
+
+    #[test]
+    fn frontmatter_value_reads_top_level_keys() {
+        let content = "---\ntitle: T\nbsky_url: https://bsky.app/x\ntags:\n  bsky_url: nested\n---\n\nbsky_url: body\n";
+        assert_eq!(
+            frontmatter_value(content, "bsky_url"),
+            Some("https://bsky.app/x")
+        );
+        assert_eq!(frontmatter_value(content, "title"), Some("T"));
+        assert_eq!(frontmatter_value(content, "atproto_uri"), None);
+        assert_eq!(frontmatter_value("no frontmatter", "title"), None);
+    }
 }
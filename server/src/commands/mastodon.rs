@@ -0,0 +1,201 @@
+//! Cross-posts notes and blog posts to Mastodon as statuses linking back to the site. Notes
+//! are posted with their text and images, blog posts with their title and subtitle and the
+//! link card Mastodon builds from the page. Either can set a `content_warning`.
+
+use chrono::NaiveDate;
+use cja::color_eyre::eyre::eyre;
+
+use crate::bluesky::{markdown_images, markdown_to_plain_text};
+use crate::commands::bluesky::{load_images, note_url};
+use crate::commands::buttondown::extract_post_dir;
+use crate::commands::publish::{ContentItem, Destination, DestinationKind, PlannedWrite, Step};
+use crate::mastodon::{compose_status, MastodonClient, MastodonConfig, MASTODON_MAX_MEDIA};
+
+/// Cutoff date - only cross-post content dated on or after this date
+const CUTOFF_DATE: &str = "2026-10-18";
+
+fn post_url(item: &ContentItem) -> cja::Result<String> {
+    Ok(format!(
+        "https://coreyja.com/posts/{}",
+        extract_post_dir(&item.path)?
+    ))
+}
+
+/// Posts each note and blog post once, recording the status's `mastodon_url` in its
+/// frontmatter
+#[derive(Default)]
+pub(crate) struct Mastodon {
+    client: Option<MastodonClient>,
+}
+
+#[async_trait::async_trait]
+impl Destination for Mastodon {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::Mastodon
+    }
+
+    fn cutoff(&self) -> Option<NaiveDate> {
+        Some(
+            NaiveDate::parse_from_str(CUTOFF_DATE, "%Y-%m-%d")
+                .expect("CUTOFF_DATE should be valid"),
+        )
+    }
+
+    fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>> {
+        let (mut description, mastodon_url, content_warning) = match (item.note(), item.post()) {
+            (Some(note), _) => (
+                format!("post note \"{}\"", note.title),
+                &note.mastodon_url,
+                &note.content_warning,
+            ),
+            (_, Some(post)) => (
+                format!("post \"{}\"", post.title),
+                &post.mastodon_url,
+                &post.content_warning,
+            ),
+            (None, None) => return Ok(None),
+        };
+        if mastodon_url.is_some() {
+            return Ok(None);
+        }
+
+        if item.note().is_some() {
+            match markdown_images(item.body()).len().min(MASTODON_MAX_MEDIA) {
+                0 => {}
+                1 => description.push_str(" with 1 image"),
+                n => description.push_str(&format!(" with {n} images")),
+            }
+        }
+        if let Some(cw) = content_warning {
+            description.push_str(&format!(" behind the content warning \"{cw}\""));
+        }
+
+        Ok(Some(Step {
+            description,
+            writes: vec![PlannedWrite::pending("mastodon_url")],
+        }))
+    }
+
+    async fn connect(&mut self) -> cja::Result<()> {
+        let config = MastodonConfig::from_env()?;
+        self.client = Some(MastodonClient::connect(&config).await?);
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        item: &ContentItem,
+        _step: &Step,
+    ) -> cja::Result<Vec<(&'static str, String)>> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| eyre!("Not connected to Mastodon"))?;
+
+        let (text, media_ids, content_warning, url) = if let Some(note) = item.note() {
+            let url = note_url(&note.slug);
+            let text = compose_status(
+                &note.title,
+                &markdown_to_plain_text(item.body()),
+                &url,
+                client.limits(),
+            );
+
+            let mut media_ids = vec![];
+            for image in load_images(item).await? {
+                media_ids.push(client.upload_media(image.bytes, &image.alt).await?);
+            }
+
+            (text, media_ids, note.content_warning.as_deref(), url)
+        } else if let Some(post) = item.post() {
+            let url = post_url(item)?;
+            let text = compose_status(
+                &post.title,
+                post.subtitle.as_deref().unwrap_or_default(),
+                &url,
+                client.limits(),
+            );
+
+            (text, vec![], post.content_warning.as_deref(), url)
+        } else {
+            return Ok(vec![]);
+        };
+
+        let status = client
+            .post_status(&text, media_ids, content_warning, &url)
+            .await?;
+        let status_url = status
+            .url
+            .ok_or_else(|| eyre!("Mastodon didn't return a URL for status {}", status.id))?;
+        println!("Published to Mastodon: {status_url}");
+
+        Ok(vec![("mastodon_url", status_url)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use posts::source::ContentDir;
+
+    use super::*;
+
+    fn note(raw: &str) -> ContentItem {
+        ContentItem::parse(
+            PathBuf::from("notes/n.md"),
+            raw.to_string(),
+            ContentDir::Notes,
+        )
+        .unwrap()
+    }
+
+    fn post(raw: &str) -> ContentItem {
+        ContentItem::parse(
+            PathBuf::from("blog/my-post/index.md"),
+            raw.to_string(),
+            ContentDir::Blog,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn plan_skips_content_already_on_mastodon() {
+        let item = note(
+            "---\ntitle: A\ndate: 2026-10-18\nslug: a\nmastodon_url: https://hachyderm.io/@coreyja/1\n---\n\nbody\n",
+        );
+        assert!(Mastodon::default().plan(&item).unwrap().is_none());
+    }
+
+    #[test]
+    fn plan_posts_notes_with_their_images() {
+        let item = note(
+            "---\ntitle: Pics\ndate: 2026-10-18\nslug: pics\n---\n\n![one](a.png) ![two](b.png)\n",
+        );
+        let step = Mastodon::default().plan(&item).unwrap().unwrap();
+        assert_eq!(step.description, "post note \"Pics\" with 2 images");
+        assert_eq!(step.writes, vec![PlannedWrite::pending("mastodon_url")]);
+    }
+
+    #[test]
+    fn plan_posts_blog_posts_behind_their_content_warning() {
+        let item = post(
+            "---\ntitle: Spoilers\ndate: 2026-10-18\ncontent_warning: Game spoilers\n---\n\nbody\n",
+        );
+        let step = Mastodon::default().plan(&item).unwrap().unwrap();
+        assert_eq!(
+            step.description,
+            "post \"Spoilers\" behind the content warning \"Game spoilers\""
+        );
+        assert_eq!(step.writes, vec![PlannedWrite::pending("mastodon_url")]);
+    }
+
+    #[test]
+    fn blog_posts_link_to_their_canonical_url() {
+        let item = post("---\ntitle: P\ndate: 2026-10-18\n---\n\nbody\n");
+        assert_eq!(
+            post_url(&item).unwrap(),
+            "https://coreyja.com/posts/my-post"
+        );
+    }
+}
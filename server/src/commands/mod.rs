@@ -5,6 +5,10 @@ pub(crate) mod bluesky;
 pub(crate) mod buttondown;
 pub(crate) mod frontmatter;
 pub(crate) mod info;
+pub(crate) mod mastodon;
+pub(crate) mod nutrition;
+pub(crate) mod publish;
+pub(crate) mod reconcile;
 pub(crate) mod standard_site;
 pub(crate) mod validate;
 
@@ -14,13 +18,17 @@ pub(crate) enum Command {
     Serve,
     Print,
     Validate,
-    /// Publish a newsletter to Buttondown
-    PublishButtondown(buttondown::PublishButtondownArgs),
-    /// Publish a note to Bluesky
-    PublishBluesky(bluesky::PublishBlueskyArgs),
-    /// Manage standard.site publications and documents on the PDS
+    /// Syndicate posts, notes and podcast episodes to Buttondown, Bluesky, standard.site and
+    /// Mastodon
+    Publish(publish::PublishArgs),
+    /// Find Bluesky posts, standard.site documents and Buttondown emails whose content has
+    /// been removed, and optionally delete them and tombstone their pages
+    Reconcile(reconcile::ReconcileArgs),
+    /// Manage standard.site publications on the PDS
     #[command(subcommand)]
     PublishStandardSite(standard_site::StandardSiteCommand),
+    /// Import per-ingredient nutrition data from CSV
+    ImportNutrition(nutrition::ImportNutritionArgs),
 }
 
 impl Command {
@@ -29,9 +37,10 @@ impl Command {
             Command::Serve => crate::http_server::cmd::serve().await,
             Command::Print => info::print_info(),
             Command::Validate => validate::validate(),
-            Command::PublishButtondown(args) => buttondown::publish_buttondown(args).await,
-            Command::PublishBluesky(args) => bluesky::publish_bluesky(args).await,
+            Command::Publish(args) => publish::publish(args).await,
+            Command::Reconcile(args) => reconcile::reconcile(args).await,
             Command::PublishStandardSite(cmd) => standard_site::run(cmd).await,
+            Command::ImportNutrition(args) => nutrition::import_nutrition(args).await,
         }
     }
 }
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use db::cooking::{IngredientNutrition, BUNDLED_NUTRITION_CSV};
+
+#[derive(Args, Debug)]
+pub struct ImportNutritionArgs {
+    /// CSV file to import. Defaults to the nutrition data bundled with the db crate
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+}
+
+pub(crate) async fn import_nutrition(args: &ImportNutritionArgs) -> cja::Result<()> {
+    let pool = db::setup_db_pool().await?;
+
+    let imported = match &args.path {
+        Some(path) => {
+            let file = std::fs::File::open(path).map_err(|e| {
+                cja::color_eyre::eyre::eyre!("Failed to open {}: {}", path.display(), e)
+            })?;
+            IngredientNutrition::import_csv(&pool, file).await?
+        }
+        None => IngredientNutrition::import_csv(&pool, BUNDLED_NUTRITION_CSV.as_bytes()).await?,
+    };
+
+    println!("Imported nutrition data for {imported} ingredients");
+
+    Ok(())
+}
@@ -0,0 +1,668 @@
+//! One pipeline for syndicating posts, notes and podcast episodes to everywhere they go. Each destination
+//! decides what it would do for a piece of content from its frontmatter alone, and records
+//! what it did back into that frontmatter, so re-running only does what's left and
+//! `--dry-run` can show exactly what a run would write.
+
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use cja::color_eyre::eyre::eyre;
+use clap::{Args, ValueEnum};
+use posts::{
+    blog::BlogFrontMatter,
+    notes::FrontMatter as NoteFrontMatter,
+    podcast::PodcastFrontMatter,
+    publish::Publishable,
+    source::{ContentDir, ContentSource},
+};
+
+use crate::commands::{bluesky, buttondown, frontmatter, mastodon, standard_site};
+
+#[derive(Args, Debug)]
+pub struct PublishArgs {
+    /// Only publish to these destinations. Defaults to all of them.
+    #[arg(long = "destination", value_enum)]
+    pub destinations: Vec<DestinationKind>,
+
+    /// Print what would be published and the frontmatter it would write, without
+    /// publishing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// The repo root, holding `blog/`, `notes/`, `podcast/` and `publications.toml`
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DestinationKind {
+    Buttondown,
+    Bluesky,
+    StandardSite,
+    Mastodon,
+}
+
+impl DestinationKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Buttondown => "buttondown",
+            Self::Bluesky => "bluesky",
+            Self::StandardSite => "standard-site",
+            Self::Mastodon => "mastodon",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ItemFrontMatter {
+    Post(BlogFrontMatter),
+    Note(NoteFrontMatter),
+    Episode(PodcastFrontMatter),
+}
+
+/// A post, note or podcast episode read from disk, along with its parsed frontmatter
+#[derive(Debug, Clone)]
+pub(crate) struct ContentItem {
+    /// Path of the markdown file, joined onto the repo root
+    pub path: PathBuf,
+    /// The whole file, frontmatter included
+    pub raw: String,
+    pub frontmatter: ItemFrontMatter,
+}
+
+impl ContentItem {
+    pub(crate) fn parse(path: PathBuf, raw: String, dir: ContentDir) -> cja::Result<Self> {
+        let (yaml, _body) = frontmatter::split_frontmatter(&raw)?;
+        let frontmatter = match dir {
+            ContentDir::Notes => ItemFrontMatter::Note(
+                serde_yaml::from_str(yaml).map_err(|e| eyre!("Invalid YAML: {e}"))?,
+            ),
+            ContentDir::Podcast => ItemFrontMatter::Episode(
+                serde_yaml::from_str(yaml).map_err(|e| eyre!("Invalid YAML: {e}"))?,
+            ),
+            _ => ItemFrontMatter::Post(
+                serde_yaml::from_str(yaml).map_err(|e| eyre!("Invalid YAML: {e}"))?,
+            ),
+        };
+
+        Ok(Self {
+            path,
+            raw,
+            frontmatter,
+        })
+    }
+
+    pub(crate) fn post(&self) -> Option<&BlogFrontMatter> {
+        match &self.frontmatter {
+            ItemFrontMatter::Post(fm) => Some(fm),
+            ItemFrontMatter::Note(_) | ItemFrontMatter::Episode(_) => None,
+        }
+    }
+
+    pub(crate) fn note(&self) -> Option<&NoteFrontMatter> {
+        match &self.frontmatter {
+            ItemFrontMatter::Note(fm) => Some(fm),
+            ItemFrontMatter::Post(_) | ItemFrontMatter::Episode(_) => None,
+        }
+    }
+
+    pub(crate) fn episode(&self) -> Option<&PodcastFrontMatter> {
+        match &self.frontmatter {
+            ItemFrontMatter::Episode(fm) => Some(fm),
+            ItemFrontMatter::Post(_) | ItemFrontMatter::Note(_) => None,
+        }
+    }
+
+    /// The markdown after the frontmatter
+    pub(crate) fn body(&self) -> &str {
+        frontmatter::split_frontmatter(&self.raw).map_or("", |(_, body)| body)
+    }
+
+    fn date(&self) -> NaiveDate {
+        match &self.frontmatter {
+            ItemFrontMatter::Post(fm) => fm.date,
+            ItemFrontMatter::Note(fm) => fm.date,
+            ItemFrontMatter::Episode(fm) => fm.date,
+        }
+    }
+
+    fn is_published(&self) -> bool {
+        match &self.frontmatter {
+            ItemFrontMatter::Post(fm) => fm.is_published(),
+            ItemFrontMatter::Note(fm) => fm.is_published(),
+            // Episodes have no drafts, they're only committed once they're out
+            ItemFrontMatter::Episode(_) => true,
+        }
+    }
+
+    /// Upsert `writes` into the frontmatter, on disk and in this item, so later destinations
+    /// see what earlier ones recorded
+    fn write_back(&mut self, writes: &[(&'static str, String)]) -> cja::Result<()> {
+        let kv: Vec<(&str, &str)> = writes.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let raw = frontmatter::append_frontmatter_keys(&self.raw, &kv);
+        let dir = match self.frontmatter {
+            ItemFrontMatter::Post(_) => ContentDir::Blog,
+            ItemFrontMatter::Note(_) => ContentDir::Notes,
+            ItemFrontMatter::Episode(_) => ContentDir::Podcast,
+        };
+        let updated = Self::parse(self.path.clone(), raw, dir)?;
+
+        std::fs::write(&self.path, &updated.raw)
+            .map_err(|e| eyre!("Failed to write {}: {}", self.path.display(), e))?;
+        *self = updated;
+
+        Ok(())
+    }
+}
+
+/// A frontmatter key a step will write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PlannedWrite {
+    pub key: &'static str,
+    /// `None` when the value only comes back from the destination, like a post's URL
+    pub value: Option<String>,
+}
+
+impl PlannedWrite {
+    pub(crate) fn known(key: &'static str, value: impl Into<String>) -> Self {
+        Self {
+            key,
+            value: Some(value.into()),
+        }
+    }
+
+    pub(crate) fn pending(key: &'static str) -> Self {
+        Self { key, value: None }
+    }
+}
+
+/// What a destination will do for one piece of content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Step {
+    pub description: String,
+    pub writes: Vec<PlannedWrite>,
+}
+
+#[async_trait::async_trait]
+pub(crate) trait Destination: Send + Sync {
+    fn kind(&self) -> DestinationKind;
+
+    /// Content dated before this is never published here, so adding a destination doesn't
+    /// flood it with everything ever written
+    fn cutoff(&self) -> Option<NaiveDate> {
+        None
+    }
+
+    /// What publishing `item` here would do, or `None` when it doesn't go here or is already
+    /// done. Drafts, scheduled content and anything before the cutoff never get this far.
+    fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>>;
+
+    /// Log in or load config. Called once, before the first step runs, so dry runs and runs
+    /// with nothing to do don't need credentials.
+    async fn connect(&mut self) -> cja::Result<()> {
+        Ok(())
+    }
+
+    /// Run the step, returning the frontmatter to write back
+    async fn publish(
+        &self,
+        item: &ContentItem,
+        step: &Step,
+    ) -> cja::Result<Vec<(&'static str, String)>>;
+}
+
+#[derive(Debug, Default)]
+struct Summary {
+    published: usize,
+    planned: usize,
+    skipped: usize,
+    failures: Vec<(PathBuf, cja::color_eyre::Report)>,
+}
+
+pub(crate) fn load_content(
+    root: &Path,
+) -> (Vec<ContentItem>, Vec<(PathBuf, cja::color_eyre::Report)>) {
+    let source = ContentSource::Disk(root.to_path_buf());
+    let mut items = vec![];
+    let mut failures = vec![];
+
+    for dir in [ContentDir::Blog, ContentDir::Notes, ContentDir::Podcast] {
+        let files = match source.files(dir) {
+            Ok(files) => files,
+            Err(e) => {
+                failures.push((root.join(dir.name()), e));
+                continue;
+            }
+        };
+
+        for file in files.into_iter().filter(|f| f.is_markdown()) {
+            let path = root.join(dir.name()).join(file.path());
+            let raw = String::from_utf8_lossy(file.contents()).into_owned();
+
+            // One malformed file shouldn't block syndicating the rest
+            match ContentItem::parse(path.clone(), raw, dir) {
+                Ok(item) => items.push(item),
+                Err(e) => failures.push((path, e)),
+            }
+        }
+    }
+
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    (items, failures)
+}
+
+/// The `-`/`+` lines for each key a step changes
+fn frontmatter_diff(item: &ContentItem, writes: &[PlannedWrite]) -> Vec<String> {
+    let mut lines = vec![];
+    for write in writes {
+        let current = frontmatter::frontmatter_value(&item.raw, write.key);
+        if current.is_some() && current == write.value.as_deref() {
+            continue;
+        }
+
+        if let Some(current) = current {
+            lines.push(format!("-{}: {current}", write.key));
+        }
+        lines.push(format!(
+            "+{}: {}",
+            write.key,
+            write.value.as_deref().unwrap_or("<set when published>")
+        ));
+    }
+    lines
+}
+
+async fn run_destination(
+    destination: &mut dyn Destination,
+    items: &mut [ContentItem],
+    dry_run: bool,
+) -> Summary {
+    let kind = destination.kind().name();
+    let mut summary = Summary::default();
+    let mut connected = false;
+
+    for item in items.iter_mut() {
+        let too_old = destination.cutoff().is_some_and(|c| item.date() < c);
+        if too_old || !item.is_published() {
+            summary.skipped += 1;
+            continue;
+        }
+
+        let step = match destination.plan(item) {
+            Ok(Some(step)) => step,
+            Ok(None) => {
+                summary.skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                summary.failures.push((item.path.clone(), e));
+                continue;
+            }
+        };
+
+        println!("[{kind}] {}: {}", item.path.display(), step.description);
+        if dry_run {
+            for line in frontmatter_diff(item, &step.writes) {
+                println!("    {line}");
+            }
+            summary.planned += 1;
+            continue;
+        }
+
+        if !connected {
+            if let Err(e) = destination.connect().await {
+                summary.failures.push((item.path.clone(), e));
+                return summary;
+            }
+            connected = true;
+        }
+
+        let published = match destination.publish(item, &step).await {
+            Ok(writes) => item.write_back(&writes),
+            Err(e) => Err(e),
+        };
+        match published {
+            Ok(()) => summary.published += 1,
+            Err(e) => {
+                eprintln!("[{kind}] Failed to publish {}: {e}", item.path.display());
+                summary.failures.push((item.path.clone(), e));
+            }
+        }
+    }
+
+    summary
+}
+
+pub async fn publish(args: &PublishArgs) -> cja::Result<()> {
+    let wanted =
+        |kind: DestinationKind| args.destinations.is_empty() || args.destinations.contains(&kind);
+
+    let mut destinations: Vec<Box<dyn Destination>> = vec![];
+    if wanted(DestinationKind::Buttondown) {
+        destinations.push(Box::new(buttondown::Buttondown::default()));
+    }
+    if wanted(DestinationKind::Bluesky) {
+        destinations.push(Box::new(bluesky::BlueskyNotes::default()));
+    }
+    if wanted(DestinationKind::StandardSite) {
+        destinations.push(Box::new(standard_site::StandardSite::from_config(
+            &args.root,
+        )?));
+    }
+    if wanted(DestinationKind::Mastodon) {
+        destinations.push(Box::new(mastodon::Mastodon::default()));
+    }
+
+    let (mut items, load_failures) = load_content(&args.root);
+    for (path, e) in &load_failures {
+        eprintln!("Failed to read {}: {e}", path.display());
+    }
+
+    let mut summaries = vec![];
+    for destination in &mut destinations {
+        let summary = run_destination(destination.as_mut(), &mut items, args.dry_run).await;
+        summaries.push((destination.kind(), summary));
+    }
+
+    println!();
+    println!("Summary{}:", if args.dry_run { " (dry run)" } else { "" });
+    let mut failed = load_failures.len();
+    for (kind, summary) in &summaries {
+        let done = if args.dry_run {
+            format!("{} planned", summary.planned)
+        } else {
+            format!("{} published", summary.published)
+        };
+        println!(
+            "  {}: {done}, {} skipped, {} failed",
+            kind.name(),
+            summary.skipped,
+            summary.failures.len()
+        );
+        for (path, e) in &summary.failures {
+            println!("    {}: {e}", path.display());
+        }
+        failed += summary.failures.len();
+    }
+
+    if failed > 0 {
+        return Err(eyre!(
+            "{failed} item(s) failed to publish (see above); the next run will retry"
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, relative: &str, contents: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn content_root() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("blog")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("notes")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("podcast")).unwrap();
+        tmp
+    }
+
+    /// Publishes every note it hasn't seen, recording a `fake_id`
+    #[derive(Default)]
+    struct Fake {
+        connected: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Destination for Fake {
+        fn kind(&self) -> DestinationKind {
+            DestinationKind::Bluesky
+        }
+
+        fn cutoff(&self) -> Option<NaiveDate> {
+            NaiveDate::from_ymd_opt(2026, 3, 1)
+        }
+
+        fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>> {
+            let Some(note) = item.note() else {
+                return Ok(None);
+            };
+            if frontmatter::frontmatter_value(&item.raw, "fake_id").is_some() {
+                return Ok(None);
+            }
+
+            Ok(Some(Step {
+                description: format!("publish {}", note.slug),
+                writes: vec![PlannedWrite::pending("fake_id")],
+            }))
+        }
+
+        async fn connect(&mut self) -> cja::Result<()> {
+            self.connected = true;
+            Ok(())
+        }
+
+        async fn publish(
+            &self,
+            item: &ContentItem,
+            _step: &Step,
+        ) -> cja::Result<Vec<(&'static str, String)>> {
+            assert!(self.connected);
+            Ok(vec![("fake_id", item.note().unwrap().slug.clone())])
+        }
+    }
+
+    const NOTE: &str = "---\ntitle: N\ndate: 2026-04-15\nslug: n\n---\n\nbody\n";
+
+    #[tokio::test]
+    async fn dry_runs_plan_without_connecting_or_writing() {
+        let tmp = content_root();
+        let path = write(tmp.path(), "notes/n.md", NOTE);
+        let (mut items, failures) = load_content(tmp.path());
+        assert!(failures.is_empty());
+
+        let mut fake = Fake::default();
+        let summary = run_destination(&mut fake, &mut items, true).await;
+
+        assert_eq!(summary.planned, 1);
+        assert!(!fake.connected);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), NOTE);
+    }
+
+    #[tokio::test]
+    async fn publishing_writes_back_and_reruns_skip_what_is_done() {
+        let tmp = content_root();
+        let path = write(tmp.path(), "notes/n.md", NOTE);
+        write(
+            tmp.path(),
+            "notes/old.md",
+            "---\ntitle: O\ndate: 2024-01-01\nslug: old\n---\n\nbody\n",
+        );
+        write(
+            tmp.path(),
+            "notes/draft.md",
+            "---\ntitle: D\ndate: 2026-04-15\nslug: d\ndraft: true\n---\n\nbody\n",
+        );
+        write(
+            tmp.path(),
+            "notes/scheduled.md",
+            "---\ntitle: S\ndate: 2026-04-15\nslug: s\npublish_at: 2999-01-01T09:00:00Z\n---\n\nbody\n",
+        );
+        write(
+            tmp.path(),
+            "blog/post/index.md",
+            "---\ntitle: P\ndate: 2026-04-15\n---\n\nbody\n",
+        );
+
+        let (mut items, _) = load_content(tmp.path());
+        let summary = run_destination(&mut Fake::default(), &mut items, false).await;
+        assert_eq!(summary.published, 1);
+        assert_eq!(summary.skipped, 4);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("fake_id: n"));
+
+        let (mut items, _) = load_content(tmp.path());
+        let summary = run_destination(&mut Fake::default(), &mut items, false).await;
+        assert_eq!(summary.published, 0);
+        assert_eq!(summary.skipped, 5);
+    }
+
+    #[tokio::test]
+    async fn newsletters_go_to_buttondown_only_once_they_are_live() {
+        let tmp = content_root();
+        for (dir, frontmatter) in [
+            ("draft", "draft: true"),
+            ("scheduled", "publish_at: 2999-01-01T09:00:00Z"),
+            ("live", "publish_at: 2026-04-15T09:00:00Z"),
+        ] {
+            write(
+                tmp.path(),
+                &format!("blog/weekly/{dir}/index.md"),
+                &format!("---\ntitle: W\ndate: 2026-04-15\nis_newsletter: true\n{frontmatter}\n---\n\nbody\n"),
+            );
+        }
+
+        let (mut items, failures) = load_content(tmp.path());
+        assert!(failures.is_empty());
+        let summary =
+            run_destination(&mut buttondown::Buttondown::default(), &mut items, true).await;
+
+        assert_eq!(summary.planned, 1);
+        assert_eq!(summary.skipped, 2);
+    }
+
+    #[test]
+    fn malformed_files_are_reported_without_stopping_the_rest() {
+        let tmp = content_root();
+        write(tmp.path(), "notes/bad.md", "no frontmatter here\n");
+        write(tmp.path(), "notes/good.md", NOTE);
+
+        let (items, failures) = load_content(tmp.path());
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].0.ends_with("notes/bad.md"));
+    }
+
+    #[test]
+    fn diff_shows_replaced_and_pending_values() {
+        let item = ContentItem::parse(
+            PathBuf::from("blog/p/index.md"),
+            "---\ntitle: P\ndate: 2026-04-15\natproto_pub_cid: old\n---\n\nbody\n".to_string(),
+            ContentDir::Blog,
+        )
+        .unwrap();
+
+        let diff = frontmatter_diff(
+            &item,
+            &[
+                PlannedWrite::pending("atproto_uri"),
+                PlannedWrite::known("atproto_pub_cid", "new"),
+                PlannedWrite::known("title", "P"),
+            ],
+        );
+
+        assert_eq!(
+            diff,
+            vec![
+                "+atproto_uri: <set when published>",
+                "-atproto_pub_cid: old",
+                "+atproto_pub_cid: new",
+            ]
+        );
+    }
+
+    // ==================== frontmatter parsing ====================
+
+    fn parse_post(raw: &str) -> cja::Result<ContentItem> {
+        ContentItem::parse(
+            PathBuf::from("blog/p/index.md"),
+            raw.to_string(),
+            ContentDir::Blog,
+        )
+    }
+
+    /// Regression test for the shape of frontmatter written by the standard.site
+    /// destination. A newsletter with those fields and no `buttondown_id` yet must
+    /// still parse, or it never gets its Buttondown draft.
+    #[test]
+    fn parses_newsletters_with_atproto_fields() {
+        let item = parse_post(
+            r#"---
+title: "coreyja.fm Episode 3: Lean Into the Latency"
+author: Corey Alexander
+date: 2026-04-07
+is_newsletter: true
+subtitle: Async is the natural mode for AI agent workflows
+atproto_uri: at://did:plc:bg2gnrjiv6htfynausierbm2/site.standard.document/weekly-20260407
+atproto_pub_cid: bafyreibn466glibcavfowito3sot5hosargmk4jmhg2ldx2lcw3ifcyxti
+publication: blog
+---
+
+Hey Team!
+"#,
+        )
+        .unwrap();
+
+        let fm = item.post().unwrap();
+        assert_eq!(fm.title, "coreyja.fm Episode 3: Lean Into the Latency");
+        assert!(fm.is_newsletter);
+        assert!(fm.buttondown_id.is_none());
+        assert!(item.body().contains("Hey Team!"));
+    }
+
+    /// Reproduces the layout of `blog/weekly/20260407/index.md` on main as of 2026-06-05,
+    /// with blank lines between the opening `---` and the first key
+    #[test]
+    fn parses_blank_lines_before_the_first_key() {
+        let item = parse_post(
+            "---\n\n\n\n\ntitle: Test\ndate: 2026-04-07\nis_newsletter: true\n---\n\nBody.\n",
+        )
+        .unwrap();
+
+        assert_eq!(item.post().unwrap().title, "Test");
+        assert!(item.body().contains("Body."));
+    }
+
+    #[test]
+    fn parses_leading_whitespace_and_rejects_missing_delimiters() {
+        assert!(parse_post("\n---\ntitle: T\ndate: 2026-01-25\n---\n\nBody.\n").is_ok());
+        assert!(parse_post("title: T\ndate: 2026-01-25\n---\n\nBody.\n").is_err());
+        assert!(parse_post("---\ntitle: T\ndate: 2026-01-25\n\nBody.\n").is_err());
+    }
+
+    #[test]
+    fn parses_notes_by_directory() {
+        let item = ContentItem::parse(
+            PathBuf::from("notes/n.md"),
+            NOTE.to_string(),
+            ContentDir::Notes,
+        )
+        .unwrap();
+
+        assert_eq!(item.note().unwrap().slug, "n");
+        assert!(item.post().is_none());
+        assert_eq!(item.body().trim(), "body");
+    }
+
+    #[test]
+    fn parses_podcast_episodes_by_directory() {
+        let item = ContentItem::parse(
+            PathBuf::from("podcast/ep.md"),
+            "---\ntitle: Ep\ndate: 2026-04-15\nslug: ep\nyoutube_id: abc\naudio_url: https://example.com/ep.mp3\naudio_length_bytes: 1\naudio_duration: \"00:01:00\"\n---\n\nShow notes\n".to_string(),
+            ContentDir::Podcast,
+        )
+        .unwrap();
+
+        assert_eq!(item.episode().unwrap().slug, "ep");
+        assert!(item.post().is_none() && item.note().is_none());
+        assert!(item.is_published());
+    }
+}
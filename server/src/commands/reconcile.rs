@@ -0,0 +1,639 @@
+//! Finds syndicated copies of content that's since been removed from the repo: Bluesky posts
+//! and standard.site documents on the PDS, and Buttondown emails. By default they're only
+//! listed. With `--delete` they're deleted, and the pages they pointed at get tombstones so
+//! the site answers for them with `410 Gone`.
+//!
+//! Something only counts as an orphan when it's shaped like what the site syndicates, nothing
+//! on disk records it, and the post, note or episode page it points at is gone too. Posts
+//! made by hand, and anything linking to an index, feed or redirect, are left alone.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use cja::color_eyre::eyre::eyre;
+use clap::Args;
+use posts::{blog::ToCanonicalPath, source::ContentDir};
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use crate::{
+    bluesky::{BlueskyClient, BlueskyConfig, ListedRecord},
+    buttondown::{ButtondownClient, ButtondownConfig, Email},
+    commands::{
+        buttondown::PLAINTEXT_MODE_COMMENT,
+        publish::{load_content, ContentItem, ItemFrontMatter},
+        standard_site::{load_config, rkey_from_at_uri, PublicationConfig},
+    },
+    tombstones::{Tombstones, TOMBSTONES_FILE},
+};
+
+const SITE_HOST: &str = "coreyja.com";
+
+/// Where posts, notes and episodes are served. Links anywhere else on the site aren't to
+/// something that could have been syndicated.
+const CONTENT_PAGE_PREFIXES: [&str; 3] = ["/posts/", "/notes/", "/podcast/"];
+
+/// Routes under [`CONTENT_PAGE_PREFIXES`] that the server answers itself rather than content,
+/// besides the feeds, which [`content_page`] skips by their extension
+const SITE_ROUTES: [&str; 1] = ["/posts/weekly"];
+
+const POST_COLLECTION: &str = "app.bsky.feed.post";
+const DOCUMENT_COLLECTION: &str = "site.standard.document";
+
+#[derive(Args, Debug)]
+pub struct ReconcileArgs {
+    /// Delete the orphans found and record tombstones for their pages, instead of only
+    /// listing them
+    #[arg(long)]
+    pub delete: bool,
+
+    /// The repo root, holding the content, `publications.toml` and `tombstones.toml`
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+/// Every page the content on disk serves, and what its frontmatter says it was syndicated as
+#[derive(Debug, Default)]
+struct LocalContent {
+    /// Drafts included, without trailing slashes
+    pages: HashSet<String>,
+    atproto_uris: HashSet<String>,
+    /// The record key at the end of each `bsky_url`, since older ones use the handle where
+    /// newer ones use the DID
+    bsky_rkeys: HashSet<String>,
+    buttondown_ids: HashSet<String>,
+}
+
+impl LocalContent {
+    fn from_items(root: &Path, items: &[ContentItem]) -> Self {
+        let mut local = Self::default();
+        let blog_dir = root.join(ContentDir::Blog.name());
+
+        for item in items {
+            let (page, atproto_uri, bsky_url) = match &item.frontmatter {
+                ItemFrontMatter::Post(fm) => {
+                    if let Some(id) = &fm.buttondown_id {
+                        local.buttondown_ids.insert(id.clone());
+                    }
+                    let page = item
+                        .path
+                        .strip_prefix(&blog_dir)
+                        .ok()
+                        .map(|rel| format!("/posts/{}", rel.to_path_buf().canonical_path()));
+                    (page, &fm.atproto_uri, &fm.bsky_url)
+                }
+                ItemFrontMatter::Note(fm) => (
+                    Some(format!("/notes/{}", fm.slug)),
+                    &fm.atproto_uri,
+                    &fm.bsky_url,
+                ),
+                ItemFrontMatter::Episode(fm) => (
+                    Some(format!("/podcast/{}", fm.slug)),
+                    &fm.atproto_uri,
+                    &None,
+                ),
+            };
+
+            if let Some(page) = page {
+                local.pages.insert(page.trim_end_matches('/').to_string());
+            }
+            if let Some(uri) = atproto_uri {
+                local.atproto_uris.insert(uri.clone());
+            }
+            if let Some(rkey) = bsky_url.as_deref().and_then(|url| url.rsplit('/').next()) {
+                local.bsky_rkeys.insert(rkey.to_string());
+            }
+        }
+
+        local
+    }
+
+    fn has_page(&self, page: &str) -> bool {
+        self.pages.contains(page.trim_end_matches('/'))
+    }
+}
+
+/// The path of a post, note or episode on the site that `url` links to. Indexes, feeds and
+/// the server's own redirects aren't pages any content produced, so they're never returned.
+fn content_page(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if url.host_str() != Some(SITE_HOST) {
+        return None;
+    }
+
+    let path = urlencoding::decode(url.path()).ok()?;
+    let path = path.trim_end_matches('/');
+    let slug = CONTENT_PAGE_PREFIXES
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))?;
+    let is_file = slug
+        .rsplit('/')
+        .next()
+        .is_some_and(|last| last.contains('.'));
+    if slug.is_empty() || is_file || SITE_ROUTES.contains(&path) {
+        return None;
+    }
+
+    Some(path.to_string())
+}
+
+/// A copy of something that's no longer on the site
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Orphan {
+    /// A post and its replies, root first
+    BlueskyThread {
+        uris: Vec<String>,
+        page: String,
+    },
+    Document {
+        uri: String,
+        page: String,
+    },
+    Email {
+        id: String,
+        subject: String,
+    },
+}
+
+impl Orphan {
+    /// The page on the site it was a copy of
+    fn page(&self) -> Option<&str> {
+        match self {
+            Self::BlueskyThread { page, .. } | Self::Document { page, .. } => Some(page),
+            Self::Email { .. } => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::BlueskyThread { uris, page } if uris.len() > 1 => {
+                format!(
+                    "[bluesky] {} and {} replies, for {page}",
+                    uris[0],
+                    uris.len() - 1
+                )
+            }
+            Self::BlueskyThread { uris, page } => format!("[bluesky] {}, for {page}", uris[0]),
+            Self::Document { uri, page } => format!("[standard-site] {uri}, for {page}"),
+            Self::Email { id, subject } => format!("[buttondown] email {id} \"{subject}\""),
+        }
+    }
+}
+
+/// Documents in one of our publications whose page is gone
+fn orphaned_documents(
+    local: &LocalContent,
+    publications: &[PublicationConfig],
+    records: &[ListedRecord],
+) -> Vec<Orphan> {
+    records
+        .iter()
+        .filter(|record| !local.atproto_uris.contains(&record.uri))
+        .filter_map(|record| {
+            let site = record.value["site"].as_str()?;
+            let publication = publications
+                .iter()
+                .find(|p| p.at_uri.as_deref() == Some(site))?;
+            let path = record.value["path"].as_str().unwrap_or_default();
+            let page = content_page(&format!("{}{path}", publication.url.trim_end_matches('/')))?;
+
+            (!local.has_page(&page)).then(|| Orphan::Document {
+                uri: record.uri.clone(),
+                page,
+            })
+        })
+        .collect()
+}
+
+/// The links in a post, from its link card and its text, in order
+fn post_links(post: &JsonValue) -> Vec<&str> {
+    let card = post["embed"]["external"]["uri"].as_str();
+    let facets = post["facets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|facet| facet["features"].as_array().into_iter().flatten())
+        .filter(|feature| feature["$type"] == "app.bsky.richtext.facet#link")
+        .filter_map(|feature| feature["uri"].as_str());
+
+    card.into_iter().chain(facets).collect()
+}
+
+/// The link back to the site in a thread the site posted: the root's link card, or for notes
+/// posted with images, the note's URL that the thread's text ends with. Threads with neither
+/// were written by hand.
+fn syndicated_link<'a>(posts: &[&'a ListedRecord]) -> Option<&'a str> {
+    let root = posts.first()?;
+    if let Some(card) = root.value["embed"]["external"]["uri"].as_str() {
+        return Some(card);
+    }
+
+    let last = posts.last()?;
+    let text = last.value["text"].as_str()?;
+    post_links(&last.value)
+        .into_iter()
+        .next_back()
+        .filter(|link| text.ends_with(link))
+}
+
+/// Threads the site posted for a page that's gone
+fn orphaned_threads(local: &LocalContent, records: &[ListedRecord]) -> Vec<Orphan> {
+    let mut threads: BTreeMap<&str, Vec<&ListedRecord>> = BTreeMap::new();
+    for record in records {
+        let root = record.value["reply"]["root"]["uri"]
+            .as_str()
+            .unwrap_or(&record.uri);
+        threads.entry(root).or_default().push(record);
+    }
+
+    threads
+        .into_iter()
+        .filter_map(|(root_uri, mut posts)| {
+            // Root first, then replies in the order they were posted
+            posts.sort_by_key(|post| {
+                (
+                    post.uri != root_uri,
+                    post.value["createdAt"].as_str().map(str::to_string),
+                )
+            });
+
+            // Replies in threads someone else started aren't copies of anything
+            posts.first().filter(|post| post.uri == root_uri)?;
+            let root_rkey = root_uri.rsplit('/').next()?;
+            if local.bsky_rkeys.contains(root_rkey) {
+                return None;
+            }
+
+            let page = content_page(syndicated_link(&posts)?)?;
+            if local.has_page(&page) {
+                return None;
+            }
+
+            Some(Orphan::BlueskyThread {
+                uris: posts.iter().map(|post| post.uri.clone()).collect(),
+                page,
+            })
+        })
+        .collect()
+}
+
+/// Emails sent from the site that no post records. Ones written in Buttondown's editor are
+/// never counted.
+fn orphaned_emails(local: &LocalContent, emails: &[Email]) -> Vec<Orphan> {
+    emails
+        .iter()
+        .filter(|email| email.body.starts_with(PLAINTEXT_MODE_COMMENT))
+        .filter(|email| !local.buttondown_ids.contains(&email.id))
+        .map(|email| Orphan::Email {
+            id: email.id.clone(),
+            subject: email.subject.clone(),
+        })
+        .collect()
+}
+
+async fn delete(
+    bluesky: &BlueskyClient,
+    buttondown: &ButtondownClient,
+    orphan: &Orphan,
+) -> cja::Result<()> {
+    match orphan {
+        // Replies first, so the thread never has a reply without its root
+        Orphan::BlueskyThread { uris, .. } => {
+            for uri in uris.iter().rev() {
+                bluesky
+                    .delete_record(POST_COLLECTION, &rkey_from_at_uri(uri)?)
+                    .await?;
+            }
+        }
+        Orphan::Document { uri, .. } => {
+            bluesky
+                .delete_record(DOCUMENT_COLLECTION, &rkey_from_at_uri(uri)?)
+                .await?;
+        }
+        Orphan::Email { id, .. } => buttondown.delete_email(id).await?,
+    }
+
+    Ok(())
+}
+
+pub async fn reconcile(args: &ReconcileArgs) -> cja::Result<()> {
+    let (items, load_failures) = load_content(&args.root);
+    if !load_failures.is_empty() {
+        for (path, e) in &load_failures {
+            eprintln!("Failed to read {}: {e}", path.display());
+        }
+        return Err(eyre!(
+            "{} file(s) failed to read (see above); fix them first so what they syndicated isn't mistaken for orphans",
+            load_failures.len()
+        ));
+    }
+    let local = LocalContent::from_items(&args.root, &items);
+    let publications = load_config(&args.root.join("publications.toml"))?.publications;
+
+    let bluesky = BlueskyClient::login(&BlueskyConfig::from_env()?).await?;
+    let buttondown = ButtondownClient::new(&ButtondownConfig::from_env()?);
+
+    let mut orphans = orphaned_threads(&local, &bluesky.list_records(POST_COLLECTION).await?);
+    orphans.extend(orphaned_documents(
+        &local,
+        &publications,
+        &bluesky.list_records(DOCUMENT_COLLECTION).await?,
+    ));
+    orphans.extend(orphaned_emails(&local, &buttondown.list_emails().await?));
+
+    if orphans.is_empty() {
+        println!("Nothing to reconcile: every syndicated copy still has its content");
+        return Ok(());
+    }
+    for orphan in &orphans {
+        println!("{}", orphan.describe());
+    }
+    if !args.delete {
+        println!();
+        println!(
+            "{} orphan(s). Run again with --delete to delete them and record tombstones for their pages.",
+            orphans.len()
+        );
+        return Ok(());
+    }
+
+    let tombstones_path = args.root.join(TOMBSTONES_FILE);
+    let mut tombstones = Tombstones::load(&tombstones_path)?;
+    let today = Utc::now().date_naive();
+    let mut failed = 0;
+    for orphan in &orphans {
+        // A page only gets its tombstone once its copies are gone, so a failure is retried
+        // in full by the next run
+        if let Err(e) = delete(&bluesky, &buttondown, orphan).await {
+            eprintln!("Failed to delete {}: {e}", orphan.describe());
+            failed += 1;
+            continue;
+        }
+        if let Some(page) = orphan.page() {
+            if tombstones.record(page, today) {
+                println!("Recorded a tombstone for {page}");
+            }
+        }
+    }
+    tombstones.save(&tombstones_path)?;
+
+    if failed > 0 {
+        return Err(eyre!(
+            "{failed} orphan(s) failed to delete (see above); the next run will retry"
+        ));
+    }
+
+    println!("Deleted {} orphan(s)", orphans.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const BLOG_PUB: &str = "at://did:plc:me/site.standard.publication/blog";
+
+    fn record(rkey: &str, collection: &str, value: JsonValue) -> ListedRecord {
+        ListedRecord {
+            uri: format!("at://did:plc:me/{collection}/{rkey}"),
+            cid: format!("cid-{rkey}"),
+            value,
+        }
+    }
+
+    fn local() -> LocalContent {
+        let root = Path::new("/repo");
+        let item = |relative: &str, dir: ContentDir, raw: &str| {
+            ContentItem::parse(root.join(relative), raw.to_string(), dir).unwrap()
+        };
+
+        LocalContent::from_items(
+            root,
+            &[
+                item(
+                    "blog/live-post/index.md",
+                    ContentDir::Blog,
+                    "---\ntitle: Live\ndate: 2026-06-01\natproto_uri: at://did:plc:me/site.standard.document/live-post\nbsky_url: https://bsky.app/profile/coreyja.com/post/livepost\nbuttondown_id: email-live\n---\n\nBody.\n",
+                ),
+                item(
+                    "notes/live-note.md",
+                    ContentDir::Notes,
+                    "---\ntitle: Note\ndate: 2026-06-01\nslug: live-note\n---\n\nBody.\n",
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn local_content_knows_its_pages_and_syndicated_copies() {
+        let local = local();
+
+        assert!(local.has_page("/posts/live-post/"));
+        assert!(local.has_page("/notes/live-note"));
+        assert!(!local.has_page("/notes/gone"));
+        assert!(local.bsky_rkeys.contains("livepost"));
+        assert!(local.buttondown_ids.contains("email-live"));
+    }
+
+    #[test]
+    fn content_pages_are_only_posts_notes_and_episodes_on_the_site() {
+        assert_eq!(
+            content_page("https://coreyja.com/posts/some-post/").as_deref(),
+            Some("/posts/some-post")
+        );
+        assert_eq!(
+            content_page("https://coreyja.com/podcast/ep1").as_deref(),
+            Some("/podcast/ep1")
+        );
+        assert_eq!(
+            content_page(
+                "https://coreyja.com/posts/BattlesnakeMinimax/Minimax%20in%20Battlesnake/"
+            )
+            .as_deref(),
+            Some("/posts/BattlesnakeMinimax/Minimax in Battlesnake")
+        );
+        assert_eq!(content_page("https://coreyja.com/projects"), None);
+        assert_eq!(content_page("https://example.com/posts/some-post"), None);
+    }
+
+    #[test]
+    fn indexes_feeds_and_redirects_are_not_content_pages() {
+        for url in [
+            "https://coreyja.com/posts",
+            "https://coreyja.com/notes/",
+            "https://coreyja.com/posts/rss.xml",
+            "https://coreyja.com/notes/rss.xml",
+            "https://coreyja.com/podcast/feed.xml",
+            "https://coreyja.com/posts/weekly/",
+            "https://coreyja.com/posts/live-post/index.md",
+            "https://coreyja.com/til/gone-note",
+        ] {
+            assert_eq!(content_page(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn documents_for_removed_pages_are_orphans() {
+        let publications = vec![PublicationConfig {
+            key: "blog".to_string(),
+            title: "Blog".to_string(),
+            description: String::new(),
+            url: "https://coreyja.com/posts".to_string(),
+            content_dir: "blog".to_string(),
+            collection: DOCUMENT_COLLECTION.to_string(),
+            at_uri: Some(BLOG_PUB.to_string()),
+            at_cid: Some("pubcid".to_string()),
+            cover_synced: true,
+        }];
+        let records = vec![
+            record(
+                "live-post",
+                DOCUMENT_COLLECTION,
+                json!({ "site": BLOG_PUB, "path": "/live-post/" }),
+            ),
+            record(
+                "gone-post",
+                DOCUMENT_COLLECTION,
+                json!({ "site": BLOG_PUB, "path": "/gone-post/" }),
+            ),
+            // Someone else's publication in the same repo isn't ours to clean up
+            record(
+                "other",
+                DOCUMENT_COLLECTION,
+                json!({ "site": "at://did:plc:me/site.standard.publication/other", "path": "/x/" }),
+            ),
+        ];
+
+        assert_eq!(
+            orphaned_documents(&local(), &publications, &records),
+            vec![Orphan::Document {
+                uri: "at://did:plc:me/site.standard.document/gone-post".to_string(),
+                page: "/posts/gone-post".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn threads_for_removed_pages_are_orphans_with_their_replies() {
+        let records = vec![
+            // Recorded in the live post's frontmatter
+            record(
+                "livepost",
+                POST_COLLECTION,
+                json!({ "createdAt": "2026-06-01T00:00:00Z", "embed": { "external": { "uri": "https://coreyja.com/posts/gone-post/" } } }),
+            ),
+            // A note posted with images, as a thread that links back at the end
+            record(
+                "root",
+                POST_COLLECTION,
+                json!({
+                    "createdAt": "2026-06-02T00:00:00Z",
+                    "text": "Gone note\n\nFollowing up on https://coreyja.com/posts/live-post/ with",
+                    "embed": { "images": [] },
+                    "facets": [{ "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": "https://coreyja.com/posts/live-post/" }] }],
+                }),
+            ),
+            record(
+                "reply2",
+                POST_COLLECTION,
+                json!({
+                    "createdAt": "2026-06-02T00:00:02Z",
+                    "text": "the end.\n\nhttps://coreyja.com/notes/gone-note",
+                    "reply": { "root": { "uri": "at://did:plc:me/app.bsky.feed.post/root" } },
+                    "facets": [{ "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": "https://coreyja.com/notes/gone-note" }] }],
+                }),
+            ),
+            record(
+                "reply1",
+                POST_COLLECTION,
+                json!({
+                    "createdAt": "2026-06-02T00:00:01Z",
+                    "text": "more about https://coreyja.com/posts/live-post/ and",
+                    "reply": { "root": { "uri": "at://did:plc:me/app.bsky.feed.post/root" } },
+                    "facets": [{ "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": "https://coreyja.com/posts/live-post/" }] }],
+                }),
+            ),
+            // Written by hand, linking to a page that's still up
+            record(
+                "manual",
+                POST_COLLECTION,
+                json!({ "createdAt": "2026-06-03T00:00:00Z", "embed": { "external": { "uri": "https://coreyja.com/notes/live-note" } } }),
+            ),
+            // Not about the site at all
+            record(
+                "chatter",
+                POST_COLLECTION,
+                json!({ "createdAt": "2026-06-04T00:00:00Z", "text": "hello" }),
+            ),
+        ];
+
+        assert_eq!(
+            orphaned_threads(&local(), &records),
+            vec![Orphan::BlueskyThread {
+                uris: vec![
+                    "at://did:plc:me/app.bsky.feed.post/root".to_string(),
+                    "at://did:plc:me/app.bsky.feed.post/reply1".to_string(),
+                    "at://did:plc:me/app.bsky.feed.post/reply2".to_string(),
+                ],
+                page: "/notes/gone-note".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn threads_linking_to_indexes_feeds_redirects_or_mid_text_are_left_alone() {
+        let card = |rkey: &str, url: &str| {
+            record(
+                rkey,
+                POST_COLLECTION,
+                json!({ "createdAt": "2026-06-01T00:00:00Z", "embed": { "external": { "uri": url } } }),
+            )
+        };
+        let records = vec![
+            card("index", "https://coreyja.com/posts/"),
+            card("feed", "https://coreyja.com/notes/rss.xml"),
+            card("redirect", "https://coreyja.com/posts/weekly/"),
+            // Written by hand, with a link to a gone note that the text doesn't end with
+            record(
+                "aside",
+                POST_COLLECTION,
+                json!({
+                    "createdAt": "2026-06-02T00:00:00Z",
+                    "text": "I wrote https://coreyja.com/notes/gone-note once",
+                    "facets": [{ "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": "https://coreyja.com/notes/gone-note" }] }],
+                }),
+            ),
+        ];
+
+        assert_eq!(orphaned_threads(&local(), &records), vec![]);
+    }
+
+    #[test]
+    fn only_emails_sent_from_the_site_can_be_orphans() {
+        let email = |id: &str, body: &str| Email {
+            id: id.to_string(),
+            subject: format!("Subject {id}"),
+            status: "sent".to_string(),
+            body: body.to_string(),
+        };
+        let sent_from_site = format!("{PLAINTEXT_MODE_COMMENT}\n<p>Hi</p>");
+        let emails = vec![
+            email("email-live", &sent_from_site),
+            email("email-gone", &sent_from_site),
+            email("written-in-buttondown", "Hi!"),
+        ];
+
+        assert_eq!(
+            orphaned_emails(&local(), &emails),
+            vec![Orphan::Email {
+                id: "email-gone".to_string(),
+                subject: "Subject email-gone".to_string(),
+            }]
+        );
+    }
+}
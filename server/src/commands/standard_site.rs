@@ -9,9 +9,13 @@ use crate::bluesky::{
     at_uri_to_web_url, Blob, BlueskyClient, BlueskyConfig, DocumentRecord, PublicationRecord,
     StrongRef,
 };
-use crate::commands::frontmatter;
-use posts::blog::{BlogFrontMatter, ToCanonicalPath};
+use crate::commands::publish::{
+    ContentItem, Destination, DestinationKind, ItemFrontMatter, PlannedWrite, Step,
+};
+use chrono::NaiveDate;
+use posts::blog::{og_card_route, BlogFrontMatter, ToCanonicalPath};
 use posts::plain::IntoPlainText;
+use posts::source::ContentDir;
 use posts::MarkdownAst;
 
 /// Posts dated on or after this cutoff get a Bluesky post in addition to
@@ -32,10 +36,6 @@ pub enum StandardSiteCommand {
     /// `at_uri` is already set, refresh the record via `putRecord` using the
     /// rkey parsed out of the cached `at_uri`.
     Init(InitArgs),
-    /// Walk the publication's `content_dir`, create/update a
-    /// `site.standard.document` for every post, then create the bsky post
-    /// with `associatedRefs`.
-    Sync(SyncArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -51,17 +51,6 @@ pub struct InitArgs {
     pub force: bool,
 }
 
-#[derive(clap::Args, Debug)]
-#[group(required = true, multiple = false)]
-pub struct SyncArgs {
-    #[arg(long, group = "target")]
-    pub all: bool,
-    #[arg(long, group = "target")]
-    pub key: Option<String>,
-    #[arg(long, default_value = "publications.toml")]
-    pub config: PathBuf,
-}
-
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PublicationsConfig {
     #[serde(rename = "publication")]
@@ -94,24 +83,52 @@ pub struct PublicationConfig {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub enum SyncOutcome {
-    /// Both `atproto_uri` and `bsky_url` already set — nothing to do.
+enum SyncPlan {
+    /// The document is current and the post needs no bsky post — nothing to do.
     Skip,
-    /// Document already synced but no bsky post yet.
-    BskyOnly,
-    /// Neither side synced — create document and bsky post.
-    Both,
+    /// Put the document, either because it doesn't exist yet or because it's
+    /// pinned to an older publication cid.
+    Document,
+    /// Put the document and create a bsky post that references it.
+    DocumentAndBsky,
 }
 
-fn classify_blog_post(fm: &BlogFrontMatter) -> SyncOutcome {
-    match (&fm.atproto_uri, &fm.bsky_url) {
-        (Some(_), Some(_)) => SyncOutcome::Skip,
-        (Some(_), None) => SyncOutcome::BskyOnly,
-        _ => SyncOutcome::Both,
+/// What needs doing for a post, given the cid of the publication it belongs to.
+///
+/// - Document put: any time the doc doesn't exist OR its pinned pub cid has drifted.
+/// - Bsky post: only when the post is recent (>= cutoff) and we don't have a `bsky_url`.
+///   When we DO need a bsky post but the doc is already current, we still re-put the
+///   doc so we have a fresh strong-ref to attach to the bsky post.
+fn plan_sync(fm: &BlogFrontMatter, pub_cid: &str) -> SyncPlan {
+    let is_historical = fm.date < bsky_post_cutoff();
+    let bsky_exists = fm.bsky_url.is_some();
+
+    if !bsky_exists && !is_historical {
+        SyncPlan::DocumentAndBsky
+    } else {
+        plan_document_sync(
+            fm.atproto_uri.as_deref(),
+            fm.atproto_pub_cid.as_deref(),
+            pub_cid,
+        )
     }
 }
 
-fn load_config(path: &Path) -> cja::Result<PublicationsConfig> {
+/// What needs doing for a document alone: a put when it doesn't exist yet or its pinned
+/// publication cid has drifted. Notes and episodes only ever need this.
+fn plan_document_sync(
+    atproto_uri: Option<&str>,
+    atproto_pub_cid: Option<&str>,
+    pub_cid: &str,
+) -> SyncPlan {
+    if atproto_uri.is_none() || atproto_pub_cid != Some(pub_cid) {
+        SyncPlan::Document
+    } else {
+        SyncPlan::Skip
+    }
+}
+
+pub(crate) fn load_config(path: &Path) -> cja::Result<PublicationsConfig> {
     let raw = std::fs::read_to_string(path)
         .map_err(|e| eyre!("Failed to read config {}: {}", path.display(), e))?;
     toml::from_str(&raw).map_err(|e| eyre!("Failed to parse {}: {}", path.display(), e))
@@ -125,14 +142,7 @@ fn save_config(path: &Path, cfg: &PublicationsConfig) -> cja::Result<()> {
     Ok(())
 }
 
-fn repo_root_for(config_path: &Path) -> PathBuf {
-    config_path
-        .parent()
-        .filter(|p| !p.as_os_str().is_empty())
-        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
-}
-
-fn rkey_from_at_uri(at_uri: &str) -> cja::Result<String> {
+pub(crate) fn rkey_from_at_uri(at_uri: &str) -> cja::Result<String> {
     let stripped = at_uri
         .strip_prefix("at://")
         .ok_or_else(|| eyre!("Invalid AT URI: missing at:// prefix: {at_uri}"))?;
@@ -167,6 +177,12 @@ fn rkey_from_blog_path(relative_path: &Path) -> String {
         .replace([std::path::MAIN_SEPARATOR, '/'], "-")
 }
 
+/// Notes and episodes are keyed by their slug, prefixed with their directory so they can't
+/// collide with each other or with a blog post's rkey in the shared collection.
+fn rkey_from_slug(dir: ContentDir, slug: &str) -> String {
+    format!("{}-{slug}", dir.name())
+}
+
 fn relative_under(root: &Path, content_dir: &str, post_path: &Path) -> cja::Result<PathBuf> {
     let prefix = root.join(content_dir);
     post_path
@@ -181,43 +197,11 @@ fn relative_under(root: &Path, content_dir: &str, post_path: &Path) -> cja::Resu
         })
 }
 
-/// Recursively walk `root` collecting any file named exactly `index.md`.
-fn collect_index_md_files(root: &Path) -> cja::Result<Vec<PathBuf>> {
-    let mut out = Vec::new();
-    walk_index_md(root, &mut out)?;
-    out.sort();
-    Ok(out)
-}
-
-fn walk_index_md(dir: &Path, out: &mut Vec<PathBuf>) -> cja::Result<()> {
-    let entries =
-        std::fs::read_dir(dir).map_err(|e| eyre!("Failed to read dir {}: {}", dir.display(), e))?;
-    for entry in entries {
-        let entry = entry.map_err(|e| eyre!("Failed to read dir entry: {}", e))?;
-        let path = entry.path();
-        let file_type = entry
-            .file_type()
-            .map_err(|e| eyre!("Failed to stat {}: {}", path.display(), e))?;
-        if file_type.is_dir() {
-            walk_index_md(&path, out)?;
-        } else if file_type.is_file()
-            && path.file_name().and_then(|s| s.to_str()) == Some("index.md")
-        {
-            out.push(path);
-        }
-    }
-    Ok(())
-}
-
-/// Build the imgproxy URL that rasterizes the publication's SVG OG card to a
-/// 1200×630 PNG. Mirrors the format used by
-/// `templates::og::og_image_url` so cover images match the per-post cards.
-fn publication_cover_imgproxy_url(app_base_url: &str, imgproxy_url: &str, key: &str) -> String {
-    let svg_url = format!(
-        "{}/og/publication/{}.svg",
-        app_base_url.trim_end_matches('/'),
-        key
-    );
+/// Build the imgproxy URL that rasterizes an SVG OG card (e.g.
+/// `/og/publication/blog.svg`) to a 1200×630 PNG. Mirrors the format used by
+/// `templates::og::og_image_url` so uploaded images match the cards the site serves.
+fn og_card_imgproxy_url(app_base_url: &str, imgproxy_url: &str, route_path: &str) -> String {
+    let svg_url = format!("{}{}", app_base_url.trim_end_matches('/'), route_path);
     format!(
         "{}/unsafe/rs:fill:1200:630/format:png/plain/{}",
         imgproxy_url.trim_end_matches('/'),
@@ -237,15 +221,16 @@ fn required_env(name: &str) -> cja::Result<String> {
     }
 }
 
-/// Fetch the publication's branded OG card as a PNG via imgproxy.
+/// Fetch an OG card (the publication's branded cover, or a post's card) as a
+/// PNG via imgproxy.
 ///
 /// Requires `APP_BASE_URL` (so we know where the deployed SVG endpoint lives)
 /// and `IMGPROXY_URL` (so the SVG gets rasterized). Returns `(bytes, mime)`
 /// suitable for `upload_blob`.
-async fn fetch_publication_cover_png(key: &str) -> cja::Result<(Vec<u8>, String)> {
+async fn fetch_og_card_png(route_path: &str) -> cja::Result<(Vec<u8>, String)> {
     let app_base_url = required_env("APP_BASE_URL")?;
     let imgproxy_url = required_env("IMGPROXY_URL")?;
-    let png_url = publication_cover_imgproxy_url(&app_base_url, &imgproxy_url, key);
+    let png_url = og_card_imgproxy_url(&app_base_url, &imgproxy_url, route_path);
 
     let resp = reqwest::get(&png_url)
         .await
@@ -268,6 +253,24 @@ async fn fetch_publication_cover_png(key: &str) -> cja::Result<(Vec<u8>, String)
     Ok((bytes, content_type))
 }
 
+/// Upload a post's OG card to use as its Bluesky link card thumbnail.
+/// Best-effort like the publication cover: a card without a thumbnail beats
+/// failing the whole sync.
+async fn card_thumbnail(client: &BlueskyClient, route_path: &str) -> Option<Blob> {
+    let upload = match fetch_og_card_png(route_path).await {
+        Ok((bytes, _mime)) => client.upload_image(&bytes).await,
+        Err(e) => Err(e),
+    };
+
+    match upload {
+        Ok((blob, _aspect_ratio)) => Some(blob),
+        Err(e) => {
+            println!("Warning: no thumbnail for {route_path}: {e}. Posting the card without one.");
+            None
+        }
+    }
+}
+
 pub async fn run(cmd: &StandardSiteCommand) -> cja::Result<()> {
     let config = BlueskyConfig::from_env()?;
     let client = BlueskyClient::login(&config).await?;
@@ -276,50 +279,9 @@ pub async fn run(cmd: &StandardSiteCommand) -> cja::Result<()> {
         StandardSiteCommand::Init(args) => {
             init_publication(&client, &args.config, &args.key, args.force).await
         }
-        StandardSiteCommand::Sync(args) => {
-            let cfg = load_config(&args.config)?;
-            let repo_root = repo_root_for(&args.config);
-            let to_sync: Vec<PublicationConfig> = if args.all {
-                cfg.publications.clone()
-            } else {
-                let key = args.key.as_ref().ok_or_else(|| {
-                    eyre!("Either --all or --key must be provided to publish-standard-site sync")
-                })?;
-                cfg.publications
-                    .iter()
-                    .find(|p| &p.key == key)
-                    .cloned()
-                    .map(|p| vec![p])
-                    .ok_or_else(|| eyre!("Publication '{}' not found in config", key))?
-            };
-
-            let mut total_failed = 0usize;
-            for pub_cfg in &to_sync {
-                let summary = sync_publication(&client, pub_cfg, &repo_root).await?;
-                println!(
-                    "Publication '{}': {} synced, {} skipped, {} failed",
-                    pub_cfg.key, summary.synced, summary.skipped, summary.failed
-                );
-                total_failed += summary.failed;
-            }
-
-            if total_failed > 0 {
-                return Err(eyre!(
-                    "{total_failed} post(s) failed to sync; next run will retry"
-                ));
-            }
-            Ok(())
-        }
     }
 }
 
-#[derive(Debug, Default)]
-struct SyncSummary {
-    synced: usize,
-    skipped: usize,
-    failed: usize,
-}
-
 async fn init_publication(
     client: &BlueskyClient,
     config_path: &Path,
@@ -352,7 +314,7 @@ async fn init_publication(
     // points at. Best-effort: if the fetch fails we proceed without a cover
     // — `cover_synced` stays `false` so the next deploy retries.
     let (cover, cover_synced): (Option<Blob>, bool) =
-        match fetch_publication_cover_png(&pub_cfg.key).await {
+        match fetch_og_card_png(&format!("/og/publication/{}.svg", pub_cfg.key)).await {
             Ok((bytes, mime)) => match client.upload_blob(bytes, &mime).await {
                 Ok(blob) => (Some(blob), true),
                 Err(e) => {
@@ -400,167 +362,272 @@ async fn init_publication(
     Ok(())
 }
 
-async fn sync_publication(
-    client: &BlueskyClient,
-    pub_cfg: &PublicationConfig,
-    repo_root: &Path,
-) -> cja::Result<SyncSummary> {
+/// A post, note or episode that belongs to one of the configured publications, with what
+/// its `site.standard.document` record needs.
+struct PublicationDocument<'a> {
+    publication: &'a PublicationConfig,
+    /// Record key, unique across every publication since they share one collection
+    rkey: String,
+    /// URL path relative to the publication's `url`, like `/some-post/`
+    path: String,
+    title: &'a str,
+    date: NaiveDate,
+    tags: &'a [String],
+    atproto_uri: Option<&'a str>,
+    plan: SyncPlan,
+    /// OG card route for the Bluesky post that links to a blog post
+    og_card: Option<String>,
+}
+
+impl PublicationDocument<'_> {
+    fn url(&self) -> String {
+        format!(
+            "{}{}",
+            self.publication.url.trim_end_matches('/'),
+            self.path
+        )
+    }
+}
+
+/// The cid of a bootstrapped publication's record, which documents pin themselves to
+fn publication_cid(pub_cfg: &PublicationConfig) -> cja::Result<&str> {
     match (&pub_cfg.at_uri, &pub_cfg.at_cid) {
-        (Some(_), Some(_)) => {}
-        (Some(_), None) => {
-            return Err(eyre!(
-                "publication '{}' is partially bootstrapped — re-run `publish-standard-site init {}`",
-                pub_cfg.key,
-                pub_cfg.key
-            ));
-        }
-        _ => {
-            return Err(eyre!(
-                "publication '{}' is not bootstrapped — run `publish-standard-site init {}` first",
-                pub_cfg.key,
-                pub_cfg.key
-            ));
-        }
+        (Some(_), Some(cid)) => Ok(cid),
+        (Some(_), None) => Err(eyre!(
+            "publication '{}' is partially bootstrapped — re-run `publish-standard-site init {}`",
+            pub_cfg.key,
+            pub_cfg.key
+        )),
+        _ => Err(eyre!(
+            "publication '{}' is not bootstrapped — run `publish-standard-site init {}` first",
+            pub_cfg.key,
+            pub_cfg.key
+        )),
     }
+}
 
-    let content_root = repo_root.join(&pub_cfg.content_dir);
-    let post_paths = collect_index_md_files(&content_root)?;
+/// Keeps a `site.standard.document` record on the PDS for every post, note and podcast
+/// episode in a publication from `publications.toml`, plus a bsky post for recent posts.
+/// Notes already get their own bsky post from [`super::bluesky::BlueskyNotes`].
+pub(crate) struct StandardSite {
+    publications: Vec<PublicationConfig>,
+    repo_root: PathBuf,
+    client: Option<BlueskyClient>,
+}
 
-    let mut summary = SyncSummary::default();
-    let mut failures: Vec<(PathBuf, cja::color_eyre::Report)> = Vec::new();
+impl StandardSite {
+    pub(crate) fn from_config(repo_root: &Path) -> cja::Result<Self> {
+        let cfg = load_config(&repo_root.join("publications.toml"))?;
+        Ok(Self {
+            publications: cfg.publications,
+            repo_root: repo_root.to_path_buf(),
+            client: None,
+        })
+    }
 
-    for post_path in &post_paths {
-        match sync_one(client, pub_cfg, post_path, repo_root).await {
-            Ok(SyncOutcome::Skip) => summary.skipped += 1,
-            Ok(_) => summary.synced += 1,
-            Err(e) => {
-                eprintln!("Failed to sync {}: {e}", post_path.display());
-                failures.push((post_path.clone(), e));
+    /// The publication notes or episodes go in, found by its `content_dir`
+    fn publication_for_dir(&self, dir: ContentDir) -> Option<&PublicationConfig> {
+        self.publications
+            .iter()
+            .find(|p| p.content_dir == dir.name())
+    }
+
+    /// `None` when the item isn't in one of the publications.
+    fn publication_document<'a>(
+        &'a self,
+        item: &'a ContentItem,
+    ) -> cja::Result<Option<PublicationDocument<'a>>> {
+        let no_tags: &[String] = &[];
+        let (dir, slug, title, date, tags, atproto_uri, atproto_pub_cid) = match &item.frontmatter {
+            ItemFrontMatter::Post(fm) => return self.post_document(item, fm),
+            ItemFrontMatter::Note(fm) => (
+                ContentDir::Notes,
+                &fm.slug,
+                &fm.title,
+                fm.date,
+                fm.tags.as_slice(),
+                &fm.atproto_uri,
+                &fm.atproto_pub_cid,
+            ),
+            ItemFrontMatter::Episode(fm) => (
+                ContentDir::Podcast,
+                &fm.slug,
+                &fm.title,
+                fm.date,
+                no_tags,
+                &fm.atproto_uri,
+                &fm.atproto_pub_cid,
+            ),
+        };
+        let Some(pub_cfg) = self.publication_for_dir(dir) else {
+            return Ok(None);
+        };
+        let pub_cid = publication_cid(pub_cfg)?;
+
+        Ok(Some(PublicationDocument {
+            publication: pub_cfg,
+            rkey: rkey_from_slug(dir, slug),
+            path: format!("/{slug}"),
+            title,
+            date,
+            tags,
+            atproto_uri: atproto_uri.as_deref(),
+            plan: plan_document_sync(atproto_uri.as_deref(), atproto_pub_cid.as_deref(), pub_cid),
+            og_card: None,
+        }))
+    }
+
+    fn post_document<'a>(
+        &'a self,
+        item: &'a ContentItem,
+        fm: &'a BlogFrontMatter,
+    ) -> cja::Result<Option<PublicationDocument<'a>>> {
+        if item.path.file_name().and_then(|s| s.to_str()) != Some("index.md") {
+            return Ok(None);
+        }
+        let Some(pub_cfg) = self.publications.iter().find(|p| p.key == fm.publication) else {
+            return Ok(None);
+        };
+        let Ok(rel) = relative_under(&self.repo_root, &pub_cfg.content_dir, &item.path) else {
+            return Ok(None);
+        };
+        let pub_cid = publication_cid(pub_cfg)?;
+
+        // `path` per spec is the URL path segment relative to the publication URL.
+        // Publication URL is `https://coreyja.com/posts`; the per-post URL is
+        // `https://coreyja.com/posts/<canonical>`. So path is `/<canonical>`.
+        Ok(Some(PublicationDocument {
+            publication: pub_cfg,
+            rkey: rkey_from_blog_path(&rel),
+            path: format!("/{}", rel.canonical_path()),
+            title: &fm.title,
+            date: fm.date,
+            tags: &fm.tags,
+            atproto_uri: fm.atproto_uri.as_deref(),
+            plan: plan_sync(fm, pub_cid),
+            og_card: Some(og_card_route(&rel, fm.is_newsletter)),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Destination for StandardSite {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::StandardSite
+    }
+
+    fn plan(&self, item: &ContentItem) -> cja::Result<Option<Step>> {
+        let Some(doc) = self.publication_document(item)? else {
+            return Ok(None);
+        };
+        let pub_cid = doc.publication.at_cid.clone().unwrap_or_default();
+
+        let rkey = &doc.rkey;
+        let (description, with_bsky) = match doc.plan {
+            SyncPlan::Skip => return Ok(None),
+            SyncPlan::Document => (format!("put document {rkey}"), false),
+            SyncPlan::DocumentAndBsky => {
+                (format!("put document {rkey} and post it to Bluesky"), true)
             }
+        };
+
+        let mut writes = vec![];
+        if doc.atproto_uri.is_none() {
+            writes.push(PlannedWrite::pending("atproto_uri"));
         }
+        writes.push(PlannedWrite::known("atproto_pub_cid", pub_cid));
+        if with_bsky {
+            writes.push(PlannedWrite::pending("bsky_url"));
+        }
+
+        Ok(Some(Step {
+            description,
+            writes,
+        }))
     }
 
-    summary.failed = failures.len();
-    Ok(summary)
-}
+    async fn connect(&mut self) -> cja::Result<()> {
+        let config = BlueskyConfig::from_env()?;
+        self.client = Some(BlueskyClient::login(&config).await?);
+        Ok(())
+    }
 
-async fn sync_one(
-    client: &BlueskyClient,
-    pub_cfg: &PublicationConfig,
-    post_path: &Path,
-    repo_root: &Path,
-) -> cja::Result<SyncOutcome> {
-    let content = std::fs::read_to_string(post_path)
-        .map_err(|e| eyre!("Failed to read {}: {}", post_path.display(), e))?;
-    let (yaml, _body) = frontmatter::split_frontmatter(&content)?;
-    let fm: BlogFrontMatter =
-        serde_yaml::from_str(yaml).map_err(|e| eyre!("Invalid frontmatter: {e}"))?;
-
-    // Filter by `publication` field — only sync posts that belong to this publication.
-    if fm.publication != pub_cfg.key {
-        return Ok(SyncOutcome::Skip);
-    }
-
-    let pub_cid = pub_cfg
-        .at_cid
-        .as_deref()
-        .ok_or_else(|| eyre!("publication at_cid missing"))?;
-    let pub_uri = pub_cfg
-        .at_uri
-        .as_deref()
-        .ok_or_else(|| eyre!("publication at_uri missing"))?;
-    let pub_ref = StrongRef {
-        uri: pub_uri.to_string(),
-        cid: pub_cid.to_string(),
-    };
+    async fn publish(
+        &self,
+        item: &ContentItem,
+        _step: &Step,
+    ) -> cja::Result<Vec<(&'static str, String)>> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| eyre!("Not logged in to Bluesky"))?;
+        let Some(doc) = self.publication_document(item)? else {
+            return Ok(vec![]);
+        };
 
-    let is_historical = fm.date < bsky_post_cutoff();
-    let doc_exists = fm.atproto_uri.is_some();
-    let doc_pinned_to_current_pub = fm.atproto_pub_cid.as_deref() == Some(pub_cid);
-    let bsky_exists = fm.bsky_url.is_some();
+        let pub_cid = doc
+            .publication
+            .at_cid
+            .as_deref()
+            .ok_or_else(|| eyre!("publication at_cid missing"))?;
+        let pub_uri = doc
+            .publication
+            .at_uri
+            .as_deref()
+            .ok_or_else(|| eyre!("publication at_uri missing"))?;
+        let pub_ref = StrongRef {
+            uri: pub_uri.to_string(),
+            cid: pub_cid.to_string(),
+        };
 
-    // What needs doing for this post?
-    // - Document put: any time the doc doesn't exist OR its pinned pub cid has drifted.
-    // - Bsky post: only when the post is recent (>= cutoff) and we don't have a bsky_url.
-    //   When we DO need a bsky post but the doc is already current, we still re-put the
-    //   doc so we have a fresh strong-ref to attach to the bsky post.
-    let need_bsky_post = !bsky_exists && !is_historical;
-    let need_doc_put = !doc_exists || !doc_pinned_to_current_pub || need_bsky_post;
-
-    if !need_doc_put && !need_bsky_post {
-        return Ok(SyncOutcome::Skip);
-    }
-
-    let rel = relative_under(repo_root, &pub_cfg.content_dir, post_path)?;
-    let blog_rkey = rkey_from_blog_path(&rel);
-    let canonical = rel.canonical_path();
-    let post_url = format!("https://coreyja.com/posts/{canonical}");
-    // `path` per spec is the URL path segment relative to the publication URL.
-    // Publication URL is `https://coreyja.com/posts`; the per-post URL is
-    // `https://coreyja.com/posts/<canonical>`. So path is `/<canonical>`.
-    let path = format!("/{canonical}");
-
-    let ast = MarkdownAst::from_str(&content)?;
-    let description: String = ast.0.plain_text().chars().take(100).collect();
-
-    // Re-put the document with the current publication URI. Idempotent on
-    // rkey; produces a fresh doc cid we attach to the bsky post if needed.
-    let record = build_document_record(&fm, pub_uri, path, description.clone());
-    let doc_response = client.put_document(&blog_rkey, record).await?;
-    let doc_ref = StrongRef {
-        uri: doc_response.uri.clone(),
-        cid: doc_response.cid.clone(),
-    };
+        let ast = MarkdownAst::from_str(&item.raw)?;
+        let description: String = ast.0.plain_text().chars().take(100).collect();
 
-    // Conditionally publish a Bluesky post (only for recent posts without one).
-    let bsky_web_url_opt = if need_bsky_post {
-        let bsky_response = client
-            .create_blog_post(
-                &fm.title,
-                &post_url,
-                &description,
-                vec![pub_ref.clone(), doc_ref.clone()],
-            )
-            .await?;
-        Some(at_uri_to_web_url(&bsky_response.uri)?)
-    } else {
-        None
-    };
+        // Re-put the document with the current publication URI. Idempotent on
+        // rkey; produces a fresh doc cid we attach to the bsky post if needed.
+        let record = build_document_record(&doc, pub_uri, description.clone());
+        let doc_response = client.put_document(&doc.rkey, record).await?;
+        let doc_ref = StrongRef {
+            uri: doc_response.uri.clone(),
+            cid: doc_response.cid.clone(),
+        };
 
-    // Frontmatter updates: atproto_uri (if first put), atproto_pub_cid (every
-    // doc put), bsky_url (if we just created a bsky post). We always write
-    // atproto_pub_cid since the doc was just put against the current pub_cid.
-    let mut new_keys: Vec<(&str, &str)> = Vec::new();
-    if !doc_exists {
-        new_keys.push(("atproto_uri", &doc_ref.uri));
-    }
-    new_keys.push(("atproto_pub_cid", pub_cid));
-    let bsky_web_url = bsky_web_url_opt.unwrap_or_default();
-    if !bsky_web_url.is_empty() {
-        new_keys.push(("bsky_url", &bsky_web_url));
-    }
-    let updated = frontmatter::append_frontmatter_keys(&content, &new_keys);
-    write_back(post_path, &updated)?;
-
-    // Outcome reporting: distinguish between "both sides changed" and
-    // "only bsky changed" for the summary printout. A doc-only re-put
-    // (drift refresh) reports as Both since we did write doc-side state.
-    if need_bsky_post && (!doc_exists || !doc_pinned_to_current_pub) {
-        Ok(SyncOutcome::Both)
-    } else if need_bsky_post {
-        Ok(SyncOutcome::BskyOnly)
-    } else {
-        Ok(SyncOutcome::Both)
+        // Frontmatter updates: atproto_uri (if first put), atproto_pub_cid (every
+        // doc put), bsky_url (if we just created a bsky post). We always write
+        // atproto_pub_cid since the doc was just put against the current pub_cid.
+        let mut new_keys = Vec::new();
+        if doc.atproto_uri.is_none() {
+            new_keys.push(("atproto_uri", doc_ref.uri.clone()));
+        }
+        new_keys.push(("atproto_pub_cid", pub_cid.to_string()));
+
+        if doc.plan == SyncPlan::DocumentAndBsky {
+            let thumb = match &doc.og_card {
+                Some(route) => card_thumbnail(client, route).await,
+                None => None,
+            };
+            let bsky_response = client
+                .create_blog_post(
+                    doc.title,
+                    &doc.url(),
+                    &description,
+                    vec![pub_ref, doc_ref],
+                    thumb,
+                )
+                .await?;
+            new_keys.push(("bsky_url", at_uri_to_web_url(&bsky_response.uri)?));
+        }
+
+        Ok(new_keys)
     }
 }
 
 fn build_document_record(
-    fm: &BlogFrontMatter,
+    doc: &PublicationDocument<'_>,
     site: &str,
-    path: String,
     description: String,
 ) -> DocumentRecord {
-    let published_at = fm
+    let published_at = doc
         .date
         .and_hms_opt(0, 0, 0)
         .expect("midnight valid")
@@ -569,22 +636,16 @@ fn build_document_record(
     DocumentRecord {
         record_type: "site.standard.document".to_string(),
         site: site.to_string(),
-        title: fm.title.clone(),
+        title: doc.title.to_string(),
         published_at,
-        path: Some(path),
+        path: Some(doc.path.clone()),
         description: Some(description),
         updated_at: Some(chrono::Utc::now().to_rfc3339()),
-        tags: fm.tags.clone(),
+        tags: doc.tags.to_vec(),
         cover_image: None,
     }
 }
 
-fn write_back(post_path: &Path, content: &str) -> cja::Result<()> {
-    std::fs::write(post_path, content)
-        .map_err(|e| eyre!("Failed to write {}: {}", post_path.display(), e))?;
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -592,6 +653,34 @@ mod tests {
     use posts::blog::Track;
     use std::io::Write;
 
+    /// Recursively walk `root` collecting any file named exactly `index.md`.
+    fn collect_index_md_files(root: &Path) -> cja::Result<Vec<PathBuf>> {
+        let mut out = Vec::new();
+        walk_index_md(root, &mut out)?;
+        out.sort();
+        Ok(out)
+    }
+
+    fn walk_index_md(dir: &Path, out: &mut Vec<PathBuf>) -> cja::Result<()> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| eyre!("Failed to read dir {}: {}", dir.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| eyre!("Failed to read dir entry: {}", e))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| eyre!("Failed to stat {}: {}", path.display(), e))?;
+            if file_type.is_dir() {
+                walk_index_md(&path, out)?;
+            } else if file_type.is_file()
+                && path.file_name().and_then(|s| s.to_str()) == Some("index.md")
+            {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+
     fn sample_publication() -> PublicationConfig {
         PublicationConfig {
             key: "blog".to_string(),
@@ -613,11 +702,18 @@ mod tests {
             track: Track::default(),
             is_newsletter: false,
             bsky_url: None,
+            mastodon_url: None,
+            content_warning: None,
             newsletter_send_at: None,
             buttondown_id: None,
+            buttondown_hash: None,
             og_image: None,
             subtitle: None,
             tags: vec![],
+            series: None,
+            toc: false,
+            draft: false,
+            publish_at: None,
             author: None,
             atproto_uri: None,
             atproto_pub_cid: None,
@@ -646,11 +742,11 @@ mod tests {
     }
 
     #[test]
-    fn publication_cover_imgproxy_url_format() {
-        let out = publication_cover_imgproxy_url(
+    fn og_card_imgproxy_url_format() {
+        let out = og_card_imgproxy_url(
             "https://coreyja.com",
             "https://img.coreyja.com",
-            "blog",
+            "/og/publication/blog.svg",
         );
         assert!(
             out.starts_with("https://img.coreyja.com/unsafe/rs:fill:1200:630/format:png/plain/")
@@ -661,11 +757,11 @@ mod tests {
     }
 
     #[test]
-    fn publication_cover_imgproxy_url_strips_trailing_slashes() {
-        let out = publication_cover_imgproxy_url(
+    fn og_card_imgproxy_url_strips_trailing_slashes() {
+        let out = og_card_imgproxy_url(
             "https://coreyja.com/",
             "https://img.coreyja.com/",
-            "blog",
+            "/og/publication/blog.svg",
         );
         assert!(!out.contains("com//og/"));
         assert!(!out.contains("com//unsafe/"));
@@ -704,26 +800,202 @@ mod tests {
         assert_eq!(loaded.publications[0].at_cid.as_deref(), Some("bafy123"));
     }
 
-    #[test]
-    fn classify_blog_post_skip_when_both_set() {
+    const PUB_CID: &str = "bafypub";
+
+    fn recent_blog_fm() -> BlogFrontMatter {
         let mut fm = sample_blog_fm();
+        fm.date = bsky_post_cutoff();
+        fm
+    }
+
+    #[test]
+    fn plan_sync_skips_when_document_is_current_and_bsky_post_exists() {
+        let mut fm = recent_blog_fm();
         fm.atproto_uri = Some("at://abc".to_string());
+        fm.atproto_pub_cid = Some(PUB_CID.to_string());
         fm.bsky_url = Some("https://bsky.app/x".to_string());
-        assert_eq!(classify_blog_post(&fm), SyncOutcome::Skip);
+        assert_eq!(plan_sync(&fm, PUB_CID), SyncPlan::Skip);
     }
 
     #[test]
-    fn classify_blog_post_bsky_only_when_doc_set() {
-        let mut fm = sample_blog_fm();
+    fn plan_sync_reputs_document_for_bsky_post_when_doc_set() {
+        let mut fm = recent_blog_fm();
         fm.atproto_uri = Some("at://abc".to_string());
-        fm.bsky_url = None;
-        assert_eq!(classify_blog_post(&fm), SyncOutcome::BskyOnly);
+        fm.atproto_pub_cid = Some(PUB_CID.to_string());
+        assert_eq!(plan_sync(&fm, PUB_CID), SyncPlan::DocumentAndBsky);
+    }
+
+    #[test]
+    fn plan_sync_both_when_neither_set() {
+        assert_eq!(
+            plan_sync(&recent_blog_fm(), PUB_CID),
+            SyncPlan::DocumentAndBsky
+        );
     }
 
     #[test]
-    fn classify_blog_post_both_when_neither_set() {
+    fn plan_sync_historical_posts_get_a_document_but_no_bsky_post() {
         let fm = sample_blog_fm();
-        assert_eq!(classify_blog_post(&fm), SyncOutcome::Both);
+        assert_eq!(plan_sync(&fm, PUB_CID), SyncPlan::Document);
+    }
+
+    #[test]
+    fn plan_sync_reputs_document_when_publication_cid_drifts() {
+        let mut fm = sample_blog_fm();
+        fm.atproto_uri = Some("at://abc".to_string());
+        fm.atproto_pub_cid = Some("bafyold".to_string());
+        assert_eq!(plan_sync(&fm, PUB_CID), SyncPlan::Document);
+        fm.atproto_pub_cid = Some(PUB_CID.to_string());
+        assert_eq!(plan_sync(&fm, PUB_CID), SyncPlan::Skip);
+    }
+
+    fn destination(at_cid: Option<&str>) -> StandardSite {
+        let mut publication = sample_publication();
+        publication.at_uri = Some("at://did:plc:abc/site.standard.publication/3xyz".to_string());
+        publication.at_cid = at_cid.map(str::to_string);
+        StandardSite {
+            publications: vec![publication],
+            repo_root: PathBuf::from("/r"),
+            client: None,
+        }
+    }
+
+    fn blog_item(relative: &str, raw: &str) -> ContentItem {
+        ContentItem::parse(
+            Path::new("/r/blog").join(relative),
+            raw.to_string(),
+            posts::source::ContentDir::Blog,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn plan_writes_document_uri_and_pins_publication_cid() {
+        let item = blog_item(
+            "look-ma-no-ai/index.md",
+            "---\ntitle: T\ndate: 2020-01-01\n---\n\nbody\n",
+        );
+        let step = destination(Some(PUB_CID))
+            .plan(&item)
+            .unwrap()
+            .expect("should be planned");
+        assert_eq!(
+            step.writes,
+            vec![
+                PlannedWrite::pending("atproto_uri"),
+                PlannedWrite::known("atproto_pub_cid", PUB_CID),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_ignores_posts_outside_configured_publications() {
+        let other_publication = blog_item(
+            "a/index.md",
+            "---\ntitle: T\ndate: 2020-01-01\npublication: other\n---\n\nbody\n",
+        );
+        let not_index = blog_item(
+            "a/chapter-01.md",
+            "---\ntitle: T\ndate: 2020-01-01\n---\n\nbody\n",
+        );
+        let dest = destination(Some(PUB_CID));
+        assert!(dest.plan(&other_publication).unwrap().is_none());
+        assert!(dest.plan(&not_index).unwrap().is_none());
+    }
+
+    #[test]
+    fn plan_errors_until_the_publication_is_bootstrapped() {
+        let item = blog_item(
+            "a/index.md",
+            "---\ntitle: T\ndate: 2020-01-01\n---\n\nbody\n",
+        );
+        let err = destination(None).plan(&item).unwrap_err();
+        assert!(err.to_string().contains("partially bootstrapped"), "{err}");
+    }
+
+    fn publication_for(key: &str, content_dir: &str) -> PublicationConfig {
+        PublicationConfig {
+            key: key.to_string(),
+            url: format!("https://coreyja.com/{key}"),
+            content_dir: content_dir.to_string(),
+            at_uri: Some(format!("at://did:plc:abc/site.standard.publication/{key}")),
+            at_cid: Some(PUB_CID.to_string()),
+            ..sample_publication()
+        }
+    }
+
+    fn item_in(dir: posts::source::ContentDir, relative: &str, raw: &str) -> ContentItem {
+        ContentItem::parse(
+            Path::new("/r").join(dir.name()).join(relative),
+            raw.to_string(),
+            dir,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn plan_document_sync_reputs_on_publication_cid_drift() {
+        assert_eq!(plan_document_sync(None, None, PUB_CID), SyncPlan::Document);
+        assert_eq!(
+            plan_document_sync(Some("at://abc"), Some("bafyold"), PUB_CID),
+            SyncPlan::Document
+        );
+        assert_eq!(
+            plan_document_sync(Some("at://abc"), Some(PUB_CID), PUB_CID),
+            SyncPlan::Skip
+        );
+    }
+
+    #[test]
+    fn notes_and_episodes_get_documents_in_their_own_publications() {
+        let mut dest = destination(Some(PUB_CID));
+        dest.publications.push(publication_for("notes", "notes"));
+        dest.publications
+            .push(publication_for("podcast", "podcast"));
+
+        let note = item_in(
+            ContentDir::Notes,
+            "hello.md",
+            "---\ntitle: Hello\ndate: 2020-01-01\nslug: hello\n---\n\nbody\n",
+        );
+        let doc = dest.publication_document(&note).unwrap().unwrap();
+        assert_eq!(doc.publication.key, "notes");
+        assert_eq!(doc.rkey, "notes-hello");
+        assert_eq!(doc.url(), "https://coreyja.com/notes/hello");
+        // Notes get their bsky post from the notes destination, never from here
+        let step = dest.plan(&note).unwrap().unwrap();
+        assert_eq!(step.description, "put document notes-hello");
+        assert_eq!(
+            step.writes,
+            vec![
+                PlannedWrite::pending("atproto_uri"),
+                PlannedWrite::known("atproto_pub_cid", PUB_CID),
+            ]
+        );
+
+        let episode = item_in(
+            ContentDir::Podcast,
+            "ep.md",
+            "---\ntitle: Ep\ndate: 2026-06-01\nslug: ep\nyoutube_id: abc\naudio_url: https://example.com/ep.mp3\naudio_length_bytes: 1\naudio_duration: \"00:01:00\"\natproto_uri: at://did:plc:abc/site.standard.document/podcast-ep\natproto_pub_cid: bafyold\n---\n\nShow notes\n",
+        );
+        let doc = dest.publication_document(&episode).unwrap().unwrap();
+        assert_eq!(doc.publication.key, "podcast");
+        assert_eq!(doc.url(), "https://coreyja.com/podcast/ep");
+        let step = dest.plan(&episode).unwrap().unwrap();
+        assert_eq!(
+            step.writes,
+            vec![PlannedWrite::known("atproto_pub_cid", PUB_CID)]
+        );
+    }
+
+    #[test]
+    fn notes_are_skipped_without_a_notes_publication() {
+        let note = item_in(
+            ContentDir::Notes,
+            "hello.md",
+            "---\ntitle: Hello\ndate: 2020-01-01\nslug: hello\n---\n\nbody\n",
+        );
+        assert!(destination(Some(PUB_CID)).plan(&note).unwrap().is_none());
     }
 
     #[test]
@@ -751,22 +1023,6 @@ mod tests {
         assert!(rkey_from_at_uri("at://did:plc:abc/collection").is_err());
     }
 
-    #[test]
-    fn repo_root_for_default_path_returns_dot() {
-        assert_eq!(
-            repo_root_for(Path::new("publications.toml")),
-            PathBuf::from(".")
-        );
-    }
-
-    #[test]
-    fn repo_root_for_nested_path_returns_parent() {
-        assert_eq!(
-            repo_root_for(Path::new("/tmp/repo/publications.toml")),
-            PathBuf::from("/tmp/repo")
-        );
-    }
-
     #[test]
     fn relative_under_strips_repo_and_content_dir() {
         let rel = relative_under(
@@ -797,12 +1053,12 @@ mod tests {
             let Ok(content) = std::fs::read_to_string(path) else {
                 continue;
             };
-            let Ok((yaml, _body)) = frontmatter::split_frontmatter(&content) else {
-                continue;
-            };
-            let Ok(fm): Result<BlogFrontMatter, _> = serde_yaml::from_str(yaml) else {
+            let Ok(item) =
+                ContentItem::parse(path.clone(), content, posts::source::ContentDir::Blog)
+            else {
                 continue;
             };
+            let fm = item.post().unwrap();
             if fm.date < cutoff {
                 continue;
             }
@@ -826,20 +1082,43 @@ mod tests {
         );
     }
 
-    /// Every blog post must produce a unique rkey (since the document record
-    /// is keyed by it). Duplicates would silently overwrite each other.
+    /// Every post, note and episode must produce a unique rkey (since the
+    /// document record is keyed by it). Duplicates would silently overwrite
+    /// each other.
     #[test]
-    fn all_blog_posts_have_unique_rkeys() {
+    fn all_documents_have_unique_rkeys() {
         let blog_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .expect("server has a parent dir")
             .join("blog");
         let posts = collect_index_md_files(&blog_dir).expect("walk blog/");
+        let mut rkeys: Vec<(String, PathBuf)> = posts
+            .iter()
+            .map(|path| {
+                let rel = path.strip_prefix(&blog_dir).unwrap_or(path).to_path_buf();
+                (rkey_from_blog_path(&rel), path.clone())
+            })
+            .collect();
+        // Notes and episodes share the collection, so their rkeys can't collide with posts
+        let source = posts::source::ContentSource::Embedded;
+        for dir in [ContentDir::Notes, ContentDir::Podcast] {
+            for file in source.files(dir).unwrap() {
+                if !file.is_markdown() {
+                    continue;
+                }
+                let raw = String::from_utf8_lossy(file.contents()).into_owned();
+                let item = ContentItem::parse(file.path().to_path_buf(), raw, dir).unwrap();
+                let slug = item.note().map_or_else(
+                    || item.episode().unwrap().slug.clone(),
+                    |fm| fm.slug.clone(),
+                );
+                rkeys.push((rkey_from_slug(dir, &slug), file.path().to_path_buf()));
+            }
+        }
+
         let mut seen: std::collections::HashMap<String, PathBuf> = std::collections::HashMap::new();
         let mut conflicts = Vec::new();
-        for path in &posts {
-            let rel = path.strip_prefix(&blog_dir).unwrap_or(path).to_path_buf();
-            let rkey = rkey_from_blog_path(&rel);
+        for (rkey, path) in &rkeys {
             if let Some(existing) = seen.insert(rkey.clone(), path.clone()) {
                 conflicts.push(format!(
                     "rkey '{rkey}' shared by {} and {}",
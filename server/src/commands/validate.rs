@@ -1,14 +1,54 @@
-use std::println;
+use std::{path::Path, println};
 
-use posts::{blog::BlogPosts, notes::NotePosts, projects::Projects};
+use cja::color_eyre::eyre::Context;
+use posts::{
+    blog::BlogPosts, notes::NotePosts, projects::Projects, tags::near_duplicate_tags, MarkdownAst,
+};
+use rss::validation::Validate;
 use url::Url;
 
 use crate::{
-    http_server::pages::blog::{md::SyntaxHighlightingContext, MyChannel},
+    http_server::{
+        feeds::{Feed, FeedFormat},
+        pages::blog::md::{html::MarkdownRenderContext, IntoHtml, SyntaxHighlightingContext},
+    },
     AppConfig,
 };
 
+/// Render the markdown to HTML, so unsupported nodes or invalid math fail validation
+/// instead of the request that first renders the page
+fn validate_renders(config: &AppConfig, path: &Path, ast: &MarkdownAst) -> cja::Result<()> {
+    let context = MarkdownRenderContext {
+        syntax_highlighting: SyntaxHighlightingContext,
+        current_article_path: String::new(),
+    };
+
+    ast.clone()
+        .into_html(config, &context)
+        .wrap_err_with(|| format!("Failed to render {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Check the RSS against the spec, and that every other format renders
+fn validate_feed(name: &str, feed: &Feed) -> cja::Result<()> {
+    println!("Validating {name} feeds...");
+    feed.to_rss().validate()?;
+    for format in FeedFormat::ALL {
+        feed.render(format)
+            .wrap_err_with(|| format!("Failed to render the {name} {} feed", format.file_name()))?;
+    }
+    println!("{name} feeds Valid! ✅");
+
+    Ok(())
+}
+
 pub(crate) fn validate() -> cja::Result<()> {
+    let config = AppConfig {
+        base_url: Url::parse("http://localhost:3000").unwrap(),
+        imgproxy_url: None,
+    };
+
     let projects = Projects::from_static_dir()?;
     projects.validate()?;
 
@@ -17,32 +57,37 @@ pub(crate) fn validate() -> cja::Result<()> {
     for post in posts.posts() {
         println!("Validating {}...", post.path().display());
         post.validate()?;
+        validate_renders(&config, post.path(), &post.ast)?;
     }
     println!("Posts Valid! ✅");
 
     let notes = NotePosts::from_static_dir()?;
 
     notes.validate()?;
+    for note in notes.by_recency() {
+        validate_renders(&config, &note.path, &note.ast)?;
+    }
 
-    println!("Validating Blog RSS feed...");
-    let config = AppConfig {
-        base_url: Url::parse("http://localhost:3000").unwrap(),
-        imgproxy_url: None,
-    };
-    let render_context = SyntaxHighlightingContext;
-    let rss = MyChannel::from_posts(&config, &render_context, &posts.by_recency())?;
-
-    rss.validate()?;
-
-    println!("Blog RSS Valid! ✅");
-
-    println!("Validating Notes RSS feed...");
-
-    let rss = MyChannel::from_posts(&config, &render_context, &notes.by_recency())?;
-
-    rss.validate()?;
+    println!("Checking for near-duplicate tags...");
+    let tags = posts
+        .posts()
+        .iter()
+        .flat_map(|p| &p.frontmatter.tags)
+        .chain(notes.posts.iter().flat_map(|n| &n.frontmatter.tags))
+        .map(String::as_str);
+    for (a, b) in near_duplicate_tags(tags) {
+        println!("⚠️ Tags \"{a}\" and \"{b}\" look like the same tag");
+    }
 
-    println!("Notes RSS Valid! ✅");
+    let render_context = SyntaxHighlightingContext;
+    validate_feed(
+        "Blog",
+        &Feed::from_posts(&config, &render_context, "/posts", &posts.by_recency())?,
+    )?;
+    validate_feed(
+        "Notes",
+        &Feed::from_posts(&config, &render_context, "/notes", &notes.by_recency())?,
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,123 @@
+//! The parsed posts, notes, podcast episodes and projects the server renders. With
+//! `CONTENT_DIR` set they're read from disk and swapped out whenever the files change, so
+//! writing a post doesn't need a recompile.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use posts::{
+    blog::BlogPosts,
+    notes::NotePosts,
+    podcast::PodcastEpisodes,
+    projects::Projects,
+    search::SearchIndex,
+    source::{Content, ContentSource},
+};
+
+use crate::http_server::pages::search::build_search_index;
+
+/// How often the content directories are checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub(crate) struct LoadedContent {
+    pub blog_posts: Arc<BlogPosts>,
+    pub note_posts: Arc<NotePosts>,
+    pub podcast_episodes: Arc<PodcastEpisodes>,
+    pub projects: Arc<Projects>,
+    pub search_index: Arc<SearchIndex>,
+}
+
+impl LoadedContent {
+    fn new(content: Content) -> cja::Result<Self> {
+        let search_index = build_search_index(
+            &content.blog_posts,
+            &content.note_posts,
+            &content.podcast_episodes,
+            &content.projects,
+        )?;
+
+        Ok(Self {
+            blog_posts: Arc::new(content.blog_posts),
+            note_posts: Arc::new(content.note_posts),
+            podcast_episodes: Arc::new(content.podcast_episodes),
+            projects: Arc::new(content.projects),
+            search_index: Arc::new(search_index),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LiveContent {
+    source: ContentSource,
+    current: Arc<RwLock<Arc<LoadedContent>>>,
+    /// Why the content on disk last failed to load. The last content that did load keeps
+    /// being used, but pages show this error until the files are fixed.
+    error: Arc<RwLock<Option<String>>>,
+}
+
+impl LiveContent {
+    pub(crate) fn load(source: ContentSource) -> cja::Result<Self> {
+        let (loaded, error) = match source.load().and_then(LoadedContent::new) {
+            Ok(loaded) => (loaded, None),
+            // A typo in a post shouldn't stop the dev server from starting, it's shown in the
+            // browser instead
+            Err(e) if matches!(source, ContentSource::Disk(_)) => {
+                tracing::warn!(?e, "Content failed to load");
+                (
+                    LoadedContent::new(Content::empty())?,
+                    Some(format!("{e:#}")),
+                )
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            source,
+            current: Arc::new(RwLock::new(Arc::new(loaded))),
+            error: Arc::new(RwLock::new(error)),
+        })
+    }
+
+    pub(crate) fn source(&self) -> &ContentSource {
+        &self.source
+    }
+
+    pub(crate) fn current(&self) -> Arc<LoadedContent> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub(crate) fn error(&self) -> Option<String> {
+        self.error.read().unwrap().clone()
+    }
+
+    fn update(&self, content: cja::Result<Content>) {
+        match content.and_then(LoadedContent::new) {
+            Ok(loaded) => {
+                tracing::info!("Reloaded content");
+                *self.current.write().unwrap() = Arc::new(loaded);
+                *self.error.write().unwrap() = None;
+            }
+            Err(e) => {
+                tracing::warn!(?e, "Content failed to reload");
+                *self.error.write().unwrap() = Some(format!("{e:#}"));
+            }
+        }
+    }
+
+    /// Reload in the background whenever the content on disk changes. Does nothing for
+    /// embedded content.
+    pub(crate) fn watch(&self) {
+        let live = self.clone();
+        let watching = self
+            .source
+            .watch(WATCH_INTERVAL, move |content| live.update(content))
+            .is_some();
+
+        if watching {
+            tracing::info!(source = ?self.source, "Watching content for changes");
+        }
+    }
+}
@@ -3,10 +3,15 @@ use std::time::Duration;
 use cja::cron::{CronRegistry, Worker};
 
 use crate::{
+    bsky,
     jobs::{
-        refresh_discord::RefreshDiscordChannels, sponsors::RefreshSponsors,
-        youtube_videos::RefreshVideos,
+        activitypub::FederatePosts, bsky_threads::RefreshBskyThreads,
+        expiring_inventory::ExpiringInventoryDigest, mastodon_threads::RefreshMastodonThreads,
+        newsletter_delivery::SendNewsletters, newsletter_stats::RefreshNewsletterStats,
+        refresh_discord::RefreshDiscordChannels, scheduled_publishing::PublishScheduledContent,
+        sponsors::RefreshSponsors, webmentions::SendWebmentions, youtube_videos::RefreshVideos,
     },
+    mastodon,
     state::AppState,
 };
 
@@ -14,12 +19,28 @@ fn one_hour() -> Duration {
     Duration::from_hours(1)
 }
 
+fn one_day() -> Duration {
+    Duration::from_hours(24)
+}
+
 pub(crate) fn cron_registry() -> CronRegistry<AppState> {
     let mut registry = CronRegistry::new();
 
     registry.register_job(RefreshSponsors, None, one_hour());
     registry.register_job(RefreshVideos, None, one_hour());
     registry.register_job(RefreshDiscordChannels, None, one_hour());
+    registry.register_job(ExpiringInventoryDigest { days_ahead: 3 }, None, one_day());
+    registry.register_job(SendWebmentions { days: 30 }, None, one_hour());
+    registry.register_job(RefreshBskyThreads, None, bsky::thread_refresh_interval());
+    registry.register_job(
+        RefreshMastodonThreads,
+        None,
+        mastodon::replies::thread_refresh_interval(),
+    );
+    registry.register_job(PublishScheduledContent, None, Duration::from_mins(5));
+    registry.register_job(RefreshNewsletterStats, None, one_hour());
+    registry.register_job(SendNewsletters, None, Duration::from_mins(5));
+    registry.register_job(FederatePosts { days: 2 }, None, Duration::from_mins(5));
 
     registry
 }
@@ -65,6 +65,29 @@ pub(crate) async fn generate_server_token(
     Ok(token.to_string())
 }
 
+/// Start a `workflow_dispatch` run of one of this repo's workflows on `main`
+pub(crate) async fn dispatch_workflow(config: &GithubConfig, workflow: &str) -> cja::Result<()> {
+    let url = format!(
+        "https://api.github.com/repos/coreyja/coreyja.com/actions/workflows/{workflow}/dispatches"
+    );
+    let client = reqwest::Client::new();
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Accept", "application/vnd.github+json".parse()?);
+    headers.insert("Authorization", format!("Bearer {}", config.pat).parse()?);
+    headers.insert("X-GitHub-Api-Version", "2022-11-28".parse()?);
+    headers.insert("User-Agent", "github.com/coreyja/coreyja.com".parse()?);
+
+    client
+        .post(&url)
+        .headers(headers)
+        .json(&serde_json::json!({ "ref": "main" }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct GithubLink {
     #[allow(clippy::struct_field_names)]
@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use chrono::NaiveTime;
+use cja::color_eyre::eyre::eyre;
+use db::cooking::MealPlanCalendarFeed;
+use maud::html;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{http_server::pages::meal_calendar::feed_path, state::AppState};
+
+use super::super::{
+    auth::session::AdminUser,
+    errors::ServerError,
+    templates::{base_constrained, header::OpenGraph},
+};
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_feeds).post(create_feed))
+        .route("/{id}/times", post(update_times))
+        .route("/{id}/delete", post(delete_feed))
+}
+
+async fn list_feeds(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ServerError> {
+    let feeds = MealPlanCalendarFeed::list(&state.db).await?;
+
+    let content = html! {
+        div class="space-y-6" {
+            div class="flex items-center justify-between" {
+                h2 class="text-2xl font-bold text-gray-900" { "Meal Plan Calendar Feeds" }
+            }
+
+            p class="text-sm text-gray-500" {
+                "Subscribe to a feed URL from any calendar app to see planned meals and prep reminders. "
+                "Anyone with the URL can read the feed, so delete and recreate a feed to revoke it."
+            }
+
+            @for feed in &feeds {
+                div class="bg-white shadow rounded-lg p-6 space-y-4" {
+                    div class="flex items-center justify-between" {
+                        h3 class="text-lg font-medium text-gray-900" { (feed.name) }
+                        form method="post" action=(format!("/admin/calendar-feeds/{}/delete", feed.calendar_feed_id)) {
+                            button type="submit" class="text-red-500 hover:text-red-700 hover:underline" { "Delete" }
+                        }
+                    }
+                    input
+                        type="text"
+                        readonly
+                        value=(state.app.app_url(&feed_path(feed)))
+                        class="block w-full rounded-md border-gray-300 font-mono text-sm";
+
+                    form method="post" action=(format!("/admin/calendar-feeds/{}/times", feed.calendar_feed_id))
+                        class="flex flex-wrap items-end gap-4" {
+                        (time_input("breakfast_time", "Breakfast", feed.breakfast_time))
+                        (time_input("lunch_time", "Lunch", feed.lunch_time))
+                        (time_input("snack_time", "Snack", feed.snack_time))
+                        (time_input("dinner_time", "Dinner", feed.dinner_time))
+                        button
+                            type="submit"
+                            class="px-4 py-2 bg-green-500 text-white rounded hover:bg-green-600" {
+                            "Save times"
+                        }
+                    }
+                }
+            }
+
+            div class="bg-white shadow rounded-lg p-6" {
+                h3 class="text-lg font-medium text-gray-900 mb-4" { "Create New Feed" }
+                form method="post" action="/admin/calendar-feeds" class="flex items-end gap-4" {
+                    div class="flex-1" {
+                        label for="name" class="block text-sm font-medium text-gray-700" { "Name" }
+                        input
+                            type="text"
+                            id="name"
+                            name="name"
+                            class="mt-1 block w-full rounded-md border-gray-300 shadow-sm sm:text-sm"
+                            placeholder="e.g., Family calendar"
+                            required;
+                    }
+                    button
+                        type="submit"
+                        class="px-4 py-2 bg-green-500 text-white rounded hover:bg-green-600" {
+                        "Create"
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(base_constrained(html! { (content) }, OpenGraph::default()))
+}
+
+fn time_input(name: &str, label: &str, value: NaiveTime) -> maud::Markup {
+    html! {
+        div {
+            label for=(name) class="block text-sm font-medium text-gray-700" { (label) }
+            input
+                type="time"
+                id=(name)
+                name=(name)
+                value=(value.format("%H:%M"))
+                class="mt-1 block rounded-md border-gray-300 shadow-sm sm:text-sm"
+                required;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateFeedForm {
+    name: String,
+}
+
+async fn create_feed(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Form(form): Form<CreateFeedForm>,
+) -> Result<Response, ServerError> {
+    if form.name.trim().is_empty() {
+        return Err(ServerError(
+            eyre!("Name must not be empty"),
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    MealPlanCalendarFeed::create(&state.db, form.name.trim().to_string()).await?;
+
+    Ok(Redirect::to("/admin/calendar-feeds").into_response())
+}
+
+#[derive(Deserialize)]
+struct UpdateTimesForm {
+    breakfast_time: String,
+    lunch_time: String,
+    dinner_time: String,
+    snack_time: String,
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, ServerError> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map_err(|_| ServerError(eyre!("Invalid time: {value}"), StatusCode::BAD_REQUEST))
+}
+
+async fn update_times(
+    Path(id): Path<Uuid>,
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Form(form): Form<UpdateTimesForm>,
+) -> Result<Response, ServerError> {
+    MealPlanCalendarFeed::update_times(
+        &state.db,
+        id,
+        parse_time(&form.breakfast_time)?,
+        parse_time(&form.lunch_time)?,
+        parse_time(&form.dinner_time)?,
+        parse_time(&form.snack_time)?,
+    )
+    .await?
+    .ok_or_else(|| ServerError(eyre!("Calendar feed not found"), StatusCode::NOT_FOUND))?;
+
+    Ok(Redirect::to("/admin/calendar-feeds").into_response())
+}
+
+async fn delete_feed(
+    Path(id): Path<Uuid>,
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Result<Response, ServerError> {
+    MealPlanCalendarFeed::delete(&state.db, id).await?;
+
+    Ok(Redirect::to("/admin/calendar-feeds").into_response())
+}
@@ -3,22 +3,27 @@ use std::fmt::Display;
 use axum::{extract::State, response::IntoResponse};
 use maud::{html, Render};
 
+use db::newsletter_stats::{NewsletterStat, NewsletterSubscriberCount};
+
 use crate::state::AppState;
 
 use super::{
     auth::session::AdminUser,
     errors::ServerError,
-    templates::{base_constrained, header::OpenGraph},
+    templates::{base_constrained, header::OpenGraph, newsletter::percent},
 };
 
 pub(crate) mod auth;
+pub(crate) mod calendar_feeds;
 pub(crate) mod crons;
 pub(crate) mod job_routes;
 pub(crate) mod memories;
 pub(crate) mod persona;
+pub(crate) mod preview;
 pub(crate) mod threads;
 pub(crate) mod tool_suggestions;
 
+#[allow(clippy::too_many_lines)]
 pub(crate) async fn dashboard(
     admin: AdminUser,
     State(app_state): State<AppState>,
@@ -58,6 +63,9 @@ pub(crate) async fn dashboard(
     .fetch_optional(&app_state.db)
     .await?;
 
+    let newsletter_stats = NewsletterStat::all(&app_state.db).await?;
+    let subscriber_count = NewsletterSubscriberCount::latest(&app_state.db).await?;
+
     Ok(base_constrained(
         html! {
             h1 class="text-xl" { "Admin Dashboard" }
@@ -68,6 +76,8 @@ pub(crate) async fn dashboard(
                 a href="/admin/tool-suggestions" class="text-blue-500 hover:underline mr-4" { "Tool Suggestions →" }
                 a href="/admin/persona" class="text-blue-500 hover:underline mr-4" { "Persona →" }
                 a href="/admin/memories" class="text-blue-500 hover:underline mr-4" { "Memory Blocks →" }
+                a href="/admin/calendar-feeds" class="text-blue-500 hover:underline mr-4" { "Meal Calendars →" }
+                a href="/admin/preview" class="text-blue-500 hover:underline mr-4" { "Drafts & Scheduled →" }
                 a href="/pace" class="text-blue-500 hover:underline" { "Pace Dashboard →" }
             }
 
@@ -79,6 +89,43 @@ pub(crate) async fn dashboard(
                 }
             }
 
+            h3 class="py-2 text-lg" { "Newsletter" }
+            @if let Some(count) = &subscriber_count {
+                p { (count.subscribers) " subscribers as of " (count.recorded_on) }
+            } @else {
+                p { "No subscriber counts yet" }
+            }
+            @if !newsletter_stats.is_empty() {
+                table class="my-2 text-sm" {
+                    thead {
+                        tr {
+                            th class="text-left pr-4" { "Newsletter" }
+                            th class="text-right pr-4" { "Recipients" }
+                            th class="text-right pr-4" { "Opens" }
+                            th class="text-right" { "Clicks" }
+                        }
+                    }
+                    tbody {
+                        @for stat in &newsletter_stats {
+                            tr {
+                                td class="pr-4" {
+                                    a href=(stat.post_path) class="hover:underline" { (stat.subject) }
+                                }
+                                td class="text-right pr-4" { (stat.recipients) }
+                                td class="text-right pr-4" {
+                                    (stat.opens)
+                                    @if let Some(rate) = stat.open_rate() { " (" (percent(rate)) ")" }
+                                }
+                                td class="text-right" {
+                                    (stat.clicks)
+                                    @if let Some(rate) = stat.click_rate() { " (" (percent(rate)) ")" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
             h3 class="py-2 text-lg" { "Google Auth Status" }
             @if let Some(google_user) = google_user {
                 p { "Local Google User ID: " (google_user.google_user_id) }
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use axum::{extract::State, routing::get, Router};
+use maud::{html, Markup};
+use posts::{blog::BlogPosts, notes::NotePosts, publish::Publishable};
+
+use crate::{
+    http_server::{
+        pages,
+        templates::{base_constrained, header::OpenGraph},
+        LinkTo,
+    },
+    state::AppState,
+};
+
+use super::super::auth::session::AdminUser;
+
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_unpublished))
+        .route("/posts/{*key}", get(pages::blog::post_preview))
+        .route("/notes/{slug}", get(pages::notes::note_preview))
+}
+
+fn status(item: &impl Publishable) -> String {
+    if item.draft() {
+        return "Draft".to_string();
+    }
+
+    match item.publish_at() {
+        Some(at) => format!("Scheduled for {}", at.format("%B %-d, %Y at %H:%M UTC")),
+        None => "Published".to_string(),
+    }
+}
+
+/// Drafts and scheduled posts and notes, linking to their preview pages
+async fn list_unpublished(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    State(blog_posts): State<Arc<BlogPosts>>,
+    State(note_posts): State<Arc<NotePosts>>,
+) -> Markup {
+    let posts: Vec<_> = blog_posts
+        .posts()
+        .iter()
+        .filter(|p| !p.is_published())
+        .map(|p| {
+            (
+                format!("/admin/preview{}", p.relative_link()),
+                p.frontmatter.title.as_str(),
+                status(p),
+            )
+        })
+        .chain(
+            note_posts
+                .posts
+                .iter()
+                .filter(|n| !n.is_published())
+                .map(|n| {
+                    (
+                        format!("/admin/preview{}", n.relative_link()),
+                        n.frontmatter.title.as_str(),
+                        status(n),
+                    )
+                }),
+        )
+        .collect();
+
+    base_constrained(
+        html! {
+            h1 class="text-xl" { "Unpublished Content" }
+
+            @if posts.is_empty() {
+                p class="my-4 text-gray-500" { "No drafts or scheduled posts" }
+            }
+
+            ul {
+                @for (href, title, status) in &posts {
+                    li class="my-4" {
+                        a href=(href) class="hover:underline" { (title) }
+                        span class="text-subtitle text-sm" { " · " (status) }
+                    }
+                }
+            }
+        },
+        OpenGraph::default_for_path(&state.app, "/admin/preview"),
+    )
+}
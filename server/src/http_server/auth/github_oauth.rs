@@ -256,7 +256,7 @@ pub(crate) async fn github_oauth(
         return ResponseResult::Ok(Redirect::temporary(&return_to));
     };
 
-    let projects = app_state.projects.clone();
+    let projects = app_state.projects();
     let project = projects.projects.iter().find(|p| p.slug().unwrap() == app);
     let Some(project) = project else {
         return Err(cja::color_eyre::eyre::eyre!("No project found for {}", app).into());
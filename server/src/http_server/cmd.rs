@@ -1,17 +1,21 @@
 use std::time::Duration;
 
 use axum::extract::{Request, State};
+use axum::http::StatusCode;
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{IntoResponse, Redirect, Response};
 use axum_extra::extract::Host;
 use cja::server::session::Session;
 use cja::Result;
 use cja::{jobs::worker::job_worker, server::run_server};
+use maud::html;
 use serde_json::Map;
 use tokio::task::JoinError;
 use tracing::info;
 
 use crate::http_server::auth::session::DBSession;
+use crate::http_server::templates::{base_constrained, header::OpenGraph};
+use crate::tombstones::Tombstones;
 use crate::tracking;
 use crate::{cron::run_cron, http_server::routes, jobs::Jobs, AppState};
 
@@ -108,6 +112,72 @@ async fn pageview_middleware(
     next.run(request).await
 }
 
+/// While the content on disk fails to parse, pages show why instead of the last content
+/// that loaded. Only ever happens with `CONTENT_DIR` set, as embedded content that doesn't
+/// parse stops the server from starting.
+async fn content_error_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let Some(error) = state.content.error() else {
+        return next.run(request).await;
+    };
+    if IGNORED_PATH_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        return next.run(request).await;
+    }
+
+    let page = base_constrained(
+        html! {
+            h1 class="text-2xl" { "Content failed to load" }
+            p class="my-4" { "Refresh once it's fixed to pick up the change." }
+            pre class="whitespace-pre-wrap text-sm" { (error) }
+        },
+        OpenGraph::default_for_path(&state.app, path),
+    );
+
+    (StatusCode::INTERNAL_SERVER_ERROR, page).into_response()
+}
+
+/// Pages that have been removed answer with `410 Gone`, or a redirect to where they moved,
+/// instead of a 404. Only 404s are replaced, so a tombstone never hides a page that's back.
+async fn tombstone_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    if response.status() != StatusCode::NOT_FOUND {
+        return response;
+    }
+    let Some(tombstone) = Tombstones::embedded().find(&path) else {
+        return response;
+    };
+
+    if let Some(redirect_to) = &tombstone.redirect_to {
+        return Redirect::permanent(redirect_to).into_response();
+    }
+
+    let page = base_constrained(
+        html! {
+            h1 class="text-2xl" { "This page has been removed" }
+            p class="my-4" {
+                "It was taken down on " (tombstone.removed_on.format("%B %-d, %Y")) ". "
+                a href="/" class="underline" { "Head back home" }
+                " for everything that's still here."
+            }
+        },
+        OpenGraph::default_for_path(&state.app, &path),
+    );
+
+    (StatusCode::GONE, page).into_response()
+}
+
 pub(crate) async fn serve() -> Result<()> {
     let discord = crate::discord::setup().await?;
 
@@ -137,6 +207,14 @@ pub(crate) async fn serve() -> Result<()> {
                 app_state.clone(),
                 pageview_middleware,
             ))
+            .layer(axum::middleware::from_fn_with_state(
+                app_state.clone(),
+                tombstone_middleware,
+            ))
+            .layer(axum::middleware::from_fn_with_state(
+                app_state.clone(),
+                content_error_middleware,
+            ))
             .with_state(app_state.clone()),
     ))];
 
@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
 use axum::extract::FromRef;
-use posts::{blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, projects::Projects};
+use posts::{
+    blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, projects::Projects,
+    search::SearchIndex,
+};
 
 use crate::{google::GoogleConfig, twitch::TwitchConfig, AppConfig, AppState};
 
@@ -27,25 +30,31 @@ impl FromRef<AppState> for SyntaxHighlightingContext {
 
 impl FromRef<AppState> for Arc<BlogPosts> {
     fn from_ref(config: &AppState) -> Self {
-        config.blog_posts.clone()
+        config.blog_posts()
     }
 }
 
 impl FromRef<AppState> for Arc<NotePosts> {
     fn from_ref(config: &AppState) -> Self {
-        config.note_posts.clone()
+        config.note_posts()
     }
 }
 
 impl FromRef<AppState> for Arc<PodcastEpisodes> {
     fn from_ref(config: &AppState) -> Self {
-        config.podcast_episodes.clone()
+        config.podcast_episodes()
     }
 }
 
 impl FromRef<AppState> for Arc<Projects> {
     fn from_ref(config: &AppState) -> Self {
-        config.projects.clone()
+        config.projects()
+    }
+}
+
+impl FromRef<AppState> for Arc<SearchIndex> {
+    fn from_ref(config: &AppState) -> Self {
+        config.search_index()
     }
 }
 
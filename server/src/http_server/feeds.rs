@@ -0,0 +1,546 @@
+//! Feeds of posts and notes. Each feed is built once from [`FeedItem`]s and can be served as
+//! RSS 2.0, Atom 1.0 or JSON Feed 1.1, with the format picked from the requested file name.
+
+use std::sync::LazyLock;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveTime, Utc};
+use posts::{date::PostedOn, title::Title, Post};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{
+    http_server::{
+        pages::blog::md::{html::MarkdownRenderContext, IntoHtml, SyntaxHighlightingContext},
+        templates::ShortDesc,
+        LinkTo,
+    },
+    AppConfig,
+};
+
+static PUBLICATIONS_TOML: &str =
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../publications.toml"));
+
+#[derive(serde::Deserialize)]
+struct PublicationsFile {
+    publication: Vec<PublicationStub>,
+}
+
+#[derive(serde::Deserialize)]
+struct PublicationStub {
+    key: String,
+    title: String,
+    description: String,
+}
+
+static PUBLICATIONS: LazyLock<Vec<PublicationStub>> = LazyLock::new(|| {
+    toml::from_str::<PublicationsFile>(PUBLICATIONS_TOML)
+        .expect("publications.toml must parse")
+        .publication
+});
+
+const AUTHOR: &str = "Corey Alexander";
+const SITE_FEED_TITLE: &str = "coreyja";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub const ALL: [Self; 3] = [Self::Rss, Self::Atom, Self::Json];
+
+    /// The last path segment each format is served at, e.g. `/posts/atom.xml`
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Rss => "rss.xml",
+            Self::Atom => "atom.xml",
+            Self::Json => "feed.json",
+        }
+    }
+
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.file_name() == file_name)
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Rss => "application/rss+xml",
+            Self::Atom => "application/atom+xml",
+            Self::Json => "application/feed+json",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Rss => "RSS",
+            Self::Atom => "Atom",
+            Self::Json => "JSON Feed",
+        }
+    }
+}
+
+/// Picks the format from the last segment of the request path, so one handler can be
+/// routed at `rss.xml`, `atom.xml` and `feed.json`
+impl<S: Send + Sync> FromRequestParts<S> for FeedFormat {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .uri
+            .path()
+            .rsplit('/')
+            .next()
+            .and_then(Self::from_file_name)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
+/// A `<link rel="alternate">` pointing at one format of a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FeedLink {
+    pub title: String,
+    pub content_type: &'static str,
+    pub href: String,
+}
+
+/// Alternate links for every format of the feed served under `path`, e.g. `/posts`. The
+/// site-wide feed lives at the root, so its `path` is empty.
+pub(crate) fn feed_links(path: &str, title: &str) -> Vec<FeedLink> {
+    FeedFormat::ALL
+        .into_iter()
+        .map(|format| FeedLink {
+            title: format!("{title} ({})", format.label()),
+            content_type: format.content_type(),
+            href: format!("{path}/{}", format.file_name()),
+        })
+        .collect()
+}
+
+/// The combined feed of posts and notes, which every page links to
+pub(crate) fn site_feed_links() -> Vec<FeedLink> {
+    feed_links("", SITE_FEED_TITLE)
+}
+
+/// Title and description of a publication in `publications.toml`
+fn publication(key: &str) -> Option<&'static PublicationStub> {
+    PUBLICATIONS.iter().find(|p| p.key == key)
+}
+
+pub(crate) fn publication_title(key: &str) -> Option<&'static str> {
+    publication(key).map(|p| p.title.as_str())
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FeedItem {
+    pub title: String,
+    /// Absolute URL, also used as the item's id
+    pub url: String,
+    pub summary: Option<String>,
+    pub content_html: String,
+    pub published: DateTime<Utc>,
+}
+
+pub(crate) trait ToFeedItem {
+    fn to_feed_item(
+        &self,
+        config: &AppConfig,
+        context: &SyntaxHighlightingContext,
+    ) -> cja::Result<FeedItem>;
+}
+
+impl<FrontMatter> ToFeedItem for Post<FrontMatter>
+where
+    FrontMatter: PostedOn + Title,
+    Post<FrontMatter>: LinkTo,
+{
+    fn to_feed_item(
+        &self,
+        config: &AppConfig,
+        context: &SyntaxHighlightingContext,
+    ) -> cja::Result<FeedItem> {
+        let content_html = self
+            .markdown()
+            .ast
+            .0
+            .into_html(
+                config,
+                &MarkdownRenderContext {
+                    syntax_highlighting: context.clone(),
+                    current_article_path: self.relative_link(),
+                },
+            )?
+            .into_string();
+
+        let reading_time = self.reading_stats.label();
+        let summary = match self.short_description() {
+            Some(description) => format!("{description} ({reading_time})"),
+            None => reading_time,
+        };
+
+        Ok(FeedItem {
+            title: self.title().to_string(),
+            url: self.absolute_link(config),
+            summary: Some(summary),
+            content_html,
+            published: self.posted_on().and_time(NaiveTime::MIN).and_utc(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Feed {
+    pub title: String,
+    pub description: Option<String>,
+    /// Absolute URL of the page the feed is for
+    pub home_page_url: String,
+    /// Absolute URL the feed is served under, without the format's file name
+    base_url: String,
+    pub items: Vec<FeedItem>,
+}
+
+impl Feed {
+    /// A feed of `items`, newest first, served under `path` (e.g. `/posts`)
+    pub fn new(config: &AppConfig, path: &str, items: Vec<FeedItem>) -> Self {
+        Self {
+            title: SITE_FEED_TITLE.to_string(),
+            description: None,
+            home_page_url: config.app_url(if path.is_empty() { "/" } else { path }),
+            base_url: config.app_url(path),
+            items,
+        }
+    }
+
+    #[instrument(skip_all)]
+    pub fn from_posts<T>(
+        config: &AppConfig,
+        context: &SyntaxHighlightingContext,
+        path: &str,
+        posts: &[&Post<T>],
+    ) -> cja::Result<Self>
+    where
+        Post<T>: ToFeedItem,
+    {
+        let items = posts
+            .iter()
+            .map(|p| p.to_feed_item(config, context))
+            .collect::<cja::Result<Vec<_>>>()?;
+
+        Ok(Self::new(config, path, items))
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Use the title and description of the `publications.toml` entry with this key, if
+    /// there is one
+    pub fn with_publication(mut self, key: &str) -> Self {
+        if let Some(publication) = publication(key) {
+            self.title.clone_from(&publication.title);
+            self.description = Some(publication.description.clone());
+        }
+        self
+    }
+
+    fn feed_url(&self, format: FeedFormat) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            format.file_name()
+        )
+    }
+
+    pub fn to_rss(&self) -> rss::Channel {
+        let items: Vec<rss::Item> = self
+            .items
+            .iter()
+            .map(|item| {
+                rss::ItemBuilder::default()
+                    .title(Some(item.title.clone()))
+                    .link(Some(item.url.clone()))
+                    .guid(Some(rss::Guid {
+                        value: item.url.clone(),
+                        permalink: true,
+                    }))
+                    .description(item.summary.clone())
+                    .pub_date(Some(item.published.to_rfc2822()))
+                    .content(Some(item.content_html.clone()))
+                    .build()
+            })
+            .collect();
+
+        rss::ChannelBuilder::default()
+            .title(self.title.clone())
+            .link(self.home_page_url.clone())
+            .description(self.description.clone().unwrap_or_default())
+            .copyright(Some(format!("Copyright {AUTHOR}")))
+            .language(Some("en-us".to_string()))
+            .items(items)
+            .build()
+    }
+
+    pub fn to_atom(&self) -> atom_syndication::Feed {
+        use atom_syndication::{Content, Entry, Link, Person, Text};
+
+        let link = |href: String, rel: &str, mime_type: Option<&str>| Link {
+            href,
+            rel: rel.to_string(),
+            mime_type: mime_type.map(str::to_string),
+            ..Link::default()
+        };
+
+        let entries: Vec<Entry> = self
+            .items
+            .iter()
+            .map(|item| Entry {
+                title: Text::plain(item.title.clone()),
+                id: item.url.clone(),
+                updated: item.published.fixed_offset(),
+                published: Some(item.published.fixed_offset()),
+                links: vec![link(item.url.clone(), "alternate", Some("text/html"))],
+                summary: item.summary.clone().map(Text::plain),
+                content: Some(Content {
+                    value: Some(item.content_html.clone()),
+                    content_type: Some("html".to_string()),
+                    ..Content::default()
+                }),
+                ..Entry::default()
+            })
+            .collect();
+
+        atom_syndication::Feed {
+            title: Text::plain(self.title.clone()),
+            id: self.feed_url(FeedFormat::Atom),
+            updated: self
+                .items
+                .iter()
+                .map(|item| item.published)
+                .max()
+                .unwrap_or_default()
+                .fixed_offset(),
+            authors: vec![Person {
+                name: AUTHOR.to_string(),
+                email: None,
+                uri: Some(self.home_page_url.clone()),
+            }],
+            links: vec![
+                link(
+                    self.feed_url(FeedFormat::Atom),
+                    "self",
+                    Some(FeedFormat::Atom.content_type()),
+                ),
+                link(self.home_page_url.clone(), "alternate", Some("text/html")),
+            ],
+            subtitle: self.description.clone().map(Text::plain),
+            lang: Some("en-us".to_string()),
+            entries,
+            ..atom_syndication::Feed::default()
+        }
+    }
+
+    pub fn to_json(&self) -> JsonFeed {
+        JsonFeed {
+            version: "https://jsonfeed.org/version/1.1",
+            title: self.title.clone(),
+            home_page_url: self.home_page_url.clone(),
+            feed_url: self.feed_url(FeedFormat::Json),
+            description: self.description.clone(),
+            language: "en-US",
+            authors: vec![JsonFeedAuthor {
+                name: AUTHOR,
+                url: self.home_page_url.clone(),
+            }],
+            items: self
+                .items
+                .iter()
+                .map(|item| JsonFeedItem {
+                    id: item.url.clone(),
+                    url: item.url.clone(),
+                    title: item.title.clone(),
+                    content_html: item.content_html.clone(),
+                    summary: item.summary.clone(),
+                    date_published: item.published.to_rfc3339(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn render(&self, format: FeedFormat) -> cja::Result<String> {
+        Ok(match format {
+            FeedFormat::Rss => self.to_rss().to_string(),
+            FeedFormat::Atom => self.to_atom().to_string(),
+            FeedFormat::Json => serde_json::to_string_pretty(&self.to_json())?,
+        })
+    }
+
+    pub fn into_response(self, format: FeedFormat) -> Response {
+        match self.render(format) {
+            Ok(body) => ([("Content-Type", format.content_type())], body).into_response(),
+            Err(e) => {
+                let e: super::ServerError = e.wrap_err("Failed to render feed").into();
+                e.into_response()
+            }
+        }
+    }
+}
+
+/// A [JSON Feed 1.1](https://www.jsonfeed.org/version/1.1/) document
+#[derive(Debug, Serialize)]
+pub(crate) struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    language: &'static str,
+    authors: Vec<JsonFeedAuthor>,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedAuthor {
+    name: &'static str,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    date_published: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use url::Url;
+
+    use super::*;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            base_url: Url::parse("https://coreyja.com").unwrap(),
+            imgproxy_url: None,
+        }
+    }
+
+    fn feed() -> Feed {
+        let item = FeedItem {
+            title: "Hello & welcome".to_string(),
+            url: "https://coreyja.com/posts/hello/".to_string(),
+            summary: Some("A first post".to_string()),
+            content_html: "<p>Hi <em>there</em></p>".to_string(),
+            published: NaiveDate::from_ymd_opt(2024, 5, 1)
+                .unwrap()
+                .and_time(NaiveTime::MIN)
+                .and_utc(),
+        };
+
+        Feed::new(&config(), "/posts", vec![item]).with_publication("blog")
+    }
+
+    #[test]
+    fn publication_title_comes_from_publications_toml() {
+        let feed = feed();
+        assert_eq!(feed.title, "coreyja.com");
+        assert!(feed.description.is_some());
+
+        let untitled = Feed::new(&config(), "", vec![])
+            .with_title("coreyja Notes")
+            .with_publication("not-a-publication");
+        assert_eq!(untitled.title, "coreyja Notes");
+        assert_eq!(untitled.home_page_url, "https://coreyja.com/");
+    }
+
+    #[test]
+    fn renders_valid_rss() {
+        use rss::validation::Validate;
+
+        let channel = feed().to_rss();
+        channel.validate().unwrap();
+        assert_eq!(channel.title(), "coreyja.com");
+        assert_eq!(
+            channel.items()[0].link(),
+            Some("https://coreyja.com/posts/hello/")
+        );
+    }
+
+    #[test]
+    fn renders_atom_that_round_trips() {
+        let xml = feed().render(FeedFormat::Atom).unwrap();
+        let parsed: atom_syndication::Feed = xml.parse().unwrap();
+
+        assert_eq!(parsed.title().as_str(), "coreyja.com");
+        assert_eq!(parsed.id(), "https://coreyja.com/posts/atom.xml");
+        assert_eq!(parsed.updated().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+
+        let entry = &parsed.entries()[0];
+        assert_eq!(entry.title().as_str(), "Hello & welcome");
+        assert_eq!(entry.links()[0].href(), "https://coreyja.com/posts/hello/");
+        assert_eq!(
+            entry.content().and_then(|c| c.value()),
+            Some("<p>Hi <em>there</em></p>")
+        );
+    }
+
+    #[test]
+    fn renders_json_feed_1_1() {
+        let json: serde_json::Value =
+            serde_json::from_str(&feed().render(FeedFormat::Json).unwrap()).unwrap();
+
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(json["feed_url"], "https://coreyja.com/posts/feed.json");
+        assert_eq!(json["home_page_url"], "https://coreyja.com/posts");
+        assert_eq!(json["items"][0]["id"], "https://coreyja.com/posts/hello/");
+        assert_eq!(
+            json["items"][0]["date_published"],
+            "2024-05-01T00:00:00+00:00"
+        );
+        assert_eq!(json["items"][0]["content_html"], "<p>Hi <em>there</em></p>");
+    }
+
+    #[tokio::test]
+    async fn feed_routes_serve_every_format() {
+        use axum::{body::Body, http::Request};
+        use tower::ServiceExt;
+
+        for (uri, content_type) in [
+            ("/posts/feed.json", "application/feed+json"),
+            ("/notes/atom.xml", "application/atom+xml"),
+            ("/rss.xml", "application/rss+xml"),
+        ] {
+            let app = crate::http_server::test_helpers::create_test_app().await;
+            let resp = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK, "{uri}");
+            assert_eq!(resp.headers()["content-type"], content_type, "{uri}");
+        }
+    }
+
+    #[test]
+    fn formats_round_trip_through_file_names() {
+        for format in FeedFormat::ALL {
+            assert_eq!(FeedFormat::from_file_name(format.file_name()), Some(format));
+        }
+        assert_eq!(FeedFormat::from_file_name("feed.xml"), None);
+
+        let links = feed_links("/notes", "coreyja Notes");
+        assert_eq!(links[1].href, "/notes/atom.xml");
+        assert_eq!(links[1].title, "coreyja Notes (Atom)");
+    }
+}
@@ -5,29 +5,23 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use chrono::{DateTime, NaiveTime, Utc};
 use include_dir::{include_dir, Dir};
 
 use posts::{
     blog::{BlogPost, ToCanonicalPath},
-    date::PostedOn,
     notes::NotePost,
     podcast::PodcastEpisode,
-    title::Title,
-    Post,
 };
 use std::sync::Arc;
 
 use crate::{AppConfig, AppState, Result};
 use errors::ServerError;
 
-use self::{
-    pages::blog::md::{html::MarkdownRenderContext, IntoHtml, SyntaxHighlightingContext},
-    templates::ShortDesc,
-};
+use self::pages::blog::md::html::MarkdownRenderContext;
 
 pub(crate) mod cmd;
 pub(crate) mod components;
+pub(crate) mod feeds;
 
 pub(crate) mod pages;
 
@@ -78,48 +72,3 @@ impl LinkTo for PodcastEpisode {
         format!("/podcast/{}", self.frontmatter.slug)
     }
 }
-
-pub(crate) trait ToRssItem {
-    fn to_rss_item(
-        &self,
-        config: &AppConfig,
-        context: &SyntaxHighlightingContext,
-    ) -> Result<rss::Item>;
-}
-
-impl<FrontMatter> ToRssItem for Post<FrontMatter>
-where
-    FrontMatter: PostedOn + Title,
-    Post<FrontMatter>: LinkTo,
-{
-    fn to_rss_item(
-        &self,
-        config: &AppConfig,
-        context: &SyntaxHighlightingContext,
-    ) -> Result<rss::Item> {
-        let link = self.absolute_link(config);
-
-        let posted_on: DateTime<Utc> = self.posted_on().and_time(NaiveTime::MIN).and_utc();
-        let formatted_date = posted_on.to_rfc2822();
-
-        Ok(rss::ItemBuilder::default()
-            .title(Some(self.title().to_string()))
-            .link(Some(link))
-            .description(self.short_description())
-            .pub_date(Some(formatted_date))
-            .content(Some(
-                self.markdown()
-                    .ast
-                    .0
-                    .into_html(
-                        config,
-                        &MarkdownRenderContext {
-                            syntax_highlighting: context.clone(),
-                            current_article_path: self.relative_link(),
-                        },
-                    )?
-                    .into_string(),
-            ))
-            .build())
-    }
-}
@@ -0,0 +1,171 @@
+//! The documents that make the site an ActivityPub actor: the actor itself, its outbox and
+//! followers, and each post and note as an object. All of them 404 while federation is off.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use cja::color_eyre::eyre::eyre;
+use db::activitypub::ActivityPubFollower;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    activitypub::{
+        actor_document, create_activity, published_objects, ActivityPubConfig,
+        ACTIVITY_CONTENT_TYPE, FOLLOWERS_PATH, OUTBOX_PATH,
+    },
+    http_server::{errors::ServerError, ResponseResult},
+    AppState,
+};
+
+pub(crate) fn activity_json(document: &JsonValue) -> Response {
+    (
+        [(header::CONTENT_TYPE, ACTIVITY_CONTENT_TYPE)],
+        document.to_string(),
+    )
+        .into_response()
+}
+
+fn not_found(what: &str) -> ServerError {
+    ServerError(eyre!("{what} not found"), StatusCode::NOT_FOUND)
+}
+
+pub(crate) fn federation_config(state: &AppState) -> ResponseResult<&ActivityPubConfig> {
+    state
+        .activitypub
+        .as_ref()
+        .ok_or_else(|| not_found("ActivityPub actor"))
+}
+
+pub(crate) async fn actor(State(state): State<AppState>) -> ResponseResult<Response> {
+    let config = federation_config(&state)?;
+
+    Ok(activity_json(&actor_document(
+        &state.app,
+        &config.public_key_pem,
+    )))
+}
+
+/// Every published post and note, newest first, as the `Create` activities that federated
+/// them
+pub(crate) async fn outbox(State(state): State<AppState>) -> ResponseResult<Response> {
+    federation_config(&state)?;
+
+    let activities: Vec<JsonValue> = published_objects(&state)?
+        .into_iter()
+        .map(|published| create_activity(&state.app, published.object))
+        .collect();
+
+    Ok(activity_json(&json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": state.app.app_url(OUTBOX_PATH),
+        "type": "OrderedCollection",
+        "totalItems": activities.len(),
+        "orderedItems": activities,
+    })))
+}
+
+/// How many followers there are, without saying who they are
+pub(crate) async fn followers(State(state): State<AppState>) -> ResponseResult<Response> {
+    federation_config(&state)?;
+
+    let count = ActivityPubFollower::all(&state.db).await?.len();
+
+    Ok(activity_json(&json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": state.app.app_url(FOLLOWERS_PATH),
+        "type": "OrderedCollection",
+        "totalItems": count,
+    })))
+}
+
+pub(crate) async fn object(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+) -> ResponseResult<Response> {
+    federation_config(&state)?;
+
+    let path = format!("/{}", path.trim_matches('/'));
+    let published = published_objects(&state)?
+        .into_iter()
+        .find(|published| published.path.trim_end_matches('/') == path)
+        .ok_or_else(|| not_found("Object"))?;
+
+    let mut object = published.object;
+    object["@context"] = json!("https://www.w3.org/ns/activitystreams");
+
+    Ok(activity_json(&object))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, Router};
+    use rsa::RsaPrivateKey;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::http_server::test_helpers::{create_test_app, create_test_state};
+
+    use super::*;
+
+    async fn federated_app(pool: PgPool) -> Router {
+        let mut state = create_test_state(pool).await;
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        state.activitypub = Some(ActivityPubConfig::new(key).unwrap());
+
+        crate::http_server::routes::make_router().with_state(state)
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, JsonValue) {
+        let resp = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn the_actor_is_hidden_while_federation_is_off() {
+        let (status, _) = get_json(create_test_app().await, "/ap/actor").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn the_actor_publishes_its_key(pool: PgPool) {
+        let (status, actor) = get_json(federated_app(pool).await, "/ap/actor").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(actor["id"], "http://localhost:3000/ap/actor");
+        assert_eq!(actor["inbox"], "http://localhost:3000/ap/inbox");
+        assert_eq!(
+            actor["publicKey"]["id"],
+            "http://localhost:3000/ap/actor#main-key"
+        );
+        assert!(actor["publicKey"]["publicKeyPem"]
+            .as_str()
+            .unwrap()
+            .starts_with("-----BEGIN PUBLIC KEY-----"));
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn posts_are_served_as_articles(pool: PgPool) {
+        let app = federated_app(pool).await;
+
+        let (status, object) =
+            get_json(app.clone(), "/ap/objects/posts/vim-fzf-with-devicons/").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(object["type"], "Article");
+        assert_eq!(
+            object["url"],
+            "http://localhost:3000/posts/vim-fzf-with-devicons/"
+        );
+
+        let (status, _) = get_json(app, "/ap/objects/posts/not-a-post/").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
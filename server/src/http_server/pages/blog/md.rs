@@ -3,5 +3,8 @@ pub mod html;
 pub(crate) use html::IntoHtml;
 pub(crate) use html::SyntaxHighlightingContext;
 
+mod email;
+pub(crate) use email::IntoEmailHtml;
+
 mod plain;
 pub(crate) use plain::IntoPlainText;
@@ -0,0 +1,512 @@
+//! Markdown rendered for email instead of the site. Mail clients drop stylesheets and
+//! classes, so everything here is styled inline, links and images are absolute, and code
+//! is highlighted with inline colors.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use color_eyre::Result;
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use markdown::mdast::{
+    AlignKind, Blockquote, Code, Delete, Emphasis, Heading, Image, InlineCode, Link, List,
+    ListItem, Math, Node, Paragraph, Root, Strong, Table, TableCell, TableRow, ThematicBreak,
+};
+use maud::{html, Markup, PreEscaped};
+use regex::{Captures, Regex};
+use url::Url;
+
+use crate::AppConfig;
+
+use super::html::{
+    generate_imgproxy_url, highlight_code, IntoHtml, MarkdownRenderContext, References,
+};
+
+const LINK_STYLE: &str = "color: #6c4fbf;";
+const BLOCK_STYLE: &str = "margin: 16px 0;";
+const LIST_STYLE: &str = "margin: 16px 0; padding-left: 24px;";
+const BLOCKQUOTE_STYLE: &str =
+    "margin: 16px 0; padding: 0 16px; border-left: 4px solid #dddddd; color: #555555;";
+const INLINE_CODE_STYLE: &str = "font-family: Menlo, Consolas, monospace; font-size: 14px; background-color: #f3f0fa; padding: 2px 4px; border-radius: 3px;";
+const PRE_STYLE: &str = "margin: 16px 0; padding: 16px; background-color: #231c2e; color: #f0f0ff; font-family: Menlo, Consolas, monospace; font-size: 14px; line-height: 1.5; overflow-x: auto; white-space: pre; border-radius: 4px;";
+const IMAGE_STYLE: &str = "display: block; max-width: 100%; height: auto; margin: 0 auto;";
+const HR_STYLE: &str = "border: none; border-top: 1px solid #dddddd; margin: 32px 0;";
+const TABLE_STYLE: &str = "width: 100%; border-collapse: collapse; margin: 16px 0;";
+const TH_STYLE: &str = "padding: 10px 16px; font-weight: 700; border-bottom: 2px solid #121131;";
+const TD_STYLE: &str = "padding: 8px 16px; border-bottom: 1px solid #e7e7ea;";
+const STRIPED_ROW_COLOR: &str = "#f6f6fe";
+
+/// Arborium wraps highlighted tokens in custom elements (`<a-k>`) which the site colors
+/// with `arborium.css`. Emails get the same colors, read out of that stylesheet so the
+/// two can't drift apart.
+static HIGHLIGHT_STYLES: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    let rule = Regex::new(r"(?m)^(a-[a-z]+)\s*\{\s*([^}]*?)\s*\}").unwrap();
+
+    rule.captures_iter(include_str!("../../../../styles/arborium.css"))
+        .map(|c| (c[1].to_owned(), c[2].to_owned()))
+        .collect()
+});
+
+static HIGHLIGHT_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<(/?)(a-[a-z]+)>").unwrap());
+
+/// Swap arborium's custom elements for `span`s with the colors inline
+fn inline_highlight_styles(highlighted: &str) -> String {
+    HIGHLIGHT_TAG
+        .replace_all(highlighted, |c: &Captures| {
+            if &c[1] == "/" {
+                return "</span>".to_owned();
+            }
+            match HIGHLIGHT_STYLES.get(&c[2]) {
+                Some(style) => format!("<span style=\"{style}\">"),
+                None => "<span>".to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+/// Resolve a URL from the markdown the way a browser would on the page it's from, so
+/// relative links and images still work from someone's inbox
+fn absolute_url(url: &str, config: &AppConfig, context: &MarkdownRenderContext) -> String {
+    let page = config.app_url(&format!(
+        "{}/",
+        context.current_article_path.trim_end_matches('/')
+    ));
+
+    Url::parse(&page)
+        .and_then(|page| page.join(url))
+        .map_or_else(|_| url.to_owned(), String::from)
+}
+
+fn text_align(align: Option<&AlignKind>) -> &'static str {
+    match align {
+        Some(AlignKind::Right) => "right",
+        Some(AlignKind::Center) => "center",
+        Some(AlignKind::Left | AlignKind::None) | None => "left",
+    }
+}
+
+pub(crate) trait IntoEmailHtml {
+    fn into_email_html(self, config: &AppConfig, context: &MarkdownRenderContext)
+        -> Result<Markup>;
+}
+
+impl IntoEmailHtml for Root {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let mut references =
+            References::collect(&self.children, context).with_inline_link_style(LINK_STYLE);
+        let children = references.resolve(self.children);
+        let footnotes = references.footnotes_in_order();
+
+        Ok(html! {
+            (children.into_email_html(config, context)?)
+            @if !footnotes.is_empty() {
+                hr style=(HR_STYLE);
+                ol style=(format!("{LIST_STYLE} font-size: 14px;")) {
+                    @for (number, definition) in footnotes {
+                        li id=(references.footnote_id(number)) {
+                            (definition.children.into_email_html(config, context)?)
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl IntoEmailHtml for Node {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        match self {
+            Node::Root(r) => r.into_email_html(config, context),
+            Node::Blockquote(x) => x.into_email_html(config, context),
+            Node::List(l) => l.into_email_html(config, context),
+            Node::ListItem(i) => i.into_email_html(config, context),
+            Node::InlineCode(c) => c.into_email_html(config, context),
+            Node::Delete(d) => d.into_email_html(config, context),
+            Node::Emphasis(e) => e.into_email_html(config, context),
+            Node::Image(i) => i.into_email_html(config, context),
+            Node::Link(l) => l.into_email_html(config, context),
+            Node::Strong(s) => s.into_email_html(config, context),
+            Node::Code(c) => c.into_email_html(config, context),
+            Node::Heading(h) => h.into_email_html(config, context),
+            Node::Table(t) => t.into_email_html(config, context),
+            Node::Paragraph(p) => p.into_email_html(config, context),
+            Node::ThematicBreak(b) => b.into_email_html(config, context),
+            Node::Math(m) => m.into_email_html(config, context),
+            Node::LinkReference(r) => r.children.into_email_html(config, context),
+            // Defined footnotes were already numbered into links by `References`, with
+            // `LINK_STYLE` inline, so these are the ones without a definition
+            Node::FootnoteReference(r) => Ok(html! {
+                sup { "[" (r.label.unwrap_or(r.identifier)) "]" }
+            }),
+            // Everything else has no styling to inline, so renders the same as on the site
+            other => other.into_html(config, context),
+        }
+    }
+}
+
+impl IntoEmailHtml for Vec<Node> {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            @for node in self {
+                (node.into_email_html(config, context)?)
+            }
+        })
+    }
+}
+
+impl IntoEmailHtml for Paragraph {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            p style=(BLOCK_STYLE) { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+/// Headings don't get anchors in email, there's no page to link into
+impl IntoEmailHtml for Heading {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let content = self.children.into_email_html(config, context)?;
+        let style =
+            |size: u32| format!("margin: 32px 0 16px; font-size: {size}px; line-height: 1.3;");
+
+        Ok(html! {
+            @match self.depth {
+                1 => h1 style=(style(26)) { (content) },
+                2 => h2 style=(style(22)) { (content) },
+                3 => h3 style=(style(18)) { (content) },
+                4 => h4 style=(style(16)) { (content) },
+                5 => h5 style=(style(16)) { (content) },
+                _ => h6 style=(style(14)) { (content) },
+            }
+        })
+    }
+}
+
+impl IntoEmailHtml for List {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let inner = self.children.into_email_html(config, context)?;
+
+        Ok(html! {
+            @if self.ordered {
+                ol style=(LIST_STYLE) { (inner) }
+            } @else {
+                ul style=(LIST_STYLE) { (inner) }
+            }
+        })
+    }
+}
+
+impl IntoEmailHtml for ListItem {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        // Tight list items hold a single paragraph, which shouldn't add margins
+        let mut children = self.children;
+        if let [Node::Paragraph(p)] = children.as_mut_slice() {
+            children = std::mem::take(&mut p.children);
+        }
+        let children = children.into_email_html(config, context)?;
+
+        Ok(html! {
+            li style="margin: 4px 0;" { (children) }
+        })
+    }
+}
+
+impl IntoEmailHtml for Blockquote {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            blockquote style=(BLOCKQUOTE_STYLE) { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+impl IntoEmailHtml for InlineCode {
+    fn into_email_html(
+        self,
+        _config: &AppConfig,
+        _context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            code style=(INLINE_CODE_STYLE) { (self.value) }
+        })
+    }
+}
+
+impl IntoEmailHtml for Code {
+    fn into_email_html(
+        self,
+        _config: &AppConfig,
+        _context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let highlighted = inline_highlight_styles(&highlight_code(&self));
+
+        Ok(html! {
+            pre style=(PRE_STYLE) { code { (PreEscaped(highlighted)) } }
+        })
+    }
+}
+
+impl IntoEmailHtml for Delete {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            del { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+impl IntoEmailHtml for Emphasis {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            em { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+impl IntoEmailHtml for Strong {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            strong { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+impl IntoEmailHtml for Link {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let href = absolute_url(&self.url, config, context);
+
+        Ok(html! {
+            a href=(href) title=[self.title] style=(LINK_STYLE) { (self.children.into_email_html(config, context)?) }
+        })
+    }
+}
+
+/// Images go through imgproxy when it's configured, sized for the email's single column.
+/// Mail clients ignore `srcset`, so there's only the one size.
+impl IntoEmailHtml for Image {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let url = absolute_url(&self.url, config, context);
+        let src = match config.imgproxy_url.as_ref() {
+            Some(imgproxy_base) => generate_imgproxy_url(imgproxy_base, &url, 1200),
+            None => url,
+        };
+
+        Ok(html! {
+            div style="margin: 24px 0; text-align: center;" {
+                img src=(src) alt=(self.alt) title=[self.title] width="592" style=(IMAGE_STYLE);
+                @if !self.alt.is_empty() {
+                    p style="margin: 4px 0 0; font-size: 14px; font-style: italic; color: #666666;" { (self.alt) }
+                }
+            }
+        })
+    }
+}
+
+/// Tables keep the presentational attributes older clients (Outlook) look at, alongside
+/// the inline styles everyone else uses
+impl IntoEmailHtml for Table {
+    fn into_email_html(
+        self,
+        config: &AppConfig,
+        context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let align = self.align;
+        let mut rows = self.children.into_iter().filter_map(|row| match row {
+            Node::TableRow(row) => Some(row),
+            _ => None,
+        });
+        let header_row = rows.next();
+
+        Ok(html! {
+            table width="100%" cellpadding="0" cellspacing="0" border="0" style=(TABLE_STYLE) {
+                @if let Some(row) = header_row {
+                    thead {
+                        (render_table_row(row, true, &align, None, config, context)?)
+                    }
+                }
+                tbody {
+                    @for (i, row) in rows.enumerate() {
+                        @let background = (i % 2 == 1).then_some(STRIPED_ROW_COLOR);
+                        (render_table_row(row, false, &align, background, config, context)?)
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn render_table_row(
+    row: TableRow,
+    header: bool,
+    align: &[AlignKind],
+    background: Option<&str>,
+    config: &AppConfig,
+    context: &MarkdownRenderContext,
+) -> Result<Markup> {
+    let cells = row.children.into_iter().filter_map(|cell| match cell {
+        Node::TableCell(cell) => Some(cell),
+        _ => None,
+    });
+
+    Ok(html! {
+        tr bgcolor=[background] {
+            @for (i, cell) in cells.enumerate() {
+                (render_table_cell(cell, header, align.get(i), config, context)?)
+            }
+        }
+    })
+}
+
+fn render_table_cell(
+    cell: TableCell,
+    header: bool,
+    align: Option<&AlignKind>,
+    config: &AppConfig,
+    context: &MarkdownRenderContext,
+) -> Result<Markup> {
+    let align = text_align(align);
+    let content = cell.children.into_email_html(config, context)?;
+
+    Ok(if header {
+        html! { th align=(align) style=(format!("{TH_STYLE} text-align: {align};")) { (content) } }
+    } else {
+        html! { td align=(align) style=(format!("{TD_STYLE} text-align: {align};")) { (content) } }
+    })
+}
+
+impl IntoEmailHtml for Math {
+    fn into_email_html(
+        self,
+        _config: &AppConfig,
+        _context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        let mathml = latex_to_mathml(&self.value, DisplayStyle::Block)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid math block `{}`: {}", self.value, e))?;
+
+        Ok(html! {
+            div style=(format!("{BLOCK_STYLE} overflow-x: auto;")) { (PreEscaped(mathml)) }
+        })
+    }
+}
+
+impl IntoEmailHtml for ThematicBreak {
+    fn into_email_html(
+        self,
+        _config: &AppConfig,
+        _context: &MarkdownRenderContext,
+    ) -> Result<Markup> {
+        Ok(html! {
+            hr style=(HR_STYLE);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use posts::MarkdownAst;
+
+    use super::*;
+    use crate::http_server::pages::blog::md::SyntaxHighlightingContext;
+
+    fn render(markdown: &str) -> Result<String> {
+        let config = AppConfig {
+            base_url: Url::parse("https://coreyja.com").unwrap(),
+            imgproxy_url: None,
+        };
+        let context = MarkdownRenderContext {
+            syntax_highlighting: SyntaxHighlightingContext,
+            current_article_path: "/posts/example/".to_string(),
+        };
+
+        Ok(MarkdownAst::from_str(markdown)?
+            .0
+            .into_email_html(&config, &context)?
+            .into_string())
+    }
+
+    #[test]
+    fn highlight_elements_become_styled_spans() {
+        assert_eq!(
+            inline_highlight_styles("<a-c>// hi</a-c> <a-zz>x</a-zz>"),
+            r#"<span style="color: #6c7086; font-style: italic;">// hi</span> <span>x</span>"#
+        );
+    }
+
+    #[test]
+    fn relative_and_fragment_links_resolve_against_the_post() {
+        let html = render("[next](../other) and [below](#setup)\n").unwrap();
+
+        assert!(html.contains(r#"href="https://coreyja.com/posts/other""#));
+        assert!(html.contains(r##"href="https://coreyja.com/posts/example/#setup""##));
+    }
+
+    #[test]
+    fn lists_and_footnotes_render_without_classes() {
+        let html = render("- done\n- todo\n\nA note[^a].\n\n[^a]: The note\n").unwrap();
+
+        assert!(html.contains(r#"<li style="margin: 4px 0;">done</li>"#));
+        assert!(html.contains(
+            r#"<li id="fn-posts-example-1"><p style="margin: 16px 0;">The note</p></li>"#
+        ));
+    }
+
+    #[test]
+    fn footnote_references_are_styled_inline() {
+        let html = render("A note[^a].\n\n[^a]: The note\n").unwrap();
+
+        assert!(html.contains(
+            r##"<sup id="fnref-posts-example-1"><a href="#fn-posts-example-1" style="color: #6c4fbf;" aria-describedby="footnotes-posts-example">1</a></sup>"##
+        ));
+        assert!(!html.contains("class="));
+    }
+}
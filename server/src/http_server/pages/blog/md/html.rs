@@ -1,12 +1,16 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::unreachable;
 
+use latex2mathml::{latex_to_mathml, DisplayStyle};
 use markdown::mdast::{
-    AlignKind, Blockquote, Break, Code, Delete, Emphasis, Heading, Html, Image, InlineCode, Link,
-    List, ListItem, Node, Paragraph, Root, Strong, Table, TableCell, TableRow, Text, ThematicBreak,
-    Yaml,
+    AlignKind, Blockquote, Break, Code, Definition, Delete, Emphasis, FootnoteDefinition,
+    FootnoteReference, Heading, Html, Image, ImageReference, InlineCode, InlineMath, Link,
+    LinkReference, List, ListItem, Math, Node, Paragraph, Root, Strong, Table, TableCell, TableRow,
+    Text, ThematicBreak, Toml, Yaml,
 };
 use maud::{html, Markup, PreEscaped};
+use posts::headings::{heading_text, slugify, Slugger};
 use url::Url;
 
 use color_eyre::Result;
@@ -15,7 +19,7 @@ use crate::AppConfig;
 
 use urlencoding::encode;
 
-fn generate_imgproxy_url(base_url: &str, image_url: &str, width: u32) -> String {
+pub(super) fn generate_imgproxy_url(base_url: &str, image_url: &str, width: u32) -> String {
     format!(
         "{}/unsafe/rs:fit:{width}:0/plain/{}",
         base_url,
@@ -40,10 +44,276 @@ pub(crate) trait IntoHtml {
 
 impl IntoHtml for Root {
     fn into_html(self, config: &AppConfig, context: &MarkdownRenderContext) -> Result<Markup> {
+        let mut references = References::collect(&self.children, context);
+        let children = references.resolve(self.children);
+        let children = anchor_headings(children, &mut Slugger::default(), config, context)?;
+        let footnotes = references.footnotes_in_order();
+
         Ok(html! {
-            (self.children.into_html(config, context)?)
+            (children.into_html(config, context)?)
+            @if !footnotes.is_empty() {
+                section id=(references.footnotes_id()) class="my-8 max-w-prose text-sm" {
+                    hr class="my-8 opacity-20";
+                    ol {
+                        @for (number, definition) in footnotes {
+                            li id=(references.footnote_id(number)) {
+                                (footnote_with_backlink(definition, &references.reference_id(number)).into_html(config, context)?)
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Document-wide state needed to render references: link definitions, and footnotes
+/// numbered in the order they are first referenced.
+///
+/// Definitions can appear anywhere in a document (usually the end), so they are collected
+/// up front and then [`References::resolve`] rewrites the tree, replacing link and image
+/// references with plain links and images, footnote references with numbered superscripts,
+/// and dropping the definitions themselves.
+#[derive(Default)]
+pub(super) struct References {
+    definitions: HashMap<String, Definition>,
+    footnote_definitions: HashMap<String, FootnoteDefinition>,
+    footnote_numbers: HashMap<String, usize>,
+    footnote_reference_counts: HashMap<String, usize>,
+    /// Inline style for footnote reference links in place of the site's classes, for
+    /// renderers without the stylesheet
+    inline_link_style: Option<&'static str>,
+    /// The page's path as a slug, added to footnote ids so they stay unique when several
+    /// posts render on one page, like the notes index and feeds
+    footnote_scope: String,
+}
+
+impl References {
+    pub(super) fn collect(nodes: &[Node], context: &MarkdownRenderContext) -> Self {
+        let mut references = Self {
+            footnote_scope: context
+                .current_article_path
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(slugify)
+                .collect::<Vec<_>>()
+                .join("-"),
+            ..Self::default()
+        };
+        references.collect_from(nodes);
+        references
+    }
+
+    fn scoped_id(&self, kind: &str, number: usize) -> String {
+        if self.footnote_scope.is_empty() {
+            format!("{kind}-{number}")
+        } else {
+            format!("{kind}-{}-{number}", self.footnote_scope)
+        }
+    }
+
+    /// Id of the footnote itself, that references link to
+    pub(super) fn footnote_id(&self, number: usize) -> String {
+        self.scoped_id("fn", number)
+    }
+
+    /// Id of the first reference to a footnote, that its backlink goes back to
+    pub(super) fn reference_id(&self, number: usize) -> String {
+        self.scoped_id("fnref", number)
+    }
+
+    /// Id of the section holding the footnotes
+    pub(super) fn footnotes_id(&self) -> String {
+        if self.footnote_scope.is_empty() {
+            "footnotes".to_string()
+        } else {
+            format!("footnotes-{}", self.footnote_scope)
+        }
+    }
+
+    /// Style footnote reference links with `style` inline instead of with classes
+    pub(super) fn with_inline_link_style(self, style: &'static str) -> Self {
+        Self {
+            inline_link_style: Some(style),
+            ..self
+        }
+    }
+
+    fn collect_from(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Definition(d) => {
+                    // CommonMark: the first definition of a label wins
+                    self.definitions
+                        .entry(d.identifier.clone())
+                        .or_insert_with(|| d.clone());
+                }
+                Node::FootnoteDefinition(d) => {
+                    self.footnote_definitions
+                        .entry(d.identifier.clone())
+                        .or_insert_with(|| d.clone());
+                }
+                _ => {}
+            }
+            if let Some(children) = node.children() {
+                self.collect_from(children);
+            }
+        }
+    }
+
+    pub(super) fn resolve(&mut self, nodes: Vec<Node>) -> Vec<Node> {
+        nodes
+            .into_iter()
+            .filter_map(|node| self.resolve_node(node))
+            .collect()
+    }
+
+    fn resolve_node(&mut self, node: Node) -> Option<Node> {
+        let node = match node {
+            Node::Definition(_) | Node::FootnoteDefinition(_) => return None,
+            Node::LinkReference(r) => match self.definitions.get(&r.identifier) {
+                Some(d) => Node::Link(Link {
+                    url: d.url.clone(),
+                    title: d.title.clone(),
+                    children: r.children,
+                    position: r.position,
+                }),
+                None => Node::LinkReference(r),
+            },
+            Node::ImageReference(r) => match self.definitions.get(&r.identifier) {
+                Some(d) => Node::Image(Image {
+                    url: d.url.clone(),
+                    title: d.title.clone(),
+                    alt: r.alt,
+                    position: r.position,
+                }),
+                None => Node::ImageReference(r),
+            },
+            Node::FootnoteReference(r) if self.footnote_definitions.contains_key(&r.identifier) => {
+                let next = self.footnote_numbers.len() + 1;
+                let number = *self
+                    .footnote_numbers
+                    .entry(r.identifier.clone())
+                    .or_insert(next);
+                let count = self
+                    .footnote_reference_counts
+                    .entry(r.identifier)
+                    .or_default();
+                *count += 1;
+
+                // Only the first reference gets the id the footnote's backlink points at
+                let id = if *count == 1 {
+                    self.reference_id(number)
+                } else {
+                    format!("{}-{count}", self.reference_id(number))
+                };
+                Node::Html(Html {
+                    value: html! {
+                        sup id=(id) {
+                            a href=(format!("#{}", self.footnote_id(number)))
+                                class=[self.inline_link_style.is_none().then_some("underline")]
+                                style=[self.inline_link_style]
+                                aria-describedby=(self.footnotes_id()) {
+                                (number)
+                            }
+                        }
+                    }
+                    .into_string(),
+                    position: r.position,
+                })
+            }
+            mut other => {
+                if let Some(children) = other.children_mut() {
+                    let resolved = self.resolve(std::mem::take(children));
+                    *children = resolved;
+                }
+                other
+            }
+        };
+
+        Some(node)
+    }
+
+    /// Footnote definitions that were referenced, in reference order, with references in
+    /// their own content resolved too
+    pub(super) fn footnotes_in_order(&mut self) -> Vec<(usize, FootnoteDefinition)> {
+        let mut rendered = vec![];
+        let mut done = 0;
+
+        // Footnotes can reference other footnotes, which get numbered as we go
+        while done < self.footnote_numbers.len() {
+            let mut pending: Vec<_> = self
+                .footnote_numbers
+                .iter()
+                .filter(|(_, n)| **n > done)
+                .map(|(id, n)| (*n, id.clone()))
+                .collect();
+            pending.sort_unstable();
+            done = self.footnote_numbers.len();
+
+            for (number, identifier) in pending {
+                if let Some(mut definition) = self.footnote_definitions.get(&identifier).cloned() {
+                    definition.children = self.resolve(definition.children);
+                    rendered.push((number, definition));
+                }
+            }
+        }
+
+        rendered.sort_unstable_by_key(|(number, _)| *number);
+        rendered
+    }
+}
+
+/// Render every heading with a unique anchor id, in document order so the ids match
+/// [`posts::headings::table_of_contents`]. The headings are replaced with their rendered
+/// HTML, the same way [`References`] replaces footnote references.
+fn anchor_headings(
+    nodes: Vec<Node>,
+    slugger: &mut Slugger,
+    config: &AppConfig,
+    context: &MarkdownRenderContext,
+) -> Result<Vec<Node>> {
+    nodes
+        .into_iter()
+        .map(|node| match node {
+            Node::Heading(h) => {
+                let id = slugger.slug(&heading_text(&h.children));
+                let position = h.position.clone();
+                Ok(Node::Html(Html {
+                    value: render_heading(h, &id, config, context)?.into_string(),
+                    position,
+                }))
+            }
+            mut other => {
+                if let Some(children) = other.children_mut() {
+                    let anchored =
+                        anchor_headings(std::mem::take(children), slugger, config, context)?;
+                    *children = anchored;
+                }
+                Ok(other)
+            }
         })
+        .collect()
+}
+
+/// The footnote's content, with a "↩" link back to where it was first referenced added
+/// to the end of its last paragraph
+fn footnote_with_backlink(definition: FootnoteDefinition, reference_id: &str) -> Vec<Node> {
+    let backlink = Node::Html(Html {
+        value: html! {
+            " "
+            a href=(format!("#{reference_id}")) class="no-underline" aria-label="Back to content" { "↩" }
+        }
+        .into_string(),
+        position: None,
+    });
+
+    let mut children = definition.children;
+    match children.last_mut() {
+        Some(Node::Paragraph(p)) => p.children.push(backlink),
+        _ => children.push(backlink),
     }
+    children
 }
 
 impl IntoHtml for Node {
@@ -51,7 +321,8 @@ impl IntoHtml for Node {
         match self {
             Node::Root(r) => r.into_html(config, context),
             Node::Blockquote(x) => x.into_html(config, context),
-            Node::FootnoteDefinition(_) => Ok(html! {}), // Skipping for now
+            // Definitions are collected and rendered by `Root`; on their own they render nothing
+            Node::FootnoteDefinition(_) | Node::Definition(_) => Ok(html! {}),
             Node::List(l) => l.into_html(config, context),
             Node::Yaml(y) => y.into_html(config, context),
             Node::Break(b) => b.into_html(config, context),
@@ -71,18 +342,21 @@ impl IntoHtml for Node {
             Node::ListItem(i) => i.into_html(config, context),
             Node::Paragraph(p) => p.into_html(config, context),
             Node::ThematicBreak(b) => b.into_html(config, context),
-            Node::MdxJsxFlowElement(_) => todo!(),
-            Node::MdxjsEsm(_) => todo!(),
-            Node::Toml(_) => todo!(),
-            Node::InlineMath(_) => todo!(),
-            Node::MdxTextExpression(_) => todo!(),
-            Node::FootnoteReference(_) => todo!(),
-            Node::ImageReference(_) => todo!(),
-            Node::MdxJsxTextElement(_) => todo!(),
-            Node::LinkReference(_) => todo!(),
-            Node::Math(_) => todo!(),
-            Node::MdxFlowExpression(_) => todo!(),
-            Node::Definition(_) => todo!(),
+            Node::Toml(t) => t.into_html(config, context),
+            Node::InlineMath(m) => m.into_html(config, context),
+            Node::Math(m) => m.into_html(config, context),
+            Node::FootnoteReference(r) => r.into_html(config, context),
+            Node::ImageReference(r) => r.into_html(config, context),
+            Node::LinkReference(r) => r.into_html(config, context),
+            // We don't enable MDX when parsing, so these should never show up. Error instead
+            // of panicking so `validate` catches it if they ever do.
+            Node::MdxJsxFlowElement(_)
+            | Node::MdxjsEsm(_)
+            | Node::MdxTextExpression(_)
+            | Node::MdxJsxTextElement(_)
+            | Node::MdxFlowExpression(_) => Err(color_eyre::eyre::eyre!(
+                "MDX is not supported in markdown content"
+            )),
         }
     }
 }
@@ -107,6 +381,57 @@ impl IntoHtml for Yaml {
     }
 }
 
+impl IntoHtml for Toml {
+    fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
+        // Like Yaml, Toml only shows up as frontmatter
+        Ok(html! {})
+    }
+}
+
+impl IntoHtml for InlineMath {
+    fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
+        let mathml = latex_to_mathml(&self.value, DisplayStyle::Inline)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid inline math `{}`: {}", self.value, e))?;
+
+        Ok(html! { (PreEscaped(mathml)) })
+    }
+}
+
+impl IntoHtml for Math {
+    fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
+        let mathml = latex_to_mathml(&self.value, DisplayStyle::Block)
+            .map_err(|e| color_eyre::eyre::eyre!("Invalid math block `{}`: {}", self.value, e))?;
+
+        Ok(html! {
+            div class="my-4 max-w-prose overflow-x-auto" { (PreEscaped(mathml)) }
+        })
+    }
+}
+
+// The reference types below are normally resolved by `Root` before rendering. These impls
+// only run when a fragment is rendered without its document, so they degrade to the
+// reference's text rather than failing.
+
+impl IntoHtml for FootnoteReference {
+    fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
+        let label = self.label.unwrap_or(self.identifier);
+
+        Ok(html! { sup { "[" (label) "]" } })
+    }
+}
+
+impl IntoHtml for LinkReference {
+    fn into_html(self, config: &AppConfig, context: &MarkdownRenderContext) -> Result<Markup> {
+        self.children.into_html(config, context)
+    }
+}
+
+impl IntoHtml for ImageReference {
+    fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
+        Ok(html! { (self.alt) })
+    }
+}
+
 impl IntoHtml for Paragraph {
     fn into_html(self, config: &AppConfig, context: &MarkdownRenderContext) -> Result<Markup> {
         Ok(html! {
@@ -235,46 +560,41 @@ impl IntoHtml for Text {
     }
 }
 
+/// A heading on its own, outside of a document, is anchored by its own text
 impl IntoHtml for Heading {
     fn into_html(self, config: &AppConfig, context: &MarkdownRenderContext) -> Result<Markup> {
-        let id = self
-            .children
-            .iter()
-            .map(|x| match x {
-                Node::Text(t) => Ok(t.value.as_str()),
-                _ => Err(color_eyre::eyre::eyre!("Heading should only contain text")),
-            })
-            .collect::<Result<String, _>>()
-            .ok()
-            .map(|x| x.to_lowercase().replace(' ', "-"));
-        let href_attr = id.as_ref().map(|x| format!("#{x}"));
-
-        let child_content = self.children.into_html(config, context)?;
-        #[allow(unreachable_code)]
-        let inner = html! {
-            @match self.depth {
-                1 => h1 id=[id] class="max-w-prose text-2xl" { (child_content) },
-                2 => h2 id=[id] class="max-w-prose text-xl" { (child_content) },
-                3 => h3 id=[id] class="max-w-prose text-lg" { (child_content) },
-                4 => h4 id=[id] class="max-w-prose text-lg text-subtitle" { (child_content) },
-                5 => h5 id=[id] class="max-w-prose text-lg text-subtitle font-light" { (child_content) },
-                6 => h6 id=[id] class="max-w-prose text-base text-subtitle" { (child_content) },
-                _ => (unreachable!("Invalid heading depth")),
-            }
-        };
-
-        Ok(html! {
-            @if let Some(href_attr) = href_attr {
-                a href=(href_attr) {
-                    (inner)
-                }
-            } @else {
-                (inner)
-            }
-        })
+        let id = slugify(&heading_text(&self.children));
+        render_heading(self, &id, config, context)
     }
 }
 
+fn render_heading(
+    heading: Heading,
+    id: &str,
+    config: &AppConfig,
+    context: &MarkdownRenderContext,
+) -> Result<Markup> {
+    let child_content = heading.children.into_html(config, context)?;
+    #[allow(unreachable_code)]
+    let inner = html! {
+        @match heading.depth {
+            1 => h1 id=(id) class="max-w-prose text-2xl" { (child_content) },
+            2 => h2 id=(id) class="max-w-prose text-xl" { (child_content) },
+            3 => h3 id=(id) class="max-w-prose text-lg" { (child_content) },
+            4 => h4 id=(id) class="max-w-prose text-lg text-subtitle" { (child_content) },
+            5 => h5 id=(id) class="max-w-prose text-lg text-subtitle font-light" { (child_content) },
+            6 => h6 id=(id) class="max-w-prose text-base text-subtitle" { (child_content) },
+            _ => (unreachable!("Invalid heading depth")),
+        }
+    };
+
+    Ok(html! {
+        a href=(format!("#{id}")) {
+            (inner)
+        }
+    })
+}
+
 impl IntoHtml for Vec<Node> {
     fn into_html(self, config: &AppConfig, context: &MarkdownRenderContext) -> Result<Markup> {
         Ok(html! {
@@ -392,20 +712,26 @@ impl IntoHtml for Strong {
     }
 }
 
+/// The code block as arborium's highlighted HTML, falling back to escaped plain text for
+/// languages it doesn't know
+pub(super) fn highlight_code(code: &Code) -> String {
+    let lang = code.lang.as_deref().unwrap_or("text");
+
+    // Map language tokens to arborium-supported languages
+    let mapped_lang = match lang {
+        "shell" => "bash",
+        "gitignore" | "crontab" | "fen" => "text",
+        other => other,
+    };
+
+    arborium::Highlighter::new()
+        .highlight(mapped_lang, &code.value)
+        .unwrap_or_else(|_| html_escape::encode_text(&code.value).to_string())
+}
+
 impl IntoHtml for Code {
     fn into_html(self, _config: &AppConfig, _context: &MarkdownRenderContext) -> Result<Markup> {
-        let lang = self.lang.as_deref().unwrap_or("text");
-
-        // Map language tokens to arborium-supported languages
-        let mapped_lang = match lang {
-            "shell" => "bash",
-            "gitignore" | "crontab" | "fen" => "text",
-            other => other,
-        };
-
-        let highlighted_html = arborium::Highlighter::new()
-            .highlight(mapped_lang, &self.value)
-            .unwrap_or_else(|_| html_escape::encode_text(&self.value).to_string());
+        let highlighted_html = highlight_code(&self);
 
         Ok(html! {
           pre class="my-4 py-4 bg-coding_background px-8 overflow-x-auto max-w-vw text-codeText" { code { (PreEscaped(highlighted_html)) } }
@@ -420,3 +746,96 @@ impl IntoHtml for ThematicBreak {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use posts::MarkdownAst;
+
+    use super::*;
+
+    fn render(markdown: &str) -> Result<String> {
+        let config = AppConfig {
+            base_url: Url::parse("http://localhost:3000").unwrap(),
+            imgproxy_url: None,
+        };
+        let context = MarkdownRenderContext {
+            syntax_highlighting: SyntaxHighlightingContext,
+            current_article_path: "/posts/example".to_string(),
+        };
+
+        Ok(MarkdownAst::from_str(markdown)?
+            .0
+            .into_html(&config, &context)?
+            .into_string())
+    }
+
+    #[test]
+    fn footnotes_are_numbered_in_reference_order_with_backlinks() {
+        let html = render(indoc::indoc! {"
+            First[^b] then second[^a] and first again[^b].
+
+            [^a]: Note A
+            [^b]: Note B
+            [^unused]: Never referenced
+        "})
+        .unwrap();
+
+        assert!(html.contains(r##"<sup id="fnref-posts-example-1"><a href="#fn-posts-example-1""##));
+        assert!(html.contains(r##"<sup id="fnref-posts-example-2"><a href="#fn-posts-example-2""##));
+        assert!(html.contains(r#"<sup id="fnref-posts-example-1-2">"#));
+        assert!(html.contains(r#"<section id="footnotes-posts-example""#));
+
+        let note_b = html.find(r#"<li id="fn-posts-example-1">"#).unwrap();
+        let note_a = html.find(r#"<li id="fn-posts-example-2">"#).unwrap();
+        assert!(note_b < note_a);
+        assert!(html[note_b..].starts_with(
+            r##"<li id="fn-posts-example-1"><p class="my-4 max-w-prose leading-loose">Note B <a href="#fnref-posts-example-1""##
+        ));
+        assert!(!html.contains("Never referenced"));
+    }
+
+    #[test]
+    fn rich_and_repeated_headings_get_unique_anchors() {
+        let html = render(indoc::indoc! {"
+            ## Using *fzf* with `vim`
+
+            ## Setup
+
+            ### Setup
+        "})
+        .unwrap();
+
+        assert!(html.contains(r##"<a href="#using-fzf-with-vim"><h2 id="using-fzf-with-vim""##));
+        assert!(html.contains(r##"<a href="#setup"><h2 id="setup""##));
+        assert!(html.contains(r##"<a href="#setup-1"><h3 id="setup-1""##));
+    }
+
+    #[test]
+    fn reference_links_and_images_resolve_through_definitions() {
+        let html = render(indoc::indoc! {r#"
+            See [the docs][docs], [Docs][] and ![a cat][cat].
+
+            [docs]: https://example.com/docs "The Docs"
+            [cat]: cat.png
+        "#})
+        .unwrap();
+
+        assert!(html.contains(r#"<a href="https://example.com/docs" title="The Docs""#));
+        assert_eq!(html.matches("https://example.com/docs").count(), 2);
+        assert!(html.contains(r#"<img src="/posts/example/cat.png" alt="a cat""#));
+        assert!(!html.contains("[docs]"));
+    }
+
+    #[test]
+    fn math_renders_as_mathml() {
+        let html =
+            render("Inline $$x^2$$ math, but $5 and $10 stay text.\n\n$$\n\\frac{a}{b}\n$$\n")
+                .unwrap();
+
+        assert!(html.contains("<math"));
+        assert!(html.contains("<mfrac>"));
+        assert!(html.contains("$5 and $10"));
+    }
+}
@@ -14,6 +14,11 @@ impl IntoPlainText for Node {
             format!("{surround_token}{inner}{surround_token}")
         }
 
+        /// Text, emphasis and links within a paragraph run together on one line
+        fn inline(children: &[Node]) -> String {
+            children.iter().map(IntoPlainText::plain_text).collect()
+        }
+
         match self {
             Node::Root(x) => x.children.plain_text(),
             Node::Blockquote(x) => x.children.plain_text(),
@@ -21,19 +26,20 @@ impl IntoPlainText for Node {
             Node::List(x) => x.children.plain_text(),
             Node::InlineCode(x) => x.value.clone(),
             Node::InlineMath(x) => x.value.clone(),
-            Node::Delete(x) => surround_with("~", &x.children.plain_text()),
-            Node::Emphasis(x) => surround_with("*", &x.children.plain_text()),
-            Node::Link(x) => x.children.plain_text(),
-            Node::Strong(x) => surround_with("*", &x.children.plain_text()),
+            Node::Delete(x) => surround_with("~", &inline(&x.children)),
+            Node::Emphasis(x) => surround_with("*", &inline(&x.children)),
+            Node::Link(x) => inline(&x.children),
+            Node::LinkReference(x) => inline(&x.children),
+            Node::Strong(x) => surround_with("*", &inline(&x.children)),
             Node::Text(x) => x.value.clone(),
             Node::Code(x) => surround_with("\n```\n", &x.value),
             Node::Math(x) => x.value.clone(),
-            Node::Heading(x) => x.children.plain_text(),
+            Node::Heading(x) => inline(&x.children),
             Node::Table(x) => x.children.plain_text(),
             Node::TableRow(x) => x.children.plain_text(),
-            Node::TableCell(x) => x.children.plain_text(),
+            Node::TableCell(x) => inline(&x.children),
             Node::ListItem(x) => x.children.iter().map(IntoPlainText::plain_text).collect(),
-            Node::Paragraph(x) => x.children.plain_text(),
+            Node::Paragraph(x) => inline(&x.children),
             Node::MdxJsxFlowElement(_)
             | Node::MdxjsEsm(_)
             | Node::Toml(_)
@@ -45,7 +51,6 @@ impl IntoPlainText for Node {
             | Node::Image(_)
             | Node::ImageReference(_)
             | Node::MdxJsxTextElement(_)
-            | Node::LinkReference(_)
             | Node::MdxFlowExpression(_)
             | Node::ThematicBreak(_)
             | Node::Definition(_) => blank(),
@@ -86,6 +91,14 @@ mod test {
         assert_eq!(plain, original);
     }
 
+    #[test]
+    fn inline_formatting_stays_on_one_line() {
+        let original = "Hello **there**, read [the docs](https://docs.rs).";
+        let parsed = MarkdownAst::from_str(original).unwrap();
+
+        assert_eq!(parsed.0.plain_text(), "Hello *there*, read the docs.");
+    }
+
     #[test]
     #[ignore = "The newlines around paragraphs aren't quite right"]
     fn heading() {
@@ -26,8 +26,9 @@ static PUBLICATIONS: LazyLock<Vec<PublicationStub>> = LazyLock::new(|| {
         .publication
 });
 
-/// Build `<link rel="site.standard.*">` head links for verification.
-fn standard_site_head_links(
+/// Build `<link rel="site.standard.*">` head links for verification. Notes and podcast
+/// episodes use these too, with their own publication's key.
+pub(crate) fn standard_site_head_links(
     atproto_uri: Option<&str>,
     publication_key: &str,
 ) -> Vec<(String, String)> {
@@ -45,129 +46,85 @@ fn standard_site_head_links(
     head_links
 }
 
+use db::webmentions::{Webmention, WebmentionKind};
 use maud::{html, Markup};
 use posts::{
-    blog::{BlogPostPath, BlogPosts, MatchesPath, ToCanonicalPath},
-    date::PostedOn,
+    blog::{BlogPost, BlogPosts, MatchesPath, ToCanonicalPath},
+    headings::{table_of_contents, TocEntry},
     notes::NotePosts,
-    Post,
+    publish::Publishable,
+    source::ContentDir,
 };
 use rsky_lexicon::app::bsky::feed::{PostView, ThreadViewPost, ThreadViewPostEnum};
-use rss::validation::Validate;
 use tracing::instrument;
 
 pub(crate) mod md;
 
 use crate::{
-    bsky::fetch_thread,
+    bsky::cached_thread,
     http_server::{
+        auth::session::AdminUser,
         errors::ServerError,
-        pages::blog::md::{html::MarkdownRenderContext, IntoHtml, SyntaxHighlightingContext},
+        feeds::{self, publication_title, Feed, FeedFormat, FeedLink, ToFeedItem},
+        pages::{
+            blog::md::{html::MarkdownRenderContext, IntoHtml, SyntaxHighlightingContext},
+            tags::{all_content, series_nav, tag_links},
+        },
         templates::{base_constrained, header::OpenGraph, post_templates::BlogPostList, ShortDesc},
-        LinkTo, ToRssItem,
+        LinkTo,
+    },
+    mastodon::{
+        replies::{self, Reply, StatusThread},
+        status_paragraphs,
     },
-    AppConfig, AppState,
+    webmention::is_web_url,
+    AppState,
 };
 
-pub(crate) struct MyChannel(rss::Channel);
-
-impl MyChannel {
-    #[instrument(skip_all)]
-    pub fn from_posts<T>(
-        config: &AppConfig,
-        context: &SyntaxHighlightingContext,
-        posts: &[&Post<T>],
-    ) -> cja::Result<Self>
-    where
-        Post<T>: ToRssItem,
-    {
-        let items: cja::Result<Vec<rss::Item>> = posts
-            .iter()
-            .map(|p| p.to_rss_item(config, context))
-            .collect();
-
-        Ok(Self::from_items(config, context, &items?))
-    }
-
-    pub fn from_items(
-        config: &AppConfig,
-        _context: &SyntaxHighlightingContext,
-        items: &[rss::Item],
-    ) -> Self {
-        use rss::ChannelBuilder;
-
-        let channel = ChannelBuilder::default()
-            .title("coreyja Blog".to_string())
-            .link(config.home_page())
-            .copyright(Some("Copyright Corey Alexander".to_string()))
-            .language(Some("en-us".to_string()))
-            .items(items)
-            .build();
-
-        Self(channel)
-    }
+/// Title of the blog feed when `publications.toml` has no `blog` entry
+const FEED_TITLE: &str = "coreyja Blog";
 
-    pub fn validate(&self) -> Result<(), rss::validation::ValidationError> {
-        self.0.validate()
-    }
+pub(crate) fn feed_links() -> Vec<FeedLink> {
+    feeds::feed_links("/posts", publication_title("blog").unwrap_or(FEED_TITLE))
 }
 
 #[instrument(skip_all)]
-pub(crate) async fn rss_feed(
+pub(crate) async fn posts_feed(
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, ServerError> {
-    let channel = MyChannel::from_posts(
+    State(posts): State<Arc<BlogPosts>>,
+    format: FeedFormat,
+) -> Result<Response, ServerError> {
+    let feed = Feed::from_posts(
         &state.app,
         &state.syntax_highlighting_context,
-        &state.blog_posts.by_recency(),
-    )?;
+        "/posts",
+        &posts.published(),
+    )?
+    .with_title(FEED_TITLE)
+    .with_publication("blog");
 
-    Ok(channel.into_response())
+    Ok(feed.into_response(format))
 }
 
+/// Posts and notes together, served at the root
 #[instrument(skip_all)]
-pub(crate) async fn full_rss_feed(
+pub(crate) async fn full_feed(
     State(state): State<AppState>,
     State(blog_posts): State<Arc<BlogPosts>>,
     State(note_posts): State<Arc<NotePosts>>,
-) -> Result<impl IntoResponse, ServerError> {
-    let mut items_with_date: Vec<(chrono::NaiveDate, rss::Item)> = vec![];
-    for p in blog_posts.by_recency() {
-        items_with_date.push((
-            p.posted_on(),
-            p.to_rss_item(&state.app, &state.syntax_highlighting_context)?,
-        ));
+    format: FeedFormat,
+) -> Result<Response, ServerError> {
+    let mut items = vec![];
+    for p in blog_posts.published() {
+        items.push(p.to_feed_item(&state.app, &state.syntax_highlighting_context)?);
     }
-    for p in note_posts.by_recency() {
-        items_with_date.push((
-            p.posted_on(),
-            p.to_rss_item(&state.app, &state.syntax_highlighting_context)?,
-        ));
+    for p in note_posts.published() {
+        items.push(p.to_feed_item(&state.app, &state.syntax_highlighting_context)?);
     }
 
-    items_with_date.sort_by_key(|&(date, _)| std::cmp::Reverse(date));
-
-    let items: Vec<rss::Item> = items_with_date.into_iter().map(|(_, i)| i).collect();
-
-    let channel = MyChannel::from_items(&state.app, &state.syntax_highlighting_context, &items);
+    items.sort_by_key(|item| std::cmp::Reverse(item.published));
 
-    Ok(channel.into_response())
-}
-
-impl IntoResponse for MyChannel {
-    fn into_response(self) -> Response {
-        let r = Response::builder()
-            .header("Content-Type", "application/rss+xml")
-            .body(self.0.to_string());
-
-        if let Ok(r) = r {
-            r.into_response()
-        } else {
-            let e: ServerError =
-                cja::color_eyre::eyre::eyre!("Failed to build RSS Feed response").into();
-            e.into_response()
-        }
-    }
+    Ok(Feed::new(&state.app, "", items).into_response(format))
 }
 
 #[instrument(skip_all)]
@@ -178,24 +135,28 @@ pub(crate) async fn posts_index(
     Ok(base_constrained(
         html! {
           h1 class="text-3xl" { "Blog Posts" }
-          (BlogPostList(posts.by_recency()))
+          (BlogPostList(posts.published()))
+        },
+        OpenGraph {
+            feeds: feed_links(),
+            ..OpenGraph::default_for_path(&state.app, "/posts")
         },
-        OpenGraph::default_for_path(&state.app, "/posts"),
     ))
 }
 
-#[allow(clippy::too_many_lines)]
 #[instrument(skip(state, posts))]
 pub(crate) async fn post_get(
     State(state): State<AppState>,
     State(posts): State<Arc<BlogPosts>>,
     Path(key): Path<String>,
 ) -> Result<Response, StatusCode> {
+    if let Some(asset) = state
+        .content
+        .source()
+        .file(ContentDir::Blog, &key)
+        .filter(|f| !f.is_markdown())
     {
-        let path = BlogPostPath::new(key.clone());
-        if path.file_exists() && !path.file_is_markdown() {
-            return Ok(path.raw_bytes().into_response());
-        }
+        return Ok(asset.into_contents().into_response());
     }
 
     let (post, m) = posts
@@ -204,12 +165,44 @@ pub(crate) async fn post_get(
         .find_map(|p| p.matches_path(&key).map(|m| (p, m)))
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    // Before redirecting, so the redirect doesn't give away an unpublished post's path
+    if !post.is_published() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
     if let MatchesPath::RedirectToCanonicalPath = m {
         return Ok(
             Redirect::permanent(&format!("/posts/{}", post.path.canonical_path())).into_response(),
         );
     }
 
+    render_post(&state, &posts, post).await
+}
+
+/// Admin-only view of any post, including drafts and scheduled posts that [`post_get`]
+/// hides
+#[instrument(skip(state, posts, _admin))]
+pub(crate) async fn post_preview(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    State(posts): State<Arc<BlogPosts>>,
+    Path(key): Path<String>,
+) -> Result<Response, StatusCode> {
+    let post = posts
+        .posts()
+        .iter()
+        .find(|p| p.matches_path(&key).is_some())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    render_post(&state, &posts, post).await
+}
+
+#[allow(clippy::too_many_lines)]
+async fn render_post(
+    state: &AppState,
+    posts: &BlogPosts,
+    post: &BlogPost,
+) -> Result<Response, StatusCode> {
     let markdown = post.markdown();
 
     let context = MarkdownRenderContext {
@@ -227,11 +220,7 @@ pub(crate) async fn post_get(
         }
     };
 
-    let card_route_path = if post.frontmatter.is_newsletter {
-        format!("/og/weekly/{}.svg", post.og_slug())
-    } else {
-        format!("/og/posts/{}.svg", post.og_slug())
-    };
+    let card_route_path = post.og_card_route();
 
     let og_image = post
         .frontmatter
@@ -248,17 +237,32 @@ pub(crate) async fn post_get(
         .app_url(&format!("/posts/{}", post.path.canonical_path()));
 
     let bsky_thread = if let Some(bsky_post_url) = &post.frontmatter.bsky_url {
-        match fetch_thread(bsky_post_url).await {
-            Ok(thread) => Some((bsky_post_url, thread)),
-            Err(e) => {
-                tracing::warn!(?e, "Failed to fetch Bluesky thread for blog post");
-                None
-            }
-        }
+        cached_thread(state, bsky_post_url)
+            .await
+            .map(|thread| (bsky_post_url, thread))
     } else {
         None
     };
 
+    let mastodon_thread = if let Some(status_url) = &post.frontmatter.mastodon_url {
+        replies::cached_thread(state, status_url)
+            .await
+            .map(|thread| (status_url, thread))
+    } else {
+        None
+    };
+
+    let mentions = load_webmentions(state, &post.relative_link()).await;
+
+    let reading_stats = post.reading_stats;
+    let related = posts.related_posts(post);
+
+    let toc = if post.frontmatter.toc {
+        table_of_contents(&post.ast.0)
+    } else {
+        vec![]
+    };
+
     let title = post.markdown().title;
     let published_time = post
         .frontmatter
@@ -273,21 +277,57 @@ pub(crate) async fn post_get(
         &post.frontmatter.publication,
     );
 
+    let note_posts = state.note_posts();
+
     Ok(base_constrained(
         html! {
+          (unpublished_banner(post))
           h1 class="text-2xl" { (post.markdown().title) }
-          subtitle class="block text-lg text-subtitle mb-8" { (post.markdown().date) }
+          subtitle class="block text-lg text-subtitle mb-8" {
+            (post.markdown().date)
+            " · "
+            span title=(format!("{} words", reading_stats.word_count)) { (reading_stats.label()) }
+          }
+          (tag_links(&post.frontmatter.tags))
+
+          @if !toc.is_empty() {
+            (table_of_contents_nav(&toc))
+          }
 
           div {
             (html)
           }
 
+          @if let Some(nav) = series_nav(&all_content(posts, &note_posts), &post.relative_link()) {
+            (nav)
+          }
+
+          @if !related.is_empty() {
+            div class="mt-8" {
+              h2 class="text-xl font-bold" { "Related posts" }
+              (BlogPostList(related))
+            }
+          }
+
           @if let Some((url, thread)) = bsky_thread {
             div class="mt-8" {
               (bluesky_post_stats(url, &thread))
               (bsky_comments(url, thread))
             }
           }
+
+          @if let Some((url, thread)) = mastodon_thread {
+            div class="mt-8" {
+              (mastodon_post_stats(url, &thread))
+              (mastodon_comments(url, &thread))
+            }
+          }
+
+          @if !mentions.is_empty() {
+            div class="mt-8" {
+              (webmentions(&mentions))
+            }
+          }
         },
         OpenGraph {
             title: title.clone(),
@@ -306,6 +346,7 @@ pub(crate) async fn post_get(
             author: post.frontmatter.author.clone(),
             tags: post.frontmatter.tags.clone(),
             head_links,
+            feeds: feed_links(),
             ..OpenGraph::default()
         },
     )
@@ -370,6 +411,242 @@ pub(crate) fn bsky_comments(post_url: &str, thread: ThreadViewPost) -> Markup {
     }
 }
 
+pub(crate) fn mastodon_post_stats(url: &str, thread: &StatusThread) -> Markup {
+    html! {
+        a href=(url) target="_blank" {
+            p class="flex items-center hover:underline gap-2 text-lg" {
+                span class="flex items-center" {
+                    svg color="gold" xmlns="http://www.w3.org/2000/svg" stroke="gold" fill="gold" stroke-width="1.5" class="size-5" viewBox="0 0 24 24" {
+                        path stroke-linejoin="round" stroke-linecap="round" d="M11.48 3.499a.562.562 0 0 1 1.04 0l2.125 5.111a.563.563 0 0 0 .475.345l5.518.442c.499.04.701.663.321.988l-4.204 3.602a.563.563 0 0 0-.182.557l1.285 5.385a.562.562 0 0 1-.84.61l-4.725-2.885a.562.562 0 0 0-.586 0L6.982 20.54a.562.562 0 0 1-.84-.61l1.285-5.386a.562.562 0 0 0-.182-.557l-4.204-3.602a.562.562 0 0 1 .321-.988l5.518-.442a.563.563 0 0 0 .475-.345L11.48 3.5Z" {}
+                    }
+                    span class="ml-1" {
+                        (thread.status.favourites_count) " favourites"
+                    }
+                }
+                span class="flex items-center" {
+                    svg viewBox="0 0 24 24" class="size-5" stroke-width="1.5" xmlns="http://www.w3.org/2000/svg" stroke="green" fill="none" {
+                        path stroke-linecap="round" stroke-linejoin="round" d="M19.5 12c0-1.232-.046-2.453-.138-3.662a4.006 4.006 0 0 0-3.7-3.7 48.678 48.678 0 0 0-7.324 0 4.006 4.006 0 0 0-3.7 3.7c-.017.22-.032.441-.046.662M19.5 12l3-3m-3 3-3-3m-12 3c0 1.232.046 2.453.138 3.662a4.006 4.006 0 0 0 3.7 3.7 48.656 48.656 0 0 0 7.324 0 4.006 4.006 0 0 0 3.7-3.7c.017-.22.032-.441.046-.662M4.5 12l3 3m-3-3-3 3" {}
+                    }
+                    span class="ml-1" {
+                        (thread.status.reblogs_count) " boosts"
+                    }
+                }
+                span class="flex items-center" {
+                    svg stroke-width="1.5" stroke="#7FBADC" fill="#7FBADC" class="size-5" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg" {
+                        path stroke-linecap="round" stroke-linejoin="round" d="M12 20.25c4.97 0 9-3.694 9-8.25s-4.03-8.25-9-8.25S3 7.444 3 12c0 2.104.859 4.023 2.273 5.48.432.447.74 1.04.586 1.641a4.483 4.483 0 0 1-.923 1.785A5.969 5.969 0 0 0 6 21c1.282 0 2.47-.402 3.445-1.087.81.22 1.668.337 2.555.337Z" {}
+                    }
+                    span class="ml-1" {
+                        (thread.status.replies_count) " replies"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replies to the Mastodon status, threaded like [`bsky_comments`]
+pub(crate) fn mastodon_comments(status_url: &str, thread: &StatusThread) -> Markup {
+    let replies = thread.reply_tree();
+
+    html! {
+        h2 class="mt-6 text-xl font-bold" {
+            "Comments from the Fediverse"
+        }
+        p class="mt-2 text-sm" {
+            "Reply on Mastodon "
+            a href=(status_url) target="_blank" rel="noreferrer noopener" class="underline" {
+                "here"
+            }
+            " to join the conversation."
+        }
+        @if !replies.is_empty() {
+            hr class="mt-2";
+            div class="mt-2 space-y-8" {
+                @for reply in &replies {
+                    (mastodon_comment(reply))
+                }
+            }
+        }
+    }
+}
+
+fn mastodon_comment(reply: &Reply) -> Markup {
+    let avatar_class_name = "h-6 w-6 shrink-0 rounded-full bg-gray-300";
+    let status = &reply.status;
+    let author = &status.account;
+    // Replies come from whatever server the replier is on, so only their web links are kept
+    let status_url = status.url.as_deref().filter(|url| is_web_url(url));
+    let author_url = Some(author.url.as_str()).filter(|url| is_web_url(url));
+    let avatar = author.avatar.as_deref().filter(|url| is_web_url(url));
+    let content = html! {
+        a href=[status_url] target="_blank" rel="noreferrer noopener" {
+            @for paragraph in status_paragraphs(&status.content) {
+                p class="whitespace-pre-line" { (paragraph) }
+            }
+        }
+    };
+
+    html! {
+        div class="my-4 text-sm" {
+            div class="flex max-w-xl flex-col gap-2" {
+                a href=[author_url] target="_blank" rel="noreferrer noopener" {
+                    @if let Some(avatar) = avatar {
+                        img class=(avatar_class_name) src=(avatar) alt="avatar";
+                    } @else {
+                        div class=(avatar_class_name) {}
+                    }
+                    p class="line-clamp-1" {
+                        (author.display_name)
+                        " "
+                        span class="text-gray-500" {
+                            "@" (author.acct)
+                        }
+                    }
+                }
+                @if status.spoiler_text.is_empty() {
+                    (content)
+                } @else {
+                    details {
+                        summary class="cursor-pointer" { (status.spoiler_text) }
+                        (content)
+                    }
+                }
+            }
+            @for reply in &reply.replies {
+                div class="border-l-2 border-neutral-600 pl-2" {
+                    (mastodon_comment(reply))
+                }
+            }
+        }
+    }
+}
+
+/// Tells admins previewing a draft or scheduled post that readers can't see it yet
+pub(crate) fn unpublished_banner(item: &impl Publishable) -> Markup {
+    html! {
+        @if item.draft() {
+            p class="mb-4 rounded border border-yellow-500 p-2 text-sm" {
+                "Draft: only admins can see this"
+            }
+        } @else if let Some(publish_at) = item.publish_at().filter(|_| !item.is_published()) {
+            p class="mb-4 rounded border border-yellow-500 p-2 text-sm" {
+                "Scheduled: publishes " (publish_at.format("%B %-d, %Y at %H:%M UTC"))
+            }
+        }
+    }
+}
+
+/// Links to each heading, indented under the shallowest heading level in the post
+fn table_of_contents_nav(entries: &[TocEntry]) -> Markup {
+    let top_depth = entries.iter().map(|e| e.depth).min().unwrap_or(1);
+
+    html! {
+        nav class="mb-8 max-w-prose text-sm" aria-label="Table of contents" {
+            h2 class="mb-2 font-bold" { "Contents" }
+            ol {
+                @for entry in entries {
+                    li class=(match entry.depth - top_depth {
+                        0 => "",
+                        1 => "ml-4",
+                        2 => "ml-8",
+                        _ => "ml-12",
+                    }) {
+                        a class="hover:underline" href=(format!("#{}", entry.id)) { (entry.text) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Webmentions for a post or note, or an empty list if they can't be loaded. Comments are
+/// an extra, so a database hiccup shouldn't take the page down with it.
+pub(crate) async fn load_webmentions(state: &AppState, target_path: &str) -> Vec<Webmention> {
+    match Webmention::list_for_target_path(&state.db, target_path).await {
+        Ok(mentions) => mentions,
+        Err(e) => {
+            tracing::warn!(?e, target_path, "Failed to load webmentions");
+            vec![]
+        }
+    }
+}
+
+/// Likes and reposts as a row of avatars, then replies and other mentions in full.
+/// Mentions stored before their links were checked on the way in are checked again here,
+/// so nothing but an `http(s)` URL ever ends up in an `href`.
+pub(crate) fn webmentions(mentions: &[Webmention]) -> Markup {
+    let mentions: Vec<_> = mentions.iter().filter(|m| is_web_url(&m.source)).collect();
+    let of_kind = |kind: WebmentionKind| {
+        mentions
+            .iter()
+            .copied()
+            .filter(move |m| m.kind == kind)
+            .collect::<Vec<_>>()
+    };
+    let likes = of_kind(WebmentionKind::Like);
+    let reposts = of_kind(WebmentionKind::Repost);
+    let responses: Vec<_> = mentions
+        .iter()
+        .copied()
+        .filter(|m| matches!(m.kind, WebmentionKind::Reply | WebmentionKind::Mention))
+        .collect();
+
+    html! {
+        h2 class="mt-6 text-xl font-bold" { "Webmentions" }
+        @for (label, group) in [("likes", &likes), ("reposts", &reposts)] {
+            @if !group.is_empty() {
+                div class="mt-2 flex flex-wrap items-center gap-1 text-sm" {
+                    span class="mr-2" { (group.len()) " " (label) }
+                    @for m in group.iter() {
+                        (webmention_avatar(m))
+                    }
+                }
+            }
+        }
+        @if !responses.is_empty() {
+            div class="mt-2 space-y-8" {
+                @for m in &responses {
+                    div class="my-4 text-sm flex max-w-xl flex-col gap-2" {
+                        div class="flex items-center gap-2" {
+                            (webmention_avatar(m))
+                            p class="line-clamp-1" {
+                                (m.author_name.as_deref().unwrap_or("Someone"))
+                                " "
+                                span class="text-gray-500" {
+                                    @if m.kind == WebmentionKind::Reply { "replied" } @else { "mentioned this" }
+                                }
+                            }
+                        }
+                        @if let Some(content) = &m.content {
+                            p { (content) }
+                        }
+                        a href=(m.source) target="_blank" rel="noreferrer noopener" class="text-xs opacity-60 hover:underline" {
+                            (m.published_at.unwrap_or(m.created_at).format("%B %-d, %Y"))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn webmention_avatar(m: &Webmention) -> Markup {
+    let avatar_class_name = "h-6 w-6 shrink-0 rounded-full bg-gray-300";
+    let name = m.author_name.as_deref().unwrap_or(&m.source);
+    let author_url = m.author_url.as_deref().filter(|url| is_web_url(url));
+    let photo = m.author_photo.as_deref().filter(|url| is_web_url(url));
+
+    html! {
+        a href=(author_url.unwrap_or(&m.source)) target="_blank" rel="noreferrer noopener" title=(name) {
+            @if let Some(photo) = photo {
+                img class=(avatar_class_name) src=(photo) alt=(name);
+            } @else {
+                div class=(avatar_class_name) {}
+            }
+        }
+    }
+}
+
 pub(crate) fn bsky_comment(comment: ThreadViewPost) -> Markup {
     let avatar_class_name = "h-6 w-6 shrink-0 rounded-full bg-gray-300";
     let author = &comment.post.author;
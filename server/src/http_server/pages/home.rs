@@ -24,10 +24,10 @@ pub(crate) async fn home_page(
     State(note_posts): State<Arc<NotePosts>>,
     State(blog_posts): State<Arc<BlogPosts>>,
 ) -> Result<Markup, ServerError> {
-    let mut recent_notes = note_posts.by_recency();
+    let mut recent_notes = note_posts.published();
     recent_notes.truncate(3);
 
-    let mut recent_posts = blog_posts.by_recency();
+    let mut recent_posts = blog_posts.published();
     recent_posts.truncate(3);
 
     let recent_videos = sqlx::query_as!(
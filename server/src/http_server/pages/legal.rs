@@ -48,7 +48,9 @@ pub(crate) async fn privacy_policy() -> Result<Markup, ServerError> {
               a href="https://buttondown.com/" target="_blank"  class="underline" { "Buttondown" }
               " to manage this list and send emails. "
               "You can sign up for my newsletter at the bottom of each page on this site. "
-              "This form submits directly to Buttondown who will store your email address."
+              "This form either passes your email address on to Buttondown who will store it, "
+              "or stores it in this site's own database and emails you a link to confirm your subscription. "
+              "Every newsletter has a link to unsubscribe."
             }
 
             h3 class="my-4 text-xl" {
@@ -50,7 +50,7 @@ async fn app_login(
     Path(from_app): Path<String>,
 ) -> impl IntoResponse {
     app_state
-        .projects
+        .projects()
         .projects
         .iter()
         .find(|p| p.slug().unwrap() == from_app)
@@ -98,7 +98,7 @@ async fn app_claim(
     Path(project_slug): Path<String>,
     Json(body): Json<ClaimBody>,
 ) -> impl IntoResponse {
-    let projects = app_state.projects.clone();
+    let projects = app_state.projects();
     let project = projects
         .projects
         .iter()
@@ -0,0 +1,303 @@
+//! iCalendar feed of the meal plan, for subscribing from a family calendar. Calendar apps
+//! can't log in, so the feed is authenticated by the secret token in its URL; feeds are
+//! created and configured from `/admin/calendar-feeds`.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use db::cooking::{CalendarMealEntry, MealPlanCalendarFeed, MealType};
+use tracing::instrument;
+
+use crate::{
+    http_server::{errors::ServerError, ResponseResult},
+    AppState,
+};
+
+/// How far back the feed goes, so the last few weeks of meals stay on the calendar
+const HISTORY_DAYS: i64 = 30;
+
+/// Meal events don't have an end time of their own, so give them a fixed length
+const MEAL_DURATION_MINUTES: i64 = 45;
+
+pub(crate) fn feed_path(feed: &MealPlanCalendarFeed) -> String {
+    format!("/meal-plans/calendar/{}.ics", feed.token)
+}
+
+#[instrument(skip_all)]
+pub(crate) async fn meal_plan_calendar(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> ResponseResult<Response> {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+    let feed = MealPlanCalendarFeed::get_by_token(&state.db, token)
+        .await?
+        .ok_or_else(|| {
+            ServerError(
+                cja::color_eyre::eyre::eyre!("No such calendar feed"),
+                StatusCode::NOT_FOUND,
+            )
+        })?;
+
+    let since = Utc::now().date_naive() - Duration::days(HISTORY_DAYS);
+    let entries = CalendarMealEntry::list_since(&state.db, since).await?;
+
+    let ics = render_calendar(&state, &feed, &entries);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        "text/calendar; charset=utf-8".parse().unwrap(),
+    );
+    headers.insert(
+        header::CACHE_CONTROL,
+        "private, max-age=300".parse().unwrap(),
+    );
+    Ok((headers, ics).into_response())
+}
+
+fn render_calendar(
+    state: &AppState,
+    feed: &MealPlanCalendarFeed,
+    entries: &[CalendarMealEntry],
+) -> String {
+    let domain = state.app.base_url.host_str().unwrap_or("coreyja.com");
+
+    let mut ics = Ics::default();
+    ics.line("BEGIN:VCALENDAR");
+    ics.line("VERSION:2.0");
+    ics.line("PRODID:-//coreyja.com//Meal Plan//EN");
+    ics.line("CALSCALE:GREGORIAN");
+    ics.line("METHOD:PUBLISH");
+    ics.property("X-WR-CALNAME", &feed.name);
+    ics.line("REFRESH-INTERVAL;VALUE=DURATION:PT1H");
+    ics.line("X-PUBLISHED-TTL:PT1H");
+
+    for entry in entries {
+        let meal_type = entry.meal_type.clone().unwrap_or(MealType::Dinner);
+        let meal_start = entry.date.and_time(feed.time_for(&meal_type));
+        let meal_end = meal_start + Duration::minutes(MEAL_DURATION_MINUTES);
+        let dtstamp = utc_timestamp(entry.updated_at);
+
+        let mut description = vec![format!("From the meal plan \"{}\"", entry.meal_plan_name)];
+        if let Some(servings) = entry.servings_override {
+            description.push(format!("Makes {servings} servings"));
+        }
+        if let Some(times) = recipe_times(entry) {
+            description.push(times);
+        }
+        let url = match (&entry.recipe_slug, entry.recipe_is_public) {
+            (Some(slug), true) => Some(state.app.app_url(&format!("/recipes/{slug}"))),
+            _ => None,
+        };
+
+        ics.line("BEGIN:VEVENT");
+        ics.line(&format!("UID:meal-{}@{domain}", entry.meal_plan_entry_id));
+        ics.line(&format!("DTSTAMP:{dtstamp}"));
+        ics.line(&format!("DTSTART:{}", floating(meal_start)));
+        ics.line(&format!("DTEND:{}", floating(meal_end)));
+        ics.property(
+            "SUMMARY",
+            &format!("{}: {}", title_case(&meal_type), entry.recipe_name),
+        );
+        ics.property("DESCRIPTION", &description.join("\n"));
+        if let Some(url) = &url {
+            ics.property("URL", url);
+        }
+        ics.line("END:VEVENT");
+
+        // Prep reminder: block out the time it takes to make the recipe, ending when the
+        // meal starts, with an alarm at the start
+        let lead_minutes = entry.prep_time.unwrap_or(0) + entry.cook_time.unwrap_or(0);
+        if lead_minutes > 0 {
+            let prep_start = meal_start - Duration::minutes(i64::from(lead_minutes));
+            let summary = format!("Start cooking: {}", entry.recipe_name);
+
+            ics.line("BEGIN:VEVENT");
+            ics.line(&format!("UID:prep-{}@{domain}", entry.meal_plan_entry_id));
+            ics.line(&format!("DTSTAMP:{dtstamp}"));
+            ics.line(&format!("DTSTART:{}", floating(prep_start)));
+            ics.line(&format!("DTEND:{}", floating(meal_start)));
+            ics.property("SUMMARY", &summary);
+            ics.property(
+                "DESCRIPTION",
+                &format!(
+                    "{} for {} at {}",
+                    recipe_times(entry).unwrap_or_default(),
+                    meal_type,
+                    meal_start.format("%-I:%M %p")
+                ),
+            );
+            if let Some(url) = &url {
+                ics.property("URL", url);
+            }
+            ics.line("BEGIN:VALARM");
+            ics.line("ACTION:DISPLAY");
+            ics.property("DESCRIPTION", &summary);
+            ics.line("TRIGGER:PT0S");
+            ics.line("END:VALARM");
+            ics.line("END:VEVENT");
+        }
+    }
+
+    ics.line("END:VCALENDAR");
+    ics.0
+}
+
+fn recipe_times(entry: &CalendarMealEntry) -> Option<String> {
+    match (entry.prep_time, entry.cook_time) {
+        (Some(prep), Some(cook)) => Some(format!("Prep {prep} min, cook {cook} min")),
+        (Some(prep), None) => Some(format!("Prep {prep} min")),
+        (None, Some(cook)) => Some(format!("Cook {cook} min")),
+        (None, None) => None,
+    }
+}
+
+fn title_case(meal_type: &MealType) -> String {
+    let name = meal_type.to_string();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// A local "floating" time, shown at the same wall-clock time in every timezone
+fn floating(dt: NaiveDateTime) -> String {
+    dt.format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn utc_timestamp(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Builds an RFC 5545 document: CRLF line endings, lines folded at 75 octets, and
+/// text values escaped
+#[derive(Default)]
+struct Ics(String);
+
+impl Ics {
+    fn property(&mut self, name: &str, value: &str) {
+        self.line(&format!("{name}:{}", escape_text(value)));
+    }
+
+    fn line(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                self.0.push_str("\r\n ");
+                width = 1;
+            }
+            self.0.push(c);
+            width += c.len_utf8();
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use chrono::NaiveTime;
+    use db::cooking::{MealPlan, MealPlanEntry, Recipe};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::http_server::test_helpers::create_test_app_with_pool;
+
+    #[test]
+    fn escapes_and_folds_long_lines() {
+        let mut ics = Ics::default();
+        ics.property("SUMMARY", &format!("Dinner; tacos, {}", "x".repeat(80)));
+
+        assert!(ics.0.starts_with("SUMMARY:Dinner\\; tacos\\, "));
+        let lines: Vec<_> = ics.0.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|l| l.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+
+    async fn get(pool: PgPool, uri: &str) -> (StatusCode, String) {
+        let app = create_test_app_with_pool(pool).await;
+        let resp = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn feed_emits_meals_at_configured_times_with_prep_reminders(pool: PgPool) {
+        let feed = MealPlanCalendarFeed::create(&pool, "Family".to_string())
+            .await
+            .unwrap();
+        MealPlanCalendarFeed::update_times(
+            &pool,
+            feed.calendar_feed_id,
+            feed.breakfast_time,
+            feed.lunch_time,
+            NaiveTime::from_hms_opt(18, 30, 0).unwrap(),
+            feed.snack_time,
+        )
+        .await
+        .unwrap();
+
+        let date = Utc::now().date_naive() + Duration::days(1);
+        let plan = MealPlan::create(&pool, "This week".to_string(), date, date, None)
+            .await
+            .unwrap();
+        let recipe = Recipe::create(
+            &pool,
+            "Chili".to_string(),
+            None,
+            Some(15),
+            Some(45),
+            6,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        MealPlanEntry::create(
+            &pool,
+            plan.meal_plan_id,
+            recipe.recipe_id,
+            date,
+            Some(MealType::Dinner),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let (status, body) = get(pool.clone(), &feed_path(&feed)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let day = date.format("%Y%m%d");
+        assert!(body.contains(&format!("DTSTART:{day}T183000\r\n")));
+        assert!(body.contains("SUMMARY:Dinner: Chili\r\n"));
+        assert!(body.contains(&format!("DTSTART:{day}T173000\r\n")));
+        assert!(body.contains("SUMMARY:Start cooking: Chili\r\n"));
+        assert!(body.contains("BEGIN:VALARM"));
+
+        let (status, _) = get(pool, "/meal-plans/calendar/not-a-token.ics").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod account;
+pub mod activitypub;
 pub mod admin;
 pub mod blog;
 pub mod bytes;
@@ -6,10 +7,15 @@ pub mod contact;
 pub mod home;
 pub mod legal;
 pub mod login;
+pub mod meal_calendar;
+pub mod newsletter;
 pub mod notes;
 pub mod og;
 pub mod pace;
 pub mod podcast;
 pub mod projects;
+pub mod recipes;
+pub mod search;
+pub mod tags;
 pub mod videos;
 pub mod well_known;
@@ -0,0 +1,318 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use cja::{
+    color_eyre::eyre::{eyre, Context as _},
+    jobs::Job as _,
+};
+use db::newsletter_subscribers::NewsletterSubscriber;
+use maud::{html, Markup};
+use uuid::Uuid;
+
+use crate::{
+    http_server::{
+        errors::{ServerError, WithStatus as _},
+        templates::{base_constrained, header::OpenGraph},
+        ResponseResult,
+    },
+    jobs::newsletter_delivery::SendNewsletterConfirmation,
+    newsletter::{verify, LinkAction},
+    AppState,
+};
+
+/// Where the signup form posted before we sent the newsletter ourselves. Signups still go
+/// here when SMTP isn't set up.
+const BUTTONDOWN_SUBSCRIBE_URL: &str = "https://buttondown.com/api/emails/embed-subscribe/coreyja";
+
+/// How long before signing up again sends another confirmation email, so the form can't be
+/// used to flood someone's inbox
+const CONFIRMATION_RESEND_MINUTES: i64 = 15;
+
+#[derive(serde::Deserialize)]
+pub(crate) struct SubscribeForm {
+    email: String,
+}
+
+fn message_page(title: &str, message: &str) -> Markup {
+    base_constrained(
+        html! {
+            h1 class="my-4 text-2xl" { (title) }
+            p class="my-4 max-w-prose" { (message) }
+        },
+        OpenGraph::default(),
+    )
+}
+
+fn check_link(
+    state: &AppState,
+    action: LinkAction,
+    subscriber_id: Uuid,
+    token: &str,
+) -> Result<(), ServerError> {
+    let signed = state
+        .newsletter_link_key
+        .as_ref()
+        .is_some_and(|key| verify(key.as_bytes(), action, subscriber_id, token));
+    if signed {
+        Ok(())
+    } else {
+        Err(ServerError(
+            eyre!("This link isn't valid, try copying it from the email again"),
+            StatusCode::NOT_FOUND,
+        ))
+    }
+}
+
+/// Sign up from the footer form. The address isn't subscribed until it's confirmed from
+/// the email this sends. Every signup gets the same response, whether or not an email was
+/// sent, so the form doesn't reveal who has already signed up.
+pub(crate) async fn subscribe(
+    State(state): State<AppState>,
+    Form(form): Form<SubscribeForm>,
+) -> ResponseResult {
+    if state.mailer.is_none() {
+        // A 307 keeps the method and body, so Buttondown gets the same form post
+        return Ok(Redirect::temporary(BUTTONDOWN_SUBSCRIBE_URL).into_response());
+    }
+
+    let email: lettre::Address = form
+        .email
+        .trim()
+        .parse()
+        .context("That doesn't look like an email address")
+        .with_status(StatusCode::BAD_REQUEST)?;
+    let subscriber = NewsletterSubscriber::subscribe(&state.db, email.as_ref()).await?;
+
+    let resend_after = chrono::Duration::minutes(CONFIRMATION_RESEND_MINUTES);
+    if !subscriber.is_active()
+        && NewsletterSubscriber::claim_confirmation(
+            &state.db,
+            subscriber.newsletter_subscriber_id,
+            resend_after,
+        )
+        .await?
+    {
+        SendNewsletterConfirmation {
+            newsletter_subscriber_id: subscriber.newsletter_subscriber_id,
+        }
+        .enqueue(state.clone(), "Newsletter signup".to_string(), None)
+        .await
+        .context("Could not enqueue newsletter confirmation")?;
+    }
+
+    Ok(message_page(
+        "Check your inbox",
+        "We sent you an email with a link to confirm your subscription to coreyja weekly.",
+    )
+    .into_response())
+}
+
+pub(crate) async fn confirm(
+    State(state): State<AppState>,
+    Path((subscriber_id, token)): Path<(Uuid, String)>,
+) -> ResponseResult {
+    check_link(&state, LinkAction::Confirm, subscriber_id, &token)?;
+
+    let page = match NewsletterSubscriber::confirm(&state.db, subscriber_id).await? {
+        Some(_) => message_page(
+            "You're subscribed!",
+            "Thanks for confirming, the next issue of coreyja weekly will be in your inbox.",
+        ),
+        None => message_page(
+            "This link has expired",
+            "You've unsubscribed since signing up. Sign up again below to get the newsletter.",
+        ),
+    };
+
+    Ok(page.into_response())
+}
+
+/// Asks before unsubscribing, since mail scanners follow links in emails
+pub(crate) async fn unsubscribe_page(
+    State(state): State<AppState>,
+    Path((subscriber_id, token)): Path<(Uuid, String)>,
+) -> ResponseResult {
+    check_link(&state, LinkAction::Unsubscribe, subscriber_id, &token)?;
+
+    Ok(base_constrained(
+        html! {
+            h1 class="my-4 text-2xl" { "Unsubscribe from coreyja weekly" }
+            p class="my-4 max-w-prose" { "You won't get any more newsletters after this." }
+            form method="post" {
+                input type="submit" value="Unsubscribe" class="bg-berryBlue rounded-lg px-8 py-2 cursor-pointer";
+            }
+        },
+        OpenGraph::default(),
+    )
+    .into_response())
+}
+
+/// Also where mail clients' own unsubscribe buttons post to, from the `List-Unsubscribe`
+/// header
+pub(crate) async fn unsubscribe(
+    State(state): State<AppState>,
+    Path((subscriber_id, token)): Path<(Uuid, String)>,
+) -> ResponseResult {
+    check_link(&state, LinkAction::Unsubscribe, subscriber_id, &token)?;
+
+    NewsletterSubscriber::unsubscribe(&state.db, subscriber_id).await?;
+
+    Ok(message_page(
+        "You've been unsubscribed",
+        "Sorry to see you go! You won't get any more emails from coreyja weekly.",
+    )
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::{
+        http_server::{
+            routes::make_router,
+            test_helpers::{create_test_app, create_test_state},
+        },
+        newsletter::{mailer::Mailer, sign},
+    };
+
+    use super::*;
+
+    fn subscribe_request(email: &str) -> Request<Body> {
+        let body = serde_urlencoded::to_string([("email", email)]).unwrap();
+        Request::builder()
+            .method("POST")
+            .uri("/newsletter/subscribe")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn signups_go_to_buttondown_without_smtp() {
+        let resp = create_test_app()
+            .await
+            .oneshot(subscribe_request("reader@example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(resp.headers()["location"], BUTTONDOWN_SUBSCRIBE_URL);
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn signing_up_sends_a_confirmation_link_that_subscribes(pool: PgPool) {
+        let mut state = create_test_state(pool.clone()).await;
+        // Nothing is sent in the request, the confirmation email is a job
+        state.mailer = Some(
+            Mailer::new("smtp://127.0.0.1:1025", "news@coreyja.com".parse().unwrap()).unwrap(),
+        );
+        let key = state.newsletter_link_key.clone().unwrap();
+        let app = make_router().with_state(state);
+
+        let resp = app
+            .clone()
+            .oneshot(subscribe_request("not an email"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = app
+            .clone()
+            .oneshot(subscribe_request("Reader@Example.com"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let subscriber_id: Uuid = sqlx::query_scalar(
+            "SELECT (payload->>'newsletter_subscriber_id')::uuid FROM jobs WHERE name = 'SendNewsletterConfirmation'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let forged = sign(b"some other key", LinkAction::Confirm, subscriber_id);
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::get(format!("/newsletter/confirm/{subscriber_id}/{forged}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let token = sign(key.as_bytes(), LinkAction::Confirm, subscriber_id);
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::get(format!("/newsletter/confirm/{subscriber_id}/{token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let subscriber = NewsletterSubscriber::find(&pool, subscriber_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(subscriber.email, "reader@example.com");
+        assert!(subscriber.is_active());
+
+        let token = sign(key.as_bytes(), LinkAction::Unsubscribe, subscriber_id);
+        let resp = app
+            .oneshot(
+                Request::post(format!("/newsletter/unsubscribe/{subscriber_id}/{token}"))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from("List-Unsubscribe=One-Click"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let subscriber = NewsletterSubscriber::find(&pool, subscriber_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!subscriber.is_active());
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn signing_up_again_soon_does_not_resend_the_confirmation(pool: PgPool) {
+        let mut state = create_test_state(pool.clone()).await;
+        state.mailer = Some(
+            Mailer::new("smtp://127.0.0.1:1025", "news@coreyja.com".parse().unwrap()).unwrap(),
+        );
+        let app = make_router().with_state(state);
+
+        let mut pages = vec![];
+        for _ in 0..2 {
+            let resp = app
+                .clone()
+                .oneshot(subscribe_request("reader@example.com"))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            pages.push(body);
+        }
+        assert_eq!(pages[0], pages[1]);
+
+        let queued: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM jobs WHERE name = 'SendNewsletterConfirmation'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(queued, 1);
+    }
+}
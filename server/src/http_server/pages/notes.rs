@@ -3,18 +3,22 @@ use std::sync::Arc;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
 };
 
 use cja::Result;
 use maud::{html, Markup};
-use posts::notes::NotePosts;
+use posts::{
+    notes::{NotePost, NotePosts},
+    publish::Publishable,
+};
 use tracing::instrument;
 
 use crate::{
-    bsky::fetch_thread,
+    bsky::cached_thread,
     http_server::{
+        auth::session::AdminUser,
         errors::ServerError,
+        feeds::{self, publication_title, Feed, FeedFormat, FeedLink},
         pages::blog::md::html::{IntoHtml, MarkdownRenderContext},
         templates::{base_constrained, header::OpenGraph, post_templates::NotePostList, ShortDesc},
         LinkTo, ResponseResult,
@@ -22,36 +26,50 @@ use crate::{
     AppState,
 };
 
-use super::blog::MyChannel;
+use super::tags::{all_content, series_nav, tag_links};
 
 #[instrument(skip_all)]
 pub(crate) async fn notes_index(
     State(state): State<AppState>,
     State(note_posts): State<Arc<NotePosts>>,
 ) -> Result<Markup, StatusCode> {
-    let posts = note_posts.by_recency();
+    let posts = note_posts.published();
 
     Ok(base_constrained(
         html! {
           h1 class="text-3xl" { "Notes" }
           (NotePostList(posts))
         },
-        OpenGraph::default_for_path(&state.app, "/notes"),
+        OpenGraph {
+            feeds: feed_links(),
+            ..OpenGraph::default_for_path(&state.app, "/notes")
+        },
     ))
 }
 
+/// Title of the notes feed when `publications.toml` has no `notes` entry
+const FEED_TITLE: &str = "coreyja Notes";
+
+pub(crate) fn feed_links() -> Vec<FeedLink> {
+    feeds::feed_links("/notes", publication_title("notes").unwrap_or(FEED_TITLE))
+}
+
 #[instrument(skip_all)]
-pub(crate) async fn rss_feed(
+pub(crate) async fn notes_feed(
     State(state): State<AppState>,
     State(posts): State<Arc<NotePosts>>,
+    format: FeedFormat,
 ) -> ResponseResult {
-    let channel = MyChannel::from_posts(
+    let feed = Feed::from_posts(
         &state.app,
         &state.syntax_highlighting_context,
-        &posts.by_recency(),
-    )?;
+        "/notes",
+        &posts.published(),
+    )?
+    .with_title(FEED_TITLE)
+    .with_publication("notes");
 
-    Ok(channel.into_response())
+    Ok(feed.into_response(format))
 }
 
 #[instrument(skip(note_posts, state))]
@@ -60,32 +78,68 @@ pub(crate) async fn notes_get(
     State(state): State<AppState>,
     Path(slug): Path<String>,
 ) -> ResponseResult<Markup> {
-    let notes = &note_posts.posts;
+    let note = note_posts
+        .posts
+        .iter()
+        .find(|p| p.frontmatter.slug == slug && p.is_published())
+        .ok_or_else(|| {
+            ServerError(
+                cja::color_eyre::eyre::eyre!("No such note found"),
+                StatusCode::NOT_FOUND,
+            )
+        })?;
 
-    let note = notes
+    render_note(&state, &note_posts, note).await
+}
+
+/// Admin-only view of any note, including drafts and scheduled notes that [`notes_get`]
+/// hides
+#[instrument(skip(note_posts, state, _admin))]
+pub(crate) async fn note_preview(
+    _admin: AdminUser,
+    State(note_posts): State<Arc<NotePosts>>,
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> ResponseResult<Markup> {
+    let note = note_posts
+        .posts
         .iter()
         .find(|p| p.frontmatter.slug == slug)
         .ok_or_else(|| {
             ServerError(
                 cja::color_eyre::eyre::eyre!("No such note found"),
-                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::NOT_FOUND,
             )
         })?;
 
+    render_note(&state, &note_posts, note).await
+}
+
+async fn render_note(
+    state: &AppState,
+    note_posts: &NotePosts,
+    note: &NotePost,
+) -> ResponseResult<Markup> {
     let markdown = note.markdown();
 
     let bsky_thread = if let Some(bsky_post_url) = &note.frontmatter.bsky_url {
-        match fetch_thread(bsky_post_url).await {
-            Ok(thread) => Some((bsky_post_url.as_str(), thread)),
-            Err(e) => {
-                tracing::warn!(?e, "Failed to fetch Bluesky thread for note");
-                None
-            }
-        }
+        cached_thread(state, bsky_post_url)
+            .await
+            .map(|thread| (bsky_post_url.as_str(), thread))
+    } else {
+        None
+    };
+
+    let mastodon_thread = if let Some(status_url) = &note.frontmatter.mastodon_url {
+        crate::mastodon::replies::cached_thread(state, status_url)
+            .await
+            .map(|thread| (status_url.as_str(), thread))
     } else {
         None
     };
 
+    let mentions = super::blog::load_webmentions(state, &note.relative_link()).await;
+
     let card_route_path = format!("/og/notes/{}.svg", note.frontmatter.slug);
     let og_image = crate::http_server::templates::og::og_image_url(&state.app, &card_route_path);
 
@@ -102,21 +156,42 @@ pub(crate) async fn notes_get(
         .and_utc()
         .to_rfc3339();
 
+    let blog_posts = state.blog_posts();
+
     Ok(base_constrained(
         html! {
+          (super::blog::unpublished_banner(note))
           h1 class="text-2xl" { (markdown.title) }
           subtitle class="block text-lg text-subtitle mb-8 " { (markdown.date) }
+          (tag_links(&note.frontmatter.tags))
 
           div {
             (markdown.ast.into_html(&state.app, &MarkdownRenderContext { syntax_highlighting: state.syntax_highlighting_context.clone(), current_article_path: note.relative_link() })?)
           }
 
+          @if let Some(nav) = series_nav(&all_content(&blog_posts, note_posts), &note.relative_link()) {
+            (nav)
+          }
+
           @if let Some((bsky_url, thread)) = bsky_thread {
             div class="mt-8" {
               (super::blog::bluesky_post_stats(bsky_url, &thread))
               (super::blog::bsky_comments(bsky_url, thread))
             }
           }
+
+          @if let Some((status_url, thread)) = mastodon_thread {
+            div class="mt-8" {
+              (super::blog::mastodon_post_stats(status_url, &thread))
+              (super::blog::mastodon_comments(status_url, &thread))
+            }
+          }
+
+          @if !mentions.is_empty() {
+            div class="mt-8" {
+              (super::blog::webmentions(&mentions))
+            }
+          }
         },
         OpenGraph {
             title: title.clone(),
@@ -134,6 +209,11 @@ pub(crate) async fn notes_get(
             published_time: Some(published_time),
             author: Some("Corey Alexander".to_string()),
             tags: note.frontmatter.tags.clone(),
+            head_links: super::blog::standard_site_head_links(
+                note.frontmatter.atproto_uri.as_deref(),
+                "notes",
+            ),
+            feeds: feed_links(),
             ..Default::default()
         },
     ))
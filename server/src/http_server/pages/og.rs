@@ -1,5 +1,6 @@
 //! OG card SVG endpoints. These return raw SVG; `imgproxy` is responsible for rasterizing
-//! and caching the PNG version that social scrapers actually consume.
+//! and caching the PNG version that social scrapers actually consume. Drafts and scheduled
+//! posts and notes get no card until they're published, the same as their pages.
 
 use std::sync::{Arc, LazyLock};
 
@@ -8,10 +9,14 @@ use axum::{
     http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use posts::{blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes};
-
-use crate::http_server::templates::og::{
-    fetch_youtube_thumbnail_b64, render_card_svg, render_publication_card_svg, CardData, CardTag,
+use posts::{blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, publish::Publishable};
+
+use crate::{
+    http_server::templates::og::{
+        fetch_youtube_thumbnail_b64, render_card_svg, render_publication_card_svg, CardData,
+        CardTag,
+    },
+    AppState,
 };
 
 static PUBLICATIONS_TOML: &str =
@@ -37,8 +42,7 @@ static PUBLICATION_CARD_STUBS: LazyLock<Vec<PublicationCardStub>> = LazyLock::ne
 
 /// Map a publication key to the `CardTag` that styles its OG card. New
 /// publications need an entry here so their cover card matches the per-post
-/// card style for that content type. Defaults to `Posts` for unknown keys —
-/// only "blog" exists today.
+/// card style for that content type. Defaults to `Posts` for unknown keys.
 fn publication_card_tag(key: &str) -> CardTag {
     match key {
         "podcast" => CardTag::Podcast,
@@ -65,13 +69,14 @@ pub async fn og_post_svg(
     let post = posts
         .posts()
         .iter()
-        .find(|p| !p.frontmatter.is_newsletter && p.og_slug() == slug)
+        .find(|p| !p.frontmatter.is_newsletter && p.og_slug() == slug && p.is_published())
         .ok_or(StatusCode::NOT_FOUND)?;
     let data = CardData {
         title: &post.frontmatter.title,
         date: post.frontmatter.date,
         tag: CardTag::Posts,
         subtitle: post.frontmatter.subtitle.as_deref(),
+        reading_minutes: Some(post.reading_stats.reading_minutes),
         youtube_thumbnail_b64: None,
     };
     Ok(svg_response(render_card_svg(&data)))
@@ -85,13 +90,14 @@ pub async fn og_weekly_svg(
     let post = posts
         .posts()
         .iter()
-        .find(|p| p.frontmatter.is_newsletter && p.og_slug() == slug)
+        .find(|p| p.frontmatter.is_newsletter && p.og_slug() == slug && p.is_published())
         .ok_or(StatusCode::NOT_FOUND)?;
     let data = CardData {
         title: &post.frontmatter.title,
         date: post.frontmatter.date,
         tag: CardTag::Newsletter,
         subtitle: post.frontmatter.subtitle.as_deref(),
+        reading_minutes: Some(post.reading_stats.reading_minutes),
         youtube_thumbnail_b64: None,
     };
     Ok(svg_response(render_card_svg(&data)))
@@ -105,13 +111,39 @@ pub async fn og_note_svg(
     let note = notes
         .posts
         .iter()
-        .find(|p| p.frontmatter.slug == slug)
+        .find(|p| p.frontmatter.slug == slug && p.is_published())
         .ok_or(StatusCode::NOT_FOUND)?;
     let data = CardData {
         title: &note.frontmatter.title,
         date: note.frontmatter.date,
         tag: CardTag::Notes,
         subtitle: None,
+        reading_minutes: None,
+        youtube_thumbnail_b64: None,
+    };
+    Ok(svg_response(render_card_svg(&data)))
+}
+
+/// Recipe cards come from the cooking tables rather than the embedded content, so only
+/// recipes that have been made public get a card.
+pub async fn og_recipe_svg(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Response, StatusCode> {
+    let slug = slug.strip_suffix(".svg").unwrap_or(&slug);
+    let recipe = db::cooking::Recipe::get_public_by_slug(&state.db, slug)
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "Failed to load recipe for OG card");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let data = CardData {
+        title: &recipe.name,
+        date: recipe.created_at.date_naive(),
+        tag: CardTag::Recipes,
+        subtitle: recipe.description.as_deref(),
+        reading_minutes: None,
         youtube_thumbnail_b64: None,
     };
     Ok(svg_response(render_card_svg(&data)))
@@ -150,6 +182,7 @@ pub async fn og_podcast_svg(
         date: ep.frontmatter.date,
         tag: CardTag::Podcast,
         subtitle: None,
+        reading_minutes: None,
         youtube_thumbnail_b64: thumbnail,
     };
     Ok(svg_response(render_card_svg(&data)))
@@ -160,12 +193,16 @@ mod tests {
     //! These tests exercise the real `make_router()` via `test_helpers::create_test_app`
     //! so route wiring, the `{slug}` path matcher, and `.svg` suffix stripping are all
     //! verified end-to-end against the same router that ships in production.
-    use crate::http_server::test_helpers::create_test_app;
+    use crate::http_server::test_helpers::{
+        create_test_app, create_test_app_with_content, write_test_content,
+    };
     use axum::{
         body::Body,
         http::{Request, StatusCode},
     };
-    use posts::{blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes};
+    use posts::{
+        blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, publish::Publishable,
+    };
     use tower::ServiceExt;
 
     fn fixtures() -> (BlogPosts, PodcastEpisodes) {
@@ -193,8 +230,8 @@ mod tests {
         let regular = blog
             .posts()
             .iter()
-            .find(|p| !p.frontmatter.is_newsletter)
-            .expect("at least one non-newsletter post in fixtures");
+            .find(|p| !p.frontmatter.is_newsletter && p.is_published())
+            .expect("at least one published non-newsletter post in fixtures");
         let slug = regular.og_slug();
 
         let resp = app
@@ -280,8 +317,8 @@ mod tests {
         let regular = blog
             .posts()
             .iter()
-            .find(|p| !p.frontmatter.is_newsletter)
-            .expect("at least one non-newsletter post in fixtures");
+            .find(|p| !p.frontmatter.is_newsletter && p.is_published())
+            .expect("at least one published non-newsletter post in fixtures");
         let slug = regular.og_slug();
         let resp = app
             .oneshot(
@@ -299,7 +336,11 @@ mod tests {
     async fn og_note_svg_returns_svg_for_known_slug() {
         let app = create_test_app().await;
         let notes = note_fixtures();
-        let note = notes.posts.first().expect("at least one note in fixtures");
+        let note = notes
+            .posts
+            .iter()
+            .find(|p| p.is_published())
+            .expect("at least one published note in fixtures");
         let slug = &note.frontmatter.slug;
 
         let resp = app
@@ -406,4 +447,51 @@ mod tests {
         // 200 regardless of whether YT thumbnail fetch succeeded — CI may have no outbound net.
         assert_eq!(resp.status(), StatusCode::OK);
     }
+
+    async fn status_of(app: axum::Router, uri: &str) -> StatusCode {
+        app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn drafts_and_scheduled_content_have_no_cards() {
+        let content = write_test_content(&[
+            (
+                "blog/live-post/index.md",
+                "---\ntitle: Live\ndate: 2026-01-01\n---\n\nBody\n",
+            ),
+            (
+                "blog/secret-post/index.md",
+                "---\ntitle: Secret\ndate: 2026-01-01\ndraft: true\n---\n\nBody\n",
+            ),
+            (
+                "blog/weekly/20990101/index.md",
+                "---\ntitle: Future newsletter\ndate: 2099-01-01\nis_newsletter: true\npublish_at: 2099-01-01T12:00:00Z\n---\n\nBody\n",
+            ),
+            (
+                "notes/later.md",
+                "---\ntitle: Later\ndate: 2099-01-01\nslug: later\npublish_at: 2099-01-01T12:00:00Z\n---\n\nBody\n",
+            ),
+        ]);
+        let app = create_test_app_with_content(content.path()).await;
+
+        assert_eq!(
+            status_of(app.clone(), "/og/posts/live-post.svg").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of(app.clone(), "/og/posts/secret-post.svg").await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status_of(app.clone(), "/og/weekly/20990101.svg").await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status_of(app, "/og/notes/later.svg").await,
+            StatusCode::NOT_FOUND
+        );
+    }
 }
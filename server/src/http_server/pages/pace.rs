@@ -82,7 +82,7 @@ fn in_window(date: NaiveDate, today: NaiveDate) -> bool {
 fn collect_artifacts(blog: &BlogPosts, notes: &NotePosts, today: NaiveDate) -> Vec<Artifact> {
     let mut artifacts: Vec<Artifact> = Vec::new();
 
-    for post in blog.posts() {
+    for post in blog.published() {
         if in_window(post.frontmatter.date, today) {
             artifacts.push(Artifact {
                 date: post.frontmatter.date,
@@ -93,7 +93,7 @@ fn collect_artifacts(blog: &BlogPosts, notes: &NotePosts, today: NaiveDate) -> V
         }
     }
 
-    for note in &notes.posts {
+    for note in notes.published() {
         if in_window(note.frontmatter.date, today) {
             artifacts.push(Artifact {
                 date: note.frontmatter.date,
@@ -182,6 +182,10 @@ pub(crate) async fn podcast_get(
             published_time: Some(published_time),
             author: Some("Corey Alexander".to_string()),
             tags: vec![],
+            head_links: super::blog::standard_site_head_links(
+                ep.frontmatter.atproto_uri.as_deref(),
+                "podcast",
+            ),
             ..OpenGraph::default()
         },
     ))
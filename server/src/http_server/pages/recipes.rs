@@ -0,0 +1,424 @@
+//! Public recipe pages. Recipes live in the cooking tables managed by the cooking agent;
+//! only the ones flipped to `is_public` are rendered here.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use db::cooking::{
+    format_quantity, NamedRecipeEquipment, NamedRecipeIngredient, Recipe, RecipeEquipment,
+    RecipeIngredient, RecipeStep, RecipeTag,
+};
+use maud::{html, Markup};
+use serde_json::json;
+use tracing::instrument;
+
+use crate::{
+    http_server::{
+        errors::ServerError,
+        templates::{base_constrained, header::OpenGraph, og::og_image_url},
+        ResponseResult,
+    },
+    AppState,
+};
+
+#[instrument(skip_all)]
+pub(crate) async fn recipes_index(State(state): State<AppState>) -> ResponseResult<Markup> {
+    let recipes = Recipe::list_public(&state.db).await?;
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl mb-8" { "Recipes" }
+
+          @if recipes.is_empty() {
+            p class="text-subtitle" { "No recipes have been shared yet." }
+          }
+
+          ul {
+            @for recipe in &recipes {
+              @if let Some(slug) = &recipe.slug {
+                li class="my-4" {
+                  a href=(recipe_path(slug)) {
+                    (recipe.name)
+                    @if let Some(total) = total_time(recipe) {
+                      span class="text-subtitle text-sm inline-block pl-4" { (format_minutes(total)) }
+                    }
+                  }
+                  @if let Some(description) = &recipe.description {
+                    p class="text-subtitle text-sm" { (description) }
+                  }
+                }
+              }
+            }
+          }
+        },
+        OpenGraph {
+            title: "Recipes".to_string(),
+            description: Some("Recipes from the coreyja kitchen".to_string()),
+            ..OpenGraph::default_for_path(&state.app, "/recipes")
+        },
+    ))
+}
+
+#[instrument(skip(state))]
+pub(crate) async fn recipes_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> ResponseResult<Markup> {
+    let recipe = Recipe::get_public_by_slug(&state.db, &slug)
+        .await?
+        .ok_or_else(|| {
+            ServerError(
+                cja::color_eyre::eyre::eyre!("No such recipe found"),
+                StatusCode::NOT_FOUND,
+            )
+        })?;
+
+    let ingredients = RecipeIngredient::get_named_by_recipe(&state.db, recipe.recipe_id).await?;
+    let steps = RecipeStep::get_by_recipe(&state.db, recipe.recipe_id).await?;
+    let equipment = RecipeEquipment::get_named_by_recipe(&state.db, recipe.recipe_id).await?;
+    let tags: Vec<String> = RecipeTag::get_by_recipe(&state.db, recipe.recipe_id)
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+
+    let canonical_url = state.app.app_url(&recipe_path(&slug));
+    let og_image = og_image_url(&state.app, &format!("/og/recipes/{slug}.svg"));
+
+    let json_ld = recipe_json_ld(&RecipeJsonLd {
+        recipe: &recipe,
+        ingredients: &ingredients,
+        steps: &steps,
+        equipment: &equipment,
+        tags: &tags,
+        url: &canonical_url,
+        image: &og_image,
+    });
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl" { (recipe.name) }
+          @if let Some(description) = &recipe.description {
+            p class="block text-lg text-subtitle mb-4" { (description) }
+          }
+
+          ul class="flex flex-wrap gap-x-6 gap-y-1 text-sm text-subtitle mb-4" {
+            li { "Serves " (recipe.servings) }
+            @if let Some(prep) = recipe.prep_time {
+              li { "Prep " (format_minutes(prep)) }
+            }
+            @if let Some(cook) = recipe.cook_time {
+              li { "Cook " (format_minutes(cook)) }
+            }
+          }
+
+          @if !tags.is_empty() {
+            div class="flex flex-wrap gap-2 mb-8" {
+              @for tag in &tags {
+                span class="rounded-md px-2 py-1 text-xs font-medium text-text ring-1 ring-inset ring-grey-800" { (tag) }
+              }
+            }
+          }
+
+          @if !ingredients.is_empty() {
+            h2 class="text-xl mt-8 mb-2" { "Ingredients" }
+            @for (group, lines) in group_ingredients(&ingredients) {
+              @if let Some(group) = group {
+                h3 class="text-lg mt-4" { (group) }
+              }
+              ul class="list-disc pl-6" {
+                @for ingredient in lines {
+                  li class="my-1" { (ingredient_line(ingredient)) }
+                }
+              }
+            }
+          }
+
+          @if !equipment.is_empty() {
+            h2 class="text-xl mt-8 mb-2" { "Equipment" }
+            ul class="list-disc pl-6" {
+              @for item in &equipment {
+                li class="my-1" {
+                  (item.equipment_name)
+                  @if item.is_optional == Some(true) {
+                    span class="text-subtitle" { " (optional)" }
+                  }
+                  @if let Some(notes) = &item.notes {
+                    span class="text-subtitle" { " — " (notes) }
+                  }
+                }
+              }
+            }
+          }
+
+          @if !steps.is_empty() {
+            h2 class="text-xl mt-8 mb-2" { "Steps" }
+            ol class="list-decimal pl-6" {
+              @for step in &steps {
+                li class="my-2" {
+                  (step.instruction)
+                  @if let Some(details) = step_details(step) {
+                    span class="text-subtitle text-sm pl-2" { "(" (details) ")" }
+                  }
+                }
+              }
+            }
+          }
+        },
+        OpenGraph {
+            title: recipe.name.clone(),
+            r#type: "article".to_string(),
+            description: recipe.description.clone(),
+            image: Some(og_image),
+            image_width: Some(1200),
+            image_height: Some(630),
+            image_alt: Some(recipe.name.clone()),
+            url: canonical_url,
+            site_name: Some("coreyja".to_string()),
+            locale: Some("en_US".to_string()),
+            twitter_site: Some("@coreyja.com".to_string()),
+            published_time: Some(recipe.created_at.to_rfc3339()),
+            author: Some("Corey Alexander".to_string()),
+            tags,
+            json_ld: Some(json_ld),
+            ..OpenGraph::default()
+        },
+    ))
+}
+
+fn recipe_path(slug: &str) -> String {
+    format!("/recipes/{slug}")
+}
+
+fn total_time(recipe: &Recipe) -> Option<i32> {
+    match (recipe.prep_time, recipe.cook_time) {
+        (None, None) => None,
+        (prep, cook) => Some(prep.unwrap_or(0) + cook.unwrap_or(0)),
+    }
+}
+
+fn format_minutes(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m} min"),
+        (h, 0) => format!("{h} hr"),
+        (h, m) => format!("{h} hr {m} min"),
+    }
+}
+
+/// ISO 8601 duration as expected by schema.org, e.g. 90 -> `PT1H30M`
+fn iso8601_duration(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("PT{m}M"),
+        (h, 0) => format!("PT{h}H"),
+        (h, m) => format!("PT{h}H{m}M"),
+    }
+}
+
+fn ingredient_line(ingredient: &NamedRecipeIngredient) -> String {
+    let mut line = format!(
+        "{} {} {}",
+        format_quantity(&ingredient.quantity),
+        ingredient.unit_name,
+        ingredient.ingredient_name
+    );
+    if let Some(preparation) = &ingredient.preparation {
+        line.push_str(&format!(", {preparation}"));
+    }
+    if let Some(notes) = &ingredient.notes {
+        line.push_str(&format!(" ({notes})"));
+    }
+    if ingredient.is_optional == Some(true) {
+        line.push_str(" (optional)");
+    }
+    line
+}
+
+fn step_details(step: &RecipeStep) -> Option<String> {
+    let mut details = vec![];
+    if let Some(duration) = step.duration {
+        details.push(format_minutes(duration));
+    }
+    if let Some(temperature) = step.temperature {
+        let unit = step
+            .temperature_unit
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        details.push(format!("{temperature}°{unit}"));
+    }
+
+    if details.is_empty() {
+        None
+    } else {
+        Some(details.join(", "))
+    }
+}
+
+/// Group consecutive ingredients by their `ingredient_group`, keeping the recipe's order
+fn group_ingredients(
+    ingredients: &[NamedRecipeIngredient],
+) -> Vec<(Option<&str>, Vec<&NamedRecipeIngredient>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&NamedRecipeIngredient>)> = vec![];
+    for ingredient in ingredients {
+        let group = ingredient.ingredient_group.as_deref();
+        match groups.last_mut() {
+            Some((last, lines)) if *last == group => lines.push(ingredient),
+            _ => groups.push((group, vec![ingredient])),
+        }
+    }
+    groups
+}
+
+struct RecipeJsonLd<'a> {
+    recipe: &'a Recipe,
+    ingredients: &'a [NamedRecipeIngredient],
+    steps: &'a [RecipeStep],
+    equipment: &'a [NamedRecipeEquipment],
+    tags: &'a [String],
+    url: &'a str,
+    image: &'a str,
+}
+
+/// Build the schema.org `Recipe` document for a recipe page. The result is embedded in a
+/// `<script>` tag, so `</` is escaped to keep recipe text from closing it early.
+fn recipe_json_ld(data: &RecipeJsonLd<'_>) -> String {
+    let recipe = data.recipe;
+
+    let mut doc = json!({
+        "@context": "https://schema.org",
+        "@type": "Recipe",
+        "name": recipe.name,
+        "url": data.url,
+        "image": [data.image],
+        "author": { "@type": "Person", "name": "Corey Alexander" },
+        "datePublished": recipe.created_at.date_naive().to_string(),
+        "recipeYield": format!("{} servings", recipe.servings),
+        "recipeIngredient": data.ingredients.iter().map(ingredient_line).collect::<Vec<_>>(),
+        "recipeInstructions": data.steps.iter().map(|step| json!({
+            "@type": "HowToStep",
+            "position": step.step_number,
+            "text": step.instruction,
+        })).collect::<Vec<_>>(),
+    });
+
+    if let Some(description) = &recipe.description {
+        doc["description"] = json!(description);
+    }
+    if let Some(prep) = recipe.prep_time {
+        doc["prepTime"] = json!(iso8601_duration(prep));
+    }
+    if let Some(cook) = recipe.cook_time {
+        doc["cookTime"] = json!(iso8601_duration(cook));
+    }
+    if let Some(total) = total_time(recipe) {
+        doc["totalTime"] = json!(iso8601_duration(total));
+    }
+    if !data.equipment.is_empty() {
+        doc["tool"] = data
+            .equipment
+            .iter()
+            .map(|e| json!({ "@type": "HowToTool", "name": e.equipment_name }))
+            .collect();
+    }
+    if !data.tags.is_empty() {
+        doc["keywords"] = json!(data.tags.join(", "));
+    }
+
+    doc.to_string().replace("</", "<\\/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::http_server::test_helpers::create_test_app_with_pool;
+
+    #[test]
+    fn iso8601_duration_formats_hours_and_minutes() {
+        assert_eq!(iso8601_duration(15), "PT15M");
+        assert_eq!(iso8601_duration(60), "PT1H");
+        assert_eq!(iso8601_duration(90), "PT1H30M");
+    }
+
+    async fn create_recipe(pool: &PgPool, name: &str) -> Recipe {
+        Recipe::create(
+            pool,
+            name.to_string(),
+            Some("Cheesy </script> goodness".to_string()),
+            Some(10),
+            Some(80),
+            4,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn get(pool: PgPool, uri: &str) -> (StatusCode, String) {
+        let app = create_test_app_with_pool(pool).await;
+        let resp = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn public_recipe_renders_with_json_ld(pool: PgPool) {
+        let recipe = create_recipe(&pool, "Mom's Mac & Cheese").await;
+        let recipe = recipe.set_public(&pool, true).await.unwrap();
+        assert_eq!(recipe.slug.as_deref(), Some("moms-mac-cheese"));
+
+        let (status, body) = get(pool.clone(), "/recipes/moms-mac-cheese").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"<script type="application/ld+json">"#));
+        assert!(body.contains(r#""@type":"Recipe""#));
+        assert!(body.contains(r#""totalTime":"PT1H30M""#));
+        assert!(
+            !body.contains("Cheesy </script>"),
+            "JSON-LD must not be able to close its own script tag"
+        );
+
+        let (status, body) = get(pool, "/recipes").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"href="/recipes/moms-mac-cheese""#));
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn private_recipe_404s_and_keeps_slug_when_unpublished(pool: PgPool) {
+        let recipe = create_recipe(&pool, "Chili").await;
+        let published = recipe.set_public(&pool, true).await.unwrap();
+        let unpublished = published.set_public(&pool, false).await.unwrap();
+        assert_eq!(unpublished.slug.as_deref(), Some("chili"));
+
+        let (status, _) = get(pool.clone(), "/recipes/chili").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = get(pool, "/og/recipes/chili.svg").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn duplicate_names_get_distinct_slugs(pool: PgPool) {
+        let first = create_recipe(&pool, "Chili").await;
+        let second = create_recipe(&pool, "Chili").await;
+
+        let first = first.set_public(&pool, true).await.unwrap();
+        let second = second.set_public(&pool, true).await.unwrap();
+
+        assert_eq!(first.slug.as_deref(), Some("chili"));
+        assert_eq!(second.slug.as_deref(), Some("chili-2"));
+    }
+}
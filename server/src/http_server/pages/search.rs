@@ -0,0 +1,339 @@
+//! Site search across posts, notes, podcast episodes and projects. The index is built
+//! once at boot (see [`build_search_index`]) and backs both the `/search` page and the
+//! `/api/search` JSON endpoint.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+use maud::{html, Markup, Render};
+use posts::{
+    blog::BlogPosts,
+    notes::NotePosts,
+    plain::IntoPlainText,
+    podcast::PodcastEpisodes,
+    projects::Projects,
+    search::{ContentKind, SearchDocument, SearchHit, SearchIndex, Snippet},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    http_server::{
+        errors::ServerError,
+        templates::{base_constrained, header::OpenGraph},
+        LinkTo, ResponseResult,
+    },
+    AppState,
+};
+
+/// Most results shown on the search page
+const PAGE_LIMIT: usize = 50;
+
+const DEFAULT_API_LIMIT: usize = 20;
+
+pub(crate) fn build_search_index(
+    blog_posts: &BlogPosts,
+    note_posts: &NotePosts,
+    podcast_episodes: &PodcastEpisodes,
+    projects: &Projects,
+) -> cja::Result<SearchIndex> {
+    let mut documents = vec![];
+
+    // Drafts are never searchable; scheduled content is, once its time comes
+    for post in blog_posts.posts().iter().filter(|p| !p.frontmatter.draft) {
+        documents.push(SearchDocument {
+            kind: ContentKind::Post,
+            title: post.title().to_string(),
+            url: post.relative_link(),
+            date: Some(*post.date()),
+            tags: post.frontmatter.tags.clone(),
+            body: post.ast.0.plain_text(),
+            publish_at: post.frontmatter.publish_at,
+        });
+    }
+
+    for note in note_posts.posts.iter().filter(|n| !n.frontmatter.draft) {
+        documents.push(SearchDocument {
+            kind: ContentKind::Note,
+            title: note.frontmatter.title.clone(),
+            url: note.relative_link(),
+            date: Some(note.frontmatter.date),
+            tags: note.frontmatter.tags.clone(),
+            body: note.ast.0.plain_text(),
+            publish_at: note.frontmatter.publish_at,
+        });
+    }
+
+    for episode in &podcast_episodes.episodes {
+        documents.push(SearchDocument {
+            kind: ContentKind::Podcast,
+            title: episode.frontmatter.title.clone(),
+            url: episode.relative_link(),
+            date: Some(episode.frontmatter.date),
+            tags: vec![],
+            body: episode.ast.0.plain_text(),
+            publish_at: None,
+        });
+    }
+
+    for project in &projects.projects {
+        let body = project.ast.0.plain_text();
+        let body = match &project.frontmatter.subtitle {
+            Some(subtitle) => format!("{subtitle}\n{body}"),
+            None => body,
+        };
+
+        documents.push(SearchDocument {
+            kind: ContentKind::Project,
+            title: project.frontmatter.title.clone(),
+            url: project.relative_link()?,
+            date: None,
+            tags: vec![],
+            body,
+            publish_at: None,
+        });
+    }
+
+    Ok(SearchIndex::new(documents))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SearchParams {
+    #[serde(default)]
+    q: String,
+    kind: Option<String>,
+    limit: Option<usize>,
+}
+
+impl SearchParams {
+    /// An empty `kind` means every kind, so the page's "Everything" option can submit `kind=`
+    fn kind(&self) -> Result<Option<ContentKind>, ServerError> {
+        self.kind
+            .as_deref()
+            .filter(|k| !k.is_empty())
+            .map(|k| {
+                k.parse().map_err(|e: String| {
+                    ServerError(cja::color_eyre::eyre::eyre!(e), StatusCode::BAD_REQUEST)
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Search `index`, keeping only hits of `kind` when one is given
+pub(crate) fn search<'a>(
+    index: &'a SearchIndex,
+    query: &str,
+    kind: Option<ContentKind>,
+    limit: usize,
+) -> Vec<SearchHit<'a>> {
+    index
+        .search(query)
+        .into_iter()
+        .filter(|hit| kind.is_none_or(|kind| hit.document.kind == kind))
+        .take(limit)
+        .collect()
+}
+
+#[instrument(skip(state))]
+pub(crate) async fn search_page(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> ResponseResult<Markup> {
+    let kind = params.kind()?;
+    let query = params.q.trim();
+    let search_index = state.search_index();
+    let hits = search(&search_index, query, kind, PAGE_LIMIT);
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl" { "Search" }
+
+          form method="get" action="/search" class="my-8 flex flex-col sm:flex-row gap-4" {
+            input
+              type="search"
+              name="q"
+              value=(query)
+              placeholder="Search posts, notes, podcast episodes and projects"
+              aria-label="Search"
+              class="flex-grow py-2 px-2 rounded-md text-grey-999"
+              ;
+
+            select name="kind" aria-label="Content type" class="py-2 px-2 rounded-md text-grey-999" {
+              option value="" selected[kind.is_none()] { "Everything" }
+              @for option in ContentKind::ALL {
+                option value=(option) selected[kind == Some(option)] { (KindLabel(option)) }
+              }
+            }
+
+            input type="submit" value="Search" class="bg-berryBlue rounded-lg px-8 py-2";
+          }
+
+          @if !query.is_empty() {
+            @if hits.is_empty() {
+              p class="text-subtitle" { "Nothing matched \"" (query) "\"." }
+            } @else {
+              ul {
+                @for hit in &hits {
+                  li class="my-8" {
+                    a href=(hit.document.url) class="text-lg underline" { (hit.document.title) }
+                    div class="text-subtitle text-sm" {
+                      (KindLabel(hit.document.kind))
+                      @if let Some(date) = hit.document.date {
+                        " · " (date)
+                      }
+                    }
+                    p class="max-w-prose leading-loose" { (SnippetHtml(&hit.snippet)) }
+                  }
+                }
+              }
+            }
+          }
+        },
+        OpenGraph {
+            title: "Search".to_string(),
+            ..OpenGraph::default_for_path(&state.app, "/search")
+        },
+    ))
+}
+
+struct KindLabel(ContentKind);
+
+impl Render for KindLabel {
+    fn render(&self) -> Markup {
+        let label = match self.0 {
+            ContentKind::Post => "Post",
+            ContentKind::Note => "Note",
+            ContentKind::Podcast => "Podcast",
+            ContentKind::Project => "Project",
+        };
+
+        html! { (label) }
+    }
+}
+
+struct SnippetHtml<'a>(&'a Snippet);
+
+impl Render for SnippetHtml<'_> {
+    fn render(&self) -> Markup {
+        html! {
+          @for part in &self.0.parts {
+            @if part.highlighted {
+              mark class="bg-warning-200 rounded-sm" { (part.text) }
+            } @else {
+              (part.text)
+            }
+          }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchResult {
+    kind: ContentKind,
+    title: String,
+    url: String,
+    date: Option<NaiveDate>,
+    score: f64,
+    snippet: Snippet,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchResponse {
+    query: String,
+    results: Vec<SearchResult>,
+}
+
+#[instrument(skip(state))]
+pub(crate) async fn search_json(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> ResponseResult<Json<SearchResponse>> {
+    let kind = params.kind()?;
+    let limit = params.limit.unwrap_or(DEFAULT_API_LIMIT).min(PAGE_LIMIT);
+    let query = params.q.trim();
+
+    let search_index = state.search_index();
+    let results = search(&search_index, query, kind, limit)
+        .into_iter()
+        .map(|hit| SearchResult {
+            kind: hit.document.kind,
+            title: hit.document.title.clone(),
+            url: state.app.app_url(&hit.document.url),
+            date: hit.document.date,
+            score: hit.score,
+            snippet: hit.snippet,
+        })
+        .collect();
+
+    Ok(Json(SearchResponse {
+        query: query.to_string(),
+        results,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::http_server::test_helpers::create_test_app;
+
+    async fn get(uri: &str) -> (StatusCode, String) {
+        let app = create_test_app().await;
+        let resp = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    #[test]
+    fn index_covers_every_kind_of_content() {
+        let index = build_search_index(
+            &BlogPosts::from_static_dir().unwrap(),
+            &NotePosts::from_static_dir().unwrap(),
+            &PodcastEpisodes::from_static_dir().unwrap(),
+            &Projects::from_static_dir().unwrap(),
+        )
+        .unwrap();
+
+        for kind in ContentKind::ALL {
+            assert!(
+                index.documents().iter().any(|d| d.kind == kind),
+                "no {kind} documents were indexed"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn search_page_highlights_matches() {
+        let (status, body) = get("/search?q=battlesnake").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("<mark"));
+    }
+
+    #[tokio::test]
+    async fn json_endpoint_filters_by_kind() {
+        let (status, body) = get("/api/search?q=battlesnake&kind=project&limit=5").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let results = json["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        assert!(results.len() <= 5);
+        assert!(results.iter().all(|r| r["kind"] == "project"));
+
+        let (status, _) = get("/api/search?q=battlesnake&kind=video").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,479 @@
+//! Tag and series pages. Blog posts and notes share both: `/tags/{tag}` lists everything
+//! with a tag (with feeds such as `/tags/{tag}/rss.xml`), and `/series/{series}` lists a
+//! series' parts in order, linked from each part by [`series_nav`].
+
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use chrono::NaiveDate;
+use maud::{html, Markup};
+use posts::{
+    blog::{BlogPost, BlogPosts},
+    notes::{NotePost, NotePosts},
+    tags::tag_slug,
+};
+use tracing::instrument;
+
+use crate::{
+    http_server::{
+        errors::ServerError,
+        feeds::{feed_links, Feed, FeedFormat, FeedItem, ToFeedItem},
+        pages::blog::md::SyntaxHighlightingContext,
+        templates::{base_constrained, header::OpenGraph},
+        LinkTo, ResponseResult,
+    },
+    AppConfig, AppState,
+};
+
+/// A blog post or note, the two kinds of content that carry tags and series
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TaggedContent<'a> {
+    Post(&'a BlogPost),
+    Note(&'a NotePost),
+}
+
+impl TaggedContent<'_> {
+    fn title(&self) -> &str {
+        match self {
+            TaggedContent::Post(p) => &p.frontmatter.title,
+            TaggedContent::Note(n) => &n.frontmatter.title,
+        }
+    }
+
+    fn date(&self) -> NaiveDate {
+        match self {
+            TaggedContent::Post(p) => p.frontmatter.date,
+            TaggedContent::Note(n) => n.frontmatter.date,
+        }
+    }
+
+    fn tags(&self) -> &[String] {
+        match self {
+            TaggedContent::Post(p) => &p.frontmatter.tags,
+            TaggedContent::Note(n) => &n.frontmatter.tags,
+        }
+    }
+
+    fn series(&self) -> Option<&str> {
+        match self {
+            TaggedContent::Post(p) => p.frontmatter.series.as_deref(),
+            TaggedContent::Note(n) => n.frontmatter.series.as_deref(),
+        }
+    }
+
+    fn relative_link(&self) -> String {
+        match self {
+            TaggedContent::Post(p) => p.relative_link(),
+            TaggedContent::Note(n) => n.relative_link(),
+        }
+    }
+
+    fn to_feed_item(
+        &self,
+        config: &AppConfig,
+        context: &SyntaxHighlightingContext,
+    ) -> cja::Result<FeedItem> {
+        match self {
+            TaggedContent::Post(p) => p.to_feed_item(config, context),
+            TaggedContent::Note(n) => n.to_feed_item(config, context),
+        }
+    }
+
+    fn has_tag(&self, slug: &str) -> bool {
+        self.tags().iter().any(|t| tag_slug(t) == slug)
+    }
+}
+
+/// Every published post and note, newest first
+pub(crate) fn all_content<'a>(
+    blog_posts: &'a BlogPosts,
+    note_posts: &'a NotePosts,
+) -> Vec<TaggedContent<'a>> {
+    let mut content: Vec<_> = blog_posts
+        .published()
+        .into_iter()
+        .map(TaggedContent::Post)
+        .chain(note_posts.published().into_iter().map(TaggedContent::Note))
+        .collect();
+    content.sort_by_key(|c| (std::cmp::Reverse(c.date()), c.relative_link()));
+
+    content
+}
+
+pub(crate) fn tag_path(tag: &str) -> String {
+    format!("/tags/{}", tag_slug(tag))
+}
+
+pub(crate) fn series_path(series: &str) -> String {
+    format!("/series/{}", tag_slug(series))
+}
+
+/// A tag or series name, grouped by slug, with the spelling used most often
+struct Group<'a> {
+    slug: String,
+    name: &'a str,
+    count: usize,
+}
+
+fn group_by_slug<'a>(names: impl Iterator<Item = &'a str>) -> Vec<Group<'a>> {
+    let mut spellings: BTreeMap<String, BTreeMap<&str, usize>> = BTreeMap::new();
+    for name in names {
+        *spellings
+            .entry(tag_slug(name))
+            .or_default()
+            .entry(name)
+            .or_default() += 1;
+    }
+
+    let mut groups: Vec<_> = spellings
+        .into_iter()
+        .filter(|(slug, _)| !slug.is_empty())
+        .map(|(slug, spellings)| Group {
+            count: spellings.values().sum(),
+            name: spellings
+                .iter()
+                .max_by_key(|(_, count)| **count)
+                .map(|(name, _)| *name)
+                .unwrap_or_default(),
+            slug,
+        })
+        .collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.slug.cmp(&b.slug)));
+
+    groups
+}
+
+fn content_list(content: &[TaggedContent<'_>]) -> Markup {
+    html! {
+      ul {
+        @for c in content {
+          li class="my-4" {
+            a href=(c.relative_link()) {
+              span class="text-subtitle text-sm inline-block w-[80px]" { (c.date()) }
+              " "
+              (c.title())
+              @if let TaggedContent::Note(_) = c {
+                span class="text-subtitle text-sm pl-2" { "(Note)" }
+              }
+            }
+          }
+        }
+      }
+    }
+}
+
+/// Links to each of the content's tag pages, for showing under its title
+pub(crate) fn tag_links(tags: &[String]) -> Markup {
+    html! {
+      @if !tags.is_empty() {
+        ul class="flex flex-wrap gap-x-4 gap-y-1 text-sm -mt-6 mb-8" {
+          @for tag in tags {
+            li {
+              a href=(tag_path(tag)) class="text-subtitle hover:underline" { "#" (tag) }
+            }
+          }
+        }
+      }
+    }
+}
+
+/// Previous/next links for content that is part of a series. Parts are ordered by date,
+/// then by path so parts published on the same day keep their file order.
+pub(crate) fn series_nav(content: &[TaggedContent<'_>], current_link: &str) -> Option<Markup> {
+    let current = content.iter().find(|c| c.relative_link() == current_link)?;
+    let series = current.series()?;
+    let parts = series_parts(content, &tag_slug(series));
+    let position = parts
+        .iter()
+        .position(|c| c.relative_link() == current_link)?;
+
+    let previous = position.checked_sub(1).map(|i| parts[i]);
+    let next = parts.get(position + 1);
+
+    Some(html! {
+      nav aria-label="Series" class="my-8 p-4 border border-berryBlue rounded-lg" {
+        p class="text-sm text-subtitle" {
+          "Part " (position + 1) " of " (parts.len()) " in "
+          a href=(series_path(series)) class="underline" { (series) }
+        }
+        div class="flex justify-between gap-4 mt-2" {
+          @if let Some(previous) = previous {
+            a href=(previous.relative_link()) rel="prev" class="hover:underline" { "← " (previous.title()) }
+          } @else {
+            span {}
+          }
+          @if let Some(next) = next {
+            a href=(next.relative_link()) rel="next" class="text-right hover:underline" { (next.title()) " →" }
+          }
+        }
+      }
+    })
+}
+
+fn series_parts<'a>(content: &[TaggedContent<'a>], slug: &str) -> Vec<TaggedContent<'a>> {
+    let mut parts: Vec<_> = content
+        .iter()
+        .filter(|c| c.series().is_some_and(|s| tag_slug(s) == slug))
+        .copied()
+        .collect();
+    parts.sort_by_key(|c| (c.date(), c.relative_link()));
+
+    parts
+}
+
+#[instrument(skip_all)]
+pub(crate) async fn tags_index(State(state): State<AppState>) -> ResponseResult<Markup> {
+    let (blog_posts, note_posts) = (state.blog_posts(), state.note_posts());
+    let content = all_content(&blog_posts, &note_posts);
+    let tags = group_by_slug(content.iter().flat_map(|c| c.tags()).map(String::as_str));
+    let series = group_by_slug(content.iter().filter_map(TaggedContent::series));
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl" { "Tags" }
+          ul class="my-8 flex flex-wrap gap-x-6 gap-y-2" {
+            @for tag in &tags {
+              li {
+                a href=(format!("/tags/{}", tag.slug)) class="hover:underline" {
+                  "#" (tag.name)
+                  span class="text-subtitle text-sm pl-1" { "(" (tag.count) ")" }
+                }
+              }
+            }
+          }
+
+          @if !series.is_empty() {
+            h2 class="text-2xl" { "Series" }
+            ul class="my-8" {
+              @for s in &series {
+                li class="my-4" {
+                  a href=(format!("/series/{}", s.slug)) class="hover:underline" {
+                    (s.name)
+                    span class="text-subtitle text-sm pl-2" { (s.count) " parts" }
+                  }
+                }
+              }
+            }
+          }
+        },
+        OpenGraph::default_for_path(&state.app, "/tags"),
+    ))
+}
+
+/// Serves `/tags/{tag}` and the tag's feeds, e.g. `/tags/{tag}/atom.xml`. A catch-all is
+/// used so old links with trailing slashes or other spellings of a tag still resolve, by
+/// redirecting to the canonical slug.
+#[instrument(skip(state))]
+pub(crate) async fn tag_get(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+) -> ResponseResult<Response> {
+    let trimmed = path.trim_matches('/');
+    let (tag, format) = match trimmed
+        .rsplit_once('/')
+        .and_then(|(tag, file_name)| Some((tag, FeedFormat::from_file_name(file_name)?)))
+    {
+        Some((tag, format)) => (tag, Some(format)),
+        None => (trimmed, None),
+    };
+
+    let slug = tag_slug(tag);
+    let (blog_posts, note_posts) = (state.blog_posts(), state.note_posts());
+    let content: Vec<_> = all_content(&blog_posts, &note_posts)
+        .into_iter()
+        .filter(|c| c.has_tag(&slug))
+        .collect();
+
+    if content.is_empty() {
+        if format.is_some() {
+            return Err(ServerError(
+                cja::color_eyre::eyre::eyre!("No such tag"),
+                StatusCode::NOT_FOUND,
+            ));
+        }
+
+        return Ok(Redirect::permanent("/tags").into_response());
+    }
+
+    let canonical = match format {
+        Some(format) => format!("{slug}/{}", format.file_name()),
+        None => slug.clone(),
+    };
+    if path.trim_start_matches('/') != canonical {
+        return Ok(Redirect::permanent(&format!("/tags/{canonical}")).into_response());
+    }
+
+    let name = group_by_slug(content.iter().flat_map(|c| c.tags()).map(String::as_str))
+        .into_iter()
+        .find(|g| g.slug == slug)
+        .map_or_else(|| slug.clone(), |g| g.name.to_string());
+    let feed_title = format!("coreyja: #{name}");
+
+    if let Some(format) = format {
+        let items = content
+            .iter()
+            .map(|c| c.to_feed_item(&state.app, &state.syntax_highlighting_context))
+            .collect::<cja::Result<Vec<_>>>()?;
+        let feed = Feed::new(&state.app, &tag_path(&slug), items).with_title(feed_title);
+
+        return Ok(feed.into_response(format));
+    }
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl" { "#" (name) }
+          p class="text-subtitle text-sm" {
+            a href=(format!("/tags/{slug}/rss.xml")) class="underline" { "RSS feed" }
+            " · "
+            a href="/tags" class="underline" { "All tags" }
+          }
+          (content_list(&content))
+        },
+        OpenGraph {
+            title: format!("#{name}"),
+            feeds: feed_links(&tag_path(&slug), &feed_title),
+            ..OpenGraph::default_for_path(&state.app, &tag_path(&slug))
+        },
+    )
+    .into_response())
+}
+
+#[instrument(skip(state))]
+pub(crate) async fn series_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> ResponseResult<Markup> {
+    let (blog_posts, note_posts) = (state.blog_posts(), state.note_posts());
+    let content = all_content(&blog_posts, &note_posts);
+    let parts = series_parts(&content, &slug);
+    let Some(name) = parts.first().and_then(TaggedContent::series) else {
+        return Err(ServerError(
+            cja::color_eyre::eyre::eyre!("No such series"),
+            StatusCode::NOT_FOUND,
+        ));
+    };
+
+    Ok(base_constrained(
+        html! {
+          h1 class="text-3xl" { (name) }
+          p class="text-subtitle text-sm" { "A series in " (parts.len()) " parts" }
+          ol class="list-decimal list-inside" {
+            @for part in &parts {
+              li class="my-4" {
+                a href=(part.relative_link()) class="hover:underline" { (part.title()) }
+                span class="text-subtitle text-sm pl-2" { (part.date()) }
+              }
+            }
+          }
+        },
+        OpenGraph {
+            title: name.to_string(),
+            ..OpenGraph::default_for_path(&state.app, &series_path(name))
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::http_server::test_helpers::create_test_app;
+
+    async fn get(uri: &str) -> (StatusCode, Option<String>, String) {
+        let app = create_test_app().await;
+        let resp = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let location = resp
+            .headers()
+            .get("location")
+            .map(|l| l.to_str().unwrap().to_string());
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            location,
+            String::from_utf8_lossy(&bytes).to_string(),
+        )
+    }
+
+    #[test]
+    fn series_nav_links_neighbouring_parts_in_order() {
+        let blog_posts = BlogPosts::from_static_dir().unwrap();
+        let note_posts = NotePosts::from_static_dir().unwrap();
+        let content = all_content(&blog_posts, &note_posts);
+
+        let first = series_nav(&content, "/posts/bookclub-working-in-public/chapter-01/")
+            .unwrap()
+            .into_string();
+        assert!(first.contains("Part 1 of 2"));
+        assert!(
+            first.contains(r#"href="/posts/bookclub-working-in-public/chapter-02/" rel="next""#)
+        );
+        assert!(!first.contains(r#"rel="prev""#));
+
+        let second = series_nav(&content, "/posts/bookclub-working-in-public/chapter-02/")
+            .unwrap()
+            .into_string();
+        assert!(second.contains("Part 2 of 2"));
+        assert!(second.contains(r#"rel="prev""#));
+
+        assert!(series_nav(&content, "/posts/vim-fzf-with-devicons/").is_none());
+    }
+
+    #[tokio::test]
+    async fn tag_page_lists_tagged_content_and_canonicalizes_spelling() {
+        let (status, _, body) = get("/tags/battlesnake").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("#battlesnake"));
+        assert!(body.contains("Minimax in Battlesnake"));
+
+        let (status, location, _) = get("/tags/Minimax/").await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(location.as_deref(), Some("/tags/minimax"));
+
+        let (status, location, _) = get("/tags/not-a-real-tag").await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(location.as_deref(), Some("/tags"));
+    }
+
+    #[tokio::test]
+    async fn tag_feeds_are_valid() {
+        let (status, _, body) = get("/tags/vim/rss.xml").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let channel = rss::Channel::read_from(body.as_bytes()).unwrap();
+        assert_eq!(channel.title(), "coreyja: #vim");
+        assert!(!channel.items().is_empty());
+
+        let (status, _, body) = get("/tags/vim/atom.xml").await;
+        assert_eq!(status, StatusCode::OK);
+        let feed: atom_syndication::Feed = body.parse().unwrap();
+        assert_eq!(feed.title().as_str(), "coreyja: #vim");
+        assert_eq!(feed.entries().len(), channel.items().len());
+
+        let (status, _, _) = get("/tags/not-a-real-tag/rss.xml").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn series_page_lists_parts_in_order() {
+        let (status, _, body) = get("/series/working-in-public-book-club").await;
+        assert_eq!(status, StatusCode::OK);
+        let first = body.find("Working in Public - Chapter 1").unwrap();
+        let second = body.find("Working in Public - Chapter 2").unwrap();
+        assert!(first < second);
+
+        let (status, _, _) = get("/series/nope").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
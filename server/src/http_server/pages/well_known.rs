@@ -1,4 +1,5 @@
-//! `.well-known` endpoints for standard.site discovery / verification.
+//! `.well-known` endpoints for standard.site discovery / verification, and WebFinger so
+//! Fediverse servers can find the site's ActivityPub actor.
 //!
 //! Per <https://standard.site/docs/verification/>, a publication served at
 //! `https://example.com/path/to/pub` must answer
@@ -10,10 +11,17 @@
 use std::sync::LazyLock;
 
 use axum::{
-    extract::Path,
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
+use serde_json::json;
+
+use crate::{
+    activitypub::{actor_id, ACTIVITY_CONTENT_TYPE, USERNAME},
+    state::AppConfig,
+    AppState,
+};
 
 static PUBLICATIONS_TOML: &str =
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../publications.toml"));
@@ -82,10 +90,73 @@ pub async fn well_known_publication_with_path(
     Ok(plain_text_response(at_uri))
 }
 
+#[derive(serde::Deserialize)]
+pub struct WebfingerQuery {
+    resource: String,
+}
+
+/// Whether a WebFinger `resource` names our actor, either as `acct:coreyja@host` or by its
+/// actor URL
+fn is_our_actor(config: &AppConfig, resource: &str) -> bool {
+    let host = config.base_url.host_str().unwrap_or_default();
+    let acct = resource.strip_prefix("acct:").unwrap_or(resource);
+
+    acct.eq_ignore_ascii_case(&format!("{USERNAME}@{host}")) || resource == actor_id(config)
+}
+
+/// Handles `/.well-known/webfinger?resource=acct:coreyja@coreyja.com`, which is how a
+/// Fediverse search for `@coreyja@coreyja.com` finds the actor
+pub async fn webfinger(
+    State(state): State<AppState>,
+    Query(query): Query<WebfingerQuery>,
+) -> Result<Response, StatusCode> {
+    if state.activitypub.is_none() || !is_our_actor(&state.app, &query.resource) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let host = state.app.base_url.host_str().unwrap_or_default();
+    let body = json!({
+        "subject": format!("acct:{USERNAME}@{host}"),
+        "aliases": [actor_id(&state.app), state.app.app_url("/")],
+        "links": [
+            {
+                "rel": "self",
+                "type": ACTIVITY_CONTENT_TYPE,
+                "href": actor_id(&state.app),
+            },
+            {
+                "rel": "http://webfinger.net/rel/profile-page",
+                "type": "text/html",
+                "href": state.app.app_url("/"),
+            },
+        ],
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/jrd+json")],
+        body.to_string(),
+    )
+        .into_response())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn webfinger_matches_our_account_and_actor() {
+        let config = AppConfig {
+            base_url: url::Url::parse("https://coreyja.com").unwrap(),
+            imgproxy_url: None,
+        };
+
+        assert!(is_our_actor(&config, "acct:coreyja@coreyja.com"));
+        assert!(is_our_actor(&config, "acct:CoreyJA@coreyja.com"));
+        assert!(is_our_actor(&config, "https://coreyja.com/ap/actor"));
+        assert!(!is_our_actor(&config, "acct:someone@coreyja.com"));
+        assert!(!is_our_actor(&config, "acct:coreyja@example.social"));
+    }
+
     #[test]
     fn publication_path_strips_scheme_and_trailing_slash() {
         assert_eq!(publication_path("https://coreyja.com/posts"), "/posts");
@@ -1,6 +1,7 @@
 use std::{path::PathBuf, str::FromStr};
 
-use posts::blog::BlogPosts;
+use db::newsletter_stats::{NewsletterStat, NewsletterSubscriberCount};
+use posts::{blog::BlogPosts, publish::Publishable};
 use serde::{Deserialize, Serialize};
 
 use super::{
@@ -25,8 +26,13 @@ pub(crate) fn make_router() -> Router<AppState> {
         .route("/", get(pages::home::home_page))
         .route("/privacy", get(pages::legal::privacy_policy))
         .route("/contact", get(pages::contact::contact))
-        .route("/posts/rss.xml", get(pages::blog::rss_feed))
-        .route("/rss.xml", get(pages::blog::full_rss_feed))
+        // Feed handlers pick RSS, Atom or JSON Feed from the file name they're routed at
+        .route("/posts/rss.xml", get(pages::blog::posts_feed))
+        .route("/posts/atom.xml", get(pages::blog::posts_feed))
+        .route("/posts/feed.json", get(pages::blog::posts_feed))
+        .route("/rss.xml", get(pages::blog::full_feed))
+        .route("/atom.xml", get(pages::blog::full_feed))
+        .route("/feed.json", get(pages::blog::full_feed))
         .route("/posts", get(pages::blog::posts_index))
         .route(
             "/posts/weekly/",
@@ -38,7 +44,9 @@ pub(crate) fn make_router() -> Router<AppState> {
         .route("/posts/{*key}", get(pages::blog::post_get))
         .route("/pace", get(pages::pace::pace_dashboard))
         .route("/notes", get(pages::notes::notes_index))
-        .route("/notes/rss.xml", get(pages::notes::rss_feed))
+        .route("/notes/rss.xml", get(pages::notes::notes_feed))
+        .route("/notes/atom.xml", get(pages::notes::notes_feed))
+        .route("/notes/feed.json", get(pages::notes::notes_feed))
         .route("/notes/{slug}", get(pages::notes::notes_get))
         .route("/til", get(|| async { Redirect::permanent("/notes") }))
         .route(
@@ -65,6 +73,7 @@ pub(crate) fn make_router() -> Router<AppState> {
         .route("/og/podcast/{slug}", get(pages::og::og_podcast_svg))
         .route("/og/weekly/{slug}", get(pages::og::og_weekly_svg))
         .route("/og/notes/{slug}", get(pages::og::og_note_svg))
+        .route("/og/recipes/{slug}", get(pages::og::og_recipe_svg))
         .route("/og/publication/{key}", get(pages::og::og_publication_svg))
         // standard.site verification endpoints (plain text, publication AT URI).
         // `/.well-known/site.standard.publication`        → publication at domain root
@@ -77,14 +86,43 @@ pub(crate) fn make_router() -> Router<AppState> {
             "/.well-known/site.standard.publication/{*path}",
             get(pages::well_known::well_known_publication_with_path),
         )
+        .route("/.well-known/webfinger", get(pages::well_known::webfinger))
+        // The site's ActivityPub actor. Objects are served under the path of the page
+        // they're for, see `activitypub::OBJECTS_PATH`.
+        .route("/ap/actor", get(pages::activitypub::actor))
+        .route("/ap/outbox", get(pages::activitypub::outbox))
+        .route("/ap/followers", get(pages::activitypub::followers))
+        .route("/ap/objects/{*path}", get(pages::activitypub::object))
+        .route("/ap/inbox", post(webhooks::activitypub::inbox))
         .route("/projects", get(pages::projects::projects_index))
         .route("/projects/{slug}", get(pages::projects::projects_get))
+        .route("/recipes", get(pages::recipes::recipes_index))
+        .route("/recipes/{slug}", get(pages::recipes::recipes_get))
+        // Token-authenticated, with the same strip-the-suffix-in-the-handler approach as
+        // the OG routes above for the `.ics` extension
+        .route(
+            "/meal-plans/calendar/{token}",
+            get(pages::meal_calendar::meal_plan_calendar),
+        )
+        .route("/search", get(pages::search::search_page))
+        .route("/api/search", get(pages::search::search_json))
         .route("/videos", get(pages::videos::video_index))
         .route("/videos/{id}", get(pages::videos::video_get))
-        .route("/tags/{*tag}", get(redirect_to_posts_index))
+        .route("/tags", get(pages::tags::tags_index))
+        .route("/tags/{*path}", get(pages::tags::tag_get))
+        .route("/series/{slug}", get(pages::tags::series_get))
         .route("/year/{*year}", get(redirect_to_posts_index))
         .nest("/blog", old_blog_routes())
         .route("/newsletter", get(newsletter_get))
+        .route("/newsletter/subscribe", post(pages::newsletter::subscribe))
+        .route(
+            "/newsletter/confirm/{id}/{token}",
+            get(pages::newsletter::confirm),
+        )
+        .route(
+            "/newsletter/unsubscribe/{id}/{token}",
+            get(pages::newsletter::unsubscribe_page).post(pages::newsletter::unsubscribe),
+        )
         .route("/auth/github", get(auth::github_oauth::github_oauth))
         .route("/api/linear/auth", get(auth::linear_oauth::linear_auth))
         .route(
@@ -128,7 +166,10 @@ pub(crate) fn make_router() -> Router<AppState> {
         )
         .nest("/admin/persona", admin::persona::router())
         .nest("/admin/memories", admin::memories::router())
+        .nest("/admin/calendar-feeds", admin::calendar_feeds::router())
+        .nest("/admin/preview", admin::preview::router())
         .route("/webhooks/cookd", post(webhooks::cookd::handler))
+        .route("/webmention", post(webhooks::webmention::receive))
         .route(
             "/api/linear/webhooks",
             post(webhooks::linear::linear_webhook),
@@ -179,7 +220,11 @@ async fn fallback(uri: Uri, State(posts): State<Arc<BlogPosts>>) -> Result<Respo
     let key = key.strip_prefix('/').unwrap_or(key);
     let key = key.strip_suffix('/').unwrap_or(key);
 
-    let post = posts.posts().iter().find(|p| p.matches_path(key).is_some());
+    // Drafts and scheduled posts 404 at their own URL, so they can't be found from here either
+    let post = posts
+        .posts()
+        .iter()
+        .find(|p| p.is_published() && p.matches_path(key).is_some());
 
     let resp = match post {
         Some(post) => {
@@ -213,16 +258,83 @@ async fn static_assets(Path(p): Path<String>) -> ResponseResult {
     Ok((headers, entry.contents()).into_response())
 }
 
-async fn newsletter_get(State(posts): State<Arc<BlogPosts>>) -> ResponseResult {
+async fn newsletter_get(
+    State(app_state): State<AppState>,
+    State(posts): State<Arc<BlogPosts>>,
+) -> ResponseResult {
     let newsletters = posts
-        .by_recency()
+        .published()
         .into_iter()
         .filter(|p| p.frontmatter.is_newsletter)
         .collect::<Vec<_>>();
 
+    let subscribers = NewsletterSubscriberCount::latest(&app_state.db)
+        .await?
+        .map(|count| count.subscribers);
+    let open_rate = NewsletterStat::overall_open_rate(&NewsletterStat::all(&app_state.db).await?);
+
     Ok((
         axum::http::StatusCode::OK,
-        templates::newsletter::newsletter_page(newsletters),
+        templates::newsletter::newsletter_page(newsletters, subscribers, open_rate),
     )
         .into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::http_server::test_helpers::{create_test_app_with_content, write_test_content};
+
+    #[tokio::test]
+    async fn the_fallback_only_redirects_to_published_posts() {
+        let content = write_test_content(&[
+            (
+                "blog/live-post/index.md",
+                "---\ntitle: Live\ndate: 2026-01-01\n---\n\nBody\n",
+            ),
+            (
+                "blog/secret-post/index.md",
+                "---\ntitle: Secret\ndate: 2026-01-01\ndraft: true\n---\n\nBody\n",
+            ),
+        ]);
+        let app = create_test_app_with_content(content.path()).await;
+        let get = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let live = get("/live-post").await.unwrap();
+        assert_eq!(live.status(), axum::http::StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(live.headers()["location"], "/posts/live-post/");
+
+        let secret = get("/secret-post").await.unwrap();
+        assert_eq!(secret.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+    #[tokio::test]
+    async fn unpublished_posts_are_not_redirected_to_either() {
+        let content = write_test_content(&[
+            (
+                "blog/live-post/index.md",
+                "---\ntitle: Live\ndate: 2026-01-01\n---\n\nBody\n",
+            ),
+            (
+                "blog/secret-post/index.md",
+                "---\ntitle: Secret\ndate: 2026-01-01\npublish_at: 2999-01-01T09:00:00Z\n---\n\nBody\n",
+            ),
+        ]);
+        let app = create_test_app_with_content(content.path()).await;
+        let get = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let live = get("/posts/live-post/index.md").await.unwrap();
+        assert_eq!(live.status(), axum::http::StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(live.headers()["location"], "/posts/live-post/");
+
+        let secret = get("/posts/secret-post/index.md").await.unwrap();
+        assert_eq!(secret.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -13,9 +13,8 @@ pub fn newsletter_signup_footer() -> Markup {
         }
 
         form
-          action="https://buttondown.com/api/emails/embed-subscribe/coreyja"
+          action="/newsletter/subscribe"
           method="post"
-          target="popupwindow"
           class="w-full max-w-md flex flex-col gap-4"
           {
             input
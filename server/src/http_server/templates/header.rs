@@ -2,6 +2,7 @@ use std::borrow::Borrow;
 
 use maud::{html, Markup, PreEscaped, Render};
 
+use crate::http_server::feeds::{site_feed_links, FeedLink};
 use crate::http_server::templates::LOGO_DARK_FLAT_SVG;
 use crate::AppConfig;
 
@@ -28,6 +29,12 @@ pub struct OpenGraph {
     /// Used to point at `site.standard.document` / `site.standard.publication`
     /// records on the PDS for verification.
     pub head_links: Vec<(String, String)>,
+    /// Serialized JSON-LD document emitted as a `<script type="application/ld+json">`
+    /// block. Callers are responsible for escaping `</` in string values.
+    pub json_ld: Option<String>,
+    /// Feeds for this page's section, advertised with `<link rel="alternate">` ahead of the
+    /// site-wide feeds that every page links to.
+    pub feeds: Vec<FeedLink>,
 }
 
 impl Default for OpenGraph {
@@ -52,6 +59,8 @@ impl Default for OpenGraph {
             author: None,
             tags: Vec::new(),
             head_links: Vec::new(),
+            json_ld: None,
+            feeds: Vec::new(),
         }
     }
 }
@@ -138,6 +147,12 @@ impl Render for OpenGraph {
           @for (rel, href) in &self.head_links {
             link rel=(rel) href=(href) {}
           }
+          @for feed in self.feeds.iter().chain(&site_feed_links()) {
+            link rel="alternate" type=(feed.content_type) title=(feed.title) href=(feed.href) {}
+          }
+          @if let Some(json_ld) = &self.json_ld {
+            script type="application/ld+json" { (PreEscaped(json_ld)) }
+          }
         }
     }
 }
@@ -170,6 +185,7 @@ pub fn head(og: impl Borrow<OpenGraph>) -> Markup {
         meta name="msapplication-TileColor" content="#603cba";
         meta name="msapplication-config" content="/static/icons/browserconfig.xml";
         meta name="theme-color" content="#401f74";
+        link rel="webmention" href="/webmention";
 
         (og.borrow())
       }
@@ -211,6 +227,7 @@ pub fn header() -> Markup {
             (HeaderLink { href: "/podcast", text: "Podcast" })
             (HeaderLink { href: "/projects", text: "Projects" })
             (HeaderLink { href: "/newsletter", text: "Newsletter" })
+            (HeaderLink { href: "/search", text: "Search" })
           }
         }
       }
@@ -260,6 +277,23 @@ mod tests {
         assert!(out.contains(r#"content="https://coreyja.com/posts/foo""#));
     }
 
+    #[test]
+    fn section_feeds_are_advertised_before_site_feeds() {
+        let og = OpenGraph {
+            feeds: crate::http_server::feeds::feed_links("/notes", "coreyja Notes"),
+            ..OpenGraph::default()
+        };
+        let out = rendered(&og);
+        let notes = out
+            .find(r#"type="application/atom+xml" title="coreyja Notes (Atom)" href="/notes/atom.xml""#)
+            .expect("notes Atom feed should be linked");
+        let site = out
+            .find(r#"type="application/feed+json" title="coreyja (JSON Feed)" href="/feed.json""#)
+            .expect("site-wide JSON Feed should be linked");
+        assert!(notes < site);
+        assert!(rendered(&OpenGraph::default()).contains(r#"href="/rss.xml""#));
+    }
+
     #[test]
     fn image_present_emits_default_twitter_card_and_mirrored_tags() {
         // With an image but no explicit twitter_card, Render should default to
@@ -408,4 +442,17 @@ mod tests {
         assert!(out.contains(r#"name="twitter:site" content="@coreyja.com""#));
         assert!(out.contains(r#"property="article:author" content="Corey""#));
     }
+
+    #[test]
+    fn json_ld_emits_script_block_unescaped() {
+        let og = OpenGraph {
+            json_ld: Some(r#"{"@type":"Recipe","name":"Mac & Cheese"}"#.to_string()),
+            ..OpenGraph::default()
+        };
+        let out = rendered(&og);
+        assert!(out.contains(
+            r#"<script type="application/ld+json">{"@type":"Recipe","name":"Mac & Cheese"}</script>"#
+        ));
+        assert!(!rendered(&OpenGraph::default()).contains("application/ld+json"));
+    }
 }
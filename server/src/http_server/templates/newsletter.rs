@@ -5,7 +5,16 @@ use crate::http_server::templates::post_templates::BlogPostList;
 
 use super::{base_constrained, header::OpenGraph};
 
-pub(crate) fn newsletter_page(newsletters: Vec<&BlogPost>) -> Markup {
+/// A rate from the newsletter stats as a whole percentage
+pub(crate) fn percent(rate: f64) -> String {
+    format!("{:.0}%", rate * 100.0)
+}
+
+pub(crate) fn newsletter_page(
+    newsletters: Vec<&BlogPost>,
+    subscribers: Option<i32>,
+    open_rate: Option<f64>,
+) -> Markup {
     base_constrained(
         html! {
           div  class="max-w-prose"  {
@@ -24,6 +33,15 @@ pub(crate) fn newsletter_page(newsletters: Vec<&BlogPost>) -> Markup {
               "Sign up below to read one day early!"
             }
 
+            @if let Some(subscribers) = subscribers {
+              p class="text-subtitle mb-12" {
+                "Join " (subscribers) " readers"
+                @if let Some(open_rate) = open_rate {
+                  ", " (percent(open_rate)) " of whom open each issue on average"
+                }
+              }
+            }
+
             p class="my-8 leading-loose" {
               "The newsletter will contain a summary of the week's posts, as well as more ramblings from me.
           Each week will be different, some weeks I'll share status updates on the projects I'm working on,
@@ -50,6 +50,7 @@ pub enum CardTag {
     Podcast,
     Newsletter,
     Notes,
+    Recipes,
 }
 
 impl CardTag {
@@ -59,6 +60,7 @@ impl CardTag {
             CardTag::Podcast => "PODCAST",
             CardTag::Newsletter => "NEWSLETTER",
             CardTag::Notes => "NOTES",
+            CardTag::Recipes => "RECIPES",
         }
     }
 }
@@ -71,6 +73,8 @@ pub struct CardData<'a> {
     /// on the card. Matches the publication card pattern where the bottom
     /// slot is used for a description rather than a date.
     pub subtitle: Option<&'a str>,
+    /// Shown after the date, e.g. `January 10, 2026 · 5 min read`
+    pub reading_minutes: Option<usize>,
     /// Base64-encoded JPEG (no `data:` prefix). When `Some`, the `YouTube` `<image>` block is kept.
     pub youtube_thumbnail_b64: Option<String>,
 }
@@ -175,7 +179,10 @@ pub fn render_card_svg(data: &CardData<'_>) -> String {
     let svg = svg.replace("{{font_face}}", QUICKSAND_FONT_CSS.as_str());
     let svg = svg.replace("{{logo_svg_contents}}", super::LOGO_DARK_FLAT_SVG);
     let svg = substitute_title(&svg, data.title);
-    let formatted_date = data.date.format("%B %-d, %Y").to_string();
+    let mut formatted_date = data.date.format("%B %-d, %Y").to_string();
+    if let Some(minutes) = data.reading_minutes {
+        formatted_date.push_str(&format!(" · {minutes} min read"));
+    }
     let date_escaped = html_escape::encode_text(&formatted_date).into_owned();
     let svg = if let Some(s) = data.subtitle {
         // Subtitle present: subtitle replaces the date in the bottom-left,
@@ -340,6 +347,7 @@ mod tests {
             date: chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(),
             tag: CardTag::Posts,
             subtitle: None,
+            reading_minutes: None,
             youtube_thumbnail_b64: None,
         }
     }
@@ -412,6 +420,7 @@ mod tests {
             date: chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(),
             tag: CardTag::Posts,
             subtitle: Some("My great tagline"),
+            reading_minutes: None,
             youtube_thumbnail_b64: None,
         };
         let svg = render_card_svg(&data);
@@ -431,6 +440,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_card_svg_shows_reading_time_after_the_date() {
+        let data = CardData {
+            reading_minutes: Some(5),
+            ..sample_data()
+        };
+        let svg = render_card_svg(&data);
+        assert!(svg.contains("January 10, 2026 · 5 min read"));
+    }
+
+    #[test]
+    fn render_card_svg_keeps_reading_time_with_the_date_when_subtitled() {
+        let data = CardData {
+            subtitle: Some("My great tagline"),
+            reading_minutes: Some(5),
+            ..sample_data()
+        };
+        let svg = render_card_svg(&data);
+        assert!(svg.contains("My great tagline"));
+        assert!(svg.contains("January 10, 2026 · 5 min read"));
+    }
+
     #[test]
     fn render_card_svg_without_subtitle_omits_bottom_right_slot() {
         let svg = render_card_svg(&sample_data());
@@ -475,6 +506,7 @@ mod tests {
             date: chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(),
             tag: CardTag::Posts,
             subtitle: None,
+            reading_minutes: None,
             youtube_thumbnail_b64: None,
         };
         let svg = render_card_svg(&data);
@@ -502,6 +534,7 @@ mod tests {
             date: chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(),
             tag: CardTag::Podcast,
             subtitle: None,
+            reading_minutes: None,
             youtube_thumbnail_b64: Some("ZmFrZWltYWdl".to_string()),
         };
         let svg = render_card_svg(&data);
@@ -516,6 +549,7 @@ mod tests {
             date: chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(),
             tag: CardTag::Posts,
             subtitle: None,
+            reading_minutes: None,
             youtube_thumbnail_b64: None,
         };
         let svg = render_card_svg(&data);
@@ -38,6 +38,7 @@ impl Render for BlogPostList<'_> {
 
                       (post.title())
                   }
+                  span class="text-subtitle text-sm" { " · " (post.reading_stats.label()) }
                 }
             }
         }
@@ -20,7 +20,6 @@ use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions},
     PgPool,
 };
-use std::sync::Arc;
 
 use crate::http_server::pages::blog::md::SyntaxHighlightingContext;
 use crate::{AppConfig, AppState};
@@ -36,9 +35,46 @@ pub async fn create_test_app() -> Router {
 /// Like [`create_test_app`] but uses the caller-supplied `PgPool`. Use this
 /// from `#[sqlx::test(migrations = "../db/migrations")]` tests.
 pub async fn create_test_app_with_pool(pool: PgPool) -> Router {
+    crate::http_server::routes::make_router().with_state(create_test_state(pool).await)
+}
+
+/// Like [`create_test_app`] but serving only the content written under `root`, with the
+/// same `blog/`, `notes/`, `podcast/` and `projects/` layout as the repo. For tests that need
+/// drafts, scheduled posts or other content the real repo doesn't have.
+pub async fn create_test_app_with_content(root: &std::path::Path) -> Router {
+    let mut state = create_test_state(lazy_test_pool()).await;
+    state.content =
+        crate::content::LiveContent::load(posts::source::ContentSource::Disk(root.to_path_buf()))
+            .unwrap();
+
+    crate::http_server::routes::make_router().with_state(state)
+}
+
+/// Write `files` under a fresh temp dir laid out like the repo's content, for
+/// [`create_test_app_with_content`]
+pub fn write_test_content(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let root = tempfile::tempdir().unwrap();
+    for dir in posts::source::ContentDir::ALL {
+        std::fs::create_dir_all(root.path().join(dir.name())).unwrap();
+    }
+    for (path, contents) in files {
+        let path = root.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    root
+}
+
+/// The fully populated `AppState` behind [`create_test_app_with_pool`], for tests that run
+/// jobs or other code outside the router. SMTP isn't configured, set `mailer` to send mail,
+/// and neither is federation, set `activitypub` to turn it on.
+pub async fn create_test_state(pool: PgPool) -> AppState {
     set_test_env_vars();
 
-    let state = AppState {
+    let content =
+        crate::content::LiveContent::load(posts::source::ContentSource::Embedded).unwrap();
+
+    AppState {
         twitch: crate::twitch::TwitchConfig::from_env().unwrap(),
         github: crate::github::GithubConfig::from_env().unwrap(),
         open_ai: openai::OpenAiConfig::from_env().unwrap(),
@@ -47,10 +83,7 @@ pub async fn create_test_app_with_pool(pool: PgPool) -> Router {
         anthropic: crate::anthropic::AnthropicConfig::from_env().unwrap(),
         app: AppConfig::from_env().unwrap(),
         syntax_highlighting_context: SyntaxHighlightingContext,
-        blog_posts: Arc::new(posts::blog::BlogPosts::from_static_dir().unwrap()),
-        note_posts: Arc::new(posts::notes::NotePosts::from_static_dir().unwrap()),
-        podcast_episodes: Arc::new(posts::podcast::PodcastEpisodes::from_static_dir().unwrap()),
-        projects: Arc::new(posts::projects::Projects::from_static_dir().unwrap()),
+        content,
         versions: crate::state::VersionInfo {
             git_commit: "test-commit",
             rustc_version: "test-rustc",
@@ -60,9 +93,12 @@ pub async fn create_test_app_with_pool(pool: PgPool) -> Router {
         encrypt_config: crate::encrypt::Config::from_env().unwrap(),
         posthog_key: None,
         discord: crate::discord::DiscordClient::for_testing(),
-    };
-
-    crate::http_server::routes::make_router().with_state(state)
+        mailer: None,
+        newsletter_link_key: Some(crate::newsletter::LinkKey::new(
+            "test newsletter link secret, long enough",
+        )),
+        activitypub: None,
+    }
 }
 
 /// A `PgPool` that never actually connects. `connect_lazy_with` defers
@@ -0,0 +1,534 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, Uri},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use cja::{
+    color_eyre::eyre::{eyre, Context as _},
+    jobs::Job as _,
+};
+use db::{
+    activitypub::ActivityPubFollower,
+    webmentions::{Webmention, WebmentionDetails, WebmentionKind},
+};
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    activitypub::{
+        actor_id, fetch_actor, find_published_path, id_of, same_origin,
+        signatures::{date_is_fresh, digest_header, signing_string, verify, SignatureHeader},
+        ActivityPubConfig, RemoteActor,
+    },
+    http_server::{
+        errors::{ServerError, WithStatus as _},
+        pages::activitypub::federation_config,
+        ResponseResult,
+    },
+    jobs::activitypub::DeliverActivity,
+    mastodon::status_paragraphs,
+    webmention::{truncate, MAX_CONTENT_CHARS},
+    AppState,
+};
+
+fn unauthorized(message: &str) -> ServerError {
+    ServerError(eyre!("{message}"), StatusCode::UNAUTHORIZED)
+}
+
+/// Check the request was signed by the actor it claims to be from, and return that actor
+async fn verify_sender(
+    state: &AppState,
+    config: &ActivityPubConfig,
+    uri: &Uri,
+    headers: &HeaderMap,
+    body: &[u8],
+    activity_actor: &str,
+) -> ResponseResult<RemoteActor> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let signature = SignatureHeader::parse(
+        header("signature").ok_or_else(|| unauthorized("Request is not signed"))?,
+    )
+    .with_status(StatusCode::UNAUTHORIZED)?;
+    signature
+        .check_covers_required_headers()
+        .with_status(StatusCode::UNAUTHORIZED)?;
+
+    if !header("date").is_some_and(|date| date_is_fresh(date, Utc::now())) {
+        return Err(unauthorized("Request date is missing or too far from now"));
+    }
+    if header("digest") != Some(digest_header(body).as_str()) {
+        return Err(unauthorized("Digest doesn't match the body"));
+    }
+
+    // A key from anywhere but the actor's own server can't be theirs, so don't fetch it
+    if !same_origin(&signature.key_id, activity_actor) {
+        return Err(unauthorized(
+            "Signing key doesn't belong to the activity's actor",
+        ));
+    }
+    let actor = fetch_actor(config, &state.app, &signature.key_id)
+        .await
+        .with_status(StatusCode::UNAUTHORIZED)?;
+    if actor.public_key.id != signature.key_id
+        || actor.public_key.owner != actor.id
+        || actor.id != activity_actor
+    {
+        return Err(unauthorized(
+            "Signing key doesn't belong to the activity's actor",
+        ));
+    }
+
+    let path_and_query = uri.path_and_query().map_or("/", |p| p.as_str());
+    let to_verify = signing_string("post", path_and_query, &signature.headers, header)
+        .with_status(StatusCode::UNAUTHORIZED)?;
+    verify(
+        &actor.public_key.public_key_pem,
+        &to_verify,
+        &signature.signature,
+    )
+    .with_status(StatusCode::UNAUTHORIZED)?;
+
+    Ok(actor)
+}
+
+/// Receive an activity from another server. Follows and unfollows update our followers,
+/// and likes, boosts and replies to our posts and notes are stored as webmentions so they
+/// render alongside the rest. Anything else is accepted and ignored.
+pub(crate) async fn inbox(
+    State(state): State<AppState>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ResponseResult<impl IntoResponse> {
+    let config = federation_config(&state)?;
+
+    let activity: JsonValue = serde_json::from_slice(&body)
+        .context("Activity is not valid JSON")
+        .with_status(StatusCode::BAD_REQUEST)?;
+    let activity_type = activity["type"].as_str().unwrap_or_default();
+    let actor_url = id_of(&activity["actor"])
+        .ok_or_else(|| ServerError(eyre!("Activity has no actor"), StatusCode::BAD_REQUEST))?;
+
+    let actor = match verify_sender(&state, config, &uri, &headers, &body, actor_url).await {
+        Ok(actor) => actor,
+        // Deleted accounts announce it after their key is gone, so there's no way to check
+        // the signature. There's nothing of theirs to remove that a later failed delivery
+        // wouldn't, so these are dropped rather than refused.
+        Err(_) if activity_type == "Delete" => return Ok(StatusCode::ACCEPTED),
+        Err(e) => return Err(e),
+    };
+
+    handle_activity(&state, &actor, &activity).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Whether `id` lives on the actor's server. The signature only vouches for the actor, so
+/// an id anywhere else could be naming somebody else's activity or post.
+fn is_from_actor(actor: &RemoteActor, id: Option<&str>) -> bool {
+    id.is_some_and(|id| same_origin(id, &actor.id))
+}
+
+/// Act on an activity from an actor whose signature has been checked
+async fn handle_activity(
+    state: &AppState,
+    actor: &RemoteActor,
+    activity: &JsonValue,
+) -> ResponseResult<()> {
+    let activity_type = activity["type"].as_str().unwrap_or_default();
+
+    // Follows, likes and boosts point at us, but everything else acts on the actor's own
+    // objects, so those have to come from the actor's server too
+    let acts_on_own_object = matches!(activity_type, "Undo" | "Create" | "Update" | "Delete");
+    if !is_from_actor(actor, id_of(activity))
+        || (acts_on_own_object && !is_from_actor(actor, id_of(&activity["object"])))
+    {
+        return Err(ServerError(
+            eyre!("Activity refers to objects from another server than its actor"),
+            StatusCode::FORBIDDEN,
+        ));
+    }
+
+    match activity_type {
+        "Follow" => accept_follow(state, actor, activity).await?,
+        "Undo" => undo(state, actor, &activity["object"]).await?,
+        "Like" => store_reaction(state, actor, activity, WebmentionKind::Like).await?,
+        "Announce" => store_reaction(state, actor, activity, WebmentionKind::Repost).await?,
+        "Create" | "Update" => store_reply(state, actor, &activity["object"]).await?,
+        "Delete" => {
+            if let Some(object_id) = id_of(&activity["object"]) {
+                Webmention::delete_by_source_and_author(&state.db, object_id, &actor.profile_url())
+                    .await?;
+            }
+        }
+        _ => {
+            tracing::debug!(activity_type, actor = actor.id, "Ignoring activity");
+        }
+    }
+
+    Ok(())
+}
+
+async fn accept_follow(
+    state: &AppState,
+    actor: &RemoteActor,
+    follow: &JsonValue,
+) -> cja::Result<()> {
+    if id_of(&follow["object"]) != Some(actor_id(&state.app).as_str()) {
+        return Ok(());
+    }
+
+    ActivityPubFollower::upsert(&state.db, &actor.id, &actor.inbox, actor.shared_inbox()).await?;
+    tracing::info!(actor = actor.id, "New ActivityPub follower");
+
+    let accept = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": format!("{}#accepts/{}", actor_id(&state.app), uuid::Uuid::new_v4()),
+        "type": "Accept",
+        "actor": actor_id(&state.app),
+        "object": follow,
+    });
+    DeliverActivity {
+        inbox: actor.inbox.clone(),
+        activity: accept,
+    }
+    .enqueue(state.clone(), "ActivityPub follow".to_string(), None)
+    .await?;
+
+    Ok(())
+}
+
+async fn undo(state: &AppState, actor: &RemoteActor, object: &JsonValue) -> cja::Result<()> {
+    match object["type"].as_str() {
+        Some("Follow") => {
+            ActivityPubFollower::delete(&state.db, &actor.id).await?;
+            tracing::info!(actor = actor.id, "ActivityPub follower left");
+        }
+        _ => {
+            // Likes and boosts are stored under the id of the activity that made them
+            if let Some(id) = id_of(object) {
+                Webmention::delete_by_source_and_author(&state.db, id, &actor.profile_url())
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn author_details(actor: &RemoteActor) -> WebmentionDetails {
+    WebmentionDetails {
+        author_name: actor.display_name(),
+        author_url: Some(actor.profile_url()),
+        author_photo: actor.avatar_url(),
+        ..WebmentionDetails::default()
+    }
+}
+
+async fn store_reaction(
+    state: &AppState,
+    actor: &RemoteActor,
+    activity: &JsonValue,
+    kind: WebmentionKind,
+) -> cja::Result<()> {
+    let (Some(source), Some(object_id)) = (id_of(activity), id_of(&activity["object"])) else {
+        return Ok(());
+    };
+    let Some(target_path) = find_published_path(state, object_id) else {
+        return Ok(());
+    };
+
+    Webmention::upsert(
+        &state.db,
+        source,
+        &state.app.app_url(&target_path),
+        &target_path,
+        kind,
+        author_details(actor),
+    )
+    .await?;
+    tracing::info!(source, target_path, %kind, "Stored ActivityPub reaction");
+
+    Ok(())
+}
+
+/// Store a post that replies to one of ours. Replies to anything else, like a reply in a
+/// thread under one of our posts, are skipped.
+async fn store_reply(state: &AppState, actor: &RemoteActor, object: &JsonValue) -> cja::Result<()> {
+    if object["type"].as_str() != Some("Note")
+        || id_of(&object["attributedTo"]) != Some(actor.id.as_str())
+    {
+        return Ok(());
+    }
+    let (Some(source), Some(in_reply_to)) = (id_of(object), id_of(&object["inReplyTo"])) else {
+        return Ok(());
+    };
+    let Some(target_path) = find_published_path(state, in_reply_to) else {
+        return Ok(());
+    };
+
+    let content = status_paragraphs(object["content"].as_str().unwrap_or_default()).join("\n\n");
+    let details = WebmentionDetails {
+        content: Some(truncate(&content, MAX_CONTENT_CHARS)).filter(|c| !c.is_empty()),
+        published_at: object["published"]
+            .as_str()
+            .and_then(|p| DateTime::parse_from_rfc3339(p).ok())
+            .map(|p| p.with_timezone(&Utc)),
+        ..author_details(actor)
+    };
+
+    Webmention::upsert(
+        &state.db,
+        source,
+        &state.app.app_url(&target_path),
+        &target_path,
+        WebmentionKind::Reply,
+        details,
+    )
+    .await?;
+    tracing::info!(source, target_path, "Stored ActivityPub reply");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use rsa::RsaPrivateKey;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::{activitypub::ActorPublicKey, http_server::test_helpers::create_test_state};
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn unsigned_activities_are_refused(pool: PgPool) {
+        let mut state = create_test_state(pool).await;
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        state.activitypub = Some(ActivityPubConfig::new(key).unwrap());
+        let app = crate::http_server::routes::make_router().with_state(state);
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/ap/inbox")
+                    .header("content-type", "application/activity+json")
+                    .body(Body::from(
+                        json!({
+                            "id": "https://example.social/users/friend#follows/1",
+                            "type": "Follow",
+                            "actor": "https://example.social/users/friend",
+                            "object": "http://localhost:3000/ap/actor",
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    const POST: &str = "http://localhost:3000/posts/vim-fzf-with-devicons/";
+
+    fn remote_actor(name: &str) -> RemoteActor {
+        let id = format!("https://example.social/users/{name}");
+        RemoteActor {
+            inbox: format!("{id}/inbox"),
+            endpoints: None,
+            public_key: ActorPublicKey {
+                id: format!("{id}#main-key"),
+                owner: id.clone(),
+                public_key_pem: String::new(),
+            },
+            name: None,
+            preferred_username: Some(name.to_string()),
+            url: Some(json!(format!("https://example.social/@{name}"))),
+            icon: None,
+            id,
+        }
+    }
+
+    async fn mention_count(pool: &PgPool) -> i64 {
+        sqlx::query_scalar("SELECT count(*) FROM webmentions")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn follows_are_recorded_and_accepted(pool: PgPool) {
+        let state = create_test_state(pool.clone()).await;
+        let friend = remote_actor("friend");
+
+        handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": "https://example.social/users/friend#follows/1",
+                "type": "Follow",
+                "actor": friend.id,
+                "object": actor_id(&state.app),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let followers = ActivityPubFollower::all(&pool).await.unwrap();
+        assert_eq!(followers.len(), 1);
+        assert_eq!(followers[0].actor_id, friend.id);
+
+        let inbox: String =
+            sqlx::query_scalar("SELECT payload->>'inbox' FROM jobs WHERE name = 'DeliverActivity'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(inbox, friend.inbox);
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn likes_with_ids_from_another_server_are_refused(pool: PgPool) {
+        let state = create_test_state(pool.clone()).await;
+        let friend = remote_actor("friend");
+
+        let result = handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": "https://elsewhere.social/users/someone/likes/1",
+                "type": "Like",
+                "actor": friend.id,
+                "object": POST,
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(mention_count(&pool).await, 0);
+
+        handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": "https://example.social/users/friend/likes/1",
+                "type": "Like",
+                "actor": friend.id,
+                "object": POST,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mention_count(&pool).await, 1);
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn only_the_author_can_undo_or_delete_their_mentions(pool: PgPool) {
+        let state = create_test_state(pool.clone()).await;
+        let friend = remote_actor("friend");
+        let stranger = remote_actor("stranger");
+        let like = "https://example.social/users/friend/likes/1";
+        let reply = "https://example.social/users/friend/statuses/1";
+
+        handle_activity(
+            &state,
+            &friend,
+            &json!({ "id": like, "type": "Like", "actor": friend.id, "object": POST }),
+        )
+        .await
+        .unwrap();
+        handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": format!("{reply}/activity"),
+                "type": "Create",
+                "actor": friend.id,
+                "object": {
+                    "id": reply,
+                    "type": "Note",
+                    "attributedTo": friend.id,
+                    "inReplyTo": POST,
+                    "content": "<p>Nice post</p>",
+                },
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mention_count(&pool).await, 2);
+
+        // Same server, so the ids pass the origin check, but it isn't the stranger's to remove
+        handle_activity(
+            &state,
+            &stranger,
+            &json!({
+                "id": "https://example.social/users/stranger#undo/1",
+                "type": "Undo",
+                "actor": stranger.id,
+                "object": { "id": like, "type": "Like" },
+            }),
+        )
+        .await
+        .unwrap();
+        handle_activity(
+            &state,
+            &stranger,
+            &json!({
+                "id": "https://example.social/users/stranger#delete/1",
+                "type": "Delete",
+                "actor": stranger.id,
+                "object": reply,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mention_count(&pool).await, 2);
+
+        // And nobody can reach across servers for them
+        let outsider = RemoteActor {
+            id: "https://elsewhere.social/users/outsider".to_string(),
+            ..remote_actor("outsider")
+        };
+        let result = handle_activity(
+            &state,
+            &outsider,
+            &json!({
+                "id": "https://elsewhere.social/users/outsider#delete/1",
+                "type": "Delete",
+                "actor": outsider.id,
+                "object": reply,
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(mention_count(&pool).await, 2);
+
+        handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": "https://example.social/users/friend#undo/1",
+                "type": "Undo",
+                "actor": friend.id,
+                "object": { "id": like, "type": "Like" },
+            }),
+        )
+        .await
+        .unwrap();
+        handle_activity(
+            &state,
+            &friend,
+            &json!({
+                "id": "https://example.social/users/friend#delete/1",
+                "type": "Delete",
+                "actor": friend.id,
+                "object": { "id": reply, "type": "Tombstone" },
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mention_count(&pool).await, 0);
+    }
+}
@@ -1,2 +1,4 @@
+pub(crate) mod activitypub;
 pub(crate) mod cookd;
 pub(crate) mod linear;
+pub(crate) mod webmention;
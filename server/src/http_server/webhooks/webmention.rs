@@ -0,0 +1,231 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Form};
+use cja::{
+    color_eyre::eyre::{eyre, Context},
+    jobs::Job as _,
+};
+use url::Url;
+
+use crate::{
+    http_server::{
+        errors::{ServerError, WithStatus as _},
+        LinkTo, ResponseResult,
+    },
+    jobs::webmentions::VerifyWebmention,
+    webmention::{is_fetchable, same_page},
+    AppState,
+};
+
+#[derive(serde::Deserialize)]
+pub(crate) struct WebmentionForm {
+    source: String,
+    target: String,
+}
+
+fn bad_request(message: &str) -> ServerError {
+    ServerError(eyre!("{message}"), StatusCode::BAD_REQUEST)
+}
+
+/// Receive a webmention. The request is only checked for being well-formed and about one of
+/// our posts or notes here; the source is fetched and verified later by
+/// [`VerifyWebmention`], as the spec recommends.
+pub(crate) async fn receive(
+    State(state): State<AppState>,
+    Form(form): Form<WebmentionForm>,
+) -> ResponseResult<impl IntoResponse> {
+    let source = Url::parse(&form.source)
+        .context("source is not a valid URL")
+        .with_status(StatusCode::BAD_REQUEST)?;
+    let target = Url::parse(&form.target)
+        .context("target is not a valid URL")
+        .with_status(StatusCode::BAD_REQUEST)?;
+
+    if !is_fetchable(&source) {
+        return Err(bad_request("source must be a public http(s) URL"));
+    }
+    if same_page(&source, &target) {
+        return Err(bad_request("source and target must be different pages"));
+    }
+    if target.host_str() != state.app.base_url.host_str() {
+        return Err(bad_request("target is not on this site"));
+    }
+
+    let blog_posts = state.blog_posts();
+    let note_posts = state.note_posts();
+    let target_path = blog_posts
+        .published()
+        .into_iter()
+        .map(LinkTo::relative_link)
+        .chain(
+            note_posts
+                .published()
+                .into_iter()
+                .map(LinkTo::relative_link),
+        )
+        .find(|path| path.trim_end_matches('/') == target.path().trim_end_matches('/'))
+        .ok_or_else(|| bad_request("target is not a post or note on this site"))?;
+
+    VerifyWebmention {
+        source: source.to_string(),
+        target: target.to_string(),
+        target_path,
+    }
+    .enqueue(state.clone(), "Webmention received".to_string(), None)
+    .await
+    .context("Could not enqueue webmention verification")?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        "Webmention received, it will show up once the source has been verified",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use crate::http_server::test_helpers::{create_test_app, create_test_app_with_pool};
+
+    use super::*;
+
+    fn webmention_request(source: &str, target: &str) -> Request<Body> {
+        let body = serde_urlencoded::to_string([("source", source), ("target", target)]).unwrap();
+        Request::builder()
+            .method("POST")
+            .uri("/webmention")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_mentions_that_are_not_about_our_content() {
+        for (source, target) in [
+            (
+                "not a url",
+                "http://localhost:3000/posts/vim-fzf-with-devicons/",
+            ),
+            (
+                "http://127.0.0.1/",
+                "http://localhost:3000/posts/vim-fzf-with-devicons/",
+            ),
+            (
+                "https://example.com/reply",
+                "https://example.org/posts/vim-fzf-with-devicons/",
+            ),
+            (
+                "https://example.com/reply",
+                "http://localhost:3000/posts/not-a-post/",
+            ),
+        ] {
+            let app = create_test_app().await;
+            let resp = app
+                .oneshot(webmention_request(source, target))
+                .await
+                .unwrap();
+            assert_eq!(
+                resp.status(),
+                StatusCode::BAD_REQUEST,
+                "{source} -> {target}"
+            );
+        }
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn queues_verification_for_a_post(pool: PgPool) {
+        let app = create_test_app_with_pool(pool.clone()).await;
+        let resp = app
+            .oneshot(webmention_request(
+                "https://example.com/reply",
+                "http://localhost:3000/posts/vim-fzf-with-devicons",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+        let target_path: String = sqlx::query_scalar(
+            "SELECT payload->>'target_path' FROM jobs WHERE name = 'VerifyWebmention'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(target_path, "/posts/vim-fzf-with-devicons/");
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn verified_mentions_render_under_the_post(pool: PgPool) {
+        db::webmentions::Webmention::upsert(
+            &pool,
+            "https://example.com/reply",
+            "http://localhost:3000/posts/vim-fzf-with-devicons/",
+            "/posts/vim-fzf-with-devicons/",
+            db::webmentions::WebmentionKind::Reply,
+            db::webmentions::WebmentionDetails {
+                author_name: Some("Jane Example".to_string()),
+                content: Some("Great post!".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let app = create_test_app_with_pool(pool).await;
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/vim-fzf-with-devicons/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("Webmentions"));
+        assert!(body.contains("Jane Example"));
+        assert!(body.contains("Great post!"));
+        assert!(body.contains(r#"href="https://example.com/reply""#));
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn stored_links_that_are_not_web_urls_never_render(pool: PgPool) {
+        db::webmentions::Webmention::upsert(
+            &pool,
+            "https://example.com/reply",
+            "http://localhost:3000/posts/vim-fzf-with-devicons/",
+            "/posts/vim-fzf-with-devicons/",
+            db::webmentions::WebmentionKind::Reply,
+            db::webmentions::WebmentionDetails {
+                author_name: Some("Mallory".to_string()),
+                author_url: Some("javascript:alert(1)".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let app = create_test_app_with_pool(pool).await;
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/vim-fzf-with-devicons/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("Mallory"));
+        assert!(!body.contains("javascript:"));
+    }
+}
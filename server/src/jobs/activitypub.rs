@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use cja::jobs::Job;
+use db::activitypub::{ActivityPubDelivery, ActivityPubFollower};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    activitypub::{create_activity, deliver, id_of, published_objects},
+    AppState,
+};
+
+/// POST a signed activity to one inbox. Each inbox gets its own job so a server that's down
+/// is retried without re-sending to everyone else.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliverActivity {
+    pub inbox: String,
+    pub activity: JsonValue,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for DeliverActivity {
+    const NAME: &'static str = "DeliverActivity";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let Some(config) = &app_state.activitypub else {
+            tracing::info!("ActivityPub isn't configured, not delivering");
+            return Ok(());
+        };
+
+        deliver(config, &app_state.app, &self.inbox, &self.activity).await?;
+        tracing::info!(inbox = self.inbox, activity = ?id_of(&self.activity), "Delivered activity");
+
+        Ok(())
+    }
+}
+
+/// Send followers a `Create` for each recent post and note they haven't been sent yet.
+/// Servers sharing an inbox get one delivery between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatePosts {
+    /// How far back to look for posts, so turning federation on doesn't send the archive
+    pub days: i64,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for FederatePosts {
+    const NAME: &'static str = "FederatePosts";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        if app_state.activitypub.is_none() {
+            return Ok(());
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(self.days);
+        let followers = ActivityPubFollower::all(&app_state.db).await?;
+        let inboxes: BTreeSet<&str> = followers
+            .iter()
+            .map(ActivityPubFollower::delivery_inbox)
+            .collect();
+
+        for published in published_objects(&app_state)? {
+            let Some(object_id) = id_of(&published.object).map(str::to_string) else {
+                continue;
+            };
+            let is_recent = published.object["published"]
+                .as_str()
+                .and_then(|p| chrono::DateTime::parse_from_rfc3339(p).ok())
+                .is_some_and(|p| p >= cutoff);
+            if !is_recent || ActivityPubDelivery::exists(&app_state.db, &object_id).await? {
+                continue;
+            }
+
+            let activity = create_activity(&app_state.app, published.object);
+            for inbox in &inboxes {
+                DeliverActivity {
+                    inbox: (*inbox).to_string(),
+                    activity: activity.clone(),
+                }
+                .enqueue(
+                    app_state.clone(),
+                    format!("Federating {}", published.path),
+                    None,
+                )
+                .await?;
+            }
+
+            let follower_count = i32::try_from(followers.len()).unwrap_or(i32::MAX);
+            ActivityPubDelivery::record(&app_state.db, &object_id, follower_count).await?;
+            tracing::info!(object_id, inboxes = inboxes.len(), "Federated post");
+        }
+
+        Ok(())
+    }
+}
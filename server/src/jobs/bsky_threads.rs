@@ -0,0 +1,54 @@
+use cja::jobs::Job;
+use serde::{Deserialize, Serialize};
+
+use crate::{bsky::refresh_thread, AppState};
+
+/// Refetch one post's Bluesky thread, queued when a page view finds it missing or stale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshBskyThread {
+    pub post_url: String,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for RefreshBskyThread {
+    const NAME: &'static str = "RefreshBskyThread";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        refresh_thread(&app_state, &self.post_url).await
+    }
+}
+
+/// Refetch the Bluesky threads of every published post and note that has one, so new
+/// replies are picked up even on pages nobody has viewed recently
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshBskyThreads;
+
+#[async_trait::async_trait]
+impl Job<AppState> for RefreshBskyThreads {
+    const NAME: &'static str = "RefreshBskyThreads";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let (blog_posts, note_posts) = (app_state.blog_posts(), app_state.note_posts());
+
+        let post_urls = blog_posts
+            .published()
+            .into_iter()
+            .filter_map(|p| p.frontmatter.bsky_url.clone())
+            .chain(
+                note_posts
+                    .published()
+                    .into_iter()
+                    .filter_map(|n| n.frontmatter.bsky_url.clone()),
+            )
+            .collect::<Vec<_>>();
+
+        // One failing thread shouldn't stop the rest, it keeps serving its stale copy
+        for post_url in post_urls {
+            if let Err(e) = refresh_thread(&app_state, &post_url).await {
+                tracing::warn!(?e, post_url, "Failed to refresh Bluesky thread");
+            }
+        }
+
+        Ok(())
+    }
+}
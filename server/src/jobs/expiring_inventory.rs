@@ -0,0 +1,275 @@
+use chrono::NaiveDate;
+use cja::jobs::Job;
+use db::{
+    agentic_threads::{Stitch, Thread},
+    cooking::{
+        format_quantity, Ingredient, Inventory, MealPlan, Recipe, RecipeIngredientMatch, Unit,
+    },
+};
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, CreateMessage};
+use uuid::Uuid;
+
+use crate::{
+    agent_config::{AgentConfig, AgentId},
+    agentic_threads::ThreadBuilder,
+    jobs::thread_processor::ProcessThreadStep,
+    AppState,
+};
+
+/// How many recipe suggestions to include in a single digest
+const MAX_SUGGESTED_RECIPES: i64 = 5;
+
+/// Cap on listed items so the digest stays under Discord's 2000 character message limit
+const MAX_LISTED_ITEMS: usize = 15;
+
+/// Daily check for inventory that is about to expire.
+///
+/// Matches the expiring ingredients against recipes that use them. When there is a meal
+/// plan covering today and at least one recipe matched, an autonomous cooking thread is
+/// started to suggest adding those recipes to the plan. Otherwise the digest is posted
+/// straight to the cooking agent's Discord channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringInventoryDigest {
+    pub days_ahead: i32,
+}
+
+#[derive(Debug, Clone)]
+struct ExpiringItem {
+    ingredient_id: Uuid,
+    name: String,
+    quantity: String,
+    unit: Option<String>,
+    expiration_date: NaiveDate,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for ExpiringInventoryDigest {
+    const NAME: &'static str = "ExpiringInventoryDigest";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let db = &app_state.db;
+
+        let expiring = Inventory::get_expiring_soon(db, self.days_ahead).await?;
+        let mut items = Vec::with_capacity(expiring.len());
+        for inventory in expiring {
+            let Some(expiration_date) = inventory.expiration_date else {
+                continue;
+            };
+            let ingredient = Ingredient::get_by_id(db, inventory.ingredient_id)
+                .await?
+                .ok_or_else(|| cja::color_eyre::eyre::eyre!("Ingredient not found"))?;
+            let unit = match inventory.unit_id {
+                Some(unit_id) => Unit::get_by_id(db, unit_id).await?.map(|u| u.name),
+                None => None,
+            };
+
+            items.push(ExpiringItem {
+                ingredient_id: ingredient.ingredient_id,
+                name: ingredient.name,
+                quantity: format_quantity(&inventory.quantity),
+                unit,
+                expiration_date,
+            });
+        }
+
+        if items.is_empty() {
+            tracing::info!(days_ahead = self.days_ahead, "No inventory expiring soon");
+            return Ok(());
+        }
+
+        let mut ingredient_ids: Vec<Uuid> = items.iter().map(|i| i.ingredient_id).collect();
+        ingredient_ids.sort_unstable();
+        ingredient_ids.dedup();
+        let recipes =
+            Recipe::find_using_ingredients(db, &ingredient_ids, MAX_SUGGESTED_RECIPES).await?;
+
+        let today = chrono::Utc::now().date_naive();
+        let digest = format_digest(&items, &recipes, today);
+        let config = AgentId::Cooking.config();
+
+        let current_plan = MealPlan::get_by_date_range(db, today, today)
+            .await?
+            .into_iter()
+            .next();
+
+        match current_plan {
+            Some(plan) if !recipes.is_empty() => {
+                start_meal_plan_thread(&app_state, &config, &plan, &digest).await
+            }
+            _ => post_digest(&app_state, &config, &digest).await,
+        }
+    }
+}
+
+async fn post_digest(app_state: &AppState, config: &AgentConfig, digest: &str) -> cja::Result<()> {
+    let channel_id = config.discord_channel_id.ok_or_else(|| {
+        cja::color_eyre::eyre::eyre!("Cooking agent has no discord_channel_id configured")
+    })?;
+
+    ChannelId::new(channel_id)
+        .send_message(&app_state.discord, CreateMessage::new().content(digest))
+        .await?;
+
+    Ok(())
+}
+
+async fn start_meal_plan_thread(
+    app_state: &AppState,
+    config: &AgentConfig,
+    plan: &MealPlan,
+    digest: &str,
+) -> cja::Result<()> {
+    let channel_id = config.discord_channel_id.ok_or_else(|| {
+        cja::color_eyre::eyre::eyre!("Cooking agent has no discord_channel_id configured")
+    })?;
+
+    let thread = ThreadBuilder::new(app_state.db.clone())
+        .with_agent(config.id)
+        .with_goal(format!(
+            "Suggest recipes for the meal plan '{}' that use up expiring ingredients",
+            plan.name
+        ))
+        .autonomous()
+        .build()
+        .await?;
+
+    let prompt = format!(
+        "{digest}\n\n\
+        The current meal plan is \"{name}\" (meal_plan_id: {id}) running {start} to {end}. \
+        Use list_meal_plans to see what is already planned, then use send_discord_message to post \
+        a short message to the cooking channel (channel_id: {channel_id}) suggesting which of these \
+        recipes to add to the plan and on which days, so the expiring ingredients get used first. \
+        Don't add anything to the meal plan yourself; someone will confirm in the channel. \
+        Complete the thread once the message is sent.",
+        name = plan.name,
+        id = plan.meal_plan_id,
+        start = plan.start_date,
+        end = plan.end_date,
+    );
+
+    Stitch::create_initial_user_message(&app_state.db, thread.thread_id, &prompt).await?;
+    Thread::update_status(&app_state.db, thread.thread_id, "running").await?;
+
+    ProcessThreadStep {
+        thread_id: thread.thread_id,
+    }
+    .enqueue(
+        app_state.clone(),
+        "Expiring inventory digest".to_string(),
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn format_digest(
+    items: &[ExpiringItem],
+    recipes: &[RecipeIngredientMatch],
+    today: NaiveDate,
+) -> String {
+    let mut digest = String::from("**Use it up!** These are expiring soon:\n");
+
+    for item in items.iter().take(MAX_LISTED_ITEMS) {
+        let amount = match &item.unit {
+            Some(unit) => format!("{} {unit}", item.quantity),
+            None => item.quantity.clone(),
+        };
+        let when = match (item.expiration_date - today).num_days() {
+            d if d < 0 => "already expired".to_string(),
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            d => format!("in {d} days"),
+        };
+        digest.push_str(&format!("- {} ({amount}) — {when}\n", item.name));
+    }
+    if items.len() > MAX_LISTED_ITEMS {
+        digest.push_str(&format!("- …and {} more\n", items.len() - MAX_LISTED_ITEMS));
+    }
+
+    if recipes.is_empty() {
+        digest.push_str("\nNo saved recipes use these yet.");
+    } else {
+        digest.push_str("\nRecipes that would use them:\n");
+        for recipe in recipes {
+            digest.push_str(&format!(
+                "- {} (uses {})\n",
+                recipe.name,
+                recipe.matched_ingredients.join(", ")
+            ));
+        }
+    }
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_name() {
+        assert_eq!(ExpiringInventoryDigest::NAME, "ExpiringInventoryDigest");
+    }
+
+    #[test]
+    fn test_job_serialization() {
+        let job = ExpiringInventoryDigest { days_ahead: 3 };
+
+        let serialized = serde_json::to_string(&job).unwrap();
+        let deserialized: ExpiringInventoryDigest = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.days_ahead, 3);
+    }
+
+    fn item(name: &str, unit: Option<&str>, expiration_date: NaiveDate) -> ExpiringItem {
+        ExpiringItem {
+            ingredient_id: Uuid::new_v4(),
+            name: name.to_string(),
+            quantity: "2".to_string(),
+            unit: unit.map(ToString::to_string),
+            expiration_date,
+        }
+    }
+
+    #[test]
+    fn digest_lists_items_relative_to_today_and_matching_recipes() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let items = vec![
+            item("spinach", Some("cups"), today),
+            item("eggs", None, today.succ_opt().unwrap()),
+            item(
+                "milk",
+                Some("cups"),
+                NaiveDate::from_ymd_opt(2026, 10, 21).unwrap(),
+            ),
+        ];
+        let recipes = vec![RecipeIngredientMatch {
+            recipe_id: Uuid::new_v4(),
+            name: "Frittata".to_string(),
+            matched_ingredients: vec!["eggs".to_string(), "spinach".to_string()],
+        }];
+
+        let digest = format_digest(&items, &recipes, today);
+
+        assert!(digest.contains("- spinach (2 cups) — today"));
+        assert!(digest.contains("- eggs (2) — tomorrow"));
+        assert!(digest.contains("- milk (2 cups) — in 3 days"));
+        assert!(digest.contains("- Frittata (uses eggs, spinach)"));
+    }
+
+    #[test]
+    fn digest_caps_listed_items() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let items: Vec<_> = (0..20)
+            .map(|i| item(&format!("item {i}"), None, today))
+            .collect();
+
+        let digest = format_digest(&items, &[], today);
+
+        assert!(digest.contains("…and 5 more"));
+        assert!(digest.contains("No saved recipes use these yet."));
+        assert!(digest.len() < 2000);
+    }
+}
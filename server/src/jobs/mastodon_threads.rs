@@ -0,0 +1,55 @@
+use cja::jobs::Job;
+use serde::{Deserialize, Serialize};
+
+use crate::{mastodon::replies::refresh_thread, AppState};
+
+/// Refetch the replies to one Mastodon status, queued when a page view finds them missing
+/// or stale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshMastodonThread {
+    pub status_url: String,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for RefreshMastodonThread {
+    const NAME: &'static str = "RefreshMastodonThread";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        refresh_thread(&app_state, &self.status_url).await
+    }
+}
+
+/// Refetch the Mastodon replies of every published post and note that was cross-posted
+/// there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshMastodonThreads;
+
+#[async_trait::async_trait]
+impl Job<AppState> for RefreshMastodonThreads {
+    const NAME: &'static str = "RefreshMastodonThreads";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let (blog_posts, note_posts) = (app_state.blog_posts(), app_state.note_posts());
+
+        let status_urls = blog_posts
+            .published()
+            .into_iter()
+            .filter_map(|p| p.frontmatter.mastodon_url.clone())
+            .chain(
+                note_posts
+                    .published()
+                    .into_iter()
+                    .filter_map(|n| n.frontmatter.mastodon_url.clone()),
+            )
+            .collect::<Vec<_>>();
+
+        // One failing thread shouldn't stop the rest, it keeps serving its stale copy
+        for status_url in status_urls {
+            if let Err(e) = refresh_thread(&app_state, &status_url).await {
+                tracing::warn!(?e, status_url, "Failed to refresh Mastodon thread");
+            }
+        }
+
+        Ok(())
+    }
+}
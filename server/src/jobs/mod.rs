@@ -9,13 +9,21 @@ use self::{
     thread_processor::ProcessThreadStep, youtube_videos::RefreshVideos,
 };
 
+pub mod activitypub;
+pub mod bsky_threads;
 pub mod bytes_discord_posts;
 pub mod discord_message_processor;
 pub mod discord_thread_create_processor;
+pub mod expiring_inventory;
 pub mod linear_webhook_processor;
+pub mod mastodon_threads;
+pub mod newsletter_delivery;
+pub mod newsletter_stats;
 pub mod refresh_discord;
+pub mod scheduled_publishing;
 pub mod sponsors;
 pub mod thread_processor;
+pub mod webmentions;
 pub mod youtube_videos;
 
 impl_job_registry!(
@@ -24,8 +32,21 @@ impl_job_registry!(
     RefreshVideos,
     bytes_discord_posts::PostByteSubmission,
     refresh_discord::RefreshDiscordChannels,
+    expiring_inventory::ExpiringInventoryDigest,
     ProcessThreadStep,
     ProcessDiscordMessage,
     ProcessDiscordThreadCreate,
-    ProcessLinearWebhook
+    ProcessLinearWebhook,
+    webmentions::VerifyWebmention,
+    webmentions::SendWebmentions,
+    bsky_threads::RefreshBskyThread,
+    bsky_threads::RefreshBskyThreads,
+    mastodon_threads::RefreshMastodonThread,
+    mastodon_threads::RefreshMastodonThreads,
+    scheduled_publishing::PublishScheduledContent,
+    newsletter_stats::RefreshNewsletterStats,
+    newsletter_delivery::SendNewsletters,
+    newsletter_delivery::SendNewsletterConfirmation,
+    activitypub::DeliverActivity,
+    activitypub::FederatePosts
 );
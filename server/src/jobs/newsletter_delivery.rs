@@ -0,0 +1,268 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use cja::{color_eyre::eyre::eyre, jobs::Job};
+use db::newsletter_subscribers::{NewsletterDelivery, NewsletterSubscriber};
+use posts::blog::BlogPost;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    http_server::LinkTo as _,
+    newsletter::{
+        email::{confirmation_email, NewsletterEmail},
+        mailer::Mailer,
+        signed_url, LinkAction, LinkKey,
+    },
+    AppState,
+};
+
+/// How many emails of each newsletter one run sends, so a big list goes out over a few runs
+/// instead of one long job
+const BATCH_SIZE: i64 = 50;
+
+/// Sends that fail this many times are given up on
+const MAX_ATTEMPTS: i32 = 5;
+
+/// How long a run has to send the batch it claimed before another run may take it over
+const CLAIM_MINUTES: i64 = 30;
+
+/// Newsletters due further back than this aren't sent, so turning native delivery on
+/// doesn't send out the archive
+const LOOKBACK_DAYS: i64 = 3;
+
+/// When a newsletter goes out: its `newsletter_send_at`, or the start of its date
+fn send_at(post: &BlogPost) -> DateTime<Utc> {
+    post.frontmatter
+        .newsletter_send_at
+        .unwrap_or_else(|| post.frontmatter.date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn link_key(app_state: &AppState) -> cja::Result<&LinkKey> {
+    app_state
+        .newsletter_link_key
+        .as_ref()
+        .ok_or_else(|| eyre!("NEWSLETTER_LINK_SECRET isn't set, so links can't be signed"))
+}
+
+/// Newsletters that are due, but not so long ago that they predate native delivery
+fn due_newsletters<'a>(posts: &[&'a BlogPost], now: DateTime<Utc>) -> Vec<&'a BlogPost> {
+    let since = now - Duration::days(LOOKBACK_DAYS);
+
+    posts
+        .iter()
+        .filter(|p| p.frontmatter.is_newsletter)
+        .filter(|p| (since..=now).contains(&send_at(p)))
+        .copied()
+        .collect()
+}
+
+/// Send due newsletters to confirmed subscribers over SMTP. Each run queues a delivery for
+/// everyone who had confirmed by the send time, then sends a batch of those still pending.
+/// Failed sends are retried by later runs. Does nothing unless `SMTP_URL` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendNewsletters;
+
+impl SendNewsletters {
+    async fn send(app_state: &AppState, mailer: &Mailer, post: &BlogPost) -> cja::Result<()> {
+        let db = &app_state.db;
+        let path = post.relative_link();
+        let key = link_key(app_state)?;
+
+        let queued = NewsletterDelivery::queue(db, &path, send_at(post)).await?;
+        if queued > 0 {
+            tracing::info!(path, queued, "Queued newsletter deliveries");
+        }
+
+        let pending = NewsletterDelivery::claim_pending(
+            db,
+            &path,
+            MAX_ATTEMPTS,
+            BATCH_SIZE,
+            Duration::minutes(CLAIM_MINUTES),
+        )
+        .await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let email = NewsletterEmail::render(post, &app_state.app)?;
+        for delivery in pending {
+            let unsubscribe_url = signed_url(
+                &app_state.app,
+                key.as_bytes(),
+                LinkAction::Unsubscribe,
+                delivery.newsletter_subscriber_id,
+            );
+
+            match mailer
+                .send(&delivery.email, &email.to_subscriber(unsubscribe_url))
+                .await
+            {
+                Ok(()) => {
+                    NewsletterDelivery::mark_sent(db, delivery.newsletter_delivery_id).await?;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        path,
+                        delivery_id = %delivery.newsletter_delivery_id,
+                        attempts = delivery.attempts + 1,
+                        ?e,
+                        "Failed to send newsletter"
+                    );
+                    NewsletterDelivery::record_failure(
+                        db,
+                        delivery.newsletter_delivery_id,
+                        &format!("{e:#}"),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for SendNewsletters {
+    const NAME: &'static str = "SendNewsletters";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let Some(mailer) = app_state.mailer.clone() else {
+            return Ok(());
+        };
+
+        let blog_posts = app_state.blog_posts();
+        for post in due_newsletters(&blog_posts.published(), Utc::now()) {
+            Self::send(&app_state, &mailer, post).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Email a new subscriber the link to confirm their address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendNewsletterConfirmation {
+    pub newsletter_subscriber_id: Uuid,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for SendNewsletterConfirmation {
+    const NAME: &'static str = "SendNewsletterConfirmation";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let mailer = app_state
+            .mailer
+            .as_ref()
+            .ok_or_else(|| eyre!("SMTP_URL isn't set, so confirmations can't be sent"))?;
+        let key = link_key(&app_state)?;
+
+        let Some(subscriber) =
+            NewsletterSubscriber::find(&app_state.db, self.newsletter_subscriber_id).await?
+        else {
+            return Ok(());
+        };
+        if subscriber.confirmed_at.is_some() || subscriber.unsubscribed_at.is_some() {
+            return Ok(());
+        }
+
+        let confirm_url = signed_url(
+            &app_state.app,
+            key.as_bytes(),
+            LinkAction::Confirm,
+            subscriber.newsletter_subscriber_id,
+        );
+        mailer
+            .send(&subscriber.email, &confirmation_email(&confirm_url))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use posts::{blog::BlogPosts, source::SourceFile};
+    use sqlx::PgPool;
+
+    use crate::{http_server::test_helpers::create_test_state, newsletter::smtp_sink::SmtpSink};
+
+    use super::*;
+
+    fn post(path: &str, frontmatter: &str) -> SourceFile {
+        SourceFile::new(
+            path,
+            format!("---\ntitle: Weekly\n{frontmatter}\n---\n\nHi\n").into_bytes(),
+        )
+    }
+
+    #[test]
+    fn only_recently_due_newsletters_are_sent() {
+        let posts = BlogPosts::from_files(&[
+            post("weekly/a/index.md", "date: 2026-10-17\nis_newsletter: true"),
+            post(
+                "weekly/b/index.md",
+                "date: 2026-10-17\nis_newsletter: true\nnewsletter_send_at: 2026-10-19T14:00:00Z",
+            ),
+            post("weekly/c/index.md", "date: 2026-09-01\nis_newsletter: true"),
+            post("d/index.md", "date: 2026-10-17"),
+        ])
+        .unwrap();
+        let now = "2026-10-18T12:00:00Z".parse().unwrap();
+
+        let due = due_newsletters(&posts.posts().iter().collect::<Vec<_>>(), now);
+
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].relative_link(), "/posts/weekly/a/");
+    }
+
+    #[sqlx::test(migrations = "../db/migrations")]
+    async fn newsletters_go_to_confirmed_subscribers_and_failures_are_retried(pool: PgPool) {
+        let sink = SmtpSink::start(1).await;
+        let mailer =
+            Mailer::new(&sink.url(), "coreyja <news@coreyja.com>".parse().unwrap()).unwrap();
+        let app_state = create_test_state(pool.clone()).await;
+
+        let mut subscribers = vec![];
+        for email in ["a@example.com", "b@example.com", "unconfirmed@example.com"] {
+            subscribers.push(NewsletterSubscriber::subscribe(&pool, email).await.unwrap());
+        }
+        for subscriber in &subscribers[..2] {
+            NewsletterSubscriber::confirm(&pool, subscriber.newsletter_subscriber_id)
+                .await
+                .unwrap();
+        }
+
+        let posts = BlogPosts::from_files(&[post(
+            "weekly/a/index.md",
+            "date: 2026-10-17\nis_newsletter: true\nnewsletter_send_at: 2100-01-01T00:00:00Z",
+        )])
+        .unwrap();
+        let newsletter = &posts.posts()[0];
+
+        // The sink rejects the first recipient, who is retried on the next run
+        SendNewsletters::send(&app_state, &mailer, newsletter)
+            .await
+            .unwrap();
+        assert_eq!(sink.messages().len(), 1);
+        SendNewsletters::send(&app_state, &mailer, newsletter)
+            .await
+            .unwrap();
+        SendNewsletters::send(&app_state, &mailer, newsletter)
+            .await
+            .unwrap();
+
+        let messages = sink.messages();
+        assert_eq!(messages.len(), 2);
+        for subscriber in &subscribers[..2] {
+            let message = messages
+                .iter()
+                .find(|m| m.recipients == [subscriber.email.clone()])
+                .unwrap();
+            assert!(message.data.contains(&format!(
+                "/newsletter/unsubscribe/{}/",
+                subscriber.newsletter_subscriber_id
+            )));
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use cja::jobs::Job;
+use db::newsletter_stats::{EmailCounts, NewsletterStat, NewsletterSubscriberCount};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    buttondown::{ButtondownClient, ButtondownConfig},
+    http_server::LinkTo,
+    AppState,
+};
+
+/// Pull open, click and subscriber counts from Buttondown, for the admin dashboard and
+/// the newsletter page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshNewsletterStats;
+
+#[async_trait::async_trait]
+impl Job<AppState> for RefreshNewsletterStats {
+    const NAME: &'static str = "RefreshNewsletterStats";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let config = match ButtondownConfig::from_env() {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!(?e, "Buttondown isn't configured, skipping newsletter stats");
+                return Ok(());
+            }
+        };
+        let client = ButtondownClient::new(&config);
+
+        let blog_posts = app_state.blog_posts();
+        let sent = blog_posts.posts().iter().filter_map(|p| {
+            let id = p.frontmatter.buttondown_id.as_ref()?;
+            p.frontmatter.is_newsletter.then_some((p, id))
+        });
+
+        // One email Buttondown can't find or report on shouldn't stop the rest refreshing
+        for (post, id) in sent {
+            let email = match client.get_email(id).await {
+                Ok(email) => email,
+                Err(e) => {
+                    tracing::warn!(?e, id, "Failed to fetch newsletter email");
+                    continue;
+                }
+            };
+            if email.is_unsent() {
+                continue;
+            }
+
+            let analytics = match client.email_analytics(id).await {
+                Ok(analytics) => analytics,
+                Err(e) => {
+                    tracing::warn!(?e, id, "Failed to fetch newsletter analytics");
+                    continue;
+                }
+            };
+            NewsletterStat::upsert(
+                &app_state.db,
+                id,
+                &post.relative_link(),
+                &email.subject,
+                EmailCounts {
+                    recipients: analytics.recipients,
+                    opens: analytics.opens,
+                    clicks: analytics.clicks,
+                },
+            )
+            .await?;
+        }
+
+        let subscribers = client.subscriber_count().await?;
+        NewsletterSubscriberCount::record(&app_state.db, Utc::now().date_naive(), subscribers)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use chrono::{Duration, Utc};
+use cja::jobs::Job;
+use db::scheduled_publications::ScheduledPublication;
+use posts::publish::Publishable;
+use serde::{Deserialize, Serialize};
+
+use crate::{github::dispatch_workflow, http_server::LinkTo, AppState};
+
+/// The workflow that syndicates new posts and notes to Bluesky and standard.site
+const SYNDICATION_WORKFLOW: &str = "bluesky.yml";
+
+/// Content scheduled further back than this is assumed to have been syndicated by the
+/// deploy that shipped it, so a fresh database doesn't re-announce old posts
+const LOOKBACK_DAYS: i64 = 3;
+
+/// Syndicate scheduled posts and notes once their `publish_at` passes. They're already
+/// deployed, so nothing else would start the syndication workflow when they go live.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishScheduledContent;
+
+#[async_trait::async_trait]
+impl Job<AppState> for PublishScheduledContent {
+    const NAME: &'static str = "PublishScheduledContent";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let now = Utc::now();
+        let since = now - Duration::days(LOOKBACK_DAYS);
+
+        let (blog_posts, note_posts) = (app_state.blog_posts(), app_state.note_posts());
+
+        let went_live = blog_posts
+            .posts()
+            .iter()
+            .filter(|p| p.is_published_at(now))
+            .filter_map(|p| p.publish_at().map(|at| (p.relative_link(), at)))
+            .chain(
+                note_posts
+                    .posts
+                    .iter()
+                    .filter(|n| n.is_published_at(now))
+                    .filter_map(|n| n.publish_at().map(|at| (n.relative_link(), at))),
+            )
+            .filter(|(_, at)| *at >= since)
+            .collect::<Vec<_>>();
+
+        let mut due = vec![];
+        for (path, at) in went_live {
+            if !ScheduledPublication::exists(&app_state.db, &path).await? {
+                due.push((path, at));
+            }
+        }
+
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        // Only record them once the workflow has been started, so a failed dispatch is
+        // retried on the next run
+        dispatch_workflow(&app_state.github, SYNDICATION_WORKFLOW).await?;
+
+        for (path, at) in due {
+            tracing::info!(path, %at, "Scheduled content went live");
+            ScheduledPublication::record(&app_state.db, &path, at).await?;
+        }
+
+        Ok(())
+    }
+}
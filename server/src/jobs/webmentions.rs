@@ -0,0 +1,149 @@
+use chrono::Utc;
+use cja::jobs::Job;
+use db::webmentions::{SentWebmention, Webmention};
+use posts::{date::PostedOn, Post};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    http_server::LinkTo,
+    webmention::{self, fetch, is_fetchable, links_to, parse_mention},
+    AppState,
+};
+
+/// Verify a received webmention by fetching its source, then store it, refresh it, or
+/// delete it if the source no longer links to the target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyWebmention {
+    pub source: String,
+    pub target: String,
+    /// Path of the post or note the target resolved to
+    pub target_path: String,
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for VerifyWebmention {
+    const NAME: &'static str = "VerifyWebmention";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let db = &app_state.db;
+        let source = Url::parse(&self.source)?;
+        let target = Url::parse(&self.target)?;
+
+        // Checked when the mention came in too, but the job's payload is all we have to go on
+        if !is_fetchable(&source) {
+            tracing::warn!(source = self.source, "Refusing to fetch webmention source");
+            return Ok(());
+        }
+
+        let page = fetch(&source).await?;
+        if matches!(page.status, StatusCode::GONE | StatusCode::NOT_FOUND) {
+            let deleted = Webmention::delete(db, &self.source, &self.target).await?;
+            tracing::info!(source = self.source, deleted, "Webmention source is gone");
+            return Ok(());
+        }
+        if !page.status.is_success() {
+            return Err(cja::color_eyre::eyre::eyre!(
+                "Fetching webmention source {} returned {}",
+                self.source,
+                page.status
+            ));
+        }
+
+        if !links_to(&page.url, &page.html, &target) {
+            let deleted = Webmention::delete(db, &self.source, &self.target).await?;
+            tracing::info!(
+                source = self.source,
+                target = self.target,
+                deleted,
+                "Webmention source does not link to the target"
+            );
+            return Ok(());
+        }
+
+        let (kind, details) = parse_mention(&page.url, &page.html, &target);
+        Webmention::upsert(
+            db,
+            &self.source,
+            &self.target,
+            &self.target_path,
+            kind,
+            details,
+        )
+        .await?;
+        tracing::info!(source = self.source, %kind, "Stored webmention");
+
+        Ok(())
+    }
+}
+
+/// Send webmentions for the links in recent posts and notes. Each link is only tried once,
+/// whether or not its page accepts webmentions, so this is safe to run on every deploy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendWebmentions {
+    /// How far back to look for posts, so older posts aren't backfilled
+    pub days: i64,
+}
+
+impl SendWebmentions {
+    async fn send_for_post<T>(&self, app_state: &AppState, post: &Post<T>) -> cja::Result<()>
+    where
+        Post<T>: LinkTo,
+    {
+        let source = Url::parse(&post.absolute_link(&app_state.app))?;
+        let own_host = app_state.app.base_url.host_str();
+
+        for target in webmention::outbound_links(&post.ast.0.children, own_host) {
+            if SentWebmention::exists(&app_state.db, source.as_str(), target.as_str()).await? {
+                continue;
+            }
+
+            // Failures to reach the target aren't recorded, so the next run retries them
+            match webmention::send(&source, &target).await {
+                Ok(sent) => {
+                    let endpoint = sent.as_ref().map(|(endpoint, _)| endpoint.as_str());
+                    let status = sent.as_ref().map(|(_, status)| i32::from(status.as_u16()));
+                    SentWebmention::record(
+                        &app_state.db,
+                        source.as_str(),
+                        target.as_str(),
+                        endpoint,
+                        status,
+                    )
+                    .await?;
+                    tracing::info!(%source, %target, ?endpoint, ?status, "Sent webmention");
+                }
+                Err(e) => {
+                    tracing::warn!(%source, %target, ?e, "Failed to send webmention");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Job<AppState> for SendWebmentions {
+    const NAME: &'static str = "SendWebmentions";
+
+    async fn run(&self, app_state: AppState) -> cja::Result<()> {
+        let cutoff = Utc::now().date_naive() - chrono::Duration::days(self.days);
+
+        let (blog_posts, note_posts) = (app_state.blog_posts(), app_state.note_posts());
+
+        for post in blog_posts.published() {
+            if post.frontmatter.posted_on() >= cutoff {
+                self.send_for_post(&app_state, post).await?;
+            }
+        }
+        for note in note_posts.published() {
+            if note.frontmatter.posted_on() >= cutoff {
+                self.send_for_post(&app_state, note).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
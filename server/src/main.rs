@@ -40,13 +40,20 @@ pub(crate) mod linear;
 pub(crate) mod discord;
 pub(crate) mod discord_interactive;
 
+pub(crate) mod activitypub;
 pub(crate) mod bluesky;
 pub(crate) mod bsky;
+pub(crate) mod content;
+pub(crate) mod mastodon;
+pub(crate) mod tombstones;
+pub(crate) mod webmention;
 
 pub(crate) mod al;
 
 pub(crate) mod memory;
 
+pub(crate) mod newsletter;
+
 pub(crate) mod agentic_threads;
 
 pub(crate) mod agent_config;
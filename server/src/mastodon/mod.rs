@@ -0,0 +1,426 @@
+//! Cross-posting to Mastodon over its REST API, and reading back the replies to what we
+//! posted so they can be shown as comments.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use cja::color_eyre::eyre::eyre;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+pub mod replies;
+
+pub struct MastodonConfig {
+    /// Base URL of the instance the account lives on, like `https://hachyderm.io`
+    pub instance_url: String,
+    pub access_token: String,
+}
+
+impl MastodonConfig {
+    pub fn from_env() -> cja::Result<Self> {
+        let instance_url = std::env::var("MASTODON_INSTANCE_URL")
+            .map_err(|_| eyre!("Missing MASTODON_INSTANCE_URL environment variable"))?;
+        let access_token = std::env::var("MASTODON_ACCESS_TOKEN")
+            .map_err(|_| eyre!("Missing MASTODON_ACCESS_TOKEN environment variable"))?;
+        Ok(Self {
+            instance_url: instance_url.trim_end_matches('/').to_string(),
+            access_token,
+        })
+    }
+}
+
+/// How long a status can be on the instance. Every URL counts as a fixed number of
+/// characters, however long it really is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusLimits {
+    pub max_characters: usize,
+    pub characters_reserved_per_url: usize,
+}
+
+impl Default for StatusLimits {
+    /// Mastodon's defaults, for instances that don't say
+    fn default() -> Self {
+        Self {
+            max_characters: 500,
+            characters_reserved_per_url: 23,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct InstanceResponse {
+    configuration: InstanceConfiguration,
+}
+
+#[derive(Deserialize, Debug)]
+struct InstanceConfiguration {
+    statuses: StatusConfiguration,
+}
+
+#[derive(Deserialize, Debug)]
+struct StatusConfiguration {
+    max_characters: usize,
+    characters_reserved_per_url: usize,
+}
+
+/// A status, as returned by the API. Only what we post or render is kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Status {
+    pub id: String,
+    /// Link to the status on its author's instance. `None` for some remote statuses.
+    pub url: Option<String>,
+    /// The status as HTML, sanitized by the instance
+    pub content: String,
+    /// The content warning, empty when there isn't one
+    #[serde(default)]
+    pub spoiler_text: String,
+    pub in_reply_to_id: Option<String>,
+    pub account: Account,
+    #[serde(default)]
+    pub replies_count: u64,
+    #[serde(default)]
+    pub reblogs_count: u64,
+    #[serde(default)]
+    pub favourites_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Account {
+    /// `user` for local accounts, `user@instance` for remote ones
+    pub acct: String,
+    #[serde(default)]
+    pub display_name: String,
+    pub url: String,
+    pub avatar: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct NewStatus<'a> {
+    status: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    media_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spoiler_text: Option<&'a str>,
+    visibility: &'static str,
+}
+
+#[derive(Deserialize, Debug)]
+struct MediaAttachment {
+    id: String,
+    /// `None` while the instance is still processing the upload
+    url: Option<String>,
+}
+
+/// Most media attachments a status can have
+pub const MASTODON_MAX_MEDIA: usize = 4;
+
+/// How many times to check on an upload the instance is still processing before giving up
+const MEDIA_PROCESSING_ATTEMPTS: usize = 30;
+
+pub struct MastodonClient {
+    client: reqwest::Client,
+    instance_url: String,
+    access_token: String,
+    limits: StatusLimits,
+}
+
+impl MastodonClient {
+    /// Connect to the account's instance, reading its status length limits
+    pub async fn connect(config: &MastodonConfig) -> cja::Result<Self> {
+        let client = reqwest::Client::new();
+        let resp = client
+            .get(format!("{}/api/v2/instance", config.instance_url))
+            .send()
+            .await?;
+
+        // Older instances don't have the v2 endpoint, they get the defaults
+        let limits = if resp.status().is_success() {
+            let instance: InstanceResponse = resp.json().await?;
+            StatusLimits {
+                max_characters: instance.configuration.statuses.max_characters,
+                characters_reserved_per_url: instance
+                    .configuration
+                    .statuses
+                    .characters_reserved_per_url,
+            }
+        } else {
+            StatusLimits::default()
+        };
+
+        Ok(Self {
+            client,
+            instance_url: config.instance_url.clone(),
+            access_token: config.access_token.clone(),
+            limits,
+        })
+    }
+
+    pub fn limits(&self) -> StatusLimits {
+        self.limits
+    }
+
+    /// Upload an image to attach to a status, waiting for the instance to finish
+    /// processing it so the status can be posted straight away
+    pub async fn upload_media(&self, bytes: Vec<u8>, description: &str) -> cja::Result<String> {
+        let format =
+            image::guess_format(&bytes).map_err(|e| eyre!("Unrecognized image format: {}", e))?;
+        let file_name = format!(
+            "image.{}",
+            format.extensions_str().first().copied().unwrap_or("bin")
+        );
+        let part = reqwest::multipart::Part::bytes(bytes)
+            .file_name(file_name)
+            .mime_str(format.to_mime_type())?;
+        let form = reqwest::multipart::Form::new()
+            .part("file", part)
+            .text("description", description.to_string());
+
+        let resp = self
+            .client
+            .post(format!("{}/api/v2/media", self.instance_url))
+            .bearer_auth(&self.access_token)
+            .multipart(form)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(eyre!(
+                "Failed to upload media to Mastodon ({status}): {body}"
+            ));
+        }
+
+        let mut media: MediaAttachment = resp.json().await?;
+        for _ in 0..MEDIA_PROCESSING_ATTEMPTS {
+            if media.url.is_some() {
+                return Ok(media.id);
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            media = self
+                .client
+                .get(format!("{}/api/v1/media/{}", self.instance_url, media.id))
+                .bearer_auth(&self.access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+        }
+
+        Err(eyre!("Mastodon is still processing media {}", media.id))
+    }
+
+    /// Post a public status. `idempotency_key` makes retrying a post that may have gone
+    /// through safe, the instance returns the first status instead of posting it twice.
+    pub async fn post_status(
+        &self,
+        text: &str,
+        media_ids: Vec<String>,
+        content_warning: Option<&str>,
+        idempotency_key: &str,
+    ) -> cja::Result<Status> {
+        let resp = self
+            .client
+            .post(format!("{}/api/v1/statuses", self.instance_url))
+            .bearer_auth(&self.access_token)
+            .header("Idempotency-Key", idempotency_key)
+            .json(&NewStatus {
+                status: text,
+                media_ids,
+                spoiler_text: content_warning.filter(|cw| !cw.is_empty()),
+                visibility: "public",
+            })
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(eyre!("Failed to post Mastodon status ({status}): {body}"));
+        }
+
+        Ok(resp.json().await?)
+    }
+}
+
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://\S+").unwrap());
+static BR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<br\s*/?>").unwrap());
+
+/// Length of `text` as the instance counts it, with every URL counting as
+/// `characters_reserved_per_url`
+pub fn status_length(text: &str, limits: StatusLimits) -> usize {
+    let urls = URL_RE.find_iter(text).collect::<Vec<_>>();
+    let url_chars: usize = urls.iter().map(|m| m.as_str().chars().count()).sum();
+    text.chars().count() - url_chars + urls.len() * limits.characters_reserved_per_url
+}
+
+/// Compose a status: `{title}\n\n{body}\n\n{url}`, truncating the body with an ellipsis
+/// when it doesn't fit. The body is plain text, Mastodon links URLs by itself.
+pub fn compose_status(title: &str, body: &str, url: &str, limits: StatusLimits) -> String {
+    let body = body.trim();
+    if body.is_empty() {
+        return format!("{title}\n\n{url}");
+    }
+    let full = format!("{title}\n\n{body}\n\n{url}");
+    if status_length(&full, limits) <= limits.max_characters {
+        return full;
+    }
+
+    let mut truncated: Vec<char> = body.chars().collect();
+    loop {
+        // Don't leave whitespace dangling before the ellipsis
+        while truncated.last().is_some_and(|c| c.is_whitespace()) {
+            truncated.pop();
+        }
+        if truncated.is_empty() {
+            return format!("{title}\n\n{url}");
+        }
+        let cut: String = truncated.iter().collect();
+        let text = format!("{title}\n\n{cut}…\n\n{url}");
+        if status_length(&text, limits) <= limits.max_characters {
+            return text;
+        }
+        truncated.pop();
+    }
+}
+
+/// Split a status URL like `https://hachyderm.io/@coreyja/113...` into its instance's base
+/// URL and the status ID
+pub fn parse_status_url(status_url: &str) -> cja::Result<(String, String)> {
+    let url = Url::parse(status_url)?;
+    let id = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| eyre!("Not a Mastodon status URL: {status_url}"))?;
+
+    Ok((url.origin().ascii_serialization(), id.to_string()))
+}
+
+/// The paragraphs of a status's HTML content as plain text, with line breaks kept
+pub fn status_paragraphs(content_html: &str) -> Vec<String> {
+    let html = scraper::Html::parse_fragment(&BR_RE.replace_all(content_html, "\n"));
+    let paragraphs = scraper::Selector::parse("p").unwrap();
+
+    let texts: Vec<String> = html
+        .select(&paragraphs)
+        .map(|p| p.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect();
+    if !texts.is_empty() {
+        return texts;
+    }
+
+    let text = html.root_element().text().collect::<String>();
+    let text = text.trim();
+    if text.is_empty() {
+        vec![]
+    } else {
+        vec![text.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_count_as_reserved_characters() {
+        let limits = StatusLimits::default();
+        assert_eq!(status_length("hi", limits), 2);
+        assert_eq!(
+            status_length(
+                "hi https://coreyja.com/notes/a-very-long-slug-that-goes-on-and-on",
+                limits
+            ),
+            3 + 23
+        );
+    }
+
+    #[test]
+    fn short_notes_are_posted_whole() {
+        let status = compose_status(
+            "Title",
+            "Some body.",
+            "https://coreyja.com/notes/n",
+            StatusLimits::default(),
+        );
+        assert_eq!(status, "Title\n\nSome body.\n\nhttps://coreyja.com/notes/n");
+    }
+
+    #[test]
+    fn long_bodies_are_truncated_to_the_limit() {
+        let limits = StatusLimits {
+            max_characters: 60,
+            characters_reserved_per_url: 23,
+        };
+        let status = compose_status(
+            "Title",
+            "one two three four five six seven eight nine ten eleven",
+            "https://coreyja.com/notes/n",
+            limits,
+        );
+        assert_eq!(
+            status,
+            "Title\n\none two three four five six…\n\nhttps://coreyja.com/notes/n"
+        );
+        assert!(status_length(&status, limits) <= 60);
+    }
+
+    #[test]
+    fn empty_bodies_leave_title_and_url() {
+        let status = compose_status(
+            "Title",
+            "  ",
+            "https://coreyja.com/posts/p",
+            StatusLimits::default(),
+        );
+        assert_eq!(status, "Title\n\nhttps://coreyja.com/posts/p");
+    }
+
+    #[test]
+    fn status_urls_split_into_instance_and_id() {
+        let (instance, id) =
+            parse_status_url("https://hachyderm.io/@coreyja/113000000000000000").unwrap();
+        assert_eq!(instance, "https://hachyderm.io");
+        assert_eq!(id, "113000000000000000");
+
+        assert!(parse_status_url("https://hachyderm.io/@coreyja").is_err());
+    }
+
+    #[test]
+    fn status_content_is_read_as_plain_paragraphs() {
+        let content = r#"<p><span class="h-card"><a href="https://hachyderm.io/@coreyja" class="u-url mention">@<span>coreyja</span></a></span> nice post!</p><p>line one<br>line two</p>"#;
+        assert_eq!(
+            status_paragraphs(content),
+            vec!["@coreyja nice post!", "line one\nline two"]
+        );
+    }
+
+    #[test]
+    fn statuses_deserialize_from_the_api() {
+        let status: Status = serde_json::from_value(serde_json::json!({
+            "id": "113000000000000001",
+            "url": "https://example.social/@friend/113000000000000001",
+            "uri": "https://example.social/users/friend/statuses/113000000000000001",
+            "content": "<p>hello</p>",
+            "spoiler_text": "",
+            "in_reply_to_id": "113000000000000000",
+            "replies_count": 0,
+            "reblogs_count": 1,
+            "favourites_count": 2,
+            "account": {
+                "id": "1",
+                "acct": "friend@example.social",
+                "display_name": "Friend",
+                "url": "https://example.social/@friend",
+                "avatar": "https://example.social/avatar.png"
+            }
+        }))
+        .unwrap();
+        assert_eq!(status.in_reply_to_id.as_deref(), Some("113000000000000000"));
+        assert_eq!(status.account.acct, "friend@example.social");
+        assert_eq!(status.favourites_count, 2);
+    }
+}
@@ -0,0 +1,216 @@
+//! Replies to our Mastodon statuses, cached like Bluesky threads (see [`crate::bsky`]) so
+//! pages never wait on a Mastodon instance.
+
+use std::{collections::HashMap, sync::LazyLock, time::Duration};
+
+use cja::jobs::Job as _;
+use db::mastodon_threads::MastodonThread;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::{parse_status_url, Status};
+use crate::{jobs::mastodon_threads::RefreshMastodonThread, AppState};
+
+const DEFAULT_THREAD_REFRESH_MINUTES: u64 = 10;
+
+static THREAD_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    let minutes = std::env::var("MASTODON_THREAD_REFRESH_MINUTES")
+        .ok()
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(DEFAULT_THREAD_REFRESH_MINUTES);
+
+    Duration::from_secs(minutes * 60)
+});
+
+/// A status and every reply under it, as stored in the cache
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusThread {
+    pub status: Status,
+    /// Replies at any depth, in the order the instance's `/context` returns them
+    pub descendants: Vec<Status>,
+}
+
+/// A reply and the replies to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub status: Status,
+    pub replies: Vec<Reply>,
+}
+
+impl StatusThread {
+    /// The replies to the status, nested under the reply they answer
+    pub fn reply_tree(&self) -> Vec<Reply> {
+        fn build(parent_id: &str, children: &mut HashMap<String, Vec<Status>>) -> Vec<Reply> {
+            children
+                .remove(parent_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|status| {
+                    let replies = build(&status.id, children);
+                    Reply { status, replies }
+                })
+                .collect()
+        }
+
+        let mut children: HashMap<String, Vec<Status>> = HashMap::new();
+        for reply in &self.descendants {
+            if let Some(parent_id) = &reply.in_reply_to_id {
+                children
+                    .entry(parent_id.clone())
+                    .or_default()
+                    .push(reply.clone());
+            }
+        }
+
+        build(&self.status.id, &mut children)
+    }
+}
+
+/// How long cached Mastodon replies are served before they're refetched. Set with
+/// `MASTODON_THREAD_REFRESH_MINUTES`, defaulting to 10 minutes.
+pub fn thread_refresh_interval() -> Duration {
+    *THREAD_REFRESH_INTERVAL
+}
+
+fn thread_refresh_max_age() -> chrono::Duration {
+    chrono::Duration::from_std(thread_refresh_interval()).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Fetch a status and its replies from the instance it was posted on. Both endpoints are
+/// public, so this needs no credentials.
+pub async fn fetch_thread_json(status_url: &str) -> cja::Result<JsonValue> {
+    let (instance_url, id) = parse_status_url(status_url)?;
+
+    let status: JsonValue = reqwest::get(format!("{instance_url}/api/v1/statuses/{id}"))
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let context: JsonValue = reqwest::get(format!("{instance_url}/api/v1/statuses/{id}/context"))
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(serde_json::json!({
+        "status": status,
+        "descendants": context.get("descendants").cloned().unwrap_or_default(),
+    }))
+}
+
+/// Parse a thread as stored in the cache
+pub fn parse_thread(data: JsonValue) -> cja::Result<StatusThread> {
+    let mut thread: StatusThread = serde_json::from_value(data)?;
+    // Without a link there's nowhere to send readers to see or answer a reply
+    thread.descendants.retain(|s| s.url.is_some());
+    Ok(thread)
+}
+
+/// Refetch the replies to `status_url` and store them in the cache, once they've been
+/// checked to parse
+pub async fn refresh_thread(state: &AppState, status_url: &str) -> cja::Result<()> {
+    let data = fetch_thread_json(status_url).await?;
+    parse_thread(data.clone())?;
+
+    MastodonThread::upsert(&state.db, status_url, data).await?;
+
+    Ok(())
+}
+
+/// The cached replies to `status_url`. A missing or stale thread queues a
+/// [`RefreshMastodonThread`] job and whatever we have is served in the meantime.
+pub async fn cached_thread(state: &AppState, status_url: &str) -> Option<StatusThread> {
+    let cached = match MastodonThread::find(&state.db, status_url).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            tracing::warn!(?e, status_url, "Failed to load cached Mastodon thread");
+            return None;
+        }
+    };
+
+    if cached
+        .as_ref()
+        .is_none_or(|c| c.is_stale(thread_refresh_max_age()))
+    {
+        if let Err(e) = queue_refresh(state, status_url).await {
+            tracing::warn!(?e, status_url, "Failed to queue Mastodon thread refresh");
+        }
+    }
+
+    match parse_thread(cached?.thread?) {
+        Ok(thread) => Some(thread),
+        Err(e) => {
+            tracing::warn!(?e, status_url, "Failed to parse cached Mastodon thread");
+            None
+        }
+    }
+}
+
+async fn queue_refresh(state: &AppState, status_url: &str) -> cja::Result<()> {
+    if MastodonThread::claim_refresh(&state.db, status_url, thread_refresh_max_age()).await? {
+        RefreshMastodonThread {
+            status_url: status_url.to_string(),
+        }
+        .enqueue(state.clone(), "Stale Mastodon thread".to_string(), None)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(id: &str, in_reply_to_id: Option<&str>) -> JsonValue {
+        serde_json::json!({
+            "id": id,
+            "url": format!("https://example.social/@friend/{id}"),
+            "content": format!("<p>status {id}</p>"),
+            "in_reply_to_id": in_reply_to_id,
+            "account": {
+                "acct": "friend@example.social",
+                "display_name": "Friend",
+                "url": "https://example.social/@friend",
+                "avatar": null
+            }
+        })
+    }
+
+    #[test]
+    fn replies_nest_under_what_they_answer() {
+        let thread = parse_thread(serde_json::json!({
+            "status": status("1", None),
+            "descendants": [
+                status("2", Some("1")),
+                status("3", Some("2")),
+                status("4", Some("1")),
+            ],
+        }))
+        .unwrap();
+
+        let tree = thread.reply_tree();
+        let ids = |replies: &[Reply]| {
+            replies
+                .iter()
+                .map(|r| r.status.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&tree), vec!["2", "4"]);
+        assert_eq!(ids(&tree[0].replies), vec!["3"]);
+        assert!(tree[1].replies.is_empty());
+    }
+
+    #[test]
+    fn replies_without_a_link_are_dropped() {
+        let mut unlinked = status("2", Some("1"));
+        unlinked["url"] = JsonValue::Null;
+        let thread = parse_thread(serde_json::json!({
+            "status": status("1", None),
+            "descendants": [unlinked],
+        }))
+        .unwrap();
+
+        assert!(thread.reply_tree().is_empty());
+    }
+}
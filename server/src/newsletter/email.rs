@@ -0,0 +1,182 @@
+//! Emails built from the same markdown rendering as the site, as HTML with a plain text
+//! alternative for mail clients that don't show HTML.
+
+use maud::{html, Markup, DOCTYPE};
+use posts::blog::BlogPost;
+
+use crate::{
+    http_server::{
+        pages::blog::md::{
+            html::MarkdownRenderContext, IntoEmailHtml as _, IntoPlainText as _,
+            SyntaxHighlightingContext,
+        },
+        LinkTo as _,
+    },
+    AppConfig,
+};
+
+/// An email ready to send
+#[derive(Debug, Clone)]
+pub(crate) struct Email {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+    /// Sent as the `List-Unsubscribe` header, for emails going to the whole list
+    pub unsubscribe_url: Option<String>,
+}
+
+/// A newsletter post rendered once, then addressed to each subscriber with their own
+/// unsubscribe link
+#[derive(Debug, Clone)]
+pub(crate) struct NewsletterEmail {
+    subject: String,
+    url: String,
+    content: Markup,
+    text: String,
+}
+
+impl NewsletterEmail {
+    pub(crate) fn render(post: &BlogPost, config: &AppConfig) -> cja::Result<Self> {
+        let content = post.ast.0.clone().into_email_html(
+            config,
+            &MarkdownRenderContext {
+                syntax_highlighting: SyntaxHighlightingContext,
+                current_article_path: post.relative_link(),
+            },
+        )?;
+
+        Ok(Self {
+            subject: post.frontmatter.title.clone(),
+            url: post.absolute_link(config),
+            content,
+            text: post.ast.0.plain_text(),
+        })
+    }
+
+    pub(crate) fn to_subscriber(&self, unsubscribe_url: String) -> Email {
+        let html = layout(
+            &self.subject,
+            html! {
+                h1 style="font-size: 28px; line-height: 1.25;" { (self.subject) }
+                p style="color: #666666;" {
+                    a href=(self.url) style="color: #6c4fbf;" { "Read this on coreyja.com" }
+                }
+                (self.content)
+            },
+            html! {
+                "You're getting this because you subscribed to coreyja weekly. "
+                a href=(unsubscribe_url) style="color: #666666;" { "Unsubscribe" }
+            },
+        );
+
+        let text = format!(
+            "{}\n\n{}\n\n---\nRead this on coreyja.com: {}\nUnsubscribe: {unsubscribe_url}\n",
+            self.subject, self.text, self.url
+        );
+
+        Email {
+            subject: self.subject.clone(),
+            html,
+            text,
+            unsubscribe_url: Some(unsubscribe_url),
+        }
+    }
+}
+
+/// The email sent on signing up, to make sure the address belongs to whoever signed up
+pub(crate) fn confirmation_email(confirm_url: &str) -> Email {
+    let subject = "Confirm your subscription to coreyja weekly";
+
+    let html = layout(
+        subject,
+        html! {
+            p { "Thanks for signing up for coreyja weekly!" }
+            p {
+                "Confirm your subscription to start getting new posts in your inbox: "
+                a href=(confirm_url) style="color: #6c4fbf;" { "Confirm subscription" }
+            }
+        },
+        html! { "If you didn't sign up, ignore this email and you won't hear from us again." },
+    );
+
+    let text = format!(
+        "Thanks for signing up for coreyja weekly!\n\n\
+        Confirm your subscription to start getting new posts in your inbox: {confirm_url}\n\n\
+        If you didn't sign up, ignore this email and you won't hear from us again.\n"
+    );
+
+    Email {
+        subject: subject.to_owned(),
+        html,
+        text,
+        unsubscribe_url: None,
+    }
+}
+
+/// A single centered column. Mail clients drop stylesheets, so styles are inline.
+fn layout(title: &str, content: Markup, footer: Markup) -> String {
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { (title) }
+            }
+            body style="margin: 0; padding: 0; background-color: #ffffff;" {
+                div style="max-width: 640px; margin: 0 auto; padding: 24px; font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; font-size: 16px; line-height: 1.6; color: #1a1a1a;" {
+                    (content)
+                    hr style="border: none; border-top: 1px solid #dddddd; margin: 32px 0 16px;";
+                    p style="font-size: 12px; color: #666666;" { (footer) }
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use posts::{blog::BlogPosts, source::SourceFile};
+
+    use super::*;
+
+    const POST: &str = "---\ntitle: Weekly Update\ndate: 2026-10-18\nis_newsletter: true\n---\n\nHello **friends**, read [the docs](https://docs.rs).\n";
+
+    #[test]
+    fn newsletters_have_html_and_text_with_the_unsubscribe_link() {
+        let posts = BlogPosts::from_files(&[SourceFile::new(
+            "weekly/20261018/index.md",
+            POST.as_bytes().to_vec(),
+        )])
+        .unwrap();
+        let config = AppConfig {
+            base_url: "https://coreyja.com".parse().unwrap(),
+            imgproxy_url: None,
+        };
+
+        let email = NewsletterEmail::render(&posts.posts()[0], &config)
+            .unwrap()
+            .to_subscriber("https://coreyja.com/newsletter/unsubscribe/1/abc".to_owned());
+
+        assert_eq!(email.subject, "Weekly Update");
+        assert!(email.html.contains("<strong>friends</strong>"));
+        assert!(email
+            .html
+            .contains(r#"<a href="https://docs.rs/" style="color: #6c4fbf;">the docs</a>"#));
+        assert!(email
+            .html
+            .contains("https://coreyja.com/posts/weekly/20261018/"));
+        assert!(email
+            .html
+            .contains("href=\"https://coreyja.com/newsletter/unsubscribe/1/abc\""));
+        assert!(email.text.contains("Hello *friends*, read the docs."));
+        assert!(email
+            .text
+            .contains("Unsubscribe: https://coreyja.com/newsletter/unsubscribe/1/abc"));
+        assert_eq!(
+            email.unsubscribe_url.as_deref(),
+            Some("https://coreyja.com/newsletter/unsubscribe/1/abc")
+        );
+    }
+}
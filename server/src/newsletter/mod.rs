@@ -0,0 +1,148 @@
+//! Sending the newsletter ourselves instead of through Buttondown. Subscribers confirm their
+//! address from an emailed link, and every newsletter has a link to unsubscribe. Both links
+//! are signed, so they only work for the subscriber they were sent to.
+
+use cja::color_eyre::eyre::eyre;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::AppConfig;
+
+pub(crate) mod email;
+pub(crate) mod mailer;
+#[cfg(test)]
+pub(crate) mod smtp_sink;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Links need a key at least this long
+const MIN_LINK_KEY_BYTES: usize = 32;
+
+/// The secret confirm and unsubscribe links are signed with. It has to stay the same across
+/// restarts and deploys, or every link already emailed stops working.
+#[derive(Clone)]
+pub(crate) struct LinkKey(Vec<u8>);
+
+impl std::fmt::Debug for LinkKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LinkKey").finish_non_exhaustive()
+    }
+}
+
+impl LinkKey {
+    pub(crate) fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self(key.into())
+    }
+
+    /// Read from `NEWSLETTER_LINK_SECRET`, which has to be set when we send the newsletter
+    /// ourselves
+    pub(crate) fn from_env() -> cja::Result<Option<Self>> {
+        let secret = match std::env::var("NEWSLETTER_LINK_SECRET") {
+            Ok(secret) if !secret.is_empty() => secret,
+            _ => return Ok(None),
+        };
+        if secret.len() < MIN_LINK_KEY_BYTES {
+            return Err(eyre!(
+                "NEWSLETTER_LINK_SECRET must be at least {MIN_LINK_KEY_BYTES} bytes"
+            ));
+        }
+
+        Ok(Some(Self::new(secret)))
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// What a signed link in an email does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkAction {
+    Confirm,
+    Unsubscribe,
+}
+
+impl LinkAction {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Confirm => "confirm",
+            Self::Unsubscribe => "unsubscribe",
+        }
+    }
+}
+
+fn mac(key: &[u8], action: LinkAction, subscriber_id: Uuid) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size");
+    mac.update(action.name().as_bytes());
+    mac.update(b":");
+    mac.update(subscriber_id.as_bytes());
+    mac
+}
+
+pub(crate) fn sign(key: &[u8], action: LinkAction, subscriber_id: Uuid) -> String {
+    hex::encode(mac(key, action, subscriber_id).finalize().into_bytes())
+}
+
+pub(crate) fn verify(key: &[u8], action: LinkAction, subscriber_id: Uuid, token: &str) -> bool {
+    hex::decode(token).is_ok_and(|signature| {
+        mac(key, action, subscriber_id)
+            .verify_slice(&signature)
+            .is_ok()
+    })
+}
+
+/// Absolute URL of a signed link, like `/newsletter/unsubscribe/{id}/{token}`
+pub(crate) fn signed_url(
+    config: &AppConfig,
+    key: &[u8],
+    action: LinkAction,
+    subscriber_id: Uuid,
+) -> String {
+    config.app_url(&format!(
+        "/newsletter/{}/{subscriber_id}/{}",
+        action.name(),
+        sign(key, action, subscriber_id)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"test-key";
+
+    #[test]
+    fn links_only_work_for_their_subscriber_and_action() {
+        let id = Uuid::new_v4();
+        let token = sign(KEY, LinkAction::Unsubscribe, id);
+
+        assert!(verify(KEY, LinkAction::Unsubscribe, id, &token));
+        assert!(!verify(KEY, LinkAction::Confirm, id, &token));
+        assert!(!verify(
+            KEY,
+            LinkAction::Unsubscribe,
+            Uuid::new_v4(),
+            &token
+        ));
+        assert!(!verify(b"other-key", LinkAction::Unsubscribe, id, &token));
+        assert!(!verify(KEY, LinkAction::Unsubscribe, id, "not hex"));
+    }
+
+    #[test]
+    fn signed_urls_are_absolute() {
+        let config = AppConfig {
+            base_url: "https://coreyja.com".parse().unwrap(),
+            imgproxy_url: None,
+        };
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            signed_url(&config, KEY, LinkAction::Confirm, id),
+            format!(
+                "https://coreyja.com/newsletter/confirm/{id}/{}",
+                sign(KEY, LinkAction::Confirm, id)
+            )
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! A tiny SMTP server for tests. It accepts every message and keeps it, so sending can be
+//! checked without a real mail server.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// A message the sink received
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReceivedMessage {
+    pub recipients: Vec<String>,
+    /// Headers and body, as sent
+    pub data: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SmtpSink {
+    port: u16,
+    messages: Arc<Mutex<Vec<ReceivedMessage>>>,
+}
+
+impl SmtpSink {
+    /// Start listening on a free port. The first `rejections` recipients are refused, to
+    /// test retries.
+    pub(crate) async fn start(rejections: usize) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let messages = Arc::new(Mutex::new(vec![]));
+        let rejections = Arc::new(AtomicUsize::new(rejections));
+
+        let received = messages.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, received.clone(), rejections.clone()));
+            }
+        });
+
+        Self { port, messages }
+    }
+
+    pub(crate) fn url(&self) -> String {
+        format!("smtp://127.0.0.1:{}", self.port)
+    }
+
+    pub(crate) fn messages(&self) -> Vec<ReceivedMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+async fn handle(
+    stream: TcpStream,
+    messages: Arc<Mutex<Vec<ReceivedMessage>>>,
+    rejections: Arc<AtomicUsize>,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut message = ReceivedMessage::default();
+
+    writer.write_all(b"220 sink ESMTP\r\n").await?;
+    while let Some(line) = lines.next_line().await? {
+        let command = line.to_ascii_uppercase();
+        let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+            b"250 sink\r\n"
+        } else if command.starts_with("MAIL FROM") {
+            message = ReceivedMessage::default();
+            b"250 OK\r\n"
+        } else if command.starts_with("RCPT TO") {
+            let rejected = rejections
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if rejected {
+                b"550 Mailbox unavailable\r\n"
+            } else {
+                let recipient = line[8..].trim().trim_start_matches('<');
+                message
+                    .recipients
+                    .push(recipient.trim_end_matches('>').to_owned());
+                b"250 OK\r\n"
+            }
+        } else if command == "DATA" {
+            writer.write_all(b"354 End data with .\r\n").await?;
+            while let Some(line) = lines.next_line().await? {
+                if line == "." {
+                    break;
+                }
+                message.data.push_str(&line);
+                message.data.push('\n');
+            }
+            messages.lock().unwrap().push(std::mem::take(&mut message));
+            b"250 OK\r\n"
+        } else if command == "QUIT" {
+            writer.write_all(b"221 Bye\r\n").await?;
+            break;
+        } else if command == "RSET" || command == "NOOP" {
+            b"250 OK\r\n"
+        } else {
+            b"502 Not implemented\r\n"
+        };
+        writer.write_all(reply).await?;
+    }
+
+    Ok(())
+}
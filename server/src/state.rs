@@ -1,19 +1,25 @@
 use std::sync::Arc;
 
-use base64::Engine as _;
-use cja::{color_eyre::eyre::Context, server::cookies::CookieKey};
+use cja::{
+    color_eyre::eyre::{eyre, Context},
+    server::cookies::CookieKey,
+};
 use db::setup_db_pool;
 use openai::OpenAiConfig;
-use posts::{blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, projects::Projects};
+use posts::{
+    blog::BlogPosts, notes::NotePosts, podcast::PodcastEpisodes, projects::Projects,
+    search::SearchIndex, source::ContentSource,
+};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tracing::instrument;
 use url::Url;
 
 use crate::{
-    anthropic::AnthropicConfig, discord::DiscordClient, encrypt, github::GithubConfig,
-    google::GoogleConfig, http_server::pages::blog::md::SyntaxHighlightingContext,
-    linear::LinearConfig, twitch::TwitchConfig,
+    activitypub::ActivityPubConfig, anthropic::AnthropicConfig, content::LiveContent,
+    discord::DiscordClient, encrypt, github::GithubConfig, google::GoogleConfig,
+    http_server::pages::blog::md::SyntaxHighlightingContext, linear::LinearConfig,
+    newsletter::mailer::Mailer, newsletter::LinkKey, twitch::TwitchConfig,
 };
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -80,10 +86,6 @@ impl AppConfig {
 
         url.into()
     }
-
-    pub fn home_page(&self) -> String {
-        self.base_url.to_string()
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -112,38 +114,38 @@ pub(crate) struct AppState {
     pub anthropic: AnthropicConfig,
     pub app: AppConfig,
     pub syntax_highlighting_context: SyntaxHighlightingContext,
-    pub blog_posts: Arc<BlogPosts>,
-    pub note_posts: Arc<NotePosts>,
-    pub podcast_episodes: Arc<PodcastEpisodes>,
-    pub projects: Arc<Projects>,
+    pub content: LiveContent,
     pub versions: VersionInfo,
     pub db: PgPool,
     pub cookie_key: CookieKey,
     pub encrypt_config: encrypt::Config,
     pub posthog_key: Option<String>,
     pub discord: DiscordClient,
+    /// Sends the newsletter ourselves when `SMTP_URL` is set, instead of leaving it to
+    /// Buttondown
+    pub mailer: Option<Mailer>,
+    /// Signs the confirm and unsubscribe links in newsletter emails. Always set when
+    /// `mailer` is.
+    pub newsletter_link_key: Option<LinkKey>,
+    /// Signs requests as the site's ActivityPub actor. Federation is off without it.
+    pub activitypub: Option<ActivityPubConfig>,
 }
 
 impl AppState {
     #[instrument(name = "AppState::from_env", err, skip(discord))]
     pub async fn from_env(discord: DiscordClient) -> cja::Result<Self> {
-        let blog_posts = BlogPosts::from_static_dir()?;
-        let blog_posts = Arc::new(blog_posts);
-
-        let note_posts = NotePosts::from_static_dir()?;
-        let note_posts = Arc::new(note_posts);
-
-        let podcast_episodes = PodcastEpisodes::from_static_dir()?;
-        let podcast_episodes = Arc::new(podcast_episodes);
-
-        let projects = Projects::from_static_dir()?;
-        let projects = Arc::new(projects);
+        let content = LiveContent::load(ContentSource::from_env())?;
+        content.watch();
 
         let cookie_key = CookieKey::from_env_or_generate()?;
 
-        let main = cookie_key.master();
-        let main_str = base64::engine::general_purpose::STANDARD.encode(main);
-        tracing::info!("Generated cookie key: {:?}", main_str);
+        let mailer = Mailer::from_env()?;
+        let newsletter_link_key = LinkKey::from_env()?;
+        if mailer.is_some() && newsletter_link_key.is_none() {
+            return Err(eyre!(
+                "NEWSLETTER_LINK_SECRET must be set when SMTP_URL is, to sign newsletter links"
+            ));
+        }
 
         let app_state = AppState {
             twitch: TwitchConfig::from_env()?,
@@ -155,21 +157,43 @@ impl AppState {
             anthropic: AnthropicConfig::from_env()?,
             syntax_highlighting_context: SyntaxHighlightingContext,
             versions: VersionInfo::from_env(),
-            blog_posts,
-            note_posts,
-            podcast_episodes,
-            projects,
+            content,
             db: setup_db_pool().await?,
             cookie_key,
             encrypt_config: encrypt::Config::from_env()?,
             posthog_key: std::env::var("POSTHOG_KEY").ok(),
             discord,
+            mailer,
+            newsletter_link_key,
+            activitypub: ActivityPubConfig::from_env()?,
         };
 
         Ok(app_state)
     }
 }
 
+impl AppState {
+    pub fn blog_posts(&self) -> Arc<BlogPosts> {
+        self.content.current().blog_posts.clone()
+    }
+
+    pub fn note_posts(&self) -> Arc<NotePosts> {
+        self.content.current().note_posts.clone()
+    }
+
+    pub fn podcast_episodes(&self) -> Arc<PodcastEpisodes> {
+        self.content.current().podcast_episodes.clone()
+    }
+
+    pub fn projects(&self) -> Arc<Projects> {
+        self.content.current().projects.clone()
+    }
+
+    pub fn search_index(&self) -> Arc<SearchIndex> {
+        self.content.current().search_index.clone()
+    }
+}
+
 impl cja::app_state::AppState for AppState {
     fn version(&self) -> &str {
         self.versions.git_commit
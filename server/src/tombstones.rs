@@ -0,0 +1,137 @@
+//! Pages that used to be on the site. `reconcile --delete` records a tombstone for each page
+//! whose syndicated copies it deletes, and the site answers for them with `410 Gone`, or a
+//! permanent redirect when the tombstone has a `redirect_to`, instead of a bare 404.
+
+use std::{path::Path, sync::LazyLock};
+
+use chrono::NaiveDate;
+use cja::color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// Where the tombstones live, relative to the repo root
+pub(crate) const TOMBSTONES_FILE: &str = "tombstones.toml";
+
+/// Kept at the top of the file, since saving it drops any other comments
+const HEADER: &str = "\
+# Pages that have been removed from the site. `reconcile --delete` adds one for each page
+# whose Bluesky posts and standard.site documents it deletes. They're served as 410 Gone, or
+# add `redirect_to = \"/somewhere/else\"` to one to redirect there instead.
+";
+
+static TOMBSTONES_TOML: &str =
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../tombstones.toml"));
+
+static EMBEDDED: LazyLock<Tombstones> = LazyLock::new(|| {
+    toml::from_str(TOMBSTONES_TOML).expect("tombstones.toml must parse — check syntax")
+});
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tombstone {
+    /// Path of the page that's gone, like `/notes/some-note`
+    pub path: String,
+    pub removed_on: NaiveDate,
+    /// Where the page lives now, when it moved rather than went away
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Tombstones {
+    #[serde(rename = "tombstone", default, skip_serializing_if = "Vec::is_empty")]
+    pub tombstones: Vec<Tombstone>,
+}
+
+/// Trailing slashes are optional on every page, so they're ignored when matching
+fn normalize(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    }
+}
+
+impl Tombstones {
+    /// The tombstones the server was built with
+    pub(crate) fn embedded() -> &'static Self {
+        &EMBEDDED
+    }
+
+    pub(crate) fn load(path: &Path) -> cja::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&raw).map_err(|e| eyre!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> cja::Result<()> {
+        let serialized = toml::to_string_pretty(self)
+            .map_err(|e| eyre!("Failed to serialize tombstones: {e}"))?;
+        std::fs::write(path, format!("{HEADER}\n{serialized}"))
+            .map_err(|e| eyre!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub(crate) fn find(&self, path: &str) -> Option<&Tombstone> {
+        let path = normalize(path);
+        self.tombstones.iter().find(|t| normalize(&t.path) == path)
+    }
+
+    /// Add a tombstone for `path` unless it already has one. Returns whether it was added.
+    pub(crate) fn record(&mut self, path: &str, removed_on: NaiveDate) -> bool {
+        if self.find(path).is_some() {
+            return false;
+        }
+
+        self.tombstones.push(Tombstone {
+            path: normalize(path).to_string(),
+            removed_on,
+            redirect_to: None,
+        });
+        self.tombstones.sort_by(|a, b| a.path.cmp(&b.path));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    #[test]
+    fn the_embedded_tombstones_parse() {
+        let _ = Tombstones::embedded();
+    }
+
+    #[test]
+    fn tombstones_match_with_or_without_a_trailing_slash() {
+        let mut tombstones = Tombstones::default();
+        assert!(tombstones.record("/posts/old-post/", date()));
+
+        assert!(tombstones.find("/posts/old-post").is_some());
+        assert!(tombstones.find("/posts/old-post/").is_some());
+        assert!(tombstones.find("/posts/old").is_none());
+        assert!(!tombstones.record("/posts/old-post", date()));
+        assert_eq!(tombstones.tombstones.len(), 1);
+    }
+
+    #[test]
+    fn save_roundtrips_and_keeps_the_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TOMBSTONES_FILE);
+
+        Tombstones::default().save(&path).unwrap();
+        assert_eq!(Tombstones::load(&path).unwrap(), Tombstones::default());
+
+        let mut tombstones = Tombstones::default();
+        tombstones.record("/notes/b", date());
+        tombstones.record("/notes/a", date());
+        tombstones.tombstones[1].redirect_to = Some("/notes/c".to_string());
+        tombstones.save(&path).unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.starts_with(HEADER));
+        let loaded = Tombstones::load(&path).unwrap();
+        assert_eq!(loaded, tombstones);
+        assert_eq!(loaded.tombstones[0].path, "/notes/a");
+    }
+}
@@ -0,0 +1,610 @@
+//! [Webmention](https://www.w3.org/TR/webmention/) support: discovering a page's endpoint,
+//! checking that a source page really links to us, and reading what it says from its
+//! [microformats](https://microformats.org/wiki/h-entry).
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use db::webmentions::{WebmentionDetails, WebmentionKind};
+use markdown::mdast::Node;
+use reqwest::StatusCode;
+use scraper::{ElementRef, Html, Selector};
+use url::{Host, Url};
+
+/// Longest reply text we keep, in characters
+pub(crate) const MAX_CONTENT_CHARS: usize = 500;
+
+/// Most of a page we read, in bytes. Anything past this is dropped, since a link to us or
+/// the h-entry is near the top of any real page.
+const MAX_PAGE_BYTES: usize = 2 * 1024 * 1024;
+
+const MAX_REDIRECTS: usize = 10;
+
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    public_client_builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("coreyja.com Webmention (+https://coreyja.com)")
+        .build()
+        .expect("reqwest client builds with a static config")
+});
+
+type ResolveError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Resolve `host` like the system resolver, but fail when any address it resolves to is
+/// one [`is_public_ip`] refuses, so a public-looking name can't point us at our own network
+async fn resolve_public(host: &str) -> Result<Vec<SocketAddr>, ResolveError> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!("{host} resolves to non-public address {}", addr.ip()).into());
+    }
+
+    Ok(addrs)
+}
+
+struct PublicOnlyResolver;
+
+impl reqwest::dns::Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs = resolve_public(name.as_str()).await?;
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok::<_, ResolveError>(addrs)
+        })
+    }
+}
+
+/// A client builder for requests made on someone else's behalf, to URLs that came from
+/// outside. Every redirect hop is checked like the URL we started from, and names are only
+/// connected to when everything they resolve to is public.
+pub(crate) fn public_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .dns_resolver(Arc::new(PublicOnlyResolver))
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if !is_fetchable(attempt.url()) {
+                let message = format!("refusing to follow a redirect to {}", attempt.url());
+                attempt.error(message)
+            } else {
+                attempt.follow()
+            }
+        }))
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("selectors are static and valid")
+}
+
+/// A fetched page, with the URL it ended up at after redirects
+#[derive(Debug)]
+pub(crate) struct FetchedPage {
+    pub url: Url,
+    pub status: StatusCode,
+    pub link_headers: Vec<String>,
+    pub html: String,
+}
+
+/// Fetch a page, reading at most [`MAX_PAGE_BYTES`] of it
+pub(crate) async fn fetch(url: &Url) -> cja::Result<FetchedPage> {
+    if !is_fetchable(url) {
+        return Err(cja::color_eyre::eyre::eyre!("Refusing to fetch {url}"));
+    }
+
+    let mut response = HTTP_CLIENT.get(url.clone()).send().await?;
+    let url = response.url().clone();
+    let status = response.status();
+    let link_headers = response
+        .headers()
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(str::to_string)
+        .collect();
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let room = MAX_PAGE_BYTES - body.len();
+        body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        if body.len() == MAX_PAGE_BYTES {
+            tracing::debug!(%url, "Stopped reading a page at the size limit");
+            break;
+        }
+    }
+
+    Ok(FetchedPage {
+        url,
+        status,
+        link_headers,
+        html: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Whether a URL is one we're willing to fetch on someone else's behalf. Receiving a
+/// webmention makes us request the source, so refuse anything pointing at our own network.
+/// Only the literal host is checked here; names are checked once they're resolved, by the
+/// client from [`public_client_builder`].
+pub(crate) fn is_fetchable(url: &Url) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost"
+                && !domain.ends_with(".localhost")
+                && !domain.ends_with(".internal")
+        }
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            // 100.64.0.0/10, shared between a carrier's customers like a private range
+            let carrier_grade_nat = first == 100 && (second & 0xc0) == 64;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || first == 0
+                || carrier_grade_nat)
+        }
+        IpAddr::V6(ip) => {
+            // `::ffff:127.0.0.1` reaches 127.0.0.1, so mapped addresses are judged as IPv4
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+
+            let unique_local = (ip.segments()[0] & 0xfe00) == 0xfc00;
+            let link_local = (ip.segments()[0] & 0xffc0) == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// Whether `url` is an absolute `http(s)` URL. Links from someone else's page could be
+/// anything, `javascript:` included, so only these are stored or rendered as links.
+pub(crate) fn is_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Whether two URLs point at the same page, ignoring the scheme, fragment and a trailing
+/// slash
+pub(crate) fn same_page(a: &Url, b: &Url) -> bool {
+    a.host_str() == b.host_str()
+        && a.port_or_known_default() == b.port_or_known_default()
+        && a.path().trim_end_matches('/') == b.path().trim_end_matches('/')
+        && a.query() == b.query()
+}
+
+/// The `(href, rel)` pairs in an HTTP `Link` header, e.g.
+/// `<https://example.com/webmention>; rel="webmention"`
+fn link_header_rels(header: &str) -> Vec<(&str, &str)> {
+    let mut links = vec![];
+    let mut rest = header;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let href = &rest[start + 1..start + len];
+        let after = &rest[start + len + 1..];
+        let params_end = after.find('<').unwrap_or(after.len());
+
+        let rel = after[..params_end].split(';').find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case("rel")
+                .then(|| value.trim().trim_end_matches(',').trim().trim_matches('"'))
+        });
+        if let Some(rel) = rel {
+            links.push((href, rel));
+        }
+
+        rest = &after[params_end..];
+    }
+
+    links
+}
+
+fn has_webmention_rel(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|r| r.eq_ignore_ascii_case("webmention"))
+}
+
+/// Find a page's webmention endpoint, checking the `Link` header first and then the first
+/// `<link>` or `<a>` with `rel="webmention"`, as the spec orders them
+pub(crate) fn discover_endpoint(page: &FetchedPage) -> Option<Url> {
+    let from_header = page
+        .link_headers
+        .iter()
+        .flat_map(|header| link_header_rels(header))
+        .find(|(_, rel)| has_webmention_rel(rel))
+        .map(|(href, _)| href.to_string());
+
+    let href = from_header.or_else(|| {
+        let document = Html::parse_document(&page.html);
+        document
+            .select(&selector("link[href], a[href]"))
+            .find(|el| el.value().attr("rel").is_some_and(has_webmention_rel))
+            .and_then(|el| el.value().attr("href"))
+            .map(str::to_string)
+    })?;
+
+    page.url.join(&href).ok()
+}
+
+/// Whether the source page links to the target anywhere, as a link or embedded media
+pub(crate) fn links_to(source_url: &Url, html: &str, target: &Url) -> bool {
+    let document = Html::parse_document(html);
+    document
+        .select(&selector(
+            "a[href], link[href], img[src], video[src], audio[src]",
+        ))
+        .filter_map(|el| el.value().attr("href").or_else(|| el.value().attr("src")))
+        .filter_map(|href| source_url.join(href).ok())
+        .any(|url| same_page(&url, target))
+}
+
+/// Read the kind of mention and its author, content and date from the source's first
+/// `h-entry`. Pages without one are still plain mentions.
+pub(crate) fn parse_mention(
+    source_url: &Url,
+    html: &str,
+    target: &Url,
+) -> (WebmentionKind, WebmentionDetails) {
+    let document = Html::parse_document(html);
+    let Some(entry) = document.select(&selector(".h-entry")).next() else {
+        return (WebmentionKind::Mention, WebmentionDetails::default());
+    };
+
+    let property_links_to_target = |class: &str| {
+        entry
+            .select(&selector(&format!(".{class}")))
+            .flat_map(|el| {
+                let own = el.value().attr("href").map(str::to_string);
+                let nested = el
+                    .select(&selector("a[href]"))
+                    .filter_map(|a| a.value().attr("href").map(str::to_string));
+                own.into_iter().chain(nested).collect::<Vec<_>>()
+            })
+            .filter_map(|href| source_url.join(&href).ok())
+            .any(|url| same_page(&url, target))
+    };
+
+    let kind = if property_links_to_target("u-in-reply-to") {
+        WebmentionKind::Reply
+    } else if property_links_to_target("u-like-of") {
+        WebmentionKind::Like
+    } else if property_links_to_target("u-repost-of") {
+        WebmentionKind::Repost
+    } else {
+        WebmentionKind::Mention
+    };
+
+    let mut details = entry
+        .select(&selector(".p-author"))
+        .next()
+        .map(|author| parse_author(source_url, author))
+        .unwrap_or_default();
+
+    details.content = entry
+        .select(&selector(".e-content, .p-content, .p-summary"))
+        .next()
+        .map(|el| collapse_whitespace(el.text()))
+        .filter(|content| !content.is_empty())
+        .map(|content| truncate(&content, MAX_CONTENT_CHARS));
+
+    details.published_at = entry
+        .select(&selector(".dt-published"))
+        .next()
+        .and_then(|el| {
+            let value = el
+                .value()
+                .attr("datetime")
+                .map_or_else(|| collapse_whitespace(el.text()), str::to_string);
+            parse_datetime(&value)
+        });
+
+    (kind, details)
+}
+
+/// The author's name, URL and photo, from an `h-card` or a plain link
+fn parse_author(source_url: &Url, author: ElementRef<'_>) -> WebmentionDetails {
+    let absolute = |href: &str| {
+        source_url
+            .join(href)
+            .ok()
+            .map(String::from)
+            .filter(|url| is_web_url(url))
+    };
+
+    let name = author.select(&selector(".p-name")).next().map_or_else(
+        || collapse_whitespace(author.text()),
+        |el| collapse_whitespace(el.text()),
+    );
+    let url = author
+        .value()
+        .attr("href")
+        .or_else(|| {
+            author
+                .select(&selector(".u-url[href]"))
+                .next()
+                .and_then(|el| el.value().attr("href"))
+        })
+        .and_then(absolute);
+    let photo = author
+        .select(&selector("img.u-photo[src]"))
+        .next()
+        .and_then(|el| el.value().attr("src"))
+        .and_then(absolute);
+
+    WebmentionDetails {
+        author_name: Some(name).filter(|n| !n.is_empty()),
+        author_url: url,
+        author_photo: photo,
+        ..WebmentionDetails::default()
+    }
+}
+
+fn collapse_whitespace<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let truncated: String = text.chars().take(max_chars).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .map(|d| d.and_utc())
+        })
+}
+
+/// Absolute links to other sites in a post's markdown, in order and without duplicates
+pub(crate) fn outbound_links(children: &[Node], own_host: Option<&str>) -> Vec<Url> {
+    fn walk(nodes: &[Node], urls: &mut Vec<String>) {
+        for node in nodes {
+            match node {
+                Node::Link(link) => urls.push(link.url.clone()),
+                Node::Definition(definition) => urls.push(definition.url.clone()),
+                _ => {}
+            }
+            if let Some(children) = node.children() {
+                walk(children, urls);
+            }
+        }
+    }
+
+    let mut urls = vec![];
+    walk(children, &mut urls);
+
+    let mut links: Vec<Url> = vec![];
+    for url in urls.iter().filter_map(|u| Url::parse(u).ok()) {
+        if matches!(url.scheme(), "http" | "https")
+            && url.host_str() != own_host
+            && !links.iter().any(|l| same_page(l, &url))
+        {
+            links.push(url);
+        }
+    }
+
+    links
+}
+
+/// Notify the target's endpoint that `source` links to it. Returns the endpoint and the
+/// status it answered with, or `None` when the target doesn't accept webmentions.
+pub(crate) async fn send(source: &Url, target: &Url) -> cja::Result<Option<(Url, StatusCode)>> {
+    let page = fetch(target).await?;
+    let Some(endpoint) = discover_endpoint(&page) else {
+        return Ok(None);
+    };
+    if !is_fetchable(&endpoint) {
+        return Ok(None);
+    }
+
+    let response = HTTP_CLIENT
+        .post(endpoint.clone())
+        .form(&[("source", source.as_str()), ("target", target.as_str())])
+        .send()
+        .await?;
+
+    Ok(Some((endpoint, response.status())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(link_headers: &[&str], html: &str) -> FetchedPage {
+        FetchedPage {
+            url: Url::parse("https://example.com/blog/post").unwrap(),
+            status: StatusCode::OK,
+            link_headers: link_headers.iter().map(ToString::to_string).collect(),
+            html: html.to_string(),
+        }
+    }
+
+    #[test]
+    fn discovers_endpoint_from_header_before_html() {
+        let html = r#"<html><head><link rel="webmention" href="/from-html"></head></html>"#;
+
+        let endpoint = discover_endpoint(&page(
+            &[
+                r#"<https://example.com/style.css>; rel="stylesheet", </wm?x=1>; rel="other webmention""#,
+            ],
+            html,
+        ));
+        assert_eq!(endpoint.unwrap().as_str(), "https://example.com/wm?x=1");
+
+        let endpoint = discover_endpoint(&page(&[], html));
+        assert_eq!(endpoint.unwrap().as_str(), "https://example.com/from-html");
+
+        let endpoint = discover_endpoint(&page(&[], r#"<a rel="webmention" href="">here</a>"#));
+        assert_eq!(endpoint.unwrap().as_str(), "https://example.com/blog/post");
+
+        assert!(discover_endpoint(&page(&[], "<p>nothing</p>")).is_none());
+    }
+
+    #[test]
+    fn verifies_source_links_to_target() {
+        let source = Url::parse("https://example.com/notes/1").unwrap();
+        let target = Url::parse("https://coreyja.com/posts/hello/").unwrap();
+
+        assert!(links_to(
+            &source,
+            r#"<p>See <a href="https://coreyja.com/posts/hello#comments">this</a></p>"#,
+            &target
+        ));
+        assert!(!links_to(
+            &source,
+            r#"<p>See <a href="https://coreyja.com/posts/other/">this</a></p>"#,
+            &target
+        ));
+    }
+
+    #[test]
+    fn parses_reply_with_author_card() {
+        let source = Url::parse("https://example.com/replies/1").unwrap();
+        let target = Url::parse("https://coreyja.com/posts/hello/").unwrap();
+        let html = r#"
+          <article class="h-entry">
+            <a class="u-in-reply-to" href="https://coreyja.com/posts/hello/">In reply to</a>
+            <a class="p-author h-card" href="/about">
+              <img class="u-photo" src="/me.jpg" alt=""> <span class="p-name">Jamie   Doe</span>
+            </a>
+            <time class="dt-published" datetime="2024-05-02T10:30:00+02:00">May 2</time>
+            <div class="e-content"><p>Great  post,
+              thanks!</p></div>
+          </article>
+        "#;
+
+        let (kind, details) = parse_mention(&source, html, &target);
+        assert_eq!(kind, WebmentionKind::Reply);
+        assert_eq!(details.author_name.as_deref(), Some("Jamie Doe"));
+        assert_eq!(
+            details.author_url.as_deref(),
+            Some("https://example.com/about")
+        );
+        assert_eq!(
+            details.author_photo.as_deref(),
+            Some("https://example.com/me.jpg")
+        );
+        assert_eq!(details.content.as_deref(), Some("Great post, thanks!"));
+        assert_eq!(
+            details.published_at.unwrap().to_rfc3339(),
+            "2024-05-02T08:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn likes_of_other_pages_are_plain_mentions() {
+        let source = Url::parse("https://example.com/likes/1").unwrap();
+        let target = Url::parse("https://coreyja.com/posts/hello/").unwrap();
+
+        let like = r#"<div class="h-entry"><a class="u-like-of" href="https://coreyja.com/posts/hello/"></a></div>"#;
+        assert_eq!(
+            parse_mention(&source, like, &target).0,
+            WebmentionKind::Like
+        );
+
+        let other = r#"<div class="h-entry"><a class="u-like-of" href="https://example.org/"></a>
+          <a href="https://coreyja.com/posts/hello/">also this</a></div>"#;
+        assert_eq!(
+            parse_mention(&source, other, &target).0,
+            WebmentionKind::Mention
+        );
+
+        assert_eq!(
+            parse_mention(&source, "<p>no microformats</p>", &target).0,
+            WebmentionKind::Mention
+        );
+    }
+
+    #[test]
+    fn author_links_that_are_not_web_urls_are_dropped() {
+        let source = Url::parse("https://example.com/replies/1").unwrap();
+        let target = Url::parse("https://coreyja.com/posts/hello/").unwrap();
+        let html = r#"
+          <article class="h-entry">
+            <a class="u-in-reply-to" href="https://coreyja.com/posts/hello/">In reply to</a>
+            <div class="p-author h-card">
+              <a class="u-url" href="javascript:alert(1)"><span class="p-name">Mallory</span></a>
+              <img class="u-photo" src="data:image/svg+xml,<svg/>" alt="">
+            </div>
+          </article>
+        "#;
+
+        let (_, details) = parse_mention(&source, html, &target);
+        assert_eq!(details.author_name.as_deref(), Some("Mallory"));
+        assert_eq!(details.author_url, None);
+        assert_eq!(details.author_photo, None);
+    }
+
+    #[test]
+    fn refuses_to_fetch_internal_urls() {
+        for url in [
+            "http://localhost:3000/",
+            "http://127.0.0.1/",
+            "http://10.0.0.5/",
+            "http://[::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[::ffff:10.0.0.5]/",
+            "http://100.64.0.1/",
+            "http://100.127.255.254/",
+            "http://db.internal/",
+            "ftp://example.com/",
+        ] {
+            assert!(!is_fetchable(&Url::parse(url).unwrap()), "{url}");
+        }
+        assert!(is_fetchable(
+            &Url::parse("https://example.com/post").unwrap()
+        ));
+        assert!(is_fetchable(&Url::parse("http://100.128.0.1/").unwrap()));
+        assert!(is_fetchable(
+            &Url::parse("http://[::ffff:93.184.216.34]/").unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn names_that_resolve_to_our_own_network_are_refused() {
+        assert!(resolve_public("localhost").await.is_err());
+    }
+
+    #[test]
+    fn finds_outbound_links_in_markdown() {
+        let ast = markdown::to_mdast(
+            "Read [this](https://example.com/a) and [that][ref], not [mine](https://coreyja.com/x) or [relative](/posts/y).\n\nAgain: <https://example.com/a/>\n\n[ref]: https://example.org/b\n",
+            &markdown::ParseOptions::gfm(),
+        )
+        .unwrap();
+
+        let links = outbound_links(ast.children().unwrap(), Some("coreyja.com"));
+        let links: Vec<&str> = links.iter().map(Url::as_str).collect();
+        assert_eq!(
+            links,
+            vec!["https://example.com/a", "https://example.org/b"]
+        );
+    }
+}